@@ -0,0 +1,491 @@
+//! Cue sheets
+//!
+//! Cue sheets describe the layout of an image split into several tracks,
+//! possibly spread over several files.
+//!
+//! # Supported commands
+//! Only the `FILE`, `TRACK`, `INDEX`, `PREGAP` and `POSTGAP` commands are parsed, all others,
+//! such as `REM`, `TITLE` or `FLAGS`, are ignored.
+
+// Modules
+mod error;
+#[cfg(test)]
+mod test;
+
+// Exports
+pub use error::{OpenDataTrackError, ParseError, ParseMsfError};
+
+// Imports
+use crate::CdRomReader;
+use std::{fmt, fs, path::Path, str::FromStr};
+use zutil::IoSlice;
+
+/// A cue sheet
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct CueSheet {
+	/// All files
+	pub files: Vec<CueFile>,
+}
+
+impl CueSheet {
+	/// Creates a cue sheet for a single data track in `path`
+	#[must_use]
+	pub fn single_data_track(path: impl Into<String>) -> Self {
+		Self {
+			files: vec![CueFile {
+				path:   path.into(),
+				kind:   FileKind::Binary,
+				tracks: vec![Track {
+					number:  1,
+					kind:    TrackKind::Mode2Raw,
+					pregap:  None,
+					postgap: None,
+					indices: vec![Index {
+						number: 1,
+						pos:    Msf::default(),
+					}],
+				}],
+			}],
+		}
+	}
+
+	/// Parses a cue sheet
+	pub fn parse(s: &str) -> Result<Self, ParseError> {
+		let mut files = Vec::<CueFile>::new();
+
+		for (line_idx, line) in s.lines().enumerate() {
+			let line_idx = line_idx + 1;
+			let mut args = Args::new(line);
+			let command = match args.next() {
+				Some(command) => command,
+				None => continue,
+			};
+
+			match command.to_ascii_uppercase().as_str() {
+				"FILE" => {
+					let path = args.next_path().ok_or(ParseError::MissingArgument { line: line_idx })?;
+					let kind = args.next().ok_or(ParseError::MissingArgument { line: line_idx })?;
+					let kind = FileKind::from_str(kind).map_err(|()| ParseError::UnknownFileKind {
+						line: line_idx,
+						kind: kind.to_owned(),
+					})?;
+
+					files.push(CueFile {
+						path,
+						kind,
+						tracks: vec![],
+					});
+				},
+
+				"TRACK" => {
+					let file = files
+						.last_mut()
+						.ok_or(ParseError::TrackOutsideFile { line: line_idx })?;
+
+					let number = args.next_number(line_idx)?;
+					let kind = args.next().ok_or(ParseError::MissingArgument { line: line_idx })?;
+					let kind = TrackKind::from_str(kind).map_err(|()| ParseError::UnknownTrackKind {
+						line: line_idx,
+						kind: kind.to_owned(),
+					})?;
+
+					file.tracks.push(Track {
+						number,
+						kind,
+						pregap:  None,
+						postgap: None,
+						indices: vec![],
+					});
+				},
+
+				"INDEX" => {
+					let track = files
+						.last_mut()
+						.and_then(|file| file.tracks.last_mut())
+						.ok_or(ParseError::OutsideTrack { line: line_idx })?;
+
+					let number = args.next_number(line_idx)?;
+					let pos = args.next_msf(line_idx)?;
+					track.indices.push(Index { number, pos });
+				},
+
+				command @ ("PREGAP" | "POSTGAP") => {
+					let track = files
+						.last_mut()
+						.and_then(|file| file.tracks.last_mut())
+						.ok_or(ParseError::OutsideTrack { line: line_idx })?;
+
+					let len = args.next_msf(line_idx)?;
+					match command {
+						"PREGAP" => track.pregap = Some(len),
+						_ => track.postgap = Some(len),
+					}
+				},
+
+				command => log::debug!("Ignoring cue sheet command {:?} at line {}", command, line_idx),
+			}
+		}
+
+		Ok(Self { files })
+	}
+
+	/// Returns all tracks, along with the file they're in
+	pub fn tracks(&self) -> impl Iterator<Item = (&CueFile, &Track)> {
+		self.files
+			.iter()
+			.flat_map(|file| file.tracks.iter().map(move |track| (file, track)))
+	}
+
+	/// Returns the first data track, along with the file it's in
+	#[must_use]
+	pub fn data_track(&self) -> Option<(&CueFile, &Track)> {
+		self.tracks().find(|(_, track)| track.kind.is_data())
+	}
+
+	/// Opens the first data track.
+	///
+	/// All paths in the cue sheet are relative to `base_dir`.
+	///
+	/// The returned reader starts at the data track's `1` index, and ends at the first index of the
+	/// next track in the same file, so it excludes it's pregap, or at the end of the file.
+	pub fn open_data_track(&self, base_dir: &Path) -> Result<CdRomReader<IoSlice<fs::File>>, OpenDataTrackError> {
+		let (file, track) = self.data_track().ok_or(OpenDataTrackError::NoDataTrack)?;
+		if track.kind != TrackKind::Mode2Raw {
+			return Err(OpenDataTrackError::UnsupportedTrackKind(track.kind));
+		}
+
+		// Get the start of this track and the next, if any
+		let start = track.start().ok_or(OpenDataTrackError::NoStartIndex)?;
+		let next_start = file
+			.tracks
+			.iter()
+			.skip_while(|other| other.number != track.number)
+			.nth(1)
+			.and_then(Track::file_start);
+
+		// Then open the file and slice it
+		let path = base_dir.join(&file.path);
+		let data_file = fs::File::open(&path).map_err(|err| OpenDataTrackError::Open { path, err })?;
+		let file_len = data_file.metadata().map_err(OpenDataTrackError::FileLen)?.len();
+
+		let sector_size = CdRomReader::<fs::File>::SECTOR_SIZE;
+		let start_pos = start.sectors() * sector_size;
+		let end_pos = next_start.map_or(file_len, |next_start| next_start.sectors() * sector_size);
+		let len = end_pos
+			.checked_sub(start_pos)
+			.ok_or(OpenDataTrackError::StartPastEnd { start_pos, end_pos })?;
+
+		let data_file = IoSlice::new_with_offset_len(data_file, start_pos, len).map_err(OpenDataTrackError::Seek)?;
+		Ok(CdRomReader::new(data_file))
+	}
+}
+
+impl fmt::Display for CueSheet {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for file in &self.files {
+			writeln!(f, "FILE \"{}\" {}", file.path, file.kind)?;
+			for track in &file.tracks {
+				writeln!(f, "  TRACK {:02} {}", track.number, track.kind)?;
+				if let Some(pregap) = track.pregap {
+					writeln!(f, "    PREGAP {}", pregap)?;
+				}
+				for index in &track.indices {
+					writeln!(f, "    INDEX {:02} {}", index.number, index.pos)?;
+				}
+				if let Some(postgap) = track.postgap {
+					writeln!(f, "    POSTGAP {}", postgap)?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// A file within a cue sheet
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct CueFile {
+	/// Path, relative to the cue sheet
+	pub path: String,
+
+	/// Kind
+	pub kind: FileKind,
+
+	/// All tracks
+	pub tracks: Vec<Track>,
+}
+
+/// File kind
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+pub enum FileKind {
+	/// Raw little-endian binary
+	Binary,
+
+	/// Raw big-endian binary
+	Motorola,
+
+	/// Aiff audio
+	Aiff,
+
+	/// Wave audio
+	Wave,
+
+	/// Mp3 audio
+	Mp3,
+}
+
+impl FromStr for FileKind {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_ascii_uppercase().as_str() {
+			"BINARY" => Ok(Self::Binary),
+			"MOTOROLA" => Ok(Self::Motorola),
+			"AIFF" => Ok(Self::Aiff),
+			"WAVE" => Ok(Self::Wave),
+			"MP3" => Ok(Self::Mp3),
+			_ => Err(()),
+		}
+	}
+}
+
+impl fmt::Display for FileKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let s = match self {
+			Self::Binary => "BINARY",
+			Self::Motorola => "MOTOROLA",
+			Self::Aiff => "AIFF",
+			Self::Wave => "WAVE",
+			Self::Mp3 => "MP3",
+		};
+
+		f.write_str(s)
+	}
+}
+
+/// A track
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Track {
+	/// Number
+	pub number: u8,
+
+	/// Kind
+	pub kind: TrackKind,
+
+	/// Pregap length, not stored in the file
+	pub pregap: Option<Msf>,
+
+	/// Postgap length, not stored in the file
+	pub postgap: Option<Msf>,
+
+	/// All indices
+	pub indices: Vec<Index>,
+}
+
+impl Track {
+	/// Returns the start of this track within it's file, the position of index `1`
+	#[must_use]
+	pub fn start(&self) -> Option<Msf> {
+		self.indices
+			.iter()
+			.find(|index| index.number == 1)
+			.map(|index| index.pos)
+	}
+
+	/// Returns the start of this track's data within it's file, the position of it's lowest index.
+	///
+	/// Unlike [`Self::start`], this includes the pregap stored in the file, if any.
+	#[must_use]
+	pub fn file_start(&self) -> Option<Msf> {
+		self.indices
+			.iter()
+			.min_by_key(|index| index.number)
+			.map(|index| index.pos)
+	}
+}
+
+/// Track kind
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+pub enum TrackKind {
+	/// Audio, `2352` bytes per sector
+	Audio,
+
+	/// Karaoke, `2448` bytes per sector
+	Cdg,
+
+	/// Mode 1 data, `2048` bytes per sector
+	Mode1Data,
+
+	/// Mode 1 raw, `2352` bytes per sector
+	Mode1Raw,
+
+	/// Mode 2 data, `2336` bytes per sector
+	Mode2Data,
+
+	/// Mode 2 raw, `2352` bytes per sector
+	Mode2Raw,
+}
+
+impl TrackKind {
+	/// Returns if this track contains data
+	#[must_use]
+	pub const fn is_data(self) -> bool {
+		!matches!(self, Self::Audio | Self::Cdg)
+	}
+
+	/// Returns the sector size of this track
+	#[must_use]
+	pub const fn sector_size(self) -> u64 {
+		match self {
+			Self::Audio | Self::Mode1Raw | Self::Mode2Raw => 2352,
+			Self::Cdg => 2448,
+			Self::Mode1Data => 2048,
+			Self::Mode2Data => 2336,
+		}
+	}
+}
+
+impl FromStr for TrackKind {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_ascii_uppercase().as_str() {
+			"AUDIO" => Ok(Self::Audio),
+			"CDG" => Ok(Self::Cdg),
+			"MODE1/2048" => Ok(Self::Mode1Data),
+			"MODE1/2352" => Ok(Self::Mode1Raw),
+			"MODE2/2336" => Ok(Self::Mode2Data),
+			"MODE2/2352" => Ok(Self::Mode2Raw),
+			_ => Err(()),
+		}
+	}
+}
+
+impl fmt::Display for TrackKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let s = match self {
+			Self::Audio => "AUDIO",
+			Self::Cdg => "CDG",
+			Self::Mode1Data => "MODE1/2048",
+			Self::Mode1Raw => "MODE1/2352",
+			Self::Mode2Data => "MODE2/2336",
+			Self::Mode2Raw => "MODE2/2352",
+		};
+
+		f.write_str(s)
+	}
+}
+
+/// A track index
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Index {
+	/// Number
+	pub number: u8,
+
+	/// Position within the file
+	pub pos: Msf,
+}
+
+/// A position or length in minutes, seconds and frames.
+///
+/// Each frame is a sector, with `75` frames per second.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Default, Debug)]
+pub struct Msf {
+	/// Minutes
+	pub min: u8,
+
+	/// Seconds
+	pub sec: u8,
+
+	/// Frames
+	pub frame: u8,
+}
+
+impl Msf {
+	/// Returns the number of sectors of this position
+	#[must_use]
+	pub fn sectors(self) -> u64 {
+		(u64::from(self.min) * 60 + u64::from(self.sec)) * 75 + u64::from(self.frame)
+	}
+}
+
+impl FromStr for Msf {
+	type Err = ParseMsfError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.split(':');
+		let mut next_part = |max: u8| {
+			let part = parts.next().ok_or(ParseMsfError::MissingPart)?;
+			let value = part.parse::<u8>().map_err(ParseMsfError::Parse)?;
+			match value < max {
+				true => Ok(value),
+				false => Err(ParseMsfError::OutOfRange(value)),
+			}
+		};
+
+		let min = next_part(100)?;
+		let sec = next_part(60)?;
+		let frame = next_part(75)?;
+		if parts.next().is_some() {
+			return Err(ParseMsfError::TooManyParts);
+		}
+
+		Ok(Self { min, sec, frame })
+	}
+}
+
+impl fmt::Display for Msf {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{:02}:{:02}:{:02}", self.min, self.sec, self.frame)
+	}
+}
+
+/// Arguments of a command
+struct Args<'a> {
+	/// Remaining line
+	line: &'a str,
+}
+
+impl<'a> Args<'a> {
+	/// Creates new arguments from a line
+	fn new(line: &'a str) -> Self {
+		Self { line }
+	}
+
+	/// Returns the next argument
+	fn next(&mut self) -> Option<&'a str> {
+		let line = self.line.trim_start();
+		let end = line.find(char::is_whitespace).unwrap_or(line.len());
+		if end == 0 {
+			return None;
+		}
+
+		let (word, rest) = line.split_at(end);
+		self.line = rest;
+		Some(word)
+	}
+
+	/// Returns the next argument as a path, which may be quoted
+	fn next_path(&mut self) -> Option<String> {
+		match self.line.trim_start().strip_prefix('"') {
+			Some(rest) => {
+				let end = rest.find('"')?;
+				self.line = &rest[(end + 1)..];
+				Some(rest[..end].to_owned())
+			},
+			None => self.next().map(str::to_owned),
+		}
+	}
+
+	/// Returns the next argument as a number
+	fn next_number(&mut self, line: usize) -> Result<u8, ParseError> {
+		let arg = self.next().ok_or(ParseError::MissingArgument { line })?;
+		arg.parse().map_err(|err| ParseError::Number { line, err })
+	}
+
+	/// Returns the next argument as a msf
+	fn next_msf(&mut self, line: usize) -> Result<Msf, ParseError> {
+		let arg = self.next().ok_or(ParseError::MissingArgument { line })?;
+		arg.parse().map_err(|err| ParseError::Msf { line, err })
+	}
+}
@@ -0,0 +1,137 @@
+//! Errors
+
+// Imports
+use super::TrackKind;
+use std::{io, num::ParseIntError, path::PathBuf};
+
+/// Error type for [`CueSheet::parse`](super::CueSheet::parse)
+#[derive(PartialEq, Eq, Clone, Debug, thiserror::Error)]
+pub enum ParseError {
+	/// Missing argument
+	#[error("Missing argument at line {line}")]
+	MissingArgument {
+		/// Line
+		line: usize,
+	},
+
+	/// Unknown file kind
+	#[error("Unknown file kind {kind:?} at line {line}")]
+	UnknownFileKind {
+		/// Line
+		line: usize,
+
+		/// Kind
+		kind: String,
+	},
+
+	/// Unknown track kind
+	#[error("Unknown track kind {kind:?} at line {line}")]
+	UnknownTrackKind {
+		/// Line
+		line: usize,
+
+		/// Kind
+		kind: String,
+	},
+
+	/// Track outside of a file
+	#[error("Track outside of a file at line {line}")]
+	TrackOutsideFile {
+		/// Line
+		line: usize,
+	},
+
+	/// Command outside of a track
+	#[error("Command outside of a track at line {line}")]
+	OutsideTrack {
+		/// Line
+		line: usize,
+	},
+
+	/// Unable to parse number
+	#[error("Unable to parse number at line {line}")]
+	Number {
+		/// Line
+		line: usize,
+
+		/// Underlying error
+		#[source]
+		err: ParseIntError,
+	},
+
+	/// Unable to parse msf
+	#[error("Unable to parse msf at line {line}")]
+	Msf {
+		/// Line
+		line: usize,
+
+		/// Underlying error
+		#[source]
+		err: ParseMsfError,
+	},
+}
+
+/// Error type for [`Msf::from_str`](std::str::FromStr::from_str)
+#[derive(PartialEq, Eq, Clone, Debug, thiserror::Error)]
+pub enum ParseMsfError {
+	/// Missing part
+	#[error("Missing part")]
+	MissingPart,
+
+	/// Too many parts
+	#[error("Too many parts")]
+	TooManyParts,
+
+	/// Unable to parse part
+	#[error("Unable to parse part")]
+	Parse(#[source] ParseIntError),
+
+	/// Part was out of range
+	#[error("Part {_0} was out of range")]
+	OutOfRange(u8),
+}
+
+/// Error type for [`CueSheet::open_data_track`](super::CueSheet::open_data_track)
+#[derive(Debug, thiserror::Error)]
+pub enum OpenDataTrackError {
+	/// No data track
+	#[error("No data track")]
+	NoDataTrack,
+
+	/// Unsupported track kind
+	#[error("Unsupported data track kind {_0}")]
+	UnsupportedTrackKind(TrackKind),
+
+	/// No start index
+	#[error("Data track has no index 1")]
+	NoStartIndex,
+
+	/// Unable to open file
+	#[error("Unable to open file {path:?}")]
+	Open {
+		/// Path
+		path: PathBuf,
+
+		/// Underlying error
+		#[source]
+		err: io::Error,
+	},
+
+	/// Unable to get file length
+	#[error("Unable to get file length")]
+	FileLen(#[source] io::Error),
+
+	/// Track start was past it's end
+	#[error("Track start {start_pos:#x} was past it's end {end_pos:#x}")]
+	StartPastEnd {
+		/// Start position
+		start_pos: u64,
+
+		/// End position
+		end_pos: u64,
+	},
+
+	/// Unable to seek to track
+	#[error("Unable to seek to track")]
+	Seek(#[source] io::Error),
+}
@@ -0,0 +1,49 @@
+//! Tests
+
+// Imports
+use super::*;
+
+#[test]
+fn multi_track() {
+	let cue = r#"
+REM Some comment
+FILE "Game (Track 1).bin" BINARY
+  TRACK 01 MODE2/2352
+    INDEX 01 00:00:00
+FILE "Game (Track 2).bin" BINARY
+  TRACK 02 AUDIO
+    INDEX 00 00:00:00
+    INDEX 01 00:02:00
+"#;
+
+	let cue = CueSheet::parse(cue).expect("Unable to parse cue sheet");
+	assert_eq!(cue.files.len(), 2);
+	assert_eq!(cue.files[0].path, "Game (Track 1).bin");
+	assert_eq!(cue.files[1].tracks[0].kind, TrackKind::Audio);
+	assert_eq!(cue.files[1].tracks[0].start().map(Msf::sectors), Some(150));
+	assert_eq!(cue.files[1].tracks[0].file_start().map(Msf::sectors), Some(0));
+
+	let (file, track) = cue.data_track().expect("No data track");
+	assert_eq!(file.path, "Game (Track 1).bin");
+	assert_eq!(track.number, 1);
+}
+
+#[test]
+fn round_trip() {
+	let cue = CueSheet::single_data_track("game.bin");
+	let s = cue.to_string();
+	assert_eq!(
+		s,
+		"FILE \"game.bin\" BINARY\n  TRACK 01 MODE2/2352\n    INDEX 01 00:00:00\n"
+	);
+	assert_eq!(CueSheet::parse(&s), Ok(cue));
+}
+
+#[test]
+fn invalid() {
+	assert_eq!(
+		CueSheet::parse("TRACK 01 AUDIO"),
+		Err(ParseError::TrackOutsideFile { line: 1 })
+	);
+	assert_eq!("00:60:00".parse::<Msf>(), Err(ParseMsfError::OutOfRange(60)));
+}
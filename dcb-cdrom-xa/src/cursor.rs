@@ -1,61 +1,325 @@
 //! Cdrom XA Cursor.
+//!
+//! # Forms
+//! The cursor only exposes the data of each sector, which depends on the sector's form.
+//! By default, all sectors are considered to be form 1, with `0x800` bytes of data each, which
+//! matches the positions used by the filesystem, of `0x800` bytes per sector.
+//!
+//! Alternatively, it may be created with [`CdRomCursor::new_form_aware`] to read the form of each
+//! sector from it's sub-header, exposing `0x914` bytes for form 2 sectors, for example, to read
+//! and write interleaved audio and video. The form of all sectors is read lazily, as they are seeked
+//! over, and any sectors past the end are considered form 1.
+//!
+//! # Repairing
+//! All sectors written to through the cursor are tracked, and have their edc and ecc
+//! regenerated when the cursor is flushed or dropped. Optionally, their headers may
+//! also be repaired, see [`CdRomCursor::set_repair_headers`].
+//!
+//! As the cursor repairs sectors when dropped, it can't be cloned, else the same sectors
+//! would be repaired by each clone.
+//!
+//! Errors while repairing on drop are only logged, so [`Write::flush`] should be
+//! called to handle them.
+
+// Modules
+#[cfg(test)]
+mod test;
 
 // Imports
+use crate::{
+	sector::{header::subheader::SubMode, Form},
+	Sector,
+};
 use std::{
-	assert_matches::debug_assert_matches,
+	collections::BTreeSet,
 	convert::TryFrom,
 	io::{self, Read, Seek, SeekFrom, Write},
 };
 
 /// A cursor over a cdrom-xa file.
-// TODO: Repair sector headers and edc/ecc after writing
-#[derive(PartialEq, Clone, Debug)]
+///
+/// See the module-level documentation for more details.
+#[derive(PartialEq, Debug)]
 pub struct CdRomCursor<T> {
 	/// Underlying reader/writer
-	inner: T,
+	///
+	/// Note: Only `None` after being taken by [`CdRomCursor::into_inner`]
+	inner: Option<T>,
+
+	/// Position of the data of each sector, if form-aware.
+	///
+	/// Contains the position of all sectors whose form was read so far, followed by the
+	/// end of the last one. Always contains at least the position of the first sector.
+	sector_offsets: Option<Vec<u64>>,
+
+	/// All sectors written to since the last repair
+	dirty_sectors: BTreeSet<u64>,
+
+	/// Sector position of the first sector, if headers should also be repaired
+	repair_headers: Option<usize>,
+
+	/// Repairs all dirty sectors.
+	///
+	/// Set on the first write, as `Drop` can't require `T` to be readable / writable.
+	repair: Option<fn(&mut Self) -> Result<(), io::Error>>,
+}
+
+impl<T> CdRomCursor<T> {
+	/// Creates a new cdrom cursor.
+	///
+	/// All sectors are considered form 1.
+	#[must_use]
+	pub const fn new(inner: T) -> Self {
+		Self {
+			inner:          Some(inner),
+			sector_offsets: None,
+			dirty_sectors:  BTreeSet::new(),
+			repair_headers: None,
+			repair:         None,
+		}
+	}
+
+	/// Creates a new cdrom cursor that reads the form of each sector from it's sub-header.
+	#[must_use]
+	pub fn new_form_aware(inner: T) -> Self {
+		Self {
+			inner:          Some(inner),
+			sector_offsets: Some(vec![0]),
+			dirty_sectors:  BTreeSet::new(),
+			repair_headers: None,
+			repair:         None,
+		}
+	}
+
+	/// Sets if the headers of all written sectors should also be repaired.
+	///
+	/// Repairing a header rewrites it's sync, address and mode, and copies the first
+	/// subheader over the second. The address is calculated from `first_sector_pos`, the
+	/// sector position of the first sector of the cursor. For a whole disc image, this is
+	/// `75 * 2`, as the first sector is at 2 seconds.
+	///
+	/// If `None`, headers aren't repaired.
+	pub fn set_repair_headers(&mut self, first_sector_pos: Option<usize>) {
+		self.repair_headers = first_sector_pos;
+	}
+
+	/// Returns if this cursor reads the form of each sector
+	#[must_use]
+	pub const fn is_form_aware(&self) -> bool {
+		self.sector_offsets.is_some()
+	}
+
+	/// Returns all sectors written to since the last repair
+	#[must_use]
+	pub const fn dirty_sectors(&self) -> &BTreeSet<u64> {
+		&self.dirty_sectors
+	}
+
+	/// Consumes this cursor and returns the inner value
+	///
+	/// Any dirty sectors are repaired first, see [`CdRomCursor::repair`].
+	pub fn into_inner(mut self) -> Result<T, io::Error> {
+		if let Some(repair) = self.repair.take() {
+			repair(&mut self)?;
+		}
+
+		Ok(self.inner.take().expect("Inner value was already taken"))
+	}
+
+	/// Returns the inner value
+	fn inner(&mut self) -> &mut T {
+		self.inner.as_mut().expect("Inner value was already taken")
+	}
 }
 
-impl<T: Seek> CdRomCursor<T> {
+impl<T: Read + Seek> CdRomCursor<T> {
+	/// Reads the form of sectors until `done` returns `true` for the sector offsets,
+	/// or until the end of the inner value.
+	///
+	/// Does nothing if we aren't form-aware.
+	fn read_forms(&mut self, mut done: impl FnMut(&[u64]) -> bool) -> Result<(), io::Error> {
+		let inner = self.inner.as_mut().expect("Inner value was already taken");
+		let sector_offsets = match &mut self.sector_offsets {
+			Some(sector_offsets) if !done(&sector_offsets[..]) => sector_offsets,
+			_ => return Ok(()),
+		};
+
+		// Note: We restore the position after, even on errors.
+		let pos = inner.stream_position()?;
+		let res = (|| -> Result<(), io::Error> {
+			loop {
+				let sector_idx = u64::try_from(sector_offsets.len() - 1).expect("Sector index didn't fit into a `u64`");
+				inner.seek(SeekFrom::Start(sector_idx * 0x930 + 0x12))?;
+				let mut submode = [0; 1];
+				match inner.read_exact(&mut submode) {
+					Ok(()) => (),
+					Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+					Err(err) => return Err(err),
+				}
+
+				let form = Form::from_submode(SubMode::from_bits_truncate(submode[0]));
+				let data_size = u64::try_from(form.data_size()).expect("Data size didn't fit into a `u64`");
+				let end = sector_offsets.last().expect("Sector offsets were empty") + data_size;
+				sector_offsets.push(end);
+
+				if done(sector_offsets) {
+					return Ok(());
+				}
+			}
+		})();
+		inner.seek(SeekFrom::Start(pos))?;
+
+		res
+	}
+
+	/// Returns the position of the data of the `sector_idx`th sector and it's size
+	fn sector(&mut self, sector_idx: u64) -> Result<(u64, u64), io::Error> {
+		self.read_forms(|sector_offsets| {
+			u64::try_from(sector_offsets.len()).expect("Sector count didn't fit into a `u64`") > sector_idx + 1
+		})?;
+
+		let sector_offsets = match &self.sector_offsets {
+			Some(sector_offsets) => sector_offsets,
+			None => return Ok((sector_idx * 0x800, 0x800)),
+		};
+
+		match usize::try_from(sector_idx)
+			.ok()
+			.and_then(|sector_idx| sector_offsets.get(sector_idx..=sector_idx + 1))
+		{
+			Some(&[start, end]) => Ok((start, end - start)),
+
+			// Note: Any sectors past the end are considered form 1
+			_ => {
+				let last_idx = u64::try_from(sector_offsets.len() - 1).expect("Sector index didn't fit into a `u64`");
+				let last_offset = *sector_offsets.last().expect("Sector offsets were empty");
+				Ok((last_offset + (sector_idx - last_idx) * 0x800, 0x800))
+			},
+		}
+	}
+
+	/// Returns the index of the sector containing the data at `pos`, and it's data position.
+	fn sector_containing(&mut self, pos: u64) -> Result<(u64, u64), io::Error> {
+		self.read_forms(|sector_offsets| sector_offsets.last().map_or(false, |&end| end > pos))?;
+
+		let sector_offsets = match &self.sector_offsets {
+			Some(sector_offsets) => sector_offsets,
+			None => return Ok((pos / 0x800, (pos / 0x800) * 0x800)),
+		};
+
+		let last_offset = *sector_offsets.last().expect("Sector offsets were empty");
+		match pos < last_offset {
+			true => {
+				let sector_idx = sector_offsets.partition_point(|&offset| offset <= pos) - 1;
+				Ok((
+					u64::try_from(sector_idx).expect("Sector index didn't fit into a `u64`"),
+					sector_offsets[sector_idx],
+				))
+			},
+
+			// Note: Any sectors past the end are considered form 1
+			false => {
+				let last_idx = u64::try_from(sector_offsets.len() - 1).expect("Sector index didn't fit into a `u64`");
+				let sectors_past = (pos - last_offset) / 0x800;
+				Ok((last_idx + sectors_past, last_offset + sectors_past * 0x800))
+			},
+		}
+	}
+
+	/// Converts an outer position to an inner
+	fn outer_pos_to_inner(&mut self, pos: u64) -> Result<u64, io::Error> {
+		let (sector_idx, sector_offset) = self.sector_containing(pos)?;
+		Ok(sector_idx * 0x930 + 0x18 + (pos - sector_offset))
+	}
+
+	/// Converts an inner position to an outer
+	///
+	/// Positions within the header are considered the start of the sector's data, and positions
+	/// within the edc / ecc the end of it.
+	fn inner_pos_to_outer(&mut self, pos: u64) -> Result<u64, io::Error> {
+		let (sector_offset, data_size) = self.sector(pos / 0x930)?;
+		Ok(sector_offset + (pos % 0x930).saturating_sub(0x18).min(data_size))
+	}
+
 	/// Seeks to the next readable / writeable position if outside of it
-	/// and returns the offset within the sector data, `0..0x800`.
-	#[allow(clippy::as_conversions, clippy::cast_possible_wrap)] // `pos` is guaranteed to be within `0..0x930`
-	fn seek_next_valid_pos(&mut self) -> Result<u64, io::Error> {
-		let pos = match self.inner.stream_position()? % 0x930 {
+	/// and returns the offset within the sector data, `0..data_size`, and the data size.
+	fn seek_next_valid_pos(&mut self) -> Result<(u64, u64), io::Error> {
+		let inner_pos = self.inner().stream_position()?;
+		let (sector_idx, pos) = (inner_pos / 0x930, inner_pos % 0x930);
+		let (_, data_size) = self.sector(sector_idx)?;
+		let (pos, data_size) = match pos {
 			// If we're in the header, skip forward
-			pos @ 0..0x18 => {
-				self.inner.seek(SeekFrom::Current(0x18 - pos as i64))?;
-				0
+			pos if pos < 0x18 => {
+				self.inner().seek(SeekFrom::Start(sector_idx * 0x930 + 0x18))?;
+				(0, data_size)
 			},
 
 			// If we're within data, don't do anything
-			pos @ 0x18..0x818 => pos - 0x18,
+			pos if pos < 0x18 + data_size => (pos - 0x18, data_size),
 
 			// If we're in edc/ecc, skip forward to next sector
-			pos @ 0x818..0x930 => {
-				self.inner.seek(SeekFrom::Current(0x930 - pos as i64 + 0x18))?;
-				0
+			_ => {
+				self.inner().seek(SeekFrom::Start((sector_idx + 1) * 0x930 + 0x18))?;
+				let (_, data_size) = self.sector(sector_idx + 1)?;
+				(0, data_size)
 			},
-
-			_ => unreachable!(),
 		};
 
-		debug_assert_matches!(self.inner.stream_position()? % 0x930, 0x18..0x818);
+		debug_assert!((0x18..0x18 + data_size).contains(&(self.inner().stream_position()? % 0x930)));
 
-		Ok(pos)
+		Ok((pos, data_size))
 	}
 }
 
-impl<T> CdRomCursor<T> {
-	/// Creates a new cdrom cursor.
-	#[must_use]
-	pub const fn new(inner: T) -> Self {
-		Self { inner }
+impl<T: Read + Write + Seek> CdRomCursor<T> {
+	/// Repairs all dirty sectors
+	///
+	/// Regenerates the edc and ecc of all sectors written to, and, if enabled, their headers.
+	pub fn repair(&mut self) -> Result<(), io::Error> {
+		if self.dirty_sectors.is_empty() {
+			return Ok(());
+		}
+
+		// Save our position to restore it after
+		let pos = self.inner().stream_position()?;
+
+		while let Some(&sector_idx) = self.dirty_sectors.iter().next() {
+			// Read the sector
+			let sector_pos = sector_idx * 0x930;
+			let mut bytes = [0; 0x930];
+			self.inner().seek(SeekFrom::Start(sector_pos))?;
+			self.inner().read_exact(&mut bytes)?;
+
+			// Repair it
+			if let Some(first_sector_pos) = self.repair_headers {
+				let abs_sector_pos = usize::try_from(sector_idx)
+					.ok()
+					.and_then(|sector_idx| first_sector_pos.checked_add(sector_idx))
+					.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Sector position overflowed"))?;
+				Sector::repair_header(&mut bytes, abs_sector_pos)
+					.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+			}
+			Sector::regenerate_edc_ecc(&mut bytes);
+
+			// And write it back
+			self.inner().seek(SeekFrom::Start(sector_pos))?;
+			self.inner().write_all(&bytes)?;
+			self.dirty_sectors.remove(&sector_idx);
+		}
+
+		self.inner().seek(SeekFrom::Start(pos))?;
+
+		Ok(())
 	}
+}
 
-	/// Consumes this cursor and returns the inner value
-	#[must_use]
-	pub fn into_inner(self) -> T {
-		self.inner
+impl<T> Drop for CdRomCursor<T> {
+	fn drop(&mut self) {
+		if let Some(repair) = self.repair.take() {
+			if let Err(err) = repair(self) {
+				log::warn!("Unable to repair sectors: {}", err);
+			}
+		}
 	}
 }
 
@@ -65,12 +329,12 @@ impl<R: Read + Seek> Read for CdRomCursor<R> {
 
 		while !buf.is_empty() {
 			// Go to the start of the data
-			let pos = self.seek_next_valid_pos()?;
+			let (pos, data_size) = self.seek_next_valid_pos()?;
 
 			// Then read the remaining within the current sector
-			let bytes_until_end = usize::try_from(0x800 - pos).expect("0..0x800 didn't fit into a `usize`");
+			let bytes_until_end = usize::try_from(data_size - pos).expect("0..data_size didn't fit into a `usize`");
 			let bytes_to_read = usize::min(bytes_until_end, buf.len());
-			let bytes_read = self.inner.read(&mut buf[..bytes_to_read])?;
+			let bytes_read = self.inner().read(&mut buf[..bytes_to_read])?;
 
 			// If we got 0 bytes read, return
 			if bytes_read == 0 {
@@ -85,46 +349,61 @@ impl<R: Read + Seek> Read for CdRomCursor<R> {
 	}
 }
 
-impl<R: Seek> Seek for CdRomCursor<R> {
+impl<R: Read + Seek> Seek for CdRomCursor<R> {
 	#[allow(clippy::shadow_unrelated)] // They are related
 	fn seek(&mut self, pos: SeekFrom) -> Result<u64, io::Error> {
 		// Then check what position they want us to go
 		let pos = match pos {
-			SeekFrom::Start(pos) => 0x930 * (pos / 0x800) + 0x18 + (pos % 0x800),
-			SeekFrom::End(_) => todo!("Seek from end isn't supported yet"),
+			SeekFrom::Start(pos) => self.outer_pos_to_inner(pos)?,
+			SeekFrom::End(offset) => {
+				// Get the inner and outer length
+				// Note: Any incomplete sector at the end only counts the data within it.
+				let cur_inner_pos = self.inner().stream_position()?;
+				let inner_len = self.inner().seek(SeekFrom::End(0))?;
+				self.inner().seek(SeekFrom::Start(cur_inner_pos))?;
+				let outer_len = self.inner_pos_to_outer(inner_len)?;
+
+				// Then calculate the outer and inner position we want to go to
+				let outer_pos = zutil::signed_offset(outer_len, offset);
+				self.outer_pos_to_inner(outer_pos)?
+			},
 			SeekFrom::Current(offset) => {
 				// Seek to the next valid position before offsetting
 				self.seek_next_valid_pos()?;
 
 				// Then get the inner and outer position we're on
-				// Note: Guaranteed to be within `0x18..0x818` modulus `0x930`
-				let cur_inner_pos = self.inner.stream_position()?;
-				let cur_outer_pos = self::inner_pos_to_outer(cur_inner_pos);
+				let cur_inner_pos = self.inner().stream_position()?;
+				let cur_outer_pos = self.inner_pos_to_outer(cur_inner_pos)?;
 
 				// Then calculate the outer and inner position we want to go to
 				let outer_pos = zutil::signed_offset(cur_outer_pos, offset);
-				self::outer_pos_to_inner(outer_pos)
+				self.outer_pos_to_inner(outer_pos)?
 			},
 		};
 
 		// Seek to the inner pos and convert it to an outer pos
-		let inner_pos = self.inner.seek(SeekFrom::Start(pos))?;
-		Ok(self::inner_pos_to_outer(inner_pos))
+		let inner_pos = self.inner().seek(SeekFrom::Start(pos))?;
+		self.inner_pos_to_outer(inner_pos)
 	}
 }
 
-impl<W: Write + Seek> Write for CdRomCursor<W> {
+impl<W: Read + Write + Seek> Write for CdRomCursor<W> {
 	fn write(&mut self, mut buf: &[u8]) -> Result<usize, io::Error> {
 		let start_len = buf.len();
 
 		while !buf.is_empty() {
 			// Go to the start of the data
-			let pos = self.seek_next_valid_pos()?;
+			let (pos, data_size) = self.seek_next_valid_pos()?;
+
+			// And mark the sector as dirty
+			let sector_idx = self.inner().stream_position()? / 0x930;
+			self.dirty_sectors.insert(sector_idx);
+			self.repair = Some(Self::repair);
 
 			// Then write the remaining within the current sector
-			let bytes_until_end = usize::try_from(0x800 - pos).expect("0..0x800 didn't fit into a `usize`");
+			let bytes_until_end = usize::try_from(data_size - pos).expect("0..data_size didn't fit into a `usize`");
 			let bytes_to_write = usize::min(bytes_until_end, buf.len());
-			let bytes_written = self.inner.write(&buf[..bytes_to_write])?;
+			let bytes_written = self.inner().write(&buf[..bytes_to_write])?;
 
 			// If we got 0 bytes read, return
 			if bytes_written == 0 {
@@ -139,15 +418,7 @@ impl<W: Write + Seek> Write for CdRomCursor<W> {
 	}
 
 	fn flush(&mut self) -> Result<(), io::Error> {
-		self.inner.flush()
+		self.repair()?;
+		self.inner().flush()
 	}
 }
-
-/// Converts an outer position to an inner
-const fn outer_pos_to_inner(pos: u64) -> u64 {
-	(pos / 0x800) * 0x930 + 0x18 + (pos % 0x800)
-}
-/// Converts an inner position to an outer
-const fn inner_pos_to_outer(pos: u64) -> u64 {
-	(pos / 0x930) * 0x800 + (pos - 0x18) % 0x930
-}
@@ -0,0 +1,174 @@
+//! Tests
+
+// Imports
+use super::*;
+use crate::sector::{
+	header::{Address, SubHeader},
+	Data,
+};
+use dcb_bytes::Bytes;
+
+/// Creates a file with `len` empty sectors
+fn file(len: usize) -> io::Cursor<Vec<u8>> {
+	let mut bytes = vec![];
+	for sector_pos in 0..len {
+		let sector = Sector::new([0; 0x800], 75 * 2 + sector_pos, SubHeader::new()).expect("Unable to create sector");
+		bytes.extend_from_slice(&sector.to_bytes().expect("Unable to serialize sector"));
+	}
+
+	io::Cursor::new(bytes)
+}
+
+/// Reads the `n`th sector of `bytes`
+fn read_sector(bytes: &[u8], n: usize) -> Sector {
+	let bytes = <&[u8; 0x930]>::try_from(&bytes[n * 0x930..(n + 1) * 0x930]).expect("Sector size was wrong");
+	Sector::deserialize_bytes(bytes).expect("Unable to deserialize sector")
+}
+
+#[test]
+fn repair_on_flush() {
+	let mut cursor = CdRomCursor::new(self::file(3));
+
+	cursor.seek(SeekFrom::Start(0x7f0)).expect("Unable to seek");
+	cursor.write_all(&[0xab; 0x20]).expect("Unable to write");
+	assert_eq!(cursor.dirty_sectors().iter().copied().collect::<Vec<_>>(), [0, 1]);
+
+	cursor.flush().expect("Unable to flush");
+	assert!(cursor.dirty_sectors().is_empty());
+	assert_eq!(cursor.stream_position().expect("Unable to get position"), 0x810);
+
+	let bytes = cursor.into_inner().expect("Unable to get inner").into_inner();
+	let sectors = [0, 1, 2].map(|n| self::read_sector(&bytes, n));
+	assert_eq!(
+		sectors[0].data.as_form1().expect("Sector wasn't form 1")[0x7f0..],
+		[0xab; 0x10]
+	);
+	assert_eq!(
+		sectors[1].data.as_form1().expect("Sector wasn't form 1")[..0x10],
+		[0xab; 0x10]
+	);
+}
+
+#[test]
+fn repair_on_drop() {
+	let mut bytes = self::file(1).into_inner();
+
+	{
+		let mut cursor = CdRomCursor::new(io::Cursor::new(&mut bytes));
+		cursor.write_all(&[0xcd; 0x10]).expect("Unable to write");
+	}
+
+	let sector = self::read_sector(&bytes, 0);
+	assert_eq!(
+		sector.data.as_form1().expect("Sector wasn't form 1")[..0x10],
+		[0xcd; 0x10]
+	);
+}
+
+#[test]
+fn repair_headers() {
+	let mut bytes = self::file(2).into_inner();
+	bytes[0x930..0x93f].fill(0);
+
+	{
+		let mut cursor = CdRomCursor::new(io::Cursor::new(&mut bytes));
+		cursor.set_repair_headers(Some(75 * 2));
+		cursor.seek(SeekFrom::Start(0x800)).expect("Unable to seek");
+		cursor.write_all(&[0xef; 0x10]).expect("Unable to write");
+	}
+
+	let sector = self::read_sector(&bytes, 1);
+	let address = Address::from_sector_pos(75 * 2 + 1).expect("Unable to create address");
+	assert_eq!(sector.header.address, address);
+}
+
+#[test]
+fn repair_headers_base_sector() {
+	let mut bytes = self::file(2).into_inner();
+	bytes[0x930..0x93f].fill(0);
+
+	// Note: The cursor starts at sector 1000 of the disc
+	{
+		let mut cursor = CdRomCursor::new(io::Cursor::new(&mut bytes));
+		cursor.set_repair_headers(Some(1000));
+		cursor.seek(SeekFrom::Start(0x800)).expect("Unable to seek");
+		cursor.write_all(&[0xef; 0x10]).expect("Unable to write");
+	}
+
+	let sector = self::read_sector(&bytes, 1);
+	let address = Address::from_sector_pos(1001).expect("Unable to create address");
+	assert_eq!(sector.header.address, address);
+}
+
+#[test]
+fn seek_end() {
+	let mut cursor = CdRomCursor::new(self::file(3));
+
+	assert_eq!(cursor.stream_len().expect("Unable to get length"), 3 * 0x800);
+	assert_eq!(
+		cursor.seek(SeekFrom::End(-0x10)).expect("Unable to seek"),
+		3 * 0x800 - 0x10
+	);
+	assert_eq!(cursor.seek(SeekFrom::End(0)).expect("Unable to seek"), 3 * 0x800);
+
+	// Make sure we read the last bytes of the last sector
+	cursor.seek(SeekFrom::End(-0x10)).expect("Unable to seek");
+	let mut bytes = vec![];
+	cursor.read_to_end(&mut bytes).expect("Unable to read");
+	assert_eq!(bytes, [0; 0x10]);
+}
+
+#[test]
+fn incomplete_len() {
+	let mut file = self::file(2).into_inner();
+	file.truncate(0x930 + 0x18 + 0x100);
+	let mut cursor = CdRomCursor::new(io::Cursor::new(file));
+
+	assert_eq!(cursor.stream_len().expect("Unable to get length"), 0x800 + 0x100);
+}
+
+#[test]
+fn form_aware() {
+	// Interleave form 1 and form 2 sectors
+	let data: [Data; 3] = [[0x12; 0x800].into(), [0x34; 0x914].into(), [0x56; 0x800].into()];
+	let mut bytes = vec![];
+	for (sector_pos, data) in data.iter().enumerate() {
+		let sector = Sector::new(data.clone(), 75 * 2 + sector_pos, SubHeader::new()).expect("Unable to create sector");
+		bytes.extend_from_slice(&sector.to_bytes().expect("Unable to serialize sector"));
+	}
+	let contents = data
+		.iter()
+		.flat_map(|data| data.as_ref().iter().copied())
+		.collect::<Vec<u8>>();
+
+	// Read all the data and write over the end of the form 2 sector
+	{
+		let mut cursor = CdRomCursor::new_form_aware(io::Cursor::new(&mut bytes));
+		assert_eq!(
+			cursor.stream_len().expect("Unable to get length"),
+			0x800 + 0x914 + 0x800
+		);
+
+		let mut read = vec![];
+		cursor.read_to_end(&mut read).expect("Unable to read");
+		assert_eq!(read, contents);
+
+		cursor.seek(SeekFrom::Start(0x800 + 0x910)).expect("Unable to seek");
+		cursor.write_all(&[0x78; 0x8]).expect("Unable to write");
+		assert_eq!(
+			cursor.stream_position().expect("Unable to get position"),
+			0x800 + 0x914 + 0x4
+		);
+		assert_eq!(cursor.dirty_sectors().iter().copied().collect::<Vec<_>>(), [1, 2]);
+	}
+
+	// Then make sure all sectors are still valid and only what we wrote changed
+	let sectors = [0, 1, 2].map(|n| self::read_sector(&bytes, n));
+	assert_eq!(sectors[0].data, data[0]);
+	let form2 = sectors[1].data.as_form2().expect("Sector wasn't form 2");
+	assert_eq!(form2[..0x910], [0x34; 0x910]);
+	assert_eq!(form2[0x910..], [0x78; 0x4]);
+	let form1 = sectors[2].data.as_form1().expect("Sector wasn't form 1");
+	assert_eq!(form1[..0x4], [0x78; 0x4]);
+	assert_eq!(form1[0x4..], [0x56; 0x7fc]);
+}
@@ -0,0 +1,89 @@
+//! Compressed images
+//!
+//! Besides raw `.bin` images, images may be stored compressed as either `.ecm` or `.chd`.
+//!
+//! [`ImageReader`] detects the format of an image and exposes it's raw sectors, so it may
+//! be used as the source of a [`CdRomReader`](crate::CdRomReader).
+
+// Modules
+pub mod chd;
+pub mod ecm;
+mod error;
+
+// Exports
+pub use chd::ChdReader;
+pub use ecm::EcmReader;
+pub use error::NewError;
+
+// Imports
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Image reader
+#[derive(Clone, Debug)]
+pub enum ImageReader<R> {
+	/// Raw image
+	Raw(R),
+
+	/// Ecm image
+	Ecm(EcmReader<R>),
+
+	/// Chd image
+	Chd(ChdReader<R>),
+}
+
+impl<R: Read + Seek> ImageReader<R> {
+	/// Creates a new image reader, detecting the format by it's magic.
+	///
+	/// Images without a known magic are read as raw images.
+	pub fn new(mut reader: R) -> Result<Self, NewError> {
+		// Note: Images smaller than the magic are simply raw.
+		let mut magic = [0; 8];
+		let magic_len = self::read_magic(&mut reader, &mut magic).map_err(NewError::ReadMagic)?;
+		reader.seek(SeekFrom::Start(0)).map_err(NewError::ReadMagic)?;
+		let magic = &magic[..magic_len];
+
+		if magic.starts_with(&EcmReader::<R>::MAGIC) {
+			return EcmReader::new(reader).map(Self::Ecm).map_err(NewError::Ecm);
+		}
+		if magic == chd::Header::MAGIC {
+			return ChdReader::new(reader).map(Self::Chd).map_err(NewError::Chd);
+		}
+
+		Ok(Self::Raw(reader))
+	}
+}
+
+impl<R: Read + Seek> Read for ImageReader<R> {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+		match self {
+			Self::Raw(reader) => reader.read(buf),
+			Self::Ecm(reader) => reader.read(buf),
+			Self::Chd(reader) => reader.read(buf),
+		}
+	}
+}
+
+impl<R: Read + Seek> Seek for ImageReader<R> {
+	fn seek(&mut self, pos: SeekFrom) -> Result<u64, io::Error> {
+		match self {
+			Self::Raw(reader) => reader.seek(pos),
+			Self::Ecm(reader) => reader.seek(pos),
+			Self::Chd(reader) => reader.seek(pos),
+		}
+	}
+}
+
+/// Reads as many bytes of the magic as possible
+fn read_magic<R: Read>(reader: &mut R, magic: &mut [u8]) -> Result<usize, io::Error> {
+	let mut len = 0;
+	while len < magic.len() {
+		match reader.read(&mut magic[len..]) {
+			Ok(0) => break,
+			Ok(bytes_read) => len += bytes_read,
+			Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+			Err(err) => return Err(err),
+		}
+	}
+
+	Ok(len)
+}
@@ -0,0 +1,303 @@
+//! Chd images
+//!
+//! Chd images split the image into hunks, each compressed with one of up to 4 codecs.
+//!
+//! For cd images, each hunk contains several frames, each with a raw `0x930` byte sector
+//! followed by `0x60` bytes of subcode.
+//!
+//! # Limitations
+//! Only version 5 images, without a parent, are supported.
+//! Only the cd codecs (`cdzl`, `cdlz` and `cdfl`) and their non-cd counterparts (`zlib` and `lzma`)
+//! are supported.
+//! Tracks aren't read from the metadata, so the image starts at the first sector of the
+//! first track.
+
+// Modules
+mod codec;
+mod error;
+mod map;
+#[cfg(test)]
+mod test;
+
+// Exports
+pub use codec::Codec;
+pub use error::{DecompressError, NewError, ReadHunkError, ReadMapError};
+
+// Imports
+use byteorder::{BigEndian, ByteOrder};
+use map::{Compression, MapEntry};
+use std::{
+	convert::TryFrom,
+	io::{self, Read, Seek, SeekFrom},
+};
+
+/// Chd header
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Header {
+	/// Version
+	pub version: u32,
+
+	/// Compressors
+	pub compressors: [u32; 4],
+
+	/// Logical length
+	pub logical_bytes: u64,
+
+	/// Map offset
+	pub map_offset: u64,
+
+	/// Metadata offset
+	pub meta_offset: u64,
+
+	/// Bytes per hunk
+	pub hunk_bytes: u32,
+
+	/// Bytes per unit
+	pub unit_bytes: u32,
+}
+
+impl Header {
+	/// Magic
+	pub const MAGIC: [u8; 8] = *b"MComprHD";
+	/// Version 5 header size
+	pub const V5_SIZE: usize = 0x7c;
+
+	/// Returns the number of hunks
+	#[must_use]
+	pub fn hunks_len(&self) -> usize {
+		let hunks_len = (self.logical_bytes + u64::from(self.hunk_bytes) - 1) / u64::from(self.hunk_bytes);
+		usize::try_from(hunks_len).expect("Number of hunks didn't fit into a `usize`")
+	}
+
+	/// Parses the header of a version 5 chd
+	fn from_bytes(bytes: &[u8; Self::V5_SIZE]) -> Result<Self, NewError> {
+		let bytes = zutil::array_split!(bytes,
+			magic        : [0x8 ],
+			len          : [0x4 ],
+			version      : [0x4 ],
+			compressors  : [0x10],
+			logical_bytes: [0x8 ],
+			map_offset   : [0x8 ],
+			meta_offset  : [0x8 ],
+			hunk_bytes   : [0x4 ],
+			unit_bytes   : [0x4 ],
+			_raw_sha1    : [0x14],
+			_sha1        : [0x14],
+			parent_sha1  : [0x14],
+		);
+
+		if *bytes.magic != Self::MAGIC {
+			return Err(NewError::WrongMagic(*bytes.magic));
+		}
+
+		let version = BigEndian::read_u32(bytes.version);
+		if version != 5 {
+			return Err(NewError::UnsupportedVersion(version));
+		}
+
+		if *bytes.parent_sha1 != [0; 0x14] {
+			return Err(NewError::HasParent);
+		}
+
+		let mut compressors = [0; 4];
+		BigEndian::read_u32_into(bytes.compressors, &mut compressors);
+
+		let header = Self {
+			version,
+			compressors,
+			logical_bytes: BigEndian::read_u64(bytes.logical_bytes),
+			map_offset: BigEndian::read_u64(bytes.map_offset),
+			meta_offset: BigEndian::read_u64(bytes.meta_offset),
+			hunk_bytes: BigEndian::read_u32(bytes.hunk_bytes),
+			unit_bytes: BigEndian::read_u32(bytes.unit_bytes),
+		};
+
+		// Note: We only support cd images, which have a frame per unit.
+		if header.unit_bytes != ChdReader::<()>::FRAME_SIZE || header.hunk_bytes % header.unit_bytes != 0 {
+			return Err(NewError::NotCd {
+				hunk_bytes: header.hunk_bytes,
+				unit_bytes: header.unit_bytes,
+			});
+		}
+
+		Ok(header)
+	}
+}
+
+/// Chd image reader.
+///
+/// Exposes the raw `0x930` byte sectors of the image, without subcode.
+#[derive(Clone, Debug)]
+pub struct ChdReader<R> {
+	/// Underlying reader
+	reader: R,
+
+	/// Header
+	header: Header,
+
+	/// Codecs
+	codecs: [Option<Codec>; 4],
+
+	/// Hunk map
+	map: Vec<MapEntry>,
+
+	/// Current position
+	pos: u64,
+
+	/// Last read hunk
+	cached_hunk: Option<(usize, Vec<u8>)>,
+}
+
+impl<R> ChdReader<R> {
+	/// Frame size
+	pub const FRAME_SIZE: u32 = 0x990;
+	/// Sector size
+	pub const SECTOR_SIZE: u32 = 0x930;
+
+	/// Returns the header
+	#[must_use]
+	pub const fn header(&self) -> &Header {
+		&self.header
+	}
+
+	/// Returns the length of all sectors
+	#[must_use]
+	pub fn len(&self) -> u64 {
+		self.frames_len() * u64::from(Self::SECTOR_SIZE)
+	}
+
+	/// Returns if there are no sectors
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.frames_len() == 0
+	}
+
+	/// Returns the number of frames
+	fn frames_len(&self) -> u64 {
+		self.header.logical_bytes / u64::from(Self::FRAME_SIZE)
+	}
+}
+
+impl<R: Read + Seek> ChdReader<R> {
+	/// Creates a new chd reader, reading the header and hunk map.
+	pub fn new(mut reader: R) -> Result<Self, NewError> {
+		// Read the header
+		reader.seek(SeekFrom::Start(0)).map_err(NewError::ReadHeader)?;
+		let mut header_bytes = [0; Header::V5_SIZE];
+		reader.read_exact(&mut header_bytes).map_err(NewError::ReadHeader)?;
+		let header = Header::from_bytes(&header_bytes)?;
+
+		// Get all codecs
+		let mut codecs = [None; 4];
+		for (codec, &tag) in codecs.iter_mut().zip(&header.compressors) {
+			*codec = Codec::from_tag(tag);
+			if tag != 0 && codec.is_none() {
+				log::warn!("Unsupported chd codec {:?}", Codec::tag_name(tag));
+			}
+		}
+
+		// And read the map
+		let map = map::read(&mut reader, &header).map_err(NewError::ReadMap)?;
+
+		Ok(Self {
+			reader,
+			header,
+			codecs,
+			map,
+			pos: 0,
+			cached_hunk: None,
+		})
+	}
+
+	/// Reads the `n`th hunk
+	pub fn read_hunk(&mut self, hunk_idx: usize) -> Result<Vec<u8>, ReadHunkError> {
+		let entry = *self.map.get(hunk_idx).ok_or(ReadHunkError::OutOfBounds(hunk_idx))?;
+		let hunk_bytes = usize::try_from(self.header.hunk_bytes).expect("Hunk size didn't fit into a `usize`");
+
+		let hunk = match entry.compression {
+			Compression::Codec(codec_idx) => {
+				let codec = self.codecs[codec_idx].ok_or_else(|| {
+					ReadHunkError::UnsupportedCodec(Codec::tag_name(self.header.compressors[codec_idx]))
+				})?;
+				let src = self.read_bytes(entry.offset, entry.len)?;
+				codec
+					.decompress(&src, hunk_bytes)
+					.map_err(|err| ReadHunkError::Decompress { codec, err })?
+			},
+			Compression::None => self.read_bytes(entry.offset, entry.len)?,
+
+			// Note: Hunks may only reference previous hunks, so this can't recurse forever
+			Compression::SelfHunk => match usize::try_from(entry.offset) {
+				Ok(other_idx) if other_idx < hunk_idx => self.read_hunk(other_idx)?,
+				_ => return Err(ReadHunkError::InvalidSelfHunk(entry.offset)),
+			},
+			Compression::Parent => return Err(ReadHunkError::ParentHunk),
+			Compression::Zero => vec![0; hunk_bytes],
+		};
+
+		// Verify the crc, if we have it
+		if let Some(crc) = entry.crc {
+			let calculated = map::crc16(&hunk);
+			if calculated != crc {
+				return Err(ReadHunkError::WrongCrc { found: crc, calculated });
+			}
+		}
+
+		Ok(hunk)
+	}
+
+	/// Reads `len` bytes at `offset`
+	fn read_bytes(&mut self, offset: u64, len: u32) -> Result<Vec<u8>, ReadHunkError> {
+		let mut bytes = vec![0; usize::try_from(len).expect("Length didn't fit into a `usize`")];
+		self.reader.seek(SeekFrom::Start(offset)).map_err(ReadHunkError::Seek)?;
+		self.reader.read_exact(&mut bytes).map_err(ReadHunkError::Read)?;
+		Ok(bytes)
+	}
+}
+
+impl<R: Read + Seek> Read for ChdReader<R> {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+		// Get the frame we're in
+		let sector_size = u64::from(Self::SECTOR_SIZE);
+		let frame_idx = self.pos / sector_size;
+		let frame_offset = self.pos % sector_size;
+		if frame_idx >= self.frames_len() {
+			return Ok(0);
+		}
+
+		// Then get the hunk it's in and read it, if not cached
+		let frame_pos = frame_idx * u64::from(Self::FRAME_SIZE) + frame_offset;
+		let hunk_idx = usize::try_from(frame_pos / u64::from(self.header.hunk_bytes))
+			.expect("Hunk index didn't fit into a `usize`");
+		let hunk_offset = usize::try_from(frame_pos % u64::from(self.header.hunk_bytes))
+			.expect("Hunk offset didn't fit into a `usize`");
+		let is_cached = matches!(&self.cached_hunk, Some((idx, _)) if *idx == hunk_idx);
+		if !is_cached {
+			let hunk = self
+				.read_hunk(hunk_idx)
+				.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+			self.cached_hunk = Some((hunk_idx, hunk));
+		}
+		let (_, hunk) = self.cached_hunk.as_ref().expect("Hunk was just cached");
+
+		// Note: Frames never cross hunks, so we can read until the end of the sector
+		let remaining = usize::try_from(sector_size - frame_offset).expect("Sector size didn't fit into a `usize`");
+		let len = usize::min(buf.len(), remaining);
+		buf[..len].copy_from_slice(&hunk[hunk_offset..(hunk_offset + len)]);
+		self.pos += u64::try_from(len).expect("Length didn't fit into a `u64`");
+
+		Ok(len)
+	}
+}
+
+impl<R: Read + Seek> Seek for ChdReader<R> {
+	fn seek(&mut self, pos: SeekFrom) -> Result<u64, io::Error> {
+		self.pos = match pos {
+			SeekFrom::Start(pos) => pos,
+			SeekFrom::End(offset) => zutil::signed_offset(self.len(), offset),
+			SeekFrom::Current(offset) => zutil::signed_offset(self.pos, offset),
+		};
+
+		Ok(self.pos)
+	}
+}
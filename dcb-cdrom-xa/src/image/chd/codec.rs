@@ -0,0 +1,230 @@
+//! Codecs
+
+// Imports
+use super::{error::DecompressError, ChdReader};
+use crate::sector::{header::Header, Ecc};
+use dcb_bytes::Bytes;
+use std::{
+	convert::TryFrom,
+	io::{self, Read},
+};
+
+/// A codec
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Codec {
+	/// Deflate
+	Zlib,
+
+	/// Lzma
+	Lzma,
+
+	/// Cd deflate, for both sectors and subcode
+	CdZlib,
+
+	/// Cd lzma, for sectors, and deflate for subcode
+	CdLzma,
+
+	/// Cd flac, for sectors, and deflate for subcode
+	CdFlac,
+}
+
+impl Codec {
+	/// Returns a codec from it's tag
+	#[must_use]
+	pub const fn from_tag(tag: u32) -> Option<Self> {
+		match &tag.to_be_bytes() {
+			b"zlib" => Some(Self::Zlib),
+			b"lzma" => Some(Self::Lzma),
+			b"cdzl" => Some(Self::CdZlib),
+			b"cdlz" => Some(Self::CdLzma),
+			b"cdfl" => Some(Self::CdFlac),
+			_ => None,
+		}
+	}
+
+	/// Returns the name of a tag
+	#[must_use]
+	pub fn tag_name(tag: u32) -> String {
+		String::from_utf8_lossy(&tag.to_be_bytes()).into_owned()
+	}
+
+	/// Decompresses a hunk of `len` bytes
+	pub fn decompress(self, src: &[u8], len: usize) -> Result<Vec<u8>, DecompressError> {
+		match self {
+			Self::Zlib => self::zlib(src, len),
+			Self::Lzma => self::lzma(src, len),
+			Self::CdZlib => self::cd(src, len, self::zlib),
+			Self::CdLzma => self::cd(src, len, self::lzma),
+			Self::CdFlac => self::cd_flac(src, len),
+		}
+	}
+}
+
+/// Decompresses a cd hunk, with sectors compressed by `decompress_sectors`.
+///
+/// The hunk starts with a bitmap of all frames whose sync and error correction were
+/// removed, followed by the compressed length of the sectors, the compressed sectors and
+/// the compressed subcode.
+fn cd(
+	src: &[u8], len: usize, decompress_sectors: fn(&[u8], usize) -> Result<Vec<u8>, DecompressError>,
+) -> Result<Vec<u8>, DecompressError> {
+	let (frame_size, sector_size) = self::frame_sizes();
+	let frames = len / frame_size;
+
+	// Read the header
+	let ecc_bytes = (frames + 7) / 8;
+	let sectors_len_bytes = if len < 0x10000 { 2 } else { 3 };
+	let header_bytes = ecc_bytes + sectors_len_bytes;
+	let header = src.get(..header_bytes).ok_or(DecompressError::TooSmall)?;
+	let (ecc_bitmap, sectors_len) = header.split_at(ecc_bytes);
+	let sectors_len = sectors_len
+		.iter()
+		.fold(0, |len, &byte| (len << 8usize) | usize::from(byte));
+
+	// Then decompress both the sectors and subcode
+	let sectors_src = src
+		.get(header_bytes..(header_bytes + sectors_len))
+		.ok_or(DecompressError::TooSmall)?;
+	let sectors = decompress_sectors(sectors_src, frames * sector_size)?;
+	let subcode = self::zlib(
+		&src[(header_bytes + sectors_len)..],
+		frames * (frame_size - sector_size),
+	)?;
+
+	// And finally reassemble all frames, regenerating the sync and error correction
+	let mut hunk = vec![0; len];
+	for (frame_idx, (frame, (sector, subcode))) in hunk
+		.chunks_exact_mut(frame_size)
+		.zip(
+			sectors
+				.chunks_exact(sector_size)
+				.zip(subcode.chunks_exact(frame_size - sector_size)),
+		)
+		.enumerate()
+	{
+		frame[..sector_size].copy_from_slice(sector);
+		frame[sector_size..].copy_from_slice(subcode);
+
+		if ecc_bitmap[frame_idx / 8] & (1 << (frame_idx % 8)) != 0 {
+			let sector = <&mut [u8; 0x930]>::try_from(&mut frame[..sector_size]).expect("Sector size was wrong");
+			sector[..0xc].copy_from_slice(&Header::SYNC);
+
+			// Note: The error correction is only removed when it matches the one calculated with the
+			//       address and mode included, as in mode 1 sectors, regardless of the sector's mode.
+			let ecc = Ecc::calc_mode1(sector);
+			sector[0x81c..].copy_from_slice(&ecc.to_bytes().into_ok());
+		}
+	}
+
+	Ok(hunk)
+}
+
+/// Decompresses a cd flac hunk.
+///
+/// Unlike the other cd codecs, there's no header and the sectors are audio, so their
+/// sync and error correction is never removed.
+fn cd_flac(src: &[u8], len: usize) -> Result<Vec<u8>, DecompressError> {
+	let (frame_size, sector_size) = self::frame_sizes();
+	let frames = len / frame_size;
+
+	// Note: We ignore the subcode, as we'd need to know where the flac data ended.
+	let sectors = self::flac(src, frames * sector_size)?;
+
+	let mut hunk = vec![0; len];
+	for (frame, sector) in hunk.chunks_exact_mut(frame_size).zip(sectors.chunks_exact(sector_size)) {
+		frame[..sector_size].copy_from_slice(sector);
+	}
+
+	Ok(hunk)
+}
+
+/// Returns the frame and sector sizes
+fn frame_sizes() -> (usize, usize) {
+	let frame_size = usize::try_from(ChdReader::<()>::FRAME_SIZE).expect("Frame size didn't fit into a `usize`");
+	let sector_size = usize::try_from(ChdReader::<()>::SECTOR_SIZE).expect("Sector size didn't fit into a `usize`");
+	(frame_size, sector_size)
+}
+
+/// Decompresses raw deflate data
+fn zlib(src: &[u8], len: usize) -> Result<Vec<u8>, DecompressError> {
+	let mut bytes = vec![0; len];
+	flate2::read::DeflateDecoder::new(src)
+		.read_exact(&mut bytes)
+		.map_err(DecompressError::Zlib)?;
+	Ok(bytes)
+}
+
+/// Decompresses raw lzma data
+fn lzma(src: &[u8], len: usize) -> Result<Vec<u8>, DecompressError> {
+	use lzma_rs::decompress::raw::{LzmaDecoder, LzmaParams, LzmaProperties};
+
+	// Note: The properties are always the defaults, and the dictionary just needs to
+	//       be large enough for the whole hunk.
+	let properties = LzmaProperties { lc: 3, lp: 0, pb: 2 };
+	let dict_size = u32::try_from(usize::max(len, 0x1000)).map_err(|_| DecompressError::TooLarge)?;
+	let params = LzmaParams::new(
+		properties,
+		dict_size,
+		Some(u64::try_from(len).map_err(|_| DecompressError::TooLarge)?),
+	);
+	let mut decoder = LzmaDecoder::new(params, None).map_err(DecompressError::Lzma)?;
+
+	let mut bytes = Vec::with_capacity(len);
+	decoder
+		.decompress(&mut io::Cursor::new(src), &mut bytes)
+		.map_err(DecompressError::Lzma)?;
+	if bytes.len() != len {
+		return Err(DecompressError::WrongLen { len: bytes.len() });
+	}
+
+	Ok(bytes)
+}
+
+/// Decompresses flac frames of 16-bit stereo samples into big-endian samples.
+fn flac(src: &[u8], len: usize) -> Result<Vec<u8>, DecompressError> {
+	/// Stream header, with the stream info metadata block
+	const HEADER: [u8; 0x2a] = [
+		b'f', b'L', b'a', b'C', // Magic
+		0x80, 0x00, 0x00, 0x22, // Stream info block, last block
+		0x00, 0x00, 0x00, 0x00, // Minimum and maximum block size
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Minimum and maximum frame size, unknown
+		0x0a, 0xc4, 0x42, 0xf0, 0x00, 0x00, 0x00, 0x00, // 44100 Hz, 2 channels, 16 bits, unknown samples
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Md5
+	];
+
+	// The block size is a quarter of the length, but at most a sector
+	let (_, sector_size) = self::frame_sizes();
+	let mut block_size = len / 4;
+	while block_size > sector_size {
+		block_size /= 2;
+	}
+	let block_size = u16::try_from(block_size).expect("Block size didn't fit into a `u16`");
+
+	// Create the stream with the header, as the frames don't have it
+	let mut stream = HEADER.to_vec();
+	stream[0x8..0xa].copy_from_slice(&block_size.to_be_bytes());
+	stream[0xa..0xc].copy_from_slice(&block_size.to_be_bytes());
+	stream.extend_from_slice(src);
+
+	// Then decode all frames until we have enough samples
+	let mut reader = claxon::FlacReader::new(io::Cursor::new(stream)).map_err(DecompressError::Flac)?;
+	let mut frames = reader.blocks();
+	let mut bytes = Vec::with_capacity(len);
+	let mut buffer = vec![];
+	while bytes.len() < len {
+		let block = frames
+			.read_next_or_eof(buffer)
+			.map_err(DecompressError::Flac)?
+			.ok_or(DecompressError::TooSmall)?;
+		for (left, right) in block.stereo_samples() {
+			let left = i16::try_from(left).map_err(|_| DecompressError::FlacSample(left))?;
+			let right = i16::try_from(right).map_err(|_| DecompressError::FlacSample(right))?;
+			bytes.extend_from_slice(&left.to_be_bytes());
+			bytes.extend_from_slice(&right.to_be_bytes());
+		}
+		buffer = block.into_buffer();
+	}
+	bytes.truncate(len);
+
+	Ok(bytes)
+}
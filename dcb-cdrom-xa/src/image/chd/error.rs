@@ -0,0 +1,153 @@
+//! Errors
+
+// Imports
+use super::Codec;
+use std::io;
+
+/// Error type for [`ChdReader::new`](super::ChdReader::new)
+#[derive(Debug, thiserror::Error)]
+pub enum NewError {
+	/// Unable to read header
+	#[error("Unable to read header")]
+	ReadHeader(#[source] io::Error),
+
+	/// Wrong magic
+	#[error("Found wrong magic {_0:?}")]
+	WrongMagic([u8; 8]),
+
+	/// Unsupported version
+	#[error("Unsupported version {_0}")]
+	UnsupportedVersion(u32),
+
+	/// Image has a parent
+	#[error("Images with a parent are unsupported")]
+	HasParent,
+
+	/// Image isn't a cd
+	#[error("Image isn't a cd (hunk size: {hunk_bytes:#x}, unit size: {unit_bytes:#x})")]
+	NotCd {
+		/// Hunk size
+		hunk_bytes: u32,
+
+		/// Unit size
+		unit_bytes: u32,
+	},
+
+	/// Unable to read map
+	#[error("Unable to read map")]
+	ReadMap(#[source] ReadMapError),
+}
+
+/// Error type for [`map::read`](super::map::read)
+#[derive(Debug, thiserror::Error)]
+pub enum ReadMapError {
+	/// Unable to seek to map
+	#[error("Unable to seek to map")]
+	Seek(#[source] io::Error),
+
+	/// Unable to read map
+	#[error("Unable to read map")]
+	Read(#[source] io::Error),
+
+	/// Invalid huffman tree
+	#[error("Invalid huffman tree")]
+	HuffmanTree,
+
+	/// Unknown compression type
+	#[error("Unknown compression type {_0}")]
+	UnknownCompression(u8),
+
+	/// Wrong crc
+	#[error("Found wrong crc {found:#x}, calculated {calculated:#x}")]
+	WrongCrc {
+		/// Found crc
+		found: u16,
+
+		/// Calculated crc
+		calculated: u16,
+	},
+}
+
+/// Error type for [`ChdReader::read_hunk`](super::ChdReader::read_hunk)
+#[derive(Debug, thiserror::Error)]
+pub enum ReadHunkError {
+	/// Hunk was out of bounds
+	#[error("Hunk {_0} was out of bounds")]
+	OutOfBounds(usize),
+
+	/// Unable to seek to hunk
+	#[error("Unable to seek to hunk")]
+	Seek(#[source] io::Error),
+
+	/// Unable to read hunk
+	#[error("Unable to read hunk")]
+	Read(#[source] io::Error),
+
+	/// Unsupported codec
+	#[error("Unsupported codec {_0:?}")]
+	UnsupportedCodec(String),
+
+	/// Unable to decompress hunk
+	#[error("Unable to decompress hunk with {codec:?}")]
+	Decompress {
+		/// Codec
+		codec: Codec,
+
+		/// Underlying error
+		#[source]
+		err: DecompressError,
+	},
+
+	/// Hunk referenced an invalid hunk
+	#[error("Hunk referenced invalid hunk {_0}")]
+	InvalidSelfHunk(u64),
+
+	/// Hunk was in the parent
+	#[error("Hunk was in the parent image")]
+	ParentHunk,
+
+	/// Wrong crc
+	#[error("Found wrong crc {found:#x}, calculated {calculated:#x}")]
+	WrongCrc {
+		/// Found crc
+		found: u16,
+
+		/// Calculated crc
+		calculated: u16,
+	},
+}
+
+/// Error type for [`Codec::decompress`]
+#[derive(Debug, thiserror::Error)]
+pub enum DecompressError {
+	/// Compressed data was too small
+	#[error("Compressed data was too small")]
+	TooSmall,
+
+	/// Decompressed data was too large
+	#[error("Decompressed data was too large")]
+	TooLarge,
+
+	/// Decompressed data had the wrong length
+	#[error("Decompressed data had the wrong length {len:#x}")]
+	WrongLen {
+		/// Length
+		len: usize,
+	},
+
+	/// Unable to decompress deflate data
+	#[error("Unable to decompress deflate data")]
+	Zlib(#[source] io::Error),
+
+	/// Unable to decompress lzma data
+	#[error("Unable to decompress lzma data")]
+	Lzma(#[source] lzma_rs::error::Error),
+
+	/// Unable to decompress flac data
+	#[error("Unable to decompress flac data")]
+	Flac(#[source] claxon::Error),
+
+	/// Flac sample wasn't 16-bit
+	#[error("Flac sample {_0} wasn't 16-bit")]
+	FlacSample(i32),
+}
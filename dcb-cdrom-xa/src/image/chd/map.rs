@@ -0,0 +1,378 @@
+//! Hunk map
+
+// Imports
+use super::{error::ReadMapError, Header};
+use byteorder::{BigEndian, ByteOrder};
+use std::{
+	convert::TryFrom,
+	io::{Read, Seek, SeekFrom},
+};
+
+/// A hunk map entry
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct MapEntry {
+	/// Compression
+	pub compression: Compression,
+
+	/// Compressed length
+	pub len: u32,
+
+	/// Offset, or hunk for [`Compression::SelfHunk`]
+	pub offset: u64,
+
+	/// Crc16 of the decompressed hunk
+	pub crc: Option<u16>,
+}
+
+/// Hunk compression
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Compression {
+	/// Compressed with the `n`th codec
+	Codec(usize),
+
+	/// Uncompressed
+	None,
+
+	/// Same as another hunk in this file
+	SelfHunk,
+
+	/// Same as another hunk in the parent file
+	Parent,
+
+	/// Not stored, filled with zeros
+	Zero,
+}
+
+/// Map entry compression types
+mod ty {
+	/// Compressed with codec 0
+	pub const CODEC_0: u8 = 0;
+	/// Compressed with codec 3
+	pub const CODEC_3: u8 = 3;
+	/// Uncompressed
+	pub const NONE: u8 = 4;
+	/// Same as other hunk
+	pub const SELF: u8 = 5;
+	/// Same as parent hunk
+	pub const PARENT: u8 = 6;
+	/// Repeat last compression type a small number of times
+	pub const RLE_SMALL: u8 = 7;
+	/// Repeat last compression type a large number of times
+	pub const RLE_LARGE: u8 = 8;
+	/// Same as the last self hunk
+	pub const SELF_0: u8 = 9;
+	/// Same as the last self hunk plus 1
+	pub const SELF_1: u8 = 10;
+	/// Same as the parent hunk at the same position
+	pub const PARENT_SELF: u8 = 11;
+	/// Same as the last parent hunk
+	pub const PARENT_0: u8 = 12;
+	/// Same as the last parent hunk plus 1
+	pub const PARENT_1: u8 = 13;
+}
+
+/// Reads the hunk map
+pub fn read<R: Read + Seek>(reader: &mut R, header: &Header) -> Result<Vec<MapEntry>, ReadMapError> {
+	let hunks_len = header.hunks_len();
+	reader
+		.seek(SeekFrom::Start(header.map_offset))
+		.map_err(ReadMapError::Seek)?;
+
+	// If we're uncompressed, each entry is just the offset, in hunks
+	if header.compressors[0] == 0 {
+		let mut bytes = vec![0; hunks_len * 4];
+		reader.read_exact(&mut bytes).map_err(ReadMapError::Read)?;
+
+		let entries = bytes
+			.chunks_exact(4)
+			.map(|bytes| match BigEndian::read_u32(bytes) {
+				0 => MapEntry {
+					compression: Compression::Zero,
+					len:         0,
+					offset:      0,
+					crc:         None,
+				},
+				offset => MapEntry {
+					compression: Compression::None,
+					len:         header.hunk_bytes,
+					offset:      u64::from(offset) * u64::from(header.hunk_bytes),
+					crc:         None,
+				},
+			})
+			.collect();
+		return Ok(entries);
+	}
+
+	// Else read the map header and the compressed map
+	let mut map_header = [0; 0x10];
+	reader.read_exact(&mut map_header).map_err(ReadMapError::Read)?;
+	let map_header = zutil::array_split!(&map_header,
+		map_len    : [0x4],
+		first_offs : [0x6],
+		map_crc    : [0x2],
+		len_bits   :  0x1 ,
+		self_bits  :  0x1 ,
+		parent_bits:  0x1 ,
+		_reserved  :  0x1 ,
+	);
+	let map_len = usize::try_from(BigEndian::read_u32(map_header.map_len)).expect("`u32` didn't fit into a `usize`");
+	let first_offs = BigEndian::read_u48(map_header.first_offs);
+	let map_crc = BigEndian::read_u16(map_header.map_crc);
+
+	let mut map = vec![0; map_len];
+	reader.read_exact(&mut map).map_err(ReadMapError::Read)?;
+	let mut bits = BitReader::new(&map);
+
+	// Decode all compression types
+	let huffman = Huffman::new(&mut bits)?;
+	let mut compressions = Vec::with_capacity(hunks_len);
+	let mut last_compression = 0;
+	let mut repeat_count = 0;
+	while compressions.len() < hunks_len {
+		if repeat_count > 0 {
+			compressions.push(last_compression);
+			repeat_count -= 1;
+			continue;
+		}
+
+		match huffman.decode(&mut bits) {
+			ty::RLE_SMALL => {
+				compressions.push(last_compression);
+				repeat_count = 2 + usize::from(huffman.decode(&mut bits));
+			},
+			ty::RLE_LARGE => {
+				compressions.push(last_compression);
+				repeat_count = 2 + 16 + (usize::from(huffman.decode(&mut bits)) << 4usize);
+				repeat_count += usize::from(huffman.decode(&mut bits));
+			},
+			compression => {
+				compressions.push(compression);
+				last_compression = compression;
+			},
+		}
+	}
+
+	// Then decode the lengths, offsets and crcs
+	let mut entries = Vec::with_capacity(hunks_len);
+	let mut raw_map = Vec::with_capacity(hunks_len * 12);
+	let mut cur_offset = first_offs;
+	let mut last_self = 0;
+	let mut last_parent = 0;
+	for (hunk_idx, &compression) in compressions.iter().enumerate() {
+		let (raw_ty, len, offset, crc) = match compression {
+			ty::CODEC_0..=ty::CODEC_3 | ty::NONE => {
+				let len = match compression {
+					ty::NONE => header.hunk_bytes,
+					_ => bits.read(*map_header.len_bits),
+				};
+				let offset = cur_offset;
+				cur_offset += u64::from(len);
+				let crc = bits.read_u16();
+				(compression, len, offset, crc)
+			},
+			ty::SELF => {
+				last_self = u64::from(bits.read(*map_header.self_bits));
+				(ty::SELF, 0, last_self, 0)
+			},
+			ty::PARENT => {
+				last_parent = u64::from(bits.read(*map_header.parent_bits));
+				(ty::PARENT, 0, last_parent, 0)
+			},
+			ty::SELF_0 | ty::SELF_1 => {
+				if compression == ty::SELF_1 {
+					last_self += 1;
+				}
+				(ty::SELF, 0, last_self, 0)
+			},
+			ty::PARENT_SELF => {
+				let hunk_idx = u64::try_from(hunk_idx).expect("`usize` didn't fit into a `u64`");
+				last_parent = hunk_idx * u64::from(header.hunk_bytes) / u64::from(header.unit_bytes);
+				(ty::PARENT, 0, last_parent, 0)
+			},
+			ty::PARENT_0 | ty::PARENT_1 => {
+				if compression == ty::PARENT_1 {
+					last_parent += u64::from(header.hunk_bytes / header.unit_bytes);
+				}
+				(ty::PARENT, 0, last_parent, 0)
+			},
+			compression => return Err(ReadMapError::UnknownCompression(compression)),
+		};
+
+		// Note: The crc is calculated over the raw map, with 24-bit lengths, 48-bit offsets and 16-bit crcs
+		raw_map.push(raw_ty);
+		raw_map.extend_from_slice(&len.to_be_bytes()[1..]);
+		raw_map.extend_from_slice(&offset.to_be_bytes()[2..]);
+		raw_map.extend_from_slice(&crc.to_be_bytes());
+
+		let compression = match raw_ty {
+			ty::NONE => Compression::None,
+			ty::SELF => Compression::SelfHunk,
+			ty::PARENT => Compression::Parent,
+			codec => Compression::Codec(usize::from(codec)),
+		};
+		let crc = matches!(compression, Compression::Codec(_) | Compression::None).then(|| crc);
+		entries.push(MapEntry {
+			compression,
+			len,
+			offset,
+			crc,
+		});
+	}
+
+	// Finally check the crc
+	let calculated_crc = self::crc16(&raw_map);
+	if calculated_crc != map_crc {
+		return Err(ReadMapError::WrongCrc {
+			found:      map_crc,
+			calculated: calculated_crc,
+		});
+	}
+
+	Ok(entries)
+}
+
+/// Calculates the crc16 (`CCITT`) of `bytes`
+pub fn crc16(bytes: &[u8]) -> u16 {
+	let mut crc = 0xffffu16;
+	for &byte in bytes {
+		crc ^= u16::from(byte) << 8u16;
+		for _ in 0..8 {
+			crc = match crc & 0x8000 != 0 {
+				true => (crc << 1u16) ^ 0x1021,
+				false => crc << 1u16,
+			};
+		}
+	}
+
+	crc
+}
+
+/// Big-endian bit reader
+#[derive(Debug)]
+struct BitReader<'a> {
+	/// Bytes
+	bytes: &'a [u8],
+
+	/// Current bit position
+	pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+	/// Creates a new bit reader
+	const fn new(bytes: &'a [u8]) -> Self {
+		Self { bytes, pos: 0 }
+	}
+
+	/// Peeks the next `count` bits, up to 32.
+	///
+	/// Any bits past the end are read as zero.
+	fn peek(&self, count: u8) -> u32 {
+		let mut value = 0;
+		for n in 0..usize::from(count) {
+			let pos = self.pos + n;
+			let bit = self.bytes.get(pos / 8).map_or(0, |byte| (byte >> (7 - pos % 8)) & 1);
+			value = (value << 1u32) | u32::from(bit);
+		}
+
+		value
+	}
+
+	/// Reads the next `count` bits, up to 32.
+	fn read(&mut self, count: u8) -> u32 {
+		let value = self.peek(count);
+		self.pos += usize::from(count);
+		value
+	}
+
+	/// Reads the next 16 bits
+	fn read_u16(&mut self) -> u16 {
+		u16::try_from(self.read(16)).expect("16 bits didn't fit into a `u16`")
+	}
+}
+
+/// Huffman decoder for the map compression types
+#[derive(Debug)]
+struct Huffman {
+	/// Lookup table, indexed by the next `MAX_BITS` bits, containing the code and it's length
+	lookup: Vec<(u8, u8)>,
+}
+
+impl Huffman {
+	/// Number of codes
+	const CODES: usize = 16;
+	/// Maximum number of bits per code
+	const MAX_BITS: u8 = 8;
+
+	/// Imports a huffman tree encoded with run-length encoding
+	#[allow(clippy::as_conversions, clippy::cast_possible_truncation)] // All lengths are checked to be `<= MAX_BITS`
+	fn new(bits: &mut BitReader) -> Result<Self, ReadMapError> {
+		// Read the bit lengths of each code
+		let mut code_bits = Vec::with_capacity(Self::CODES);
+		while code_bits.len() < Self::CODES {
+			// Note: A length of `1` is used as an escape, followed by `1` for itself,
+			//       or the length and repeat count.
+			match bits.read(4) {
+				1 => match bits.read(4) {
+					1 => code_bits.push(1),
+					len => {
+						let repeat_count = bits.read(4) + 3;
+						for _ in 0..repeat_count {
+							code_bits.push(len);
+						}
+					},
+				},
+				len => code_bits.push(len),
+			}
+		}
+		if code_bits.len() != Self::CODES {
+			return Err(ReadMapError::HuffmanTree);
+		}
+
+		// Then assign canonical codes
+		let mut bits_histogram = [0u32; 33];
+		for &len in &code_bits {
+			if len > u32::from(Self::MAX_BITS) {
+				return Err(ReadMapError::HuffmanTree);
+			}
+			bits_histogram[len as usize] += 1;
+		}
+		let mut cur_start = 0;
+		for len in (1..=32).rev() {
+			let next_start = (cur_start + bits_histogram[len]) >> 1u32;
+			if len != 1 && next_start * 2 != cur_start + bits_histogram[len] {
+				return Err(ReadMapError::HuffmanTree);
+			}
+			bits_histogram[len] = cur_start;
+			cur_start = next_start;
+		}
+
+		// And build the lookup table
+		let mut lookup = vec![(0, 0); 1 << Self::MAX_BITS];
+		for (code, &len) in code_bits.iter().enumerate() {
+			if len == 0 {
+				continue;
+			}
+
+			let bits = bits_histogram[len as usize];
+			bits_histogram[len as usize] += 1;
+
+			let shift = u32::from(Self::MAX_BITS) - len;
+			let start = (bits << shift) as usize;
+			let end = ((bits + 1) << shift) as usize;
+			lookup
+				.get_mut(start..end)
+				.ok_or(ReadMapError::HuffmanTree)?
+				.fill((code as u8, len as u8));
+		}
+
+		Ok(Self { lookup })
+	}
+
+	/// Decodes the next code
+	#[allow(clippy::as_conversions)] // `MAX_BITS` bits always fit into a `usize`
+	fn decode(&self, bits: &mut BitReader) -> u8 {
+		let (code, len) = self.lookup[bits.peek(Self::MAX_BITS) as usize];
+		bits.pos += usize::from(len);
+		code
+	}
+}
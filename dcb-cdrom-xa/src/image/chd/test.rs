@@ -0,0 +1,163 @@
+//! Tests
+
+// Imports
+use super::*;
+use crate::{
+	sector::{header::SubHeader, Ecc},
+	Sector,
+};
+use dcb_bytes::Bytes;
+use std::io::Write;
+
+/// Compresses `bytes` with raw deflate
+fn deflate(bytes: &[u8]) -> Vec<u8> {
+	let mut encoder = flate2::write::DeflateEncoder::new(vec![], flate2::Compression::default());
+	encoder.write_all(bytes).expect("Unable to compress");
+	encoder.finish().expect("Unable to compress")
+}
+
+/// Compresses `bytes` with raw lzma
+fn lzma(bytes: &[u8]) -> Vec<u8> {
+	let mut compressed = vec![];
+	lzma_rs::lzma_compress(&mut io::Cursor::new(bytes), &mut compressed).expect("Unable to compress");
+
+	// Note: The `.lzma` header has the properties, dictionary size and uncompressed size,
+	//       none of which are stored in the hunk.
+	compressed.split_off(13)
+}
+
+/// Creates a cd hunk of 2 frames, with the sectors compressed by `compress_sectors`.
+///
+/// The first frame has it's sync and error correction removed, while the second one has it stored.
+///
+/// Returns the compressed hunk and the expected hunk.
+fn cd_hunk(compress_sectors: fn(&[u8]) -> Vec<u8>) -> (Vec<u8>, Vec<u8>) {
+	// Note: The error correction is only removed when it's calculated as if it was a mode 1 sector.
+	let mut sector = Sector::new([0x12; 0x800], 75 * 2, SubHeader::new())
+		.expect("Unable to create sector")
+		.to_bytes()
+		.expect("Unable to serialize sector");
+	let ecc = Ecc::calc_mode1(&sector);
+	sector[0x81c..].copy_from_slice(&ecc.to_bytes().into_ok());
+
+	let mut stripped_sector = sector;
+	stripped_sector[..0xc].fill(0);
+	stripped_sector[0x81c..].fill(0);
+
+	let raw_sector = [0x34; 0x930];
+	let subcode = [0x56; 0x60];
+
+	// Create the expected hunk
+	let mut hunk = vec![];
+	for sector in vec![&sector, &raw_sector] {
+		hunk.extend_from_slice(sector);
+		hunk.extend_from_slice(&subcode);
+	}
+
+	// Then compress it, with the bitmap of frames with their error correction removed and the sectors length
+	let sectors = compress_sectors(&[stripped_sector.as_slice(), &raw_sector].concat());
+	let sectors_len = u16::try_from(sectors.len()).expect("Compressed sectors were too large");
+	let mut src = vec![0b01];
+	src.extend_from_slice(&sectors_len.to_be_bytes());
+	src.extend_from_slice(&sectors);
+	src.extend_from_slice(&self::deflate(&[subcode; 2].concat()));
+
+	(src, hunk)
+}
+
+#[test]
+fn decompress_zlib() {
+	let hunk = (0..0x990u32).map(|idx| idx.to_le_bytes()[0]).collect::<Vec<_>>();
+	let src = self::deflate(&hunk);
+	assert_eq!(
+		Codec::Zlib.decompress(&src, hunk.len()).expect("Unable to decompress"),
+		hunk
+	);
+}
+
+#[test]
+fn decompress_lzma() {
+	let hunk = (0..0x990u32).map(|idx| idx.to_le_bytes()[0]).collect::<Vec<_>>();
+	let src = self::lzma(&hunk);
+	assert_eq!(
+		Codec::Lzma.decompress(&src, hunk.len()).expect("Unable to decompress"),
+		hunk
+	);
+}
+
+#[test]
+fn decompress_cd_zlib() {
+	let (src, hunk) = self::cd_hunk(self::deflate);
+	assert_eq!(
+		Codec::CdZlib
+			.decompress(&src, hunk.len())
+			.expect("Unable to decompress"),
+		hunk
+	);
+}
+
+#[test]
+fn decompress_cd_lzma() {
+	let (src, hunk) = self::cd_hunk(self::lzma);
+	assert_eq!(
+		Codec::CdLzma
+			.decompress(&src, hunk.len())
+			.expect("Unable to decompress"),
+		hunk
+	);
+}
+
+#[test]
+fn decompress_cd_flac() {
+	// A single flac frame of a sector's worth of samples, with a constant subframe for each channel
+	let src = [
+		0xff, 0xf8, 0x70, 0x18, 0x00, 0x02, 0x4b, 0xa1, // Header, 588 samples, 2 channels, 16 bits, crc8
+		0x00, 0x12, 0x34, // Left channel, constant `0x1234`
+		0x00, 0xed, 0xcc, // Right channel, constant `-0x1234`
+		0x2f, 0x47, // Crc16
+	];
+
+	// Note: The subcode is ignored, so it's left as zeroes.
+	let mut hunk = [0x12, 0x34, 0xed, 0xcc].repeat(0x930 / 4);
+	hunk.resize(0x990, 0);
+	assert_eq!(
+		Codec::CdFlac
+			.decompress(&src, hunk.len())
+			.expect("Unable to decompress"),
+		hunk
+	);
+}
+
+#[test]
+fn read_uncompressed() {
+	// Create an uncompressed image of 2 hunks, with the first one being all zeroes.
+	let hunk_bytes = 2 * ChdReader::<()>::FRAME_SIZE;
+	let (_, hunk) = self::cd_hunk(self::deflate);
+
+	let mut header = [0; Header::V5_SIZE];
+	header[..0x8].copy_from_slice(&Header::MAGIC);
+	BigEndian::write_u32(&mut header[0x8..0xc], 0x7c);
+	BigEndian::write_u32(&mut header[0xc..0x10], 5);
+	BigEndian::write_u64(&mut header[0x20..0x28], 2 * u64::from(hunk_bytes));
+	BigEndian::write_u64(&mut header[0x28..0x30], 0x7c);
+	BigEndian::write_u32(&mut header[0x38..0x3c], hunk_bytes);
+	BigEndian::write_u32(&mut header[0x3c..0x40], ChdReader::<()>::FRAME_SIZE);
+
+	// Note: Uncompressed maps store the offset of each hunk in hunks, where `0` is a zeroed hunk
+	let mut image = header.to_vec();
+	image.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+	image.resize(
+		usize::try_from(hunk_bytes).expect("Hunk size didn't fit into a `usize`"),
+		0,
+	);
+	image.extend_from_slice(&hunk);
+
+	let mut reader = ChdReader::new(io::Cursor::new(image)).expect("Unable to create reader");
+	assert_eq!(reader.len(), 4 * 0x930);
+
+	let mut bytes = vec![];
+	reader.read_to_end(&mut bytes).expect("Unable to read image");
+	assert!(bytes[..2 * 0x930].iter().all(|&byte| byte == 0));
+	assert_eq!(bytes[2 * 0x930..3 * 0x930], hunk[..0x930]);
+	assert_eq!(bytes[3 * 0x930..], hunk[0x990..0x990 + 0x930]);
+}
@@ -0,0 +1,304 @@
+//! Ecm images
+//!
+//! Ecm images strip the sync, error detection and error correction from each sector,
+//! as they may be regenerated from the rest of the sector.
+//!
+//! # Layout
+//! The image starts with the magic `ECM\0`, followed by several records, each with a
+//! type and count, and then the stored bytes. It ends with a record with a count
+//! of `0xFFFFFFFF`, and the error detection of the whole decoded image.
+//!
+//! The record types are the following:
+//!
+//! | Type | Stored bytes            | Decoded bytes                   |
+//! | ---- | ----------------------- | ------------------------------- |
+//! | 0    | Raw bytes               | Raw bytes                       |
+//! | 1    | Address, data           | Mode 1 sector                   |
+//! | 2    | Subheader, data         | Mode 2 form 1 sector, from 0x10 |
+//! | 3    | Subheader, data         | Mode 2 form 2 sector, from 0x10 |
+//!
+//! Mode 2 sectors are decoded from their subheader onwards, as the headers are stored
+//! as raw bytes before them.
+
+// Modules
+mod error;
+#[cfg(test)]
+mod test;
+
+// Exports
+pub use error::{DecodeSectorError, NewError};
+
+// Imports
+use crate::sector::{header::Header, Ecc, Edc};
+use dcb_bytes::Bytes;
+use std::{
+	convert::TryFrom,
+	io::{self, Read, Seek, SeekFrom},
+};
+
+/// Ecm image reader.
+///
+/// Decodes the image lazily, by indexing all records on creation.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct EcmReader<R> {
+	/// Underlying reader
+	reader: R,
+
+	/// All records
+	records: Vec<Record>,
+
+	/// Decoded length
+	len: u64,
+
+	/// Current position
+	pos: u64,
+
+	/// Last decoded sector, with it's decoded position
+	cached_sector: Option<(u64, Vec<u8>)>,
+}
+
+impl<R> EcmReader<R> {
+	/// Magic
+	pub const MAGIC: [u8; 4] = *b"ECM\0";
+
+	/// Returns the decoded length of the image
+	#[must_use]
+	pub const fn len(&self) -> u64 {
+		self.len
+	}
+
+	/// Returns if the decoded image is empty
+	#[must_use]
+	pub const fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+}
+
+impl<R: Read + Seek> EcmReader<R> {
+	/// Creates a new ecm reader, indexing all records.
+	pub fn new(mut reader: R) -> Result<Self, NewError> {
+		// Check the magic
+		let mut magic = [0; 4];
+		reader.read_exact(&mut magic).map_err(NewError::ReadMagic)?;
+		if magic != Self::MAGIC {
+			return Err(NewError::WrongMagic(magic));
+		}
+
+		// Then index all records
+		let mut records = vec![];
+		let mut len = 0;
+		while let Some((kind, count)) = self::read_record_header(&mut reader)? {
+			let pos = reader.stream_position().map_err(NewError::Seek)?;
+			records.push(Record { pos, len, kind, count });
+			len += count * kind.len();
+
+			let stored_len = i64::try_from(count * kind.stored_len()).map_err(|_| NewError::RecordTooLarge)?;
+			reader.seek(SeekFrom::Current(stored_len)).map_err(NewError::Seek)?;
+		}
+
+		Ok(Self {
+			reader,
+			records,
+			len,
+			pos: 0,
+			cached_sector: None,
+		})
+	}
+
+	/// Reads from the current position within `record`
+	fn read_record(&mut self, record: Record, buf: &mut [u8]) -> Result<usize, io::Error> {
+		let offset = self.pos - record.len;
+
+		// If it's raw, just read it
+		if record.kind == RecordKind::Raw {
+			let remaining = usize::try_from(record.count - offset).unwrap_or(usize::MAX);
+			let buf_len = usize::min(buf.len(), remaining);
+			self.reader.seek(SeekFrom::Start(record.pos + offset))?;
+			return self.reader.read(&mut buf[..buf_len]);
+		}
+
+		// Else decode the sector, if it isn't cached
+		let sector_idx = offset / record.kind.len();
+		let sector_pos = record.len + sector_idx * record.kind.len();
+		let is_cached = matches!(&self.cached_sector, Some((pos, _)) if *pos == sector_pos);
+		if !is_cached {
+			let mut stored = vec![0; usize::try_from(record.kind.stored_len()).expect("Stored length didn't fit")];
+			self.reader
+				.seek(SeekFrom::Start(record.pos + sector_idx * record.kind.stored_len()))?;
+			self.reader.read_exact(&mut stored)?;
+
+			let sector = self::decode_sector(record.kind, &stored)
+				.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+			self.cached_sector = Some((sector_pos, sector));
+		}
+		let (_, sector) = self.cached_sector.as_ref().expect("Sector was just cached");
+
+		let sector_offset = usize::try_from(offset % record.kind.len()).expect("Sector offset didn't fit");
+		let buf_len = usize::min(buf.len(), sector.len() - sector_offset);
+		buf[..buf_len].copy_from_slice(&sector[sector_offset..(sector_offset + buf_len)]);
+		Ok(buf_len)
+	}
+}
+
+impl<R: Read + Seek> Read for EcmReader<R> {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+		// Find the record we're in, if any
+		let record_idx = self.records.partition_point(|record| record.len <= self.pos);
+		let record = match record_idx.checked_sub(1).map(|idx| self.records[idx]) {
+			Some(record) if self.pos < record.len + record.count * record.kind.len() => record,
+			_ => return Ok(0),
+		};
+
+		let bytes_read = self.read_record(record, buf)?;
+		self.pos += u64::try_from(bytes_read).expect("Bytes read didn't fit into a `u64`");
+		Ok(bytes_read)
+	}
+}
+
+impl<R: Read + Seek> Seek for EcmReader<R> {
+	fn seek(&mut self, pos: SeekFrom) -> Result<u64, io::Error> {
+		self.pos = match pos {
+			SeekFrom::Start(pos) => pos,
+			SeekFrom::End(offset) => zutil::signed_offset(self.len, offset),
+			SeekFrom::Current(offset) => zutil::signed_offset(self.pos, offset),
+		};
+
+		Ok(self.pos)
+	}
+}
+
+/// A record
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+struct Record {
+	/// Position of the stored bytes
+	pos: u64,
+
+	/// Decoded position
+	len: u64,
+
+	/// Kind
+	kind: RecordKind,
+
+	/// Count
+	count: u64,
+}
+
+/// Record kind
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum RecordKind {
+	/// Raw bytes
+	Raw,
+
+	/// Mode 1 sectors
+	Mode1,
+
+	/// Mode 2 form 1 sectors
+	Mode2Form1,
+
+	/// Mode 2 form 2 sectors
+	Mode2Form2,
+}
+
+impl RecordKind {
+	/// Returns the stored length of each element of this record
+	const fn stored_len(self) -> u64 {
+		match self {
+			Self::Raw => 0x1,
+			Self::Mode1 => 0x803,
+			Self::Mode2Form1 => 0x804,
+			Self::Mode2Form2 => 0x918,
+		}
+	}
+
+	/// Returns the decoded length of each element of this record
+	const fn len(self) -> u64 {
+		match self {
+			Self::Raw => 0x1,
+			Self::Mode1 => 0x930,
+			Self::Mode2Form1 | Self::Mode2Form2 => 0x920,
+		}
+	}
+}
+
+/// Reads a record header, returning `None` if it's the last
+fn read_record_header<R: Read>(reader: &mut R) -> Result<Option<(RecordKind, u64)>, NewError> {
+	let mut read_byte = || -> Result<u8, NewError> {
+		let mut byte = [0; 1];
+		reader.read_exact(&mut byte).map_err(NewError::ReadRecordHeader)?;
+		Ok(byte[0])
+	};
+
+	// The first byte contains the kind and the first 5 bits of the count, and
+	// all bytes contain a flag indicating if there's a next byte.
+	let mut byte = read_byte()?;
+	let kind = match byte & 0x3 {
+		0 => RecordKind::Raw,
+		1 => RecordKind::Mode1,
+		2 => RecordKind::Mode2Form1,
+		_ => RecordKind::Mode2Form2,
+	};
+	let mut count = u32::from((byte >> 2u8) & 0x1f);
+	let mut bits = 5;
+	while byte & 0x80 != 0 {
+		if bits > 31 {
+			return Err(NewError::RecordCountTooLarge);
+		}
+
+		byte = read_byte()?;
+		count |= u32::from(byte & 0x7f) << bits;
+		bits += 7;
+	}
+
+	match count {
+		0xFFFFFFFF => Ok(None),
+		count if count >= 0x7FFFFFFF => Err(NewError::RecordCountTooLarge),
+		count => Ok(Some((kind, u64::from(count) + 1))),
+	}
+}
+
+/// Decodes a sector
+fn decode_sector(kind: RecordKind, stored: &[u8]) -> Result<Vec<u8>, DecodeSectorError> {
+	let mut sector = [0; 0x930];
+	match kind {
+		RecordKind::Raw => return Err(DecodeSectorError::NotSector),
+
+		RecordKind::Mode1 => {
+			sector[..0xc].copy_from_slice(&Header::SYNC);
+			sector[0xc..0xf].copy_from_slice(&stored[..0x3]);
+			sector[0xf] = 1;
+			sector[0x10..0x810].copy_from_slice(&stored[0x3..]);
+
+			let edc = Edc::calc_ecc(&sector[..0x810]).to_bytes().into_ok();
+			sector[0x810..0x814].copy_from_slice(&edc);
+
+			let ecc = Ecc::calc_mode1(&sector).to_bytes().into_ok();
+			sector[0x81c..].copy_from_slice(&ecc);
+
+			Ok(sector.to_vec())
+		},
+
+		// Note: For mode 2, only the second copy of the subheader is stored
+		RecordKind::Mode2Form1 => {
+			sector[0x14..0x818].copy_from_slice(stored);
+			sector.copy_within(0x14..0x18, 0x10);
+
+			let edc = Edc::calc_ecc(&sector[0x10..0x818]).to_bytes().into_ok();
+			sector[0x818..0x81c].copy_from_slice(&edc);
+
+			let ecc = Ecc::calc(&sector).to_bytes().into_ok();
+			sector[0x81c..].copy_from_slice(&ecc);
+
+			Ok(sector[0x10..].to_vec())
+		},
+
+		RecordKind::Mode2Form2 => {
+			sector[0x14..0x92c].copy_from_slice(stored);
+			sector.copy_within(0x14..0x18, 0x10);
+
+			let edc = Edc::calc_ecc(&sector[0x10..0x92c]).to_bytes().into_ok();
+			sector[0x92c..].copy_from_slice(&edc);
+
+			Ok(sector[0x10..].to_vec())
+		},
+	}
+}
@@ -0,0 +1,40 @@
+//! Errors
+
+// Imports
+use std::io;
+
+/// Error type for [`EcmReader::new`](super::EcmReader::new)
+#[derive(Debug, thiserror::Error)]
+pub enum NewError {
+	/// Unable to read magic
+	#[error("Unable to read magic")]
+	ReadMagic(#[source] io::Error),
+
+	/// Wrong magic
+	#[error("Found wrong magic {_0:?}")]
+	WrongMagic([u8; 4]),
+
+	/// Unable to read record header
+	#[error("Unable to read record header")]
+	ReadRecordHeader(#[source] io::Error),
+
+	/// Record count was too large
+	#[error("Record count was too large")]
+	RecordCountTooLarge,
+
+	/// Record was too large
+	#[error("Record was too large")]
+	RecordTooLarge,
+
+	/// Unable to seek past record
+	#[error("Unable to seek past record")]
+	Seek(#[source] io::Error),
+}
+
+/// Error type for [`decode_sector`](super::decode_sector)
+#[derive(PartialEq, Eq, Clone, Copy, Debug, thiserror::Error)]
+pub enum DecodeSectorError {
+	/// Record wasn't a sector
+	#[error("Record wasn't a sector")]
+	NotSector,
+}
@@ -0,0 +1,31 @@
+//! Tests
+
+// Imports
+use super::*;
+use crate::{sector::header::SubHeader, CdRomReader, Sector};
+
+#[test]
+fn decode_form1() {
+	let sector = Sector::new([0x12; 0x800], 75 * 2, SubHeader::new()).expect("Unable to create sector");
+	let sector_bytes = sector.to_bytes().expect("Unable to serialize sector");
+
+	// Note: The sync, address and mode of mode 2 sectors are stored as raw bytes
+	let mut ecm = EcmReader::<()>::MAGIC.to_vec();
+	ecm.push(0xf << 2);
+	ecm.extend_from_slice(&sector_bytes[..0x10]);
+	ecm.push(0x2);
+	ecm.extend_from_slice(&sector_bytes[0x14..0x818]);
+	ecm.extend_from_slice(&[0xfc, 0xff, 0xff, 0xff, 0x3f]);
+	ecm.extend_from_slice(&[0; 4]);
+
+	let mut reader = EcmReader::new(io::Cursor::new(ecm)).expect("Unable to create reader");
+	assert_eq!(reader.len(), 0x930);
+
+	let mut bytes = vec![];
+	reader.read_to_end(&mut bytes).expect("Unable to read image");
+	assert_eq!(bytes, sector_bytes);
+
+	reader.seek(SeekFrom::Start(0)).expect("Unable to seek");
+	let mut reader = CdRomReader::new(reader);
+	assert_eq!(reader.read_nth_sector(0).expect("Unable to read sector"), sector);
+}
@@ -0,0 +1,21 @@
+//! Errors
+
+// Imports
+use super::{chd, ecm};
+use std::io;
+
+/// Error type for [`ImageReader::new`](super::ImageReader::new)
+#[derive(Debug, thiserror::Error)]
+pub enum NewError {
+	/// Unable to read magic
+	#[error("Unable to read magic")]
+	ReadMagic(#[source] io::Error),
+
+	/// Unable to create ecm reader
+	#[error("Unable to create ecm reader")]
+	Ecm(#[source] ecm::NewError),
+
+	/// Unable to create chd reader
+	#[error("Unable to create chd reader")]
+	Chd(#[source] chd::NewError),
+}
@@ -1,15 +1,25 @@
 #![doc = include_str!("lib.md")]
 // Features
-#![feature(never_type, unwrap_infallible, exclusive_range_pattern, assert_matches)]
+#![feature(
+	never_type,
+	unwrap_infallible,
+	exclusive_range_pattern,
+	assert_matches,
+	seek_stream_len
+)]
 
 // Modules
+pub mod cue;
 pub mod cursor;
+pub mod image;
 pub mod reader;
 pub mod sector;
 pub mod writer;
 
 // Exports
+pub use cue::CueSheet;
 pub use cursor::CdRomCursor;
+pub use image::ImageReader;
 pub use reader::CdRomReader;
 pub use sector::Sector;
 pub use writer::CdRomWriter;
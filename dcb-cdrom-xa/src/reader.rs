@@ -13,7 +13,7 @@ use crate::Sector;
 use dcb_bytes::Bytes;
 use std::io::{Read, Seek, SeekFrom};
 
-/// A CD-ROM/XA Mode 2 Form 1 reader.
+/// A CD-ROM/XA Mode 2 reader.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Debug)]
 pub struct CdRomReader<R> {
 	/// Underlying reader
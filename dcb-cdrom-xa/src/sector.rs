@@ -1,7 +1,5 @@
 #![doc = include_str!("sector.md")]
 
-// TODO: Even with the form bit set, the data seems to only be 2048 bytes anyway, check what's happening
-
 // Modules
 pub mod ecc;
 pub mod edc;
@@ -11,7 +9,7 @@ pub mod header;
 // Exports
 pub use ecc::Ecc;
 pub use edc::Edc;
-pub use error::{DeserializeBytesError, NewError, SerializeBytesError};
+pub use error::{DeserializeBytesError, NewError, RepairHeaderError, SerializeBytesError};
 pub use header::Header;
 
 // Imports
@@ -33,16 +31,77 @@ pub struct Sector {
 
 impl Sector {
 	/// Creates a new sector given it's data, sector position and subheader data
-	pub fn new(data: impl Into<Data>, sector_pos: usize, subheader: SubHeader) -> Result<Self, NewError> {
+	///
+	/// The form bit of the subheader is set according to the form of `data`.
+	pub fn new(data: impl Into<Data>, sector_pos: usize, mut subheader: SubHeader) -> Result<Self, NewError> {
+		let data = data.into();
+		subheader.submode.set(SubMode::FORM, data.form() == Form::Form2);
+
 		let header = Header {
 			address: Address::from_sector_pos(sector_pos).map_err(NewError::Address)?,
 			subheader,
 		};
 
-		Ok(Self {
-			header,
-			data: data.into(),
-		})
+		Ok(Self { header, data })
+	}
+
+	/// Repairs the header of a sector's bytes.
+	///
+	/// Writes the sync, mode and the address of `sector_pos`, and copies the first
+	/// subheader over the second.
+	pub fn repair_header(bytes: &mut [u8; 0x930], sector_pos: usize) -> Result<(), RepairHeaderError> {
+		let address = Address::from_sector_pos(sector_pos).map_err(RepairHeaderError::Address)?;
+
+		let header = zutil::array_split_mut!(bytes,
+			sync      : [0xc],
+			address   : [0x3],
+			mode      :  0x1 ,
+			subheader1: [0x4],
+			subheader2: [0x4],
+			_rest     : [0x918],
+		);
+
+		*header.sync = Header::SYNC;
+		address
+			.serialize_bytes(header.address)
+			.map_err(RepairHeaderError::SerializeAddress)?;
+		*header.mode = 2;
+		*header.subheader2 = *header.subheader1;
+
+		Ok(())
+	}
+
+	/// Regenerates the edc and ecc of a sector's bytes, according to it's subheader's form.
+	///
+	/// Form 2 sectors only have their edc regenerated, as they have no ecc, and only if
+	/// they had one, as it's optional.
+	pub fn regenerate_edc_ecc(bytes: &mut [u8; 0x930]) {
+		let form = Form::from_submode(SubMode::from_bits_truncate(bytes[0x12]));
+		if form == Form::Form2 && bytes[0x92c..0x930] == [0; 4] {
+			return;
+		}
+
+		Self::write_edc_ecc(bytes, form);
+	}
+
+	/// Writes the edc and ecc of a sector's bytes of form `form`
+	fn write_edc_ecc(bytes: &mut [u8; 0x930], form: Form) {
+		match form {
+			Form::Form1 => {
+				// Calculate and write the edc
+				let edc = Edc::calc_ecc(&bytes[0x10..0x818]).to_bytes().into_ok();
+				bytes[0x818..0x81c].copy_from_slice(&edc);
+
+				// Then the ecc, which covers the edc
+				let ecc = Ecc::calc(bytes).to_bytes().into_ok();
+				bytes[0x81c..0x930].copy_from_slice(&ecc);
+			},
+
+			Form::Form2 => {
+				let edc = Edc::calc_ecc(&bytes[0x10..0x92c]).to_bytes().into_ok();
+				bytes[0x92c..0x930].copy_from_slice(&edc);
+			},
+		}
 	}
 }
 
@@ -60,45 +119,55 @@ impl Bytes for Sector {
 
 		let header = Header::deserialize_bytes(bytes.header).map_err(DeserializeBytesError::Header)?;
 
-		let data = match header.subheader.submode.contains(SubMode::FORM) {
-			false => {
+		let data = match Form::from_submode(header.subheader.submode) {
+			Form::Form1 => {
 				let bytes = zutil::array_split!(bytes.rest,
 					data  : [0x800],
 					edc   : [0x4  ],
-					ecc   : [0x114],
+					_ecc  : [0x114],
 				);
 
-				// TODO: Verify & correct ecc
-
 				// Verify edc
 				let edc = Edc::deserialize_bytes(bytes.edc).into_ok();
 				let edc_bytes = &byte_array[0x10..0x818];
 				if let Err(calculated) = edc.is_valid(edc_bytes) {
-					return Err(DeserializeBytesError::WrongEdc {
-						found:      edc.crc,
-						calculated: calculated.crc,
-					});
+					// If it's wrong, try to correct the sector using the ecc
+					let mut corrected = *byte_array;
+					match Ecc::correct(&mut corrected) {
+						Ok(repaired) if repaired != 0 => {
+							log::warn!("Corrected {} byte(s) of sector at {:?}", repaired, header.address);
+							return Self::deserialize_bytes(&corrected);
+						},
+						_ => {
+							return Err(DeserializeBytesError::WrongEdc {
+								found:      edc.crc,
+								calculated: calculated.crc,
+							})
+						},
+					}
 				}
 
 
 				Data::Form1(*bytes.data)
 			},
 
-			true => {
+			Form::Form2 => {
 				let bytes = zutil::array_split!(bytes.rest,
-					data  : [0x800],
-					rest  : [0x114],
+					data  : [0x914],
 					edc   : [0x4  ],
 				);
 
-				// Verify edc
+				// Verify edc, if it exists
+				// Note: For form 2, the edc is optional and `0` if it doesn't exist.
 				let edc = Edc::deserialize_bytes(bytes.edc).into_ok();
 				let edc_bytes = &byte_array[0x10..0x92c];
-				if let Err(calculated) = edc.is_valid(edc_bytes) {
-					return Err(DeserializeBytesError::WrongEdc {
-						found:      edc.crc,
-						calculated: calculated.crc,
-					});
+				if edc.crc != 0 {
+					if let Err(calculated) = edc.is_valid(edc_bytes) {
+						return Err(DeserializeBytesError::WrongEdc {
+							found:      edc.crc,
+							calculated: calculated.crc,
+						});
+					}
 				}
 
 				Data::Form2(*bytes.data)
@@ -109,55 +178,53 @@ impl Bytes for Sector {
 	}
 
 	fn serialize_bytes(&self, bytes: &mut Self::ByteArray) -> Result<(), Self::SerializeError> {
-		// Calculate edc before writing
-		let edc = match self.data {
-			Data::Form1(_) => Edc::calc_ecc(&bytes[0x10..0x818]),
-			Data::Form2(_) => Edc::calc_ecc(&bytes[0x10..0x92c]),
-		};
-
-
-		let bytes = zutil::array_split_mut!(bytes,
-			header: [0x18 ],
-			rest  : [0x918],
-		);
-
+		// Write the header
+		let mut header_bytes = [0; 0x18];
 		self.header
-			.serialize_bytes(bytes.header)
+			.serialize_bytes(&mut header_bytes)
 			.map_err(SerializeBytesError::Header)?;
+		bytes[..0x18].copy_from_slice(&header_bytes);
 
-		match self.data {
-			Data::Form1(data) => {
-				let bytes = zutil::array_split_mut!(bytes.rest,
-					data  : [0x800],
-					edc   : [0x4  ],
-					ecc   : [0x114],
-				);
-
-				// Write the data
-				*bytes.data = data;
+		// Write the data
+		match &self.data {
+			Data::Form1(data) => bytes[0x18..0x818].copy_from_slice(data),
+			Data::Form2(data) => bytes[0x18..0x92c].copy_from_slice(data),
+		}
 
-				// Write the edc
-				edc.serialize_bytes(bytes.edc).into_ok();
+		// Then calculate and write the edc and ecc
+		Self::write_edc_ecc(bytes, self.data.form());
 
-				// TODO: Ecc
-			},
+		Ok(())
+	}
+}
 
-			Data::Form2(data) => {
-				let bytes = zutil::array_split_mut!(bytes.rest,
-					data  : [0x800],
-					rest  : [0x114],
-					edc   : [0x4  ],
-				);
+/// Sector form
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+pub enum Form {
+	/// Form 1, with `0x800` bytes of data, error detection and error correction
+	Form1,
 
-				// Write the data
-				*bytes.data = data;
+	/// Form 2, with `0x914` bytes of data and optional error detection
+	Form2,
+}
 
-				// Write the edc
-				edc.serialize_bytes(bytes.edc).into_ok();
-			},
+impl Form {
+	/// Returns the form of a sector with submode `submode`
+	#[must_use]
+	pub fn from_submode(submode: SubMode) -> Self {
+		match submode.contains(SubMode::FORM) {
+			true => Self::Form2,
+			false => Self::Form1,
 		}
+	}
 
-		Ok(())
+	/// Returns the data size of this form
+	#[must_use]
+	pub const fn data_size(self) -> usize {
+		match self {
+			Self::Form1 => 0x800,
+			Self::Form2 => 0x914,
+		}
 	}
 }
 
@@ -166,46 +233,58 @@ impl Bytes for Sector {
 #[allow(clippy::large_enum_variant)] // TODO: Check if it's worth it
 pub enum Data {
 	/// Form 1
-	Form1([u8; 2048]),
+	Form1([u8; 0x800]),
 
 	/// Form 2
-	Form2([u8; 2048]),
+	Form2([u8; 0x914]),
 }
 
 impl Data {
+	/// Returns the form of this data
+	#[must_use]
+	pub const fn form(&self) -> Form {
+		match self {
+			Self::Form1(_) => Form::Form1,
+			Self::Form2(_) => Form::Form2,
+		}
+	}
+
 	/// Returns this data as form 1
 	#[must_use]
-	#[allow(clippy::unnecessary_wraps)] // When we figure out `Form2` this will return `None` for it.
-	pub const fn as_form1(&self) -> Option<&[u8; 2048]> {
+	pub const fn as_form1(&self) -> Option<&[u8; 0x800]> {
 		match self {
-			Self::Form1(data) |
-			// Note: We pretend form 2 is form 1 for now until we figure out what's going on
-			Self::Form2(data) => Some(data),
+			Self::Form1(data) => Some(data),
+			Self::Form2(_) => None,
 		}
 	}
 
-	/*
 	/// Returns this data as form 2
 	#[must_use]
-	pub const fn as_form2(&self) -> Option<&[u8; 2048]> {
+	pub const fn as_form2(&self) -> Option<&[u8; 0x914]> {
 		match self {
 			Self::Form2(data) => Some(data),
-			_ => None,
+			Self::Form1(_) => None,
 		}
 	}
-	*/
 }
 
-impl From<[u8; 2048]> for Data {
-	fn from(arr: [u8; 2048]) -> Self {
+impl From<[u8; 0x800]> for Data {
+	fn from(arr: [u8; 0x800]) -> Self {
 		Self::Form1(arr)
 	}
 }
 
+impl From<[u8; 0x914]> for Data {
+	fn from(arr: [u8; 0x914]) -> Self {
+		Self::Form2(arr)
+	}
+}
+
 impl AsRef<[u8]> for Data {
 	fn as_ref(&self) -> &[u8] {
 		match self {
-			Data::Form1(data) | Data::Form2(data) => data,
+			Data::Form1(data) => data,
+			Data::Form2(data) => data,
 		}
 	}
 }
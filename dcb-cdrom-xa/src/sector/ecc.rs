@@ -1,9 +1,44 @@
 //! Error correction
+//!
+//! The error correction is made up of 2 Reed-Solomon product codes, `P` and `Q`,
+//! which span from the sector address to the end of the edc.
+//!
+//! For Mode 2 sectors, the address and mode are considered as zero while calculating
+//! the error correction, so that the sector may be moved without recalculating it.
+//!
+//! # Correction
+//! Each `P` codeword spans `26` bytes and each `Q` codeword spans `45` bytes, both
+//! with `2` parity bytes, which allows correcting a single byte error in each codeword.
+//!
+//! Since each byte is covered by both a `P` and a `Q` codeword, correcting the `P` codewords
+//! and then the `Q` codewords repeatedly may correct more than a single error per codeword.
+
+// Modules
+mod error;
+#[cfg(test)]
+mod test;
+
+// Exports
+pub use error::CorrectError;
+
+// Imports
+use dcb_bytes::Bytes;
 
 /// Error correction
-pub struct Ecc;
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Ecc {
+	/// P parity
+	pub p: [u8; 0xac],
+
+	/// Q parity
+	pub q: [u8; 0x68],
+}
 
 impl Ecc {
+	/// Exponential and logarithm tables
+	pub const EXP_LOG_TABLES: [[u8; 256]; 2] = Self::exp_log_tables();
+	/// Maximum number of passes when correcting
+	pub const MAX_CORRECTION_PASSES: usize = 4;
 	/// Both tables
 	pub const TABLES: [[u8; 256]; 2] = Self::tables();
 
@@ -24,4 +59,247 @@ impl Ecc {
 
 		[lhs_table, rhs_table]
 	}
+
+	/// Calculates the exponential and logarithm tables
+	const fn exp_log_tables() -> [[u8; 256]; 2] {
+		let mut exp_table = [0u8; 256];
+		let mut log_table = [0u8; 256];
+		let mut value = 1u32;
+		let mut n = 0u32;
+		#[allow(clippy::as_conversions, clippy::cast_possible_truncation)] // `n < 256`, `value < 256`
+		while n < 255 {
+			exp_table[n as usize] = value as u8;
+			log_table[value as usize] = n as u8;
+
+			value <<= 1u32;
+			if value & 0x100 != 0 {
+				value ^= 0x11d;
+			}
+
+			n += 1;
+		}
+
+		[exp_table, log_table]
+	}
+
+	/// Checks if this error correction is valid for `sector`
+	pub fn is_valid(&self, sector: &[u8; 0x930]) -> Result<(), Self> {
+		let ecc = Self::calc(sector);
+		match ecc == *self {
+			true => Ok(()),
+			false => Err(ecc),
+		}
+	}
+
+	/// Attempts to correct `sector` using it's error correction.
+	///
+	/// Returns the number of bytes repaired. On error, `sector` is left unchanged.
+	pub fn correct(sector: &mut [u8; 0x930]) -> Result<usize, CorrectError> {
+		// Note: The address is left as zero, any correction within it is an error.
+		let mut bytes = [0; 0x924];
+		bytes.copy_from_slice(&sector[0xc..]);
+		bytes[..0x4].fill(0);
+
+		let codewords = (0..86).map(Codeword::P).chain((0..52).map(Codeword::Q));
+
+		let mut repaired = 0;
+		for _ in 0..Self::MAX_CORRECTION_PASSES {
+			let mut uncorrectable = false;
+			let mut changed = false;
+			for codeword in codewords.clone() {
+				match Self::correct_codeword(&mut bytes, codeword) {
+					Ok(true) => {
+						repaired += 1;
+						changed = true;
+					},
+					Ok(false) => (),
+					Err(()) => uncorrectable = true,
+				}
+			}
+
+			match (uncorrectable, changed) {
+				// If everything was correct, we're done
+				(false, false) => {
+					sector[0x10..].copy_from_slice(&bytes[0x4..]);
+					return Ok(repaired);
+				},
+
+				// If we couldn't change anything, give up
+				(true, false) => return Err(CorrectError::TooManyErrors),
+
+				// Else try again
+				_ => (),
+			}
+		}
+
+		Err(CorrectError::TooManyErrors)
+	}
+
+	/// Corrects a single codeword
+	///
+	/// Returns if any byte was corrected, or `Err` if the codeword is uncorrectable.
+	fn correct_codeword(bytes: &mut [u8; 0x924], codeword: Codeword) -> Result<bool, ()> {
+		let [exp_table, log_table] = &Self::EXP_LOG_TABLES;
+		let len = codeword.len();
+		let idx = |n| codeword.idx(n);
+
+		// Calculate both syndromes, `s0 = sum(c_n)` and `s1 = sum(c_n * a^(len - 1 - n))`.
+		let mut s0 = 0u8;
+		let mut s1 = 0u8;
+		for n in 0..len {
+			let value = bytes[idx(n)];
+			s0 ^= value;
+			if value != 0 {
+				let exp = (usize::from(log_table[usize::from(value)]) + len - 1 - n) % 255;
+				s1 ^= exp_table[exp];
+			}
+		}
+
+		// If both are zero, there are no errors.
+		// Note: If only one is zero, we have more than a single error.
+		match (s0, s1) {
+			(0, 0) => return Ok(false),
+			(0, _) | (_, 0) => return Err(()),
+			_ => (),
+		}
+
+		// With a single error `e` at `n`, `s0 = e` and `s1 = e * a^(len - 1 - n)`.
+		let exp = (255 + usize::from(log_table[usize::from(s1)]) - usize::from(log_table[usize::from(s0)])) % 255;
+		if exp >= len {
+			return Err(());
+		}
+		let pos = idx(len - 1 - exp);
+		if pos < 0x4 {
+			return Err(());
+		}
+
+		bytes[pos] ^= s0;
+		Ok(true)
+	}
+
+	/// Calculates the error correction of a sector
+	///
+	/// Any existing error correction within `sector` is ignored.
+	#[must_use]
+	pub fn calc(sector: &[u8; 0x930]) -> Self {
+		Self::calc_bytes(Self::ecc_bytes(sector))
+	}
+
+	/// Calculates the error correction of a mode 1 sector
+	///
+	/// Unlike mode 2 sectors, the address and mode are included in the error correction.
+	#[must_use]
+	pub fn calc_mode1(sector: &[u8; 0x930]) -> Self {
+		let mut bytes = [0; 0x8bc];
+		bytes.copy_from_slice(&sector[0xc..0x8c8]);
+		Self::calc_bytes(bytes)
+	}
+
+	/// Calculates the error correction of all bytes it covers
+	fn calc_bytes(mut bytes: [u8; 0x8bc]) -> Self {
+		let mut p = [0; 0xac];
+		Self::calc_parity(&bytes[..0x810], 86, 24, 2, 86, &mut p);
+		bytes[0x810..0x8bc].copy_from_slice(&p);
+
+		let mut q = [0; 0x68];
+		Self::calc_parity(&bytes, 52, 43, 86, 88, &mut q);
+
+		Self { p, q }
+	}
+
+	/// Returns the bytes covered by the error correction of a sector, with the address and mode zeroed
+	fn ecc_bytes(sector: &[u8; 0x930]) -> [u8; 0x8bc] {
+		let mut bytes = [0; 0x8bc];
+		bytes.copy_from_slice(&sector[0xc..0x8c8]);
+		bytes[..0x4].fill(0);
+		bytes
+	}
+
+	/// Calculates a parity over `bytes`
+	fn calc_parity(
+		bytes: &[u8], major_count: usize, minor_count: usize, major_mult: usize, minor_inc: usize, parity: &mut [u8],
+	) {
+		let [lhs_table, rhs_table] = &Self::TABLES;
+		let size = major_count * minor_count;
+		for major in 0..major_count {
+			let mut idx = (major >> 1) * major_mult + (major & 1);
+			let mut ecc_a = 0u8;
+			let mut ecc_b = 0u8;
+			for _ in 0..minor_count {
+				let value = bytes[idx];
+				idx += minor_inc;
+				if idx >= size {
+					idx -= size;
+				}
+				ecc_a ^= value;
+				ecc_b ^= value;
+				ecc_a = lhs_table[usize::from(ecc_a)];
+			}
+
+			let ecc_a = rhs_table[usize::from(lhs_table[usize::from(ecc_a)] ^ ecc_b)];
+			parity[major] = ecc_a;
+			parity[major + major_count] = ecc_a ^ ecc_b;
+		}
+	}
+}
+
+/// A codeword
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Codeword {
+	/// `P` codeword
+	P(usize),
+
+	/// `Q` codeword
+	Q(usize),
+}
+
+impl Codeword {
+	/// Returns the length of this codeword
+	const fn len(self) -> usize {
+		match self {
+			Self::P(_) => 26,
+			Self::Q(_) => 45,
+		}
+	}
+
+	/// Returns the index of the `n`th byte of this codeword
+	const fn idx(self, n: usize) -> usize {
+		match self {
+			Self::P(major) => major + 86 * n,
+			Self::Q(major) => match n {
+				0..=42 => ((major >> 1) * 86 + (major & 1) + 88 * n) % 0x8bc,
+				_ => 0x8bc + major + 52 * (n - 43),
+			},
+		}
+	}
+}
+
+impl Bytes for Ecc {
+	type ByteArray = [u8; 0x114];
+	type DeserializeError = !;
+	type SerializeError = !;
+
+	fn deserialize_bytes(bytes: &Self::ByteArray) -> Result<Self, Self::DeserializeError> {
+		let bytes = zutil::array_split!(bytes,
+			p: [0xac],
+			q: [0x68],
+		);
+
+		Ok(Self {
+			p: *bytes.p,
+			q: *bytes.q,
+		})
+	}
+
+	fn serialize_bytes(&self, bytes: &mut Self::ByteArray) -> Result<(), Self::SerializeError> {
+		let bytes = zutil::array_split_mut!(bytes,
+			p: [0xac],
+			q: [0x68],
+		);
+
+		*bytes.p = self.p;
+		*bytes.q = self.q;
+
+		Ok(())
+	}
 }
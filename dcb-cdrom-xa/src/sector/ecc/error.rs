@@ -0,0 +1,9 @@
+//! Errors
+
+/// Error type for [`Ecc::correct`](super::Ecc::correct)
+#[derive(PartialEq, Eq, Clone, Copy, Debug, thiserror::Error)]
+pub enum CorrectError {
+	/// Too many errors to correct
+	#[error("Too many errors to correct")]
+	TooManyErrors,
+}
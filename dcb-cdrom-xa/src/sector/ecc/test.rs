@@ -0,0 +1,70 @@
+//! Tests
+
+// Imports
+use super::*;
+use crate::sector::{header::SubHeader, Sector};
+use std::convert::TryFrom;
+
+/// Creates a valid sector with some data
+fn sector() -> [u8; 0x930] {
+	let mut data = [0; 0x800];
+	for (n, byte) in data.iter_mut().enumerate() {
+		#[allow(clippy::as_conversions, clippy::cast_possible_truncation)] // We want it to wrap
+		let value = (n * 7 + 3) as u8;
+		*byte = value;
+	}
+
+	Sector::new(data, 150 + 16, SubHeader::new())
+		.expect("Unable to create sector")
+		.to_bytes()
+		.expect("Unable to serialize sector")
+}
+
+#[test]
+fn valid() {
+	let sector = self::sector();
+	let ecc_bytes = <&[u8; 0x114]>::try_from(&sector[0x81c..]).expect("Ecc size was wrong");
+	let ecc = Ecc::deserialize_bytes(ecc_bytes).into_ok();
+	assert_eq!(ecc.is_valid(&sector), Ok(()));
+
+	let mut corrected = sector;
+	assert_eq!(Ecc::correct(&mut corrected), Ok(0));
+	assert_eq!(corrected, sector);
+}
+
+#[test]
+fn single_error() {
+	let sector = self::sector();
+
+	for &pos in &[0x10, 0x18, 0x400, 0x817, 0x818, 0x81c, 0x92f] {
+		let mut corrupted = sector;
+		corrupted[pos] ^= 0x5a;
+
+		assert_eq!(Ecc::correct(&mut corrupted), Ok(1), "Unable to correct byte {:#x}", pos);
+		assert_eq!(corrupted, sector);
+	}
+}
+
+#[test]
+fn multiple_errors() {
+	let sector = self::sector();
+
+	// Note: Both of these are in separate `P` and `Q` codewords
+	let mut corrupted = sector;
+	corrupted[0x20] ^= 0xff;
+	corrupted[0x500] ^= 0x01;
+
+	assert_eq!(Ecc::correct(&mut corrupted), Ok(2));
+	assert_eq!(corrupted, sector);
+}
+
+#[test]
+fn too_many_errors() {
+	let sector = self::sector();
+
+	let mut corrupted = sector;
+	corrupted[0x18..0x118].fill(0xff);
+
+	assert_eq!(Ecc::correct(&mut corrupted), Err(CorrectError::TooManyErrors));
+	assert_eq!(corrupted[0x18..0x118], [0xff; 0x100]);
+}
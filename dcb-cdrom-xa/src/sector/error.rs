@@ -11,6 +11,18 @@ pub enum NewError {
 	Address(#[source] header::address::FromSectorPosError),
 }
 
+/// Error type for [`Sector::repair_header`](super::Sector::repair_header)
+#[derive(PartialEq, Eq, Clone, Copy, Debug, thiserror::Error)]
+pub enum RepairHeaderError {
+	/// Unable to create address
+	#[error("Unable to create address")]
+	Address(#[source] header::address::FromSectorPosError),
+
+	/// Unable to write address
+	#[error("Unable to write address")]
+	SerializeAddress(#[source] header::address::SerializeBytesError),
+}
+
 /// Error type for [`Bytes::deserialize_bytes`](dcb_bytes::Bytes::deserialize_bytes)
 #[derive(PartialEq, Eq, Clone, Copy, Debug, thiserror::Error)]
 pub enum DeserializeBytesError {
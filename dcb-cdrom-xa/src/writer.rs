@@ -7,11 +7,14 @@ mod error;
 pub use error::WriteSectorError;
 
 // Imports
-use crate::{sector::header::SubHeader, Sector};
+use crate::{
+	sector::{header::SubHeader, Data},
+	Sector,
+};
 use dcb_bytes::Bytes;
 use std::io::Write;
 
-/// A CD-ROM/XA Mode 2 Form 1 writer.
+/// A CD-ROM/XA Mode 2 writer.
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct CdRomWriter<W> {
 	/// Underlying writer
@@ -42,8 +45,10 @@ impl<W> CdRomWriter<W> {
 // Write
 impl<W: Write> CdRomWriter<W> {
 	/// Writes the next sector data
-	#[allow(clippy::large_types_passed_by_value)] // `Sector` requires it to be by value. TODO: Change
-	pub fn write_sector(&mut self, data: [u8; 2048], subheader: SubHeader) -> Result<(), WriteSectorError> {
+	///
+	/// The sector will be form 1 or form 2 depending on the size of `data`, either
+	/// `0x800` or `0x914` bytes, respectively.
+	pub fn write_sector(&mut self, data: impl Into<Data>, subheader: SubHeader) -> Result<(), WriteSectorError> {
 		// Create the sector
 		let sector = Sector::new(data, self.cur_sector, subheader).map_err(WriteSectorError::Sector)?;
 
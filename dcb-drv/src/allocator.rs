@@ -0,0 +1,338 @@
+//! Sector allocation
+//!
+//! The filesystem doesn't store which sectors are free, so the [`SectorAllocator`]
+//! finds them by walking every directory and file of a drive.
+
+// Modules
+mod error;
+#[cfg(test)]
+mod test;
+
+// Exports
+pub use error::NewError;
+
+// Imports
+use crate::{DirEntryKind, DirEntryPtr, DirPtr, FilePtr};
+use std::{collections::BTreeSet, convert::TryFrom, io};
+
+/// A run of contiguous sectors
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct Extent {
+	/// Starting sector position
+	pub sector_pos: u32,
+
+	/// Number of sectors
+	pub len: u32,
+}
+
+impl Extent {
+	/// Creates a new extent
+	#[must_use]
+	pub const fn new(sector_pos: u32, len: u32) -> Self {
+		Self { sector_pos, len }
+	}
+
+	/// Returns the extent of the sectors used by a file
+	#[must_use]
+	pub const fn from_file_ptr(ptr: FilePtr) -> Self {
+		Self::new(ptr.sector_pos, self::sectors_len(ptr.size))
+	}
+
+	/// Returns the extent of the sectors used by a directory with `entries_len` entries
+	#[must_use]
+	pub const fn from_dir_ptr(ptr: DirPtr, entries_len: u32) -> Self {
+		// Note: `+1` for the null entry.
+		Self::new(ptr.sector_pos, self::sectors_len((entries_len + 1) * 0x20))
+	}
+
+	/// Returns the sector after the end of this extent
+	#[must_use]
+	pub const fn end(self) -> u32 {
+		self.sector_pos + self.len
+	}
+
+	/// Returns if this extent overlaps another
+	#[must_use]
+	pub const fn overlaps(self, other: Self) -> bool {
+		self.len != 0 && other.len != 0 && self.sector_pos < other.end() && other.sector_pos < self.end()
+	}
+}
+
+/// Owner of an extent
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum ExtentOwner {
+	/// The entries of a directory
+	Dir(DirPtr),
+
+	/// The contents of a file, given by it's entry
+	File(DirEntryPtr),
+}
+
+/// Two entries whose sectors overlap
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Overlap {
+	/// First extent and it's owner
+	pub lhs: (Extent, ExtentOwner),
+
+	/// Second extent and it's owner
+	pub rhs: (Extent, ExtentOwner),
+}
+
+impl Overlap {
+	/// Returns if both entries are files with the same extent, such as deduplicated files
+	#[must_use]
+	pub fn is_shared(&self) -> bool {
+		self.lhs.0 == self.rhs.0 && matches!((self.lhs.1, self.rhs.1), (ExtentOwner::File(_), ExtentOwner::File(_)))
+	}
+}
+
+/// Sector allocator.
+///
+/// Keeps track of how many entries use each sector of a drive, so
+/// that sectors shared by several files are only free once all of them are freed.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SectorAllocator {
+	/// Number of users of each sector
+	sectors: Vec<u16>,
+
+	/// Number of sectors in the drive
+	len: u32,
+
+	/// All overlapping entries found
+	overlaps: Vec<Overlap>,
+}
+
+impl SectorAllocator {
+	/// Creates a new allocator by walking all entries of a drive.
+	///
+	/// Only whole sectors at the end of the drive are considered part of it.
+	pub fn new<R: io::Read + io::Seek>(reader: &mut R) -> Result<Self, NewError> {
+		let drive_len = reader.stream_len().map_err(NewError::DriveLen)?;
+		let len = u32::try_from(drive_len / 0x800).map_err(|_| NewError::DriveTooLarge)?;
+
+		// Get all extents used, and check which overlap
+		let mut extents = vec![];
+		let mut visited = BTreeSet::new();
+		self::collect_extents(reader, DirPtr::root(), &mut extents, &mut visited)?;
+		let overlaps = self::find_overlaps(&mut extents);
+
+		// Then mark all of them as used
+		let mut allocator = Self {
+			sectors: vec![0; usize::try_from(len).expect("Drive length didn't fit into a `usize`")],
+			len,
+			overlaps,
+		};
+		for (extent, _) in extents {
+			allocator.mark_used(extent);
+		}
+
+		Ok(allocator)
+	}
+
+	/// Creates a new allocator for an empty drive.
+	///
+	/// Sectors may still be marked as used, and allocated after the last used sector.
+	#[must_use]
+	pub const fn empty() -> Self {
+		Self {
+			sectors:  Vec::new(),
+			len:      0,
+			overlaps: Vec::new(),
+		}
+	}
+
+	/// Returns the number of sectors in the drive
+	#[must_use]
+	pub const fn len(&self) -> u32 {
+		self.len
+	}
+
+	/// Returns if the drive has no sectors
+	#[must_use]
+	pub const fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns all overlapping entries found when walking the drive
+	#[must_use]
+	pub fn overlaps(&self) -> &[Overlap] {
+		&self.overlaps
+	}
+
+	/// Returns if a sector is free
+	#[must_use]
+	pub fn is_free(&self, sector_pos: u32) -> bool {
+		self.users(sector_pos) == 0
+	}
+
+	/// Returns if any sector of an extent is used by more than one entry
+	#[must_use]
+	pub fn is_shared(&self, extent: Extent) -> bool {
+		(extent.sector_pos..extent.end()).any(|sector_pos| self.users(sector_pos) > 1)
+	}
+
+	/// Returns all free extents within the drive
+	pub fn free_extents(&self) -> impl Iterator<Item = Extent> + '_ {
+		let mut cur_pos = 0;
+		std::iter::from_fn(move || {
+			// Skip all used sectors, then find the end of the free sectors
+			while cur_pos < self.len && !self.is_free(cur_pos) {
+				cur_pos += 1;
+			}
+			let sector_pos = cur_pos;
+			while cur_pos < self.len && self.is_free(cur_pos) {
+				cur_pos += 1;
+			}
+
+			match cur_pos > sector_pos {
+				true => Some(Extent::new(sector_pos, cur_pos - sector_pos)),
+				false => None,
+			}
+		})
+	}
+
+	/// Returns the total number of free sectors within the drive
+	#[must_use]
+	pub fn free_len(&self) -> u32 {
+		self.free_extents().map(|extent| extent.len).sum()
+	}
+
+	/// Allocates `len` sectors.
+	///
+	/// The first free extent large enough is used, else the sectors are allocated
+	/// after the last used sector, which may be past the end of the drive.
+	pub fn allocate(&mut self, len: u32) -> Extent {
+		let extent = Extent::new(self.find_free(len), len);
+		self.mark_used(extent);
+		extent
+	}
+
+	/// Allocates `len` sectors within the first `max_len` sectors.
+	///
+	/// Like [`Self::allocate`], but returns `None` if the sectors wouldn't end before `max_len`.
+	pub fn try_allocate(&mut self, len: u32, max_len: u32) -> Option<Extent> {
+		let extent = Extent::new(self.find_free(len), len);
+		if extent.end() > max_len {
+			return None;
+		}
+		self.mark_used(extent);
+		Some(extent)
+	}
+
+	/// Marks all sectors of an extent as used by another entry
+	pub fn mark_used(&mut self, extent: Extent) {
+		let end = usize::try_from(extent.end()).expect("Sector position didn't fit into a `usize`");
+		if self.sectors.len() < end {
+			self.sectors.resize(end, 0);
+		}
+
+		for users in &mut self.sectors[self::sector_range(extent)] {
+			*users = users.checked_add(1).expect("Sector had too many users");
+		}
+	}
+
+	/// Frees all sectors of an extent from one of it's users.
+	///
+	/// # Panics
+	/// Panics if any of the sectors is already free.
+	pub fn free(&mut self, extent: Extent) {
+		for sector_pos in self::sector_range(extent) {
+			let users = self
+				.sectors
+				.get_mut(sector_pos)
+				.filter(|users| **users != 0)
+				.expect("Sector was already free");
+			*users -= 1;
+		}
+	}
+
+	/// Returns the number of users of a sector
+	fn users(&self, sector_pos: u32) -> u16 {
+		let sector_pos = usize::try_from(sector_pos).expect("Sector position didn't fit into a `usize`");
+		self.sectors.get(sector_pos).copied().unwrap_or(0)
+	}
+
+	/// Returns the position of the first free extent of at least `len` sectors,
+	/// or the sector after the last used sector, if none are large enough.
+	fn find_free(&self, len: u32) -> u32 {
+		self.free_extents()
+			.find(|extent| extent.len >= len)
+			.map_or_else(|| self.used_end(), |extent| extent.sector_pos)
+	}
+
+	/// Returns the sector after the last used sector
+	fn used_end(&self) -> u32 {
+		let used_end = self
+			.sectors
+			.iter()
+			.rposition(|&users| users != 0)
+			.map_or(0, |pos| pos + 1);
+		u32::try_from(used_end).expect("Sector position didn't fit into a `u32`")
+	}
+}
+
+/// Returns the range of sector indices of an extent
+fn sector_range(extent: Extent) -> std::ops::Range<usize> {
+	let start = usize::try_from(extent.sector_pos).expect("Sector position didn't fit into a `usize`");
+	let end = usize::try_from(extent.end()).expect("Sector position didn't fit into a `usize`");
+	start..end
+}
+
+/// Returns the number of sectors needed for `size` bytes
+const fn sectors_len(size: u32) -> u32 {
+	(size + 0x7ff) / 0x800
+}
+
+/// Collects the extents used by a directory and all of it's entries recursively
+fn collect_extents<R: io::Read + io::Seek>(
+	reader: &mut R, dir_ptr: DirPtr, extents: &mut Vec<(Extent, ExtentOwner)>, visited: &mut BTreeSet<DirPtr>,
+) -> Result<(), NewError> {
+	let entries = dir_ptr
+		.read_entries(reader)
+		.map_err(NewError::ReadEntries)?
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(NewError::ReadEntry)?;
+
+	let entries_len = u32::try_from(entries.len()).expect("Number of entries didn't fit into a `u32`");
+	extents.push((Extent::from_dir_ptr(dir_ptr, entries_len), ExtentOwner::Dir(dir_ptr)));
+
+	// Note: If we've been to this directory already, don't walk it's entries again, as it could loop.
+	//       It's extent is still added, so it'll be reported as an overlap.
+	if !visited.insert(dir_ptr) {
+		return Ok(());
+	}
+
+	for (entry, idx) in entries.into_iter().zip(0..) {
+		match entry.kind {
+			DirEntryKind::File { ptr, .. } => extents.push((
+				Extent::from_file_ptr(ptr),
+				ExtentOwner::File(DirEntryPtr::new(dir_ptr, idx)),
+			)),
+			DirEntryKind::Dir { ptr } => self::collect_extents(reader, ptr, extents, visited)?,
+		}
+	}
+
+	Ok(())
+}
+
+/// Sorts all extents and returns all overlapping extents.
+///
+/// Files with the same extent are also returned, see [`Overlap::is_shared`].
+pub(crate) fn find_overlaps(extents: &mut [(Extent, ExtentOwner)]) -> Vec<Overlap> {
+	extents.sort_unstable();
+
+	let mut overlaps = vec![];
+	for (idx, &lhs) in extents.iter().enumerate() {
+		// Note: As they're sorted, we can stop once an extent starts after `lhs` ends
+		for &rhs in extents[(idx + 1)..]
+			.iter()
+			.take_while(|(rhs, _)| rhs.sector_pos < lhs.0.end())
+		{
+			if lhs.0.overlaps(rhs.0) {
+				overlaps.push(Overlap { lhs, rhs });
+			}
+		}
+	}
+
+	overlaps
+}
@@ -0,0 +1,25 @@
+//! Errors
+
+// Imports
+use crate::ptr;
+use std::io;
+
+/// Error for [`SectorAllocator::new`](super::SectorAllocator::new)
+#[derive(Debug, thiserror::Error)]
+pub enum NewError {
+	/// Unable to get drive length
+	#[error("Unable to get drive length")]
+	DriveLen(#[source] io::Error),
+
+	/// Drive was too large
+	#[error("Drive was too large")]
+	DriveTooLarge,
+
+	/// Unable to read directory entries
+	#[error("Unable to read directory entries")]
+	ReadEntries(#[source] ptr::dir::ReadEntriesError),
+
+	/// Unable to read directory entry
+	#[error("Unable to read directory entry")]
+	ReadEntry(#[source] ptr::dir::ReadEntryError),
+}
@@ -0,0 +1,88 @@
+//! Tests
+
+// Imports
+use super::*;
+use crate::test_util;
+
+/// Creates a drive of `len` sectors with a root directory with files at `files`
+fn create_drive(len: usize, files: &[(u32, u32)]) -> io::Cursor<Vec<u8>> {
+	let entries = files
+		.iter()
+		.map(|&(sector_pos, size)| test_util::entry("FILE", test_util::file(sector_pos, size)));
+	test_util::create_drive(len, entries)
+}
+
+#[test]
+fn free_extents() {
+	let mut drive = self::create_drive(8, &[(1, 0x800), (4, 0x1001)]);
+	let allocator = SectorAllocator::new(&mut drive).expect("Unable to create allocator");
+
+	assert_eq!(allocator.len(), 8);
+	assert_eq!(allocator.free_extents().collect::<Vec<_>>(), [
+		Extent::new(2, 2),
+		Extent::new(7, 1)
+	]);
+	assert_eq!(allocator.free_len(), 3);
+	assert!(allocator.overlaps().is_empty());
+}
+
+#[test]
+fn allocate_free() {
+	let mut drive = self::create_drive(8, &[(1, 0x800), (4, 0x1001)]);
+	let mut allocator = SectorAllocator::new(&mut drive).expect("Unable to create allocator");
+
+	// If it fits, the first free extent should be used, else after the last used sector.
+	assert_eq!(allocator.allocate(1), Extent::new(2, 1));
+	assert_eq!(allocator.allocate(2), Extent::new(7, 2));
+	assert_eq!(allocator.allocate(1), Extent::new(3, 1));
+
+	allocator.free(Extent::new(4, 3));
+	assert_eq!(allocator.allocate(3), Extent::new(4, 3));
+}
+
+#[test]
+fn overlaps() {
+	let mut drive = self::create_drive(4, &[(1, 0x1000), (2, 0x800)]);
+	let mut allocator = SectorAllocator::new(&mut drive).expect("Unable to create allocator");
+
+	let root = DirPtr::root();
+	assert_eq!(allocator.overlaps(), [Overlap {
+		lhs: (Extent::new(1, 2), ExtentOwner::File(DirEntryPtr::new(root, 0))),
+		rhs: (Extent::new(2, 1), ExtentOwner::File(DirEntryPtr::new(root, 1))),
+	}]);
+
+	// Shared sectors should only be free once all users free them
+	allocator.free(Extent::new(1, 2));
+	assert!(allocator.is_free(1));
+	assert!(!allocator.is_free(2));
+}
+
+#[test]
+fn shared_overlaps() {
+	let mut drive = self::create_drive(4, &[(1, 0x1000), (1, 0x1000), (2, 0x800)]);
+	let allocator = SectorAllocator::new(&mut drive).expect("Unable to create allocator");
+
+	// Files with the same extent are still reported, but as sharing it
+	let root = DirPtr::root();
+	let overlaps = allocator.overlaps();
+	assert_eq!(overlaps.len(), 3);
+	assert!(overlaps[0].is_shared());
+	assert_eq!(overlaps[0].lhs.1, ExtentOwner::File(DirEntryPtr::new(root, 0)));
+	assert_eq!(overlaps[0].rhs.1, ExtentOwner::File(DirEntryPtr::new(root, 1)));
+	assert!(!overlaps[1].is_shared());
+	assert!(!overlaps[2].is_shared());
+	assert!(allocator.is_shared(Extent::new(1, 2)));
+}
+
+#[test]
+fn try_allocate() {
+	let mut drive = self::create_drive(8, &[(1, 0x800), (4, 0x1001)]);
+	let mut allocator = SectorAllocator::new(&mut drive).expect("Unable to create allocator");
+
+	// Only free extents within `max_len` should be used
+	assert_eq!(allocator.try_allocate(3, allocator.len()), None);
+	assert_eq!(allocator.try_allocate(2, allocator.len()), Some(Extent::new(2, 2)));
+	assert_eq!(allocator.try_allocate(2, 8), None);
+	assert_eq!(allocator.try_allocate(2, 9), Some(Extent::new(7, 2)));
+	assert_eq!(allocator.free_len(), 0);
+}
@@ -9,6 +9,7 @@ pub use error::DeserializeBytesError;
 
 // Imports
 use super::ptr::{DirPtr, FilePtr};
+use ascii::AsciiString;
 use byteorder::{ByteOrder, LittleEndian};
 use chrono::NaiveDateTime;
 use dcb_bytes::Bytes;
@@ -88,6 +89,20 @@ pub struct DirEntry {
 	pub kind: DirEntryKind,
 }
 
+impl DirEntry {
+	/// Returns the full name of this entry, `NAME.EXT` for files and `NAME` for directories
+	#[must_use]
+	pub fn full_name(&self) -> AsciiString {
+		let mut name = self.name.as_ascii().to_ascii_string();
+		if let DirEntryKind::File { extension, .. } = self.kind {
+			name.push(AsciiChar::Dot);
+			name.push_str(extension.as_ascii());
+		}
+
+		name
+	}
+}
+
 impl Bytes for DirEntry {
 	type ByteArray = [u8; 0x20];
 	type DeserializeError = DeserializeBytesError;
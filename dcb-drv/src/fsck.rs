@@ -0,0 +1,430 @@
+//! Consistency checking
+//!
+//! Reading a drive stops at the first entry that can't be parsed, so [`check`]
+//! instead reads the raw bytes of each entry, reporting every issue it finds while
+//! walking as much of the drive as it can.
+//!
+//! Some issues may then be fixed with [`repair`].
+
+// Modules
+mod error;
+#[cfg(test)]
+mod test;
+
+// Exports
+pub use error::{CheckError, RepairError};
+
+// Imports
+use crate::{
+	allocator::{self, Extent, ExtentOwner},
+	ptr, DirEntryPtr, DirPtr, FilePtr,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	convert::TryFrom,
+	fmt, io,
+};
+
+/// An issue found in a drive
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Issue {
+	/// Path of the entry, with any invalid characters replaced
+	pub path: String,
+
+	/// Entry pointer, `None` for the root directory
+	pub entry_ptr: Option<DirEntryPtr>,
+
+	/// Kind
+	pub kind: IssueKind,
+}
+
+impl Issue {
+	/// Returns if this issue may be repaired by [`repair`]
+	#[must_use]
+	pub const fn is_repairable(&self) -> bool {
+		self.entry_ptr.is_some() &&
+			matches!(
+				self.kind,
+				IssueKind::DirSize(_) | IssueKind::DirExtension | IssueKind::FileOutOfBounds { .. }
+			)
+	}
+
+	/// Returns if this issue is only a warning.
+	///
+	/// Files sharing their sectors may be intentional, such as deduplicated files, but
+	/// writing to any of them through other tools will change the others.
+	#[must_use]
+	pub const fn is_warning(&self) -> bool {
+		matches!(self.kind, IssueKind::SharedExtent { .. })
+	}
+}
+
+impl fmt::Display for Issue {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}: ", self.path)?;
+		match &self.kind {
+			IssueKind::InvalidKind(kind) => write!(f, "Invalid kind {kind:#x}"),
+			IssueKind::InvalidName => write!(f, "Invalid name"),
+			IssueKind::InvalidExtension => write!(f, "Invalid extension"),
+			IssueKind::DirSize(size) => write!(f, "Directory had non-zero size {size:#x}"),
+			IssueKind::DirExtension => write!(f, "Directory had an extension"),
+			IssueKind::FileOutOfBounds { ptr, drive_len } => write!(
+				f,
+				"File at sector {} with size {:#x} was past the end of the drive ({drive_len} sectors)",
+				ptr.sector_pos, ptr.size
+			),
+			IssueKind::DirOutOfBounds { ptr, drive_len } => write!(
+				f,
+				"Directory at sector {} was past the end of the drive ({drive_len} sectors)",
+				ptr.sector_pos
+			),
+			IssueKind::DirLoop(ptr) => write!(f, "Directory at sector {} is one of it's parents", ptr.sector_pos),
+			IssueKind::Overlap {
+				extent,
+				other_path,
+				other_extent,
+			} => write!(
+				f,
+				"Sectors {}..{} overlap sectors {}..{} of {other_path}",
+				extent.sector_pos,
+				extent.end(),
+				other_extent.sector_pos,
+				other_extent.end()
+			),
+			IssueKind::SharedExtent { extent, other_path } => write!(
+				f,
+				"Sectors {}..{} are shared with {other_path}",
+				extent.sector_pos,
+				extent.end()
+			),
+		}
+	}
+}
+
+/// Issue kind
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum IssueKind {
+	/// Entry had an invalid kind
+	InvalidKind(u8),
+
+	/// Entry had an invalid name
+	InvalidName,
+
+	/// File had an invalid extension
+	InvalidExtension,
+
+	/// Directory had a non-zero size
+	DirSize(u32),
+
+	/// Directory had an extension
+	DirExtension,
+
+	/// File was past the end of the drive
+	FileOutOfBounds {
+		/// File pointer
+		ptr: FilePtr,
+
+		/// Number of sectors in the drive
+		drive_len: u32,
+	},
+
+	/// Directory was past the end of the drive
+	DirOutOfBounds {
+		/// Directory pointer
+		ptr: DirPtr,
+
+		/// Number of sectors in the drive
+		drive_len: u32,
+	},
+
+	/// Directory was one of it's parents
+	DirLoop(DirPtr),
+
+	/// Entry's sectors overlapped another entry's
+	Overlap {
+		/// Extent of this entry
+		extent: Extent,
+
+		/// Path of the other entry
+		other_path: String,
+
+		/// Extent of the other entry
+		other_extent: Extent,
+	},
+
+	/// File's sectors were the same as another file's
+	SharedExtent {
+		/// Extent of both files
+		extent: Extent,
+
+		/// Path of the other file
+		other_path: String,
+	},
+}
+
+/// Checks a drive for issues
+pub fn check<R: io::Read + io::Seek>(reader: &mut R) -> Result<Vec<Issue>, CheckError> {
+	let drive_len = reader.stream_len().map_err(CheckError::DriveLen)?;
+	let drive_len = u32::try_from(drive_len / 0x800).map_err(|_| CheckError::DriveTooLarge)?;
+
+	let mut checker = Checker {
+		reader,
+		drive_len,
+		issues: vec![],
+		extents: vec![],
+		paths: BTreeMap::new(),
+		visited: BTreeSet::new(),
+	};
+	checker.check_dir(DirPtr::root(), String::from("\\"), None, &mut vec![])?;
+
+	// Then check for overlaps
+	let Checker {
+		mut issues,
+		mut extents,
+		paths,
+		..
+	} = checker;
+	for overlap in allocator::find_overlaps(&mut extents) {
+		let (extent, owner) = overlap.lhs;
+		let (other_extent, other_owner) = overlap.rhs;
+		let (path, entry_ptr) = paths[&owner].clone();
+		let other_path = paths[&other_owner].0.clone();
+		let kind = match overlap.is_shared() {
+			true => IssueKind::SharedExtent { extent, other_path },
+			false => IssueKind::Overlap {
+				extent,
+				other_path,
+				other_extent,
+			},
+		};
+		issues.push(Issue { path, entry_ptr, kind });
+	}
+
+	Ok(issues)
+}
+
+/// Repairs all repairable issues in a drive, returning how many were repaired.
+///
+/// Directories get their size and extension cleared, while files past the end of
+/// the drive are truncated to it.
+pub fn repair<T: io::Read + io::Write + io::Seek>(cursor: &mut T, issues: &[Issue]) -> Result<usize, RepairError> {
+	let mut repaired = 0;
+	for issue in issues.iter().filter(|issue| issue.is_repairable()) {
+		let entry_ptr = issue.entry_ptr.expect("Repairable issue had no entry");
+
+		// Read the entry
+		let mut bytes = [0; 0x20];
+		entry_ptr.seek_to(cursor).map_err(RepairError::Seek)?;
+		cursor.read_exact(&mut bytes).map_err(RepairError::ReadEntry)?;
+
+		// Then fix it
+		match issue.kind {
+			IssueKind::DirSize(_) => bytes[0x8..0xc].fill(0),
+			IssueKind::DirExtension => bytes[0x1..0x4].fill(0),
+			IssueKind::FileOutOfBounds { ptr, drive_len } => {
+				let max_size = drive_len.saturating_sub(ptr.sector_pos).saturating_mul(0x800);
+				LittleEndian::write_u32(&mut bytes[0x8..0xc], ptr.size.min(max_size));
+			},
+			_ => unreachable!("Issue wasn't repairable"),
+		}
+
+		// And write it back
+		entry_ptr.seek_to(cursor).map_err(RepairError::Seek)?;
+		cursor.write_all(&bytes).map_err(RepairError::WriteEntry)?;
+		repaired += 1;
+	}
+
+	Ok(repaired)
+}
+
+/// Drive checker
+struct Checker<'a, R> {
+	/// Reader
+	reader: &'a mut R,
+
+	/// Number of sectors in the drive
+	drive_len: u32,
+
+	/// All issues found
+	issues: Vec<Issue>,
+
+	/// All extents used
+	extents: Vec<(Extent, ExtentOwner)>,
+
+	/// Path and entry pointer of each extent owner
+	paths: BTreeMap<ExtentOwner, (String, Option<DirEntryPtr>)>,
+
+	/// All directories visited
+	visited: BTreeSet<DirPtr>,
+}
+
+impl<'a, R: io::Read + io::Seek> Checker<'a, R> {
+	/// Checks a directory and all of it's entries recursively.
+	///
+	/// `parents` contains all parents of this directory, used to detect loops.
+	fn check_dir(
+		&mut self, dir_ptr: DirPtr, path: String, entry_ptr: Option<DirEntryPtr>, parents: &mut Vec<DirPtr>,
+	) -> Result<(), CheckError> {
+		let entries = self.read_entries(dir_ptr)?;
+
+		// Check the directory itself
+		// Note: `+1` for the null entry.
+		let size = (u64::try_from(entries.len()).expect("Number of entries didn't fit into a `u64`") + 1) * 0x20;
+		match self.extent(dir_ptr.sector_pos, size) {
+			Some(extent) => {
+				self.extents.push((extent, ExtentOwner::Dir(dir_ptr)));
+				self.paths.insert(ExtentOwner::Dir(dir_ptr), (path.clone(), entry_ptr));
+			},
+			None => self.push_issue(&path, entry_ptr, IssueKind::DirOutOfBounds {
+				ptr:       dir_ptr,
+				drive_len: self.drive_len,
+			}),
+		}
+
+		// Note: If we've been to this directory already, don't walk it's entries again.
+		//       It's extent is still added, so it'll be reported as an overlap.
+		if !self.visited.insert(dir_ptr) {
+			return Ok(());
+		}
+
+		parents.push(dir_ptr);
+		for (bytes, idx) in entries.iter().zip(0..) {
+			let entry_ptr = DirEntryPtr::new(dir_ptr, idx);
+			self.check_entry(bytes, entry_ptr, &path, parents)?;
+		}
+		parents.pop();
+
+		Ok(())
+	}
+
+	/// Checks an entry, given it's bytes
+	fn check_entry(
+		&mut self, bytes: &[u8; 0x20], entry_ptr: DirEntryPtr, dir_path: &str, parents: &mut Vec<DirPtr>,
+	) -> Result<(), CheckError> {
+		let bytes = zutil::array_split!(bytes,
+			kind      :  0x1,
+			extension : [0x3],
+			sector_pos: [0x4],
+			size      : [0x4],
+			_date     : [0x4],
+			name      : [0x10],
+		);
+		let sector_pos = LittleEndian::read_u32(bytes.sector_pos);
+		let size = LittleEndian::read_u32(bytes.size);
+		let name = self::trim_nulls(bytes.name);
+		let extension = self::trim_nulls(bytes.extension);
+
+		// Get the path of the entry
+		let separator = match dir_path.ends_with('\\') {
+			true => "",
+			false => "\\",
+		};
+		let mut path = format!("{}{}{}", dir_path, separator, String::from_utf8_lossy(name));
+		if *bytes.kind == 0x1 {
+			path.push('.');
+			path.push_str(&String::from_utf8_lossy(extension));
+		}
+
+		if name.is_empty() || !name.iter().copied().all(self::is_valid_name_char) {
+			self.push_issue(&path, Some(entry_ptr), IssueKind::InvalidName);
+		}
+
+		match *bytes.kind {
+			// File
+			0x1 => {
+				if !extension.iter().copied().all(self::is_valid_name_char) {
+					self.push_issue(&path, Some(entry_ptr), IssueKind::InvalidExtension);
+				}
+
+				match self.extent(sector_pos, u64::from(size)) {
+					Some(extent) => {
+						self.extents.push((extent, ExtentOwner::File(entry_ptr)));
+						self.paths.insert(ExtentOwner::File(entry_ptr), (path, Some(entry_ptr)));
+					},
+					None => self.push_issue(&path, Some(entry_ptr), IssueKind::FileOutOfBounds {
+						ptr:       FilePtr::new(sector_pos, size),
+						drive_len: self.drive_len,
+					}),
+				}
+			},
+
+			// Directory
+			0x80 => {
+				if size != 0 {
+					self.push_issue(&path, Some(entry_ptr), IssueKind::DirSize(size));
+				}
+				if !extension.is_empty() {
+					self.push_issue(&path, Some(entry_ptr), IssueKind::DirExtension);
+				}
+
+				let ptr = DirPtr::new(sector_pos);
+				match parents.contains(&ptr) {
+					true => self.push_issue(&path, Some(entry_ptr), IssueKind::DirLoop(ptr)),
+					false => self.check_dir(ptr, path, Some(entry_ptr), parents)?,
+				}
+			},
+
+			&kind => self.push_issue(&path, Some(entry_ptr), IssueKind::InvalidKind(kind)),
+		}
+
+		Ok(())
+	}
+
+	/// Reads the bytes of all entries of a directory
+	fn read_entries(&mut self, dir_ptr: DirPtr) -> Result<Vec<[u8; 0x20]>, CheckError> {
+		dir_ptr.seek_to(self.reader).map_err(CheckError::Seek)?;
+
+		let mut entries = vec![];
+		loop {
+			let mut bytes = [0; 0x20];
+			match self.reader.read_exact(&mut bytes) {
+				Ok(()) => (),
+				Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+				Err(err) => return Err(CheckError::ReadEntry(err)),
+			}
+
+			if bytes == [0; 0x20] || ptr::dir::is_ignored_entry(&bytes) {
+				break;
+			}
+			entries.push(bytes);
+		}
+
+		Ok(entries)
+	}
+
+	/// Returns the extent of `size` bytes at `sector_pos`, if it's within the drive.
+	///
+	/// Empty extents are always within the drive.
+	fn extent(&self, sector_pos: u32, size: u64) -> Option<Extent> {
+		let len = (size + 0x7ff) / 0x800;
+		let end = u64::from(sector_pos) + len;
+		match len == 0 || end <= u64::from(self.drive_len) {
+			true => Some(Extent::new(
+				sector_pos,
+				u32::try_from(len).expect("Extent length didn't fit into a `u32`"),
+			)),
+			false => None,
+		}
+	}
+
+	/// Adds an issue
+	fn push_issue(&mut self, path: &str, entry_ptr: Option<DirEntryPtr>, kind: IssueKind) {
+		self.issues.push(Issue {
+			path: path.to_owned(),
+			entry_ptr,
+			kind,
+		});
+	}
+}
+
+/// Returns `bytes` without any trailing nulls
+fn trim_nulls(bytes: &[u8]) -> &[u8] {
+	let len = bytes.iter().rposition(|&ch| ch != 0).map_or(0, |pos| pos + 1);
+	&bytes[..len]
+}
+
+/// Returns if a character is valid within a name or extension
+const fn is_valid_name_char(ch: u8) -> bool {
+	ch.is_ascii_graphic() && !matches!(ch, b'\\' | b'/' | b'.' | b'*' | b'?')
+}
@@ -0,0 +1,40 @@
+//! Errors
+
+// Imports
+use std::io;
+
+/// Error for [`check`](super::check)
+#[derive(Debug, thiserror::Error)]
+pub enum CheckError {
+	/// Unable to get drive length
+	#[error("Unable to get drive length")]
+	DriveLen(#[source] io::Error),
+
+	/// Drive was too large
+	#[error("Drive was too large")]
+	DriveTooLarge,
+
+	/// Unable to seek to directory
+	#[error("Unable to seek to directory")]
+	Seek(#[source] io::Error),
+
+	/// Unable to read entry
+	#[error("Unable to read entry")]
+	ReadEntry(#[source] io::Error),
+}
+
+/// Error for [`repair`](super::repair)
+#[derive(Debug, thiserror::Error)]
+pub enum RepairError {
+	/// Unable to seek to entry
+	#[error("Unable to seek to entry")]
+	Seek(#[source] io::Error),
+
+	/// Unable to read entry
+	#[error("Unable to read entry")]
+	ReadEntry(#[source] io::Error),
+
+	/// Unable to write entry
+	#[error("Unable to write entry")]
+	WriteEntry(#[source] io::Error),
+}
@@ -0,0 +1,116 @@
+//! Tests
+
+// Imports
+use super::*;
+use crate::{
+	test_util::{self, entry, entry_writer, file, file_writer, Lister},
+	DirEntryKind,
+};
+
+/// Creates a drive with several issues
+fn create_drive() -> io::Cursor<Vec<u8>> {
+	// Note: `A` and `B` share their sectors, while `C` is past the end of the drive
+	let mut drive = test_util::create_drive(4, [
+		entry("A", file(1, 0x10)),
+		entry("B", file(1, 0x10)),
+		entry("DIR", DirEntryKind::dir(DirPtr::new(2))),
+		entry("C", file(3, 0x1000)),
+	]);
+	DirPtr::new(2)
+		.write_entries(&mut drive, [entry("LOOP", DirEntryKind::dir(DirPtr::root()))])
+		.expect("Unable to write entries");
+
+	// Then give `DIR` a size
+	drive.get_mut()[2 * 0x20 + 0x8] = 0x10;
+
+	drive
+}
+
+#[test]
+fn check_repair() {
+	let mut drive = self::create_drive();
+
+	let dir_loop = Issue {
+		path:      String::from("\\DIR\\LOOP"),
+		entry_ptr: Some(DirEntryPtr::new(DirPtr::new(2), 0)),
+		kind:      IssueKind::DirLoop(DirPtr::root()),
+	};
+	let shared = Issue {
+		path:      String::from("\\A.BIN"),
+		entry_ptr: Some(DirEntryPtr::new(DirPtr::root(), 0)),
+		kind:      IssueKind::SharedExtent {
+			extent:     Extent::new(1, 1),
+			other_path: String::from("\\B.BIN"),
+		},
+	};
+
+	let issues = check(&mut drive).expect("Unable to check drive");
+	assert_eq!(issues, [
+		Issue {
+			path:      String::from("\\DIR"),
+			entry_ptr: Some(DirEntryPtr::new(DirPtr::root(), 2)),
+			kind:      IssueKind::DirSize(0x10),
+		},
+		dir_loop.clone(),
+		Issue {
+			path:      String::from("\\C.BIN"),
+			entry_ptr: Some(DirEntryPtr::new(DirPtr::root(), 3)),
+			kind:      IssueKind::FileOutOfBounds {
+				ptr:       FilePtr::new(3, 0x1000),
+				drive_len: 4,
+			},
+		},
+		shared.clone(),
+	]);
+
+	// After repairing, only the issues that can't be repaired should remain
+	assert_eq!(repair(&mut drive, &issues).expect("Unable to repair drive"), 2);
+	assert_eq!(check(&mut drive).expect("Unable to check drive"), [dir_loop, shared]);
+}
+
+#[test]
+fn check_shared() {
+	// `A` and `B` are deduplicated, so they share their sectors
+	let mut drive = test_util::write_drive(
+		Lister(vec![
+			entry_writer("A", None, file_writer(&[0xaa; 0x900])),
+			entry_writer("B", None, file_writer(&[0xaa; 0x900])),
+			entry_writer("C", None, file_writer(&[0xcc; 0x10])),
+		]),
+		true,
+	);
+	let shared = Issue {
+		path:      String::from("\\A.BIN"),
+		entry_ptr: Some(DirEntryPtr::new(DirPtr::root(), 0)),
+		kind:      IssueKind::SharedExtent {
+			extent:     Extent::new(1, 2),
+			other_path: String::from("\\B.BIN"),
+		},
+	};
+	let issues = check(&mut drive).expect("Unable to check drive");
+	assert_eq!(issues, [shared.clone()]);
+	assert!(issues[0].is_warning());
+	assert!(!issues[0].is_repairable());
+
+	// But if `C` only partially overlaps them, it's an error
+	let (c_ptr, mut c_entry) = DirPtr::root()
+		.find_entry(&mut drive, test_util::ascii("C.BIN"))
+		.expect("Unable to find file");
+	c_entry.kind = file(2, 0x10);
+	c_ptr.write(&mut drive, &c_entry).expect("Unable to write entry");
+
+	let overlap = |name: &str, idx| Issue {
+		path:      format!("\\{name}.BIN"),
+		entry_ptr: Some(DirEntryPtr::new(DirPtr::root(), idx)),
+		kind:      IssueKind::Overlap {
+			extent:       Extent::new(1, 2),
+			other_path:   String::from("\\C.BIN"),
+			other_extent: Extent::new(2, 1),
+		},
+	};
+	assert_eq!(check(&mut drive).expect("Unable to check drive"), [
+		shared,
+		overlap("A", 0),
+		overlap("B", 1)
+	]);
+}
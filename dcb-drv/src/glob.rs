@@ -0,0 +1,186 @@
+//! Path globbing
+//!
+//! See the [`Glob`] type for more details.
+
+// Modules
+#[cfg(test)]
+mod test;
+
+// Imports
+use crate::{
+	path::Component, ptr::dir::ReadAllEntriesError, DirEntry, DirEntryKind, DirEntryPtr, DirPtr, Path, PathBuf,
+};
+use ascii::{AsciiChar, AsciiStr, AsciiString};
+use std::{io, vec};
+
+/// Iterator over all entries matching a pattern.
+///
+/// Each component of the pattern may contain wildcards, see [`matches`] for details.
+/// Entries are returned depth-first, in the order they appear in each directory.
+#[derive(Debug)]
+pub struct Glob<R> {
+	/// Reader
+	reader: R,
+
+	/// Components of the pattern
+	pattern: Vec<AsciiString>,
+
+	/// All directories being searched, with their path and remaining entries
+	///
+	/// Each directory is matched against the component of the pattern at it's depth.
+	stack: Vec<(PathBuf, vec::IntoIter<(DirEntryPtr, DirEntry)>)>,
+}
+
+impl<R: io::Read + io::Seek> Glob<R> {
+	/// Creates a new iterator over all entries matching `pattern` within `dir_ptr`.
+	///
+	/// The pattern is normalized first, and may not go past `dir_ptr` with `..`.
+	/// If the pattern starts with `\`, the root directory is searched instead.
+	pub fn new(mut reader: R, mut dir_ptr: DirPtr, pattern: &Path) -> Result<Self, GlobError> {
+		// Split the pattern into it's components
+		let mut cmpts = vec![];
+		for cmpt in pattern.normalize().components() {
+			match cmpt {
+				Component::Root => dir_ptr = DirPtr::root(),
+				Component::CurDir => (),
+				Component::ParentDir => return Err(GlobError::ParentDir),
+				Component::Normal(name) => cmpts.push(name.to_ascii_string()),
+			}
+		}
+
+		// Then read the directory, if we have anything to match
+		let stack = match cmpts.is_empty() {
+			true => vec![],
+			false => {
+				let entries = self::read_dir(&mut reader, dir_ptr).map_err(|err| GlobError::ReadDir {
+					path: PathBuf::new(),
+					err,
+				})?;
+				vec![(PathBuf::new(), entries)]
+			},
+		};
+
+		Ok(Self {
+			reader,
+			pattern: cmpts,
+			stack,
+		})
+	}
+
+	/// Returns the reader.
+	///
+	/// May be used to read files while globbing.
+	pub fn reader(&mut self) -> &mut R {
+		&mut self.reader
+	}
+}
+
+impl<R: io::Read + io::Seek> Iterator for Glob<R> {
+	type Item = Result<GlobEntry, GlobError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			// Get the next entry of the current directory, or go back to it's parent
+			let depth = self.stack.len().checked_sub(1)?;
+			let (dir_path, entries) = self.stack.last_mut()?;
+			let (entry_ptr, entry) = match entries.next() {
+				Some(entry) => entry,
+				None => {
+					self.stack.pop();
+					continue;
+				},
+			};
+
+			// Skip it if it doesn't match
+			let name = entry.full_name();
+			if !self::matches(&self.pattern[depth], &name) {
+				continue;
+			}
+			let mut path = dir_path.clone();
+			path.push(&name);
+
+			// If it's the last component, return it
+			if depth + 1 == self.pattern.len() {
+				return Some(Ok(GlobEntry {
+					path,
+					ptr: entry_ptr,
+					entry,
+				}));
+			}
+
+			// Else search it next if it's a directory
+			if let DirEntryKind::Dir { ptr } = entry.kind {
+				match self::read_dir(&mut self.reader, ptr) {
+					Ok(entries) => self.stack.push((path, entries)),
+					Err(err) => return Some(Err(GlobError::ReadDir { path, err })),
+				}
+			}
+		}
+	}
+}
+
+/// An entry returned by [`Glob`]
+#[derive(PartialEq, Clone, Debug)]
+pub struct GlobEntry {
+	/// Path of this entry, relative to the directory being searched
+	pub path: PathBuf,
+
+	/// Entry pointer
+	pub ptr: DirEntryPtr,
+
+	/// Entry
+	pub entry: DirEntry,
+}
+
+/// Error type for [`Glob`]
+#[derive(Debug, thiserror::Error)]
+pub enum GlobError {
+	/// Pattern went past the directory being searched
+	#[error("Pattern went past the directory being searched")]
+	ParentDir,
+
+	/// Unable to read directory
+	#[error("Unable to read directory {path}")]
+	ReadDir {
+		/// Path of the directory
+		path: PathBuf,
+
+		/// Underlying error
+		#[source]
+		err: ReadAllEntriesError,
+	},
+}
+
+/// Returns if `name` matches `pattern`, ignoring case.
+///
+/// Within the pattern, `*` matches any number of characters and `?` matches a single character.
+#[must_use]
+pub fn matches(pattern: &AsciiStr, name: &AsciiStr) -> bool {
+	/// Matches two slices of characters
+	fn matches_chars(pattern: &[AsciiChar], name: &[AsciiChar]) -> bool {
+		match (pattern, name) {
+			([], []) => true,
+			([AsciiChar::Asterisk, pattern @ ..], _) => {
+				(0..=name.len()).any(|idx| matches_chars(pattern, &name[idx..]))
+			},
+			([AsciiChar::Question, pattern @ ..], [_, name @ ..]) => matches_chars(pattern, name),
+			([lhs, pattern @ ..], [rhs, name @ ..]) => lhs.eq_ignore_ascii_case(rhs) && matches_chars(pattern, name),
+			_ => false,
+		}
+	}
+
+	matches_chars(pattern.as_slice(), name.as_slice())
+}
+
+/// Reads all entries of a directory alongside their pointers
+fn read_dir<R: io::Read + io::Seek>(
+	reader: &mut R, dir_ptr: DirPtr,
+) -> Result<vec::IntoIter<(DirEntryPtr, DirEntry)>, ReadAllEntriesError> {
+	let entries = dir_ptr.read_all_entries(reader)?;
+	Ok(entries
+		.into_iter()
+		.zip(0..)
+		.map(|(entry, idx)| (DirEntryPtr::new(dir_ptr, idx), entry))
+		.collect::<Vec<_>>()
+		.into_iter())
+}
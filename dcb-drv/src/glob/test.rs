@@ -0,0 +1,63 @@
+//! Tests
+
+// Imports
+use super::*;
+use crate::test_util::{self, ascii};
+
+/// Creates a drive with the files `A.BIN`, `CARD\LC01.TIM`, `CARD\LC02.TIM` and `CARD\XC01.TIM`
+fn create_drive() -> io::Cursor<Vec<u8>> {
+	let mut drive = test_util::create_drive(2, []);
+	let root = DirPtr::root();
+	let date = test_util::date();
+
+	root.create_file(&mut drive, ascii("A.BIN"), date, &mut io::empty())
+		.expect("Unable to create file");
+	let (_, _, dir_entry) = root
+		.create_dir(&mut drive, ascii("CARD"), date)
+		.expect("Unable to create directory");
+	let dir = dir_entry.kind.as_dir_ptr().expect("Entry wasn't a directory");
+	for name in ["LC01.TIM", "LC02.TIM", "XC01.TIM"] {
+		dir.create_file(&mut drive, ascii(name), date, &mut io::empty())
+			.expect("Unable to create file");
+	}
+
+	drive
+}
+
+/// Returns the paths of all entries matching `pattern`
+fn glob_paths(drive: &mut io::Cursor<Vec<u8>>, pattern: &str) -> Vec<String> {
+	Glob::new(drive, DirPtr::root(), Path::new(ascii(pattern)))
+		.expect("Unable to create glob")
+		.map(|entry| entry.map(|entry| entry.path.to_string()))
+		.collect::<Result<_, _>>()
+		.expect("Unable to glob entries")
+}
+
+#[test]
+fn matches_pattern() {
+	assert!(matches(ascii("LC*.TIM"), ascii("LC01.TIM")));
+	assert!(matches(ascii("lc??.tim"), ascii("LC01.TIM")));
+	assert!(matches(ascii("*"), ascii("")));
+	assert!(!matches(ascii("LC?.TIM"), ascii("LC01.TIM")));
+	assert!(!matches(ascii("LC*.TIM"), ascii("XC01.TIM")));
+}
+
+#[test]
+fn glob() {
+	let mut drive = self::create_drive();
+
+	assert_eq!(self::glob_paths(&mut drive, "CARD\\LC*.TIM"), [
+		"CARD\\LC01.TIM",
+		"CARD\\LC02.TIM"
+	]);
+	assert_eq!(self::glob_paths(&mut drive, "\\*\\?C01.tim"), [
+		"CARD\\LC01.TIM",
+		"CARD\\XC01.TIM"
+	]);
+	assert_eq!(self::glob_paths(&mut drive, "CARD\\..\\*.BIN"), ["A.BIN"]);
+	assert!(self::glob_paths(&mut drive, "A.BIN\\*").is_empty());
+	assert!(matches!(
+		Glob::new(&mut drive, DirPtr::root(), Path::new(ascii("..\\*"))),
+		Err(GlobError::ParentDir)
+	));
+}
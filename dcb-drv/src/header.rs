@@ -0,0 +1,49 @@
+//! Extracted filesystem header
+//!
+//! When extracting a filesystem, the location and date of each entry, which can't be
+//! represented by the extracted files themselves, are stored in a header, so that the
+//! filesystem may be rebuilt with the same layout.
+
+// Imports
+use std::path::{Path, PathBuf};
+
+/// Header
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Header {
+	/// Number of sectors in the file
+	pub sectors_len: u32,
+
+	/// All entries, with each directory followed by it's entries
+	pub entries: Vec<EntryHeader>,
+}
+
+/// Entry header
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct EntryHeader {
+	/// Path, relative to the root directory, separated by `/`
+	pub path: String,
+
+	/// Name
+	pub name: String,
+
+	/// Extension, for files
+	pub extension: Option<String>,
+
+	/// Sector position
+	pub sector_pos: u32,
+
+	/// Date, in seconds since the unix epoch
+	pub date: i64,
+}
+
+/// Returns `path` with `suffix` appended to it
+///
+/// Used for the files extracted alongside the filesystem, such as it's header.
+#[must_use]
+pub fn path_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+	let mut path = path.as_os_str().to_os_string();
+	path.push(suffix);
+	PathBuf::from(path)
+}
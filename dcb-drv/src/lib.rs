@@ -12,16 +12,26 @@
 )]
 
 // Modules
+pub mod allocator;
 pub mod dir;
 pub mod entry;
+pub mod fsck;
+pub mod glob;
+pub mod header;
 pub mod path;
 pub mod ptr;
+pub mod replace;
 pub mod swap;
+#[cfg(test)]
+mod test_util;
 pub mod writer;
 
 // Exports
+pub use allocator::SectorAllocator;
 pub use entry::{DirEntry, DirEntryKind};
+pub use glob::Glob;
 pub use path::{Path, PathBuf};
 pub use ptr::{DirEntryPtr, DirPtr, FilePtr};
+pub use replace::replace_file;
 pub use swap::swap_files;
 pub use writer::{DirEntryWriter, DirEntryWriterKind, DirWriter, DirWriterLister};
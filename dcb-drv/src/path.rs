@@ -76,6 +76,23 @@ impl Path {
 		self
 	}
 
+	/// Splits this path into it's parent and file name, ignoring any trailing slashes.
+	///
+	/// Returns `None` if the path doesn't end in a name, such as `\` or `A\..`.
+	#[must_use]
+	pub fn split_file_name(&self) -> Option<(&Self, &AsciiStr)> {
+		let path = self.trim_trailing();
+		let (parent, name) = match path.0.as_slice().iter().rposition(|&ch| ch == AsciiChar::BackSlash) {
+			Some(idx) => (&path[..(idx + 1)], &path[(idx + 1)..]),
+			None => (Path::empty(), path),
+		};
+
+		match name.as_str() {
+			"" | "." | ".." => None,
+			_ => Some((parent, name.as_ascii())),
+		}
+	}
+
 	/// Returns an iterator over all components of this path
 	#[must_use]
 	pub const fn components(&self) -> Components {
@@ -87,6 +104,49 @@ impl Path {
 	pub fn to_path_buf(&self) -> PathBuf {
 		PathBuf(self.0.to_ascii_string())
 	}
+
+	/// Returns if this path contains any wildcards, `*` or `?`
+	#[must_use]
+	pub fn is_glob(&self) -> bool {
+		self.0
+			.chars()
+			.any(|ch| matches!(ch, AsciiChar::Asterisk | AsciiChar::Question))
+	}
+
+	/// Normalizes this path.
+	///
+	/// Removes all `.` components and resolves all `..` components with the component
+	/// before them. `..` components at the start of a relative path are kept, while
+	/// the ones at the start of an absolute path are removed.
+	#[must_use]
+	pub fn normalize(&self) -> PathBuf {
+		let mut is_absolute = false;
+		let mut parents = 0;
+		let mut cmpts = vec![];
+		for cmpt in self.components() {
+			match cmpt {
+				Component::Root => is_absolute = true,
+				Component::CurDir => (),
+				Component::ParentDir => {
+					if cmpts.pop().is_none() && !is_absolute {
+						parents += 1;
+					}
+				},
+				Component::Normal(name) => cmpts.push(name),
+			}
+		}
+
+		let mut path = PathBuf::new();
+		if is_absolute {
+			path.0.push(AsciiChar::BackSlash);
+		}
+		let parent_dir = AsciiStr::from_ascii("..").expect("`..` wasn't valid ascii");
+		for name in std::iter::repeat(parent_dir).take(parents).chain(cmpts) {
+			path.push(name);
+		}
+
+		path
+	}
 }
 
 impl PartialEq for Path {
@@ -132,6 +192,34 @@ impl fmt::Display for Path {
 #[derive(Clone, Debug)]
 pub struct PathBuf(AsciiString);
 
+impl PathBuf {
+	/// Creates a new, empty, path
+	#[must_use]
+	pub const fn new() -> Self {
+		Self(AsciiString::new())
+	}
+
+	/// Appends `name` to this path, separated by `\\`
+	pub fn push(&mut self, name: &AsciiStr) {
+		if !self.0.is_empty() && self.0.last() != Some(AsciiChar::BackSlash) {
+			self.0.push(AsciiChar::BackSlash);
+		}
+		self.0.push_str(name);
+	}
+}
+
+impl Default for PathBuf {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl From<AsciiString> for PathBuf {
+	fn from(path: AsciiString) -> Self {
+		Self(path)
+	}
+}
+
 impl ops::Deref for PathBuf {
 	type Target = Path;
 
@@ -60,3 +60,34 @@ fn extra_separators() {
 		Normal(ascii("B")),
 	]);
 }
+
+#[test]
+fn normalize() {
+	let normalize = |path| Path::new(ascii(path)).normalize().to_string();
+
+	assert_eq!(normalize("A\\.\\B\\..\\C"), "A\\C");
+	assert_eq!(normalize("\\..\\A\\\\B\\"), "\\A\\B");
+	assert_eq!(normalize("..\\A\\..\\..\\B"), "..\\..\\B");
+	assert_eq!(normalize(".\\A\\.."), "");
+}
+
+#[test]
+fn is_glob() {
+	assert!(Path::new(ascii("CARD\\LC*.TIM")).is_glob());
+	assert!(Path::new(ascii("CARD\\LC0?.TIM")).is_glob());
+	assert!(!Path::new(ascii("CARD\\LC01.TIM")).is_glob());
+}
+
+#[test]
+fn split_file_name() {
+	let split = |path| {
+		Path::new(ascii(path))
+			.split_file_name()
+			.map(|(parent, name)| (parent.as_str(), name.as_str()))
+	};
+	assert_eq!(split("A"), Some(("", "A")));
+	assert_eq!(split("\\A\\B.BIN"), Some(("\\A\\", "B.BIN")));
+	assert_eq!(split("\\A\\"), Some(("\\", "A")));
+	assert_eq!(split("\\"), None);
+	assert_eq!(split("A\\.."), None);
+}
@@ -6,13 +6,27 @@ mod error;
 mod test;
 
 // Exports
-pub use error::{FindEntryError, FindError, ReadEntriesError, ReadEntryError, WriteEntriesError};
+pub use error::{
+	CreateDirError, CreateFileError, FindDirError, FindEntryError, FindError, LinkFileError, PushEntryError,
+	ReadAllEntriesError, ReadEntriesError, ReadEntryError, RemoveEntryError, RenameEntryError, ReserveEntryError,
+	WriteEntriesError,
+};
 
 // Imports
-use crate::{path, DirEntry, DirEntryKind, DirEntryPtr, Path};
+use crate::{
+	allocator::Extent,
+	glob::{Glob, GlobError},
+	path, DirEntry, DirEntryKind, DirEntryPtr, FilePtr, Path, SectorAllocator,
+};
 use ascii::AsciiStr;
+use chrono::NaiveDateTime;
 use dcb_bytes::Bytes;
-use std::io::{self, SeekFrom};
+use std::{
+	collections::BTreeSet,
+	convert::TryFrom,
+	io::{self, SeekFrom},
+};
+use zutil::AsciiStrArr;
 
 /// Directory pointer
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
@@ -59,16 +73,12 @@ impl DirPtr {
 				}
 
 				// Special case some entries which cause problems
-				#[allow(clippy::single_match)] // We might add more matches in the future
-				match &bytes {
-					b"\x01CDD\xd5/\x00\x00\xf0?\x01\x00\xe6u\xad:\x83R\x83S\x81[ \x81` CARD2\x00" => {
-						log::warn!(
-							"Ignoring special directory entry: {:?}",
-							String::from_utf8_lossy(&bytes)
-						);
-						return None;
-					},
-					_ => (),
+				if self::is_ignored_entry(&bytes) {
+					log::warn!(
+						"Ignoring special directory entry: {:?}",
+						String::from_utf8_lossy(&bytes)
+					);
+					return None;
 				}
 
 				// Else parse it
@@ -86,10 +96,15 @@ impl DirPtr {
 		Ok(iter)
 	}
 
-	/// Finds an entry from it's path
+	/// Finds an entry from it's path.
+	///
+	/// The path is normalized before being searched and all names are compared ignoring case.
 	pub fn find<R: io::Seek + io::Read>(
 		self, reader: &mut R, path: &Path,
 	) -> Result<(DirEntryPtr, DirEntry), FindError> {
+		// Normalize the path, so only leading `..` are left
+		let path = path.normalize();
+
 		// Current directory pointer
 		let mut cur_ptr = self;
 
@@ -106,11 +121,9 @@ impl DirPtr {
 				Some(path::Component::CurDir) => continue,
 
 				// Return `Err` on parent directories
-				// Note: We don't support parent directories as we'd have to store all
-				//       of the parent directories, because directories don't have
-				//       access to their parents
-				// TODO: Using recursion / stack allocation outside of the loop we could easily store
-				//       all parent dirs we've been through, without any heap allocations.
+				// Note: After normalizing, these only exist at the start of the path, which
+				//       would require the parent of this directory, which directories don't
+				//       have access to.
 				Some(path::Component::ParentDir) => return Err(FindError::ParentDir),
 
 				// On a normal entry, find the entry in the current dir
@@ -145,32 +158,40 @@ impl DirPtr {
 		}
 	}
 
-	/// Finds an entry
+	/// Finds a directory from it's path.
+	///
+	/// Paths without any names refer to this directory, or to the root directory if they start with `\\`.
+	pub fn find_dir<R: io::Seek + io::Read>(self, reader: &mut R, path: &Path) -> Result<Self, FindDirError> {
+		match self.find(reader, path) {
+			Ok((_, entry)) => entry.kind.as_dir_ptr().ok_or(FindDirError::FoundFile),
+			Err(FindError::EmptyPath) => match path.normalize().components().next() {
+				Some(path::Component::Root) => Ok(Self::root()),
+				_ => Ok(self),
+			},
+			Err(err) => Err(FindDirError::Find(err)),
+		}
+	}
+
+	/// Returns an iterator over all entries matching `pattern`.
+	///
+	/// See [`Glob`] for more details.
+	pub fn glob<R: io::Read + io::Seek>(self, reader: R, pattern: &Path) -> Result<Glob<R>, GlobError> {
+		Glob::new(reader, self, pattern)
+	}
+
+	/// Finds an entry by it's name, ignoring case
 	pub fn find_entry<R: io::Read + io::Seek>(
 		self, reader: &mut R, entry_name: &AsciiStr,
 	) -> Result<(DirEntryPtr, DirEntry), FindEntryError> {
-		let (filename, extension) = entry_name
-			.as_str()
-			.split_once('.')
-			.map_or((entry_name.as_str(), None), |(filename, extension)| {
-				(filename, Some(extension))
-			});
+		let (filename, extension) = self::split_name(entry_name);
 
 		self.read_entries(reader)
 			.map_err(FindEntryError::SeekDir)?
 			.zip(0..)
 			.find_map(|(entry, idx)| match entry {
-				Ok(entry) => {
-					let is_match = entry.name.as_str() == filename &&
-						match entry.kind {
-							DirEntryKind::Dir { .. } => extension.is_none(),
-							DirEntryKind::File { extension: ext, .. } => extension == Some(ext.as_str()),
-						};
-
-					match is_match {
-						true => Some(Ok((DirEntryPtr::new(self, idx), entry))),
-						false => None,
-					}
+				Ok(entry) => match self::entry_matches(&entry, filename, extension) {
+					true => Some(Ok((DirEntryPtr::new(self, idx), entry))),
+					false => None,
 				},
 				Err(err) => Some(Err(err)),
 			})
@@ -178,7 +199,17 @@ impl DirPtr {
 			.map_err(FindEntryError::ReadEntry)
 	}
 
-	/// Writes a list of entries to a writer
+	/// Returns all entries in this directory
+	pub fn read_all_entries<R: io::Read + io::Seek>(
+		self, reader: &mut R,
+	) -> Result<Vec<DirEntry>, ReadAllEntriesError> {
+		self.read_entries(reader)
+			.map_err(ReadAllEntriesError::ReadEntries)?
+			.collect::<Result<_, _>>()
+			.map_err(ReadAllEntriesError::ReadEntry)
+	}
+
+	/// Writes a list of entries to a writer, followed by the null entry
 	pub fn write_entries<W: io::Seek + io::Write>(
 		self, writer: &mut W, entries: impl IntoIterator<Item = DirEntry>,
 	) -> Result<(), WriteEntriesError> {
@@ -195,6 +226,396 @@ impl DirPtr {
 			writer.write_all(&entry_bytes).map_err(WriteEntriesError::WriteEntry)?;
 		}
 
+		// And finish with the null entry
+		writer.write_all(&[0; 0x20]).map_err(WriteEntriesError::WriteEntry)?;
+
 		Ok(())
 	}
+
+	/// Creates a file in this directory with the contents of `reader`.
+	///
+	/// `name` must include the extension, e.g. `FILE.BIN`.
+	///
+	/// If this directory must grow, it may be relocated, so it's new pointer is returned
+	/// alongside the created entry.
+	pub fn create_file<T: io::Seek + io::Read + io::Write, R: io::Read>(
+		self, cursor: &mut T, name: &AsciiStr, date: NaiveDateTime, reader: &mut R,
+	) -> Result<(DirPtr, DirEntryPtr, DirEntry), CreateFileError> {
+		// Check if the name is valid and unused
+		let (filename, extension) = match self::parse_name(name) {
+			Some((filename, Some(extension))) => (filename, extension),
+			_ => return Err(CreateFileError::InvalidName),
+		};
+		let mut entries = self.read_all_entries(cursor).map_err(CreateFileError::ReadEntries)?;
+		if self::find_entry_idx(&entries, name).is_some() {
+			return Err(CreateFileError::AlreadyExists);
+		}
+
+		// Read the contents
+		let mut contents = vec![];
+		reader
+			.read_to_end(&mut contents)
+			.map_err(CreateFileError::ReadContents)?;
+		let size = u32::try_from(contents.len()).map_err(|_| CreateFileError::FileTooLarge)?;
+
+		// Then reserve space for the entry and allocate the contents before writing anything,
+		// so we don't leave anything behind if the drive is full.
+		let mut allocator = SectorAllocator::new(cursor).map_err(CreateFileError::NewAllocator)?;
+		let free_len = allocator.free_len();
+		let file_len = Extent::from_file_ptr(FilePtr::new(0, size)).len;
+		let reservation = match self.reserve_entry(cursor, &mut allocator, &entries) {
+			Ok(reservation) => reservation,
+			Err(ReserveEntryError::DriveFull { sectors_len }) => {
+				return Err(CreateFileError::DriveFull {
+					sectors_len: sectors_len + file_len,
+					free_len,
+				})
+			},
+			Err(err) => return Err(CreateFileError::ReserveEntry(err)),
+		};
+		let extent = allocator
+			.try_allocate(file_len, allocator.len())
+			.ok_or(CreateFileError::DriveFull {
+				sectors_len: reservation.sectors_len + file_len,
+				free_len,
+			})?;
+
+		// And write them
+		let ptr = FilePtr::new(extent.sector_pos, size);
+		ptr.seek_to(cursor).map_err(CreateFileError::SeekFile)?;
+		cursor.write_all(&contents).map_err(CreateFileError::WriteFile)?;
+
+		// And add the entry
+		let entry = DirEntry {
+			name: filename,
+			date,
+			kind: DirEntryKind::file(extension, ptr),
+		};
+		let (dir_ptr, entry_ptr) = reservation
+			.push_entry(cursor, &mut entries, entry.clone())
+			.map_err(CreateFileError::PushEntry)?;
+
+		Ok((dir_ptr, entry_ptr, entry))
+	}
+
+	/// Creates an empty directory in this directory.
+	///
+	/// If this directory must grow, it may be relocated, so it's new pointer is returned
+	/// alongside the created entry.
+	pub fn create_dir<T: io::Seek + io::Read + io::Write>(
+		self, cursor: &mut T, name: &AsciiStr, date: NaiveDateTime,
+	) -> Result<(DirPtr, DirEntryPtr, DirEntry), CreateDirError> {
+		// Check if the name is valid and unused
+		let dir_name = match self::parse_name(name) {
+			Some((dir_name, None)) => dir_name,
+			_ => return Err(CreateDirError::InvalidName),
+		};
+		let mut entries = self.read_all_entries(cursor).map_err(CreateDirError::ReadEntries)?;
+		if self::find_entry_idx(&entries, name).is_some() {
+			return Err(CreateDirError::AlreadyExists);
+		}
+
+		// Then reserve space for the entry and allocate the directory before writing anything
+		let mut allocator = SectorAllocator::new(cursor).map_err(CreateDirError::NewAllocator)?;
+		let free_len = allocator.free_len();
+		let reservation = match self.reserve_entry(cursor, &mut allocator, &entries) {
+			Ok(reservation) => reservation,
+			Err(ReserveEntryError::DriveFull { sectors_len }) => {
+				return Err(CreateDirError::DriveFull {
+					sectors_len: sectors_len + 1,
+					free_len,
+				})
+			},
+			Err(err) => return Err(CreateDirError::ReserveEntry(err)),
+		};
+		let extent = allocator
+			.try_allocate(1, allocator.len())
+			.ok_or(CreateDirError::DriveFull {
+				sectors_len: reservation.sectors_len + 1,
+				free_len,
+			})?;
+
+		// And write the directory, with only the null entry
+		let ptr = DirPtr::new(extent.sector_pos);
+		ptr.write_entries(cursor, std::iter::empty())
+			.map_err(CreateDirError::WriteDir)?;
+
+		// And add the entry
+		let entry = DirEntry {
+			name: dir_name,
+			date,
+			kind: DirEntryKind::dir(ptr),
+		};
+		let (dir_ptr, entry_ptr) = reservation
+			.push_entry(cursor, &mut entries, entry.clone())
+			.map_err(CreateDirError::PushEntry)?;
+
+		Ok((dir_ptr, entry_ptr, entry))
+	}
+
+	/// Adds a file in this directory pointing to the sectors of an existing file.
+	///
+	/// Used to move files within a drive without copying their contents.
+	/// `name` must include the extension, e.g. `FILE.BIN`.
+	///
+	/// If this directory must grow, it may be relocated, so it's new pointer is returned
+	/// alongside the created entry.
+	pub fn link_file<T: io::Seek + io::Read + io::Write>(
+		self, cursor: &mut T, name: &AsciiStr, date: NaiveDateTime, ptr: FilePtr,
+	) -> Result<(DirPtr, DirEntryPtr, DirEntry), LinkFileError> {
+		// Check if the name is valid and unused
+		let (filename, extension) = match self::parse_name(name) {
+			Some((filename, Some(extension))) => (filename, extension),
+			_ => return Err(LinkFileError::InvalidName),
+		};
+		let mut entries = self.read_all_entries(cursor).map_err(LinkFileError::ReadEntries)?;
+		if self::find_entry_idx(&entries, name).is_some() {
+			return Err(LinkFileError::AlreadyExists);
+		}
+
+		// Then reserve space for the entry and add it
+		let mut allocator = SectorAllocator::new(cursor).map_err(LinkFileError::NewAllocator)?;
+		let reservation = self
+			.reserve_entry(cursor, &mut allocator, &entries)
+			.map_err(LinkFileError::ReserveEntry)?;
+		let entry = DirEntry {
+			name: filename,
+			date,
+			kind: DirEntryKind::file(extension, ptr),
+		};
+		let (dir_ptr, entry_ptr) = reservation
+			.push_entry(cursor, &mut entries, entry.clone())
+			.map_err(LinkFileError::PushEntry)?;
+
+		Ok((dir_ptr, entry_ptr, entry))
+	}
+
+	/// Removes an entry from this directory.
+	///
+	/// Directories may only be removed if they're empty.
+	/// All entries after the removed entry are moved back, so their pointers change.
+	pub fn remove_entry<T: io::Seek + io::Read + io::Write>(
+		self, cursor: &mut T, name: &AsciiStr,
+	) -> Result<DirEntry, RemoveEntryError> {
+		// Find the entry
+		let mut entries = self.read_all_entries(cursor).map_err(RemoveEntryError::ReadEntries)?;
+		let idx = self::find_entry_idx(&entries, name).ok_or(RemoveEntryError::NotFound)?;
+
+		// If it's a directory, make sure it's empty
+		if let DirEntryKind::Dir { ptr } = entries[idx].kind {
+			let dir_entries = ptr.read_all_entries(cursor).map_err(RemoveEntryError::ReadDirEntries)?;
+			if !dir_entries.is_empty() {
+				return Err(RemoveEntryError::DirNotEmpty);
+			}
+		}
+
+		// Then remove it and write the remaining entries
+		// Note: The directory won't grow, so we can write it in-place.
+		let entry = entries.remove(idx);
+		self.write_entries(cursor, entries)
+			.map_err(RemoveEntryError::WriteEntries)?;
+
+		Ok(entry)
+	}
+
+	/// Renames an entry in this directory.
+	///
+	/// Files must keep an extension, while directories may not have one.
+	pub fn rename_entry<T: io::Seek + io::Read + io::Write>(
+		self, cursor: &mut T, name: &AsciiStr, new_name: &AsciiStr,
+	) -> Result<(DirEntryPtr, DirEntry), RenameEntryError> {
+		// Find the entry and make sure the new name is unused
+		let entries = self.read_all_entries(cursor).map_err(RenameEntryError::ReadEntries)?;
+		let idx = self::find_entry_idx(&entries, name).ok_or(RenameEntryError::NotFound)?;
+		if self::find_entry_idx(&entries, new_name).map_or(false, |new_idx| new_idx != idx) {
+			return Err(RenameEntryError::AlreadyExists);
+		}
+
+		// Then rename it
+		let mut entry = entries[idx].clone();
+		let (new_filename, new_extension) = self::parse_name(new_name).ok_or(RenameEntryError::InvalidName)?;
+		entry.name = new_filename;
+		match (&mut entry.kind, new_extension) {
+			(DirEntryKind::File { extension, .. }, Some(new_extension)) => *extension = new_extension,
+			(DirEntryKind::Dir { .. }, None) => (),
+			_ => return Err(RenameEntryError::InvalidName),
+		}
+
+		// And write it
+		let idx = u32::try_from(idx).expect("Number of entries didn't fit into a `u32`");
+		let entry_ptr = DirEntryPtr::new(self, idx);
+		entry_ptr.write(cursor, &entry).map_err(RenameEntryError::WriteEntry)?;
+
+		Ok((entry_ptr, entry))
+	}
+
+	/// Reserves the sectors needed to add an entry to this directory, which currently has `entries`.
+	///
+	/// If they no longer fit, the directory is grown in-place if the following sectors are free,
+	/// else it is relocated to free sectors within the drive.
+	/// The root directory cannot be relocated.
+	fn reserve_entry<R: io::Read + io::Seek>(
+		self, reader: &mut R, allocator: &mut SectorAllocator, entries: &[DirEntry],
+	) -> Result<EntryReservation, ReserveEntryError> {
+		let entries_len = u32::try_from(entries.len()).expect("Number of entries didn't fit into a `u32`");
+		let old_extent = Extent::from_dir_ptr(self, entries_len);
+		let new_extent = Extent::from_dir_ptr(self, entries_len + 1);
+
+		// Check if we need to grow and if we can do so in-place, without going past the end of the drive
+		let grown_extent = Extent::new(old_extent.end(), new_extent.len - old_extent.len);
+		let can_grow = grown_extent.end() <= allocator.len() &&
+			(grown_extent.sector_pos..grown_extent.end()).all(|sector_pos| allocator.is_free(sector_pos));
+		if new_extent.len == old_extent.len || can_grow {
+			allocator.mark_used(grown_extent);
+			return Ok(EntryReservation {
+				dir_ptr: self,
+				entries_len,
+				parent_entry_ptr: None,
+				sectors_len: grown_extent.len,
+			});
+		}
+
+		// Else relocate ourselves
+		if self == DirPtr::root() {
+			return Err(ReserveEntryError::RootFull);
+		}
+		let parent_entry_ptr = DirPtr::root()
+			.find_parent_entry(reader, self)
+			.map_err(ReserveEntryError::FindParent)?
+			.ok_or(ReserveEntryError::NoParent)?;
+
+		// Note: We don't free our current sectors, so nothing else is allocated over
+		//       them before the entries are written to the new ones.
+		let extent = allocator
+			.try_allocate(new_extent.len, allocator.len())
+			.ok_or(ReserveEntryError::DriveFull {
+				sectors_len: new_extent.len,
+			})?;
+
+		Ok(EntryReservation {
+			dir_ptr: DirPtr::new(extent.sector_pos),
+			entries_len,
+			parent_entry_ptr: Some(parent_entry_ptr),
+			sectors_len: extent.len,
+		})
+	}
+
+	/// Finds the entry of the directory `dir` within this directory or any of it's children
+	fn find_parent_entry<R: io::Read + io::Seek>(
+		self, reader: &mut R, dir: DirPtr,
+	) -> Result<Option<DirEntryPtr>, ReadAllEntriesError> {
+		let mut visited = BTreeSet::new();
+		let mut dirs = vec![self];
+		while let Some(cur_dir) = dirs.pop() {
+			// Note: If we've been to this directory already, don't read it again, as it could loop.
+			if !visited.insert(cur_dir) {
+				continue;
+			}
+
+			for (entry, idx) in cur_dir.read_all_entries(reader)?.into_iter().zip(0..) {
+				match entry.kind {
+					DirEntryKind::Dir { ptr } if ptr == dir => return Ok(Some(DirEntryPtr::new(cur_dir, idx))),
+					DirEntryKind::Dir { ptr } => dirs.push(ptr),
+					DirEntryKind::File { .. } => (),
+				}
+			}
+		}
+
+		Ok(None)
+	}
+}
+
+/// Sectors reserved for adding an entry to a directory
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+struct EntryReservation {
+	/// Directory to write the entries to
+	dir_ptr: DirPtr,
+
+	/// Number of entries before adding the entry
+	entries_len: u32,
+
+	/// Entry of the directory in it's parent, if it was relocated
+	parent_entry_ptr: Option<DirEntryPtr>,
+
+	/// Number of sectors reserved
+	sectors_len: u32,
+}
+
+impl EntryReservation {
+	/// Adds an entry to `entries` and writes them to the reserved sectors.
+	///
+	/// If the directory was relocated, it's entry in the parent directory is updated.
+	fn push_entry<T: io::Seek + io::Read + io::Write>(
+		self, cursor: &mut T, entries: &mut Vec<DirEntry>, entry: DirEntry,
+	) -> Result<(DirPtr, DirEntryPtr), PushEntryError> {
+		entries.push(entry);
+		self.dir_ptr
+			.write_entries(cursor, entries.iter().cloned())
+			.map_err(PushEntryError::WriteEntries)?;
+
+		if let Some(parent_entry_ptr) = self.parent_entry_ptr {
+			let mut parent_entries = parent_entry_ptr
+				.dir
+				.read_all_entries(cursor)
+				.map_err(PushEntryError::ReadParentEntries)?;
+			let parent_idx = usize::try_from(parent_entry_ptr.entry).expect("Entry index didn't fit into a `usize`");
+			let parent_entry = &mut parent_entries[parent_idx];
+			parent_entry.kind = DirEntryKind::dir(self.dir_ptr);
+			parent_entry_ptr
+				.write(cursor, parent_entry)
+				.map_err(PushEntryError::WriteParentEntry)?;
+		}
+
+		Ok((self.dir_ptr, DirEntryPtr::new(self.dir_ptr, self.entries_len)))
+	}
+}
+
+/// Returns if an entry is special cased as the end of a directory, as it causes problems when read
+pub(crate) fn is_ignored_entry(bytes: &[u8; 0x20]) -> bool {
+	bytes == b"\x01CDD\xd5/\x00\x00\xf0?\x01\x00\xe6u\xad:\x83R\x83S\x81[ \x81` CARD2\x00"
+}
+
+/// Splits an entry name into it's name and extension
+fn split_name(name: &AsciiStr) -> (&str, Option<&str>) {
+	name.as_str()
+		.split_once('.')
+		.map_or((name.as_str(), None), |(name, extension)| (name, Some(extension)))
+}
+
+/// Parses an entry name into it's name and extension.
+///
+/// Returns `None` if the name is empty, too long or contains any separators.
+fn parse_name(name: &AsciiStr) -> Option<(AsciiStrArr<0x10>, Option<AsciiStrArr<0x3>>)> {
+	let (name, extension) = self::split_name(name);
+	if name.is_empty() || name.contains('\\') || extension.map_or(false, |extension| extension.contains(['.', '\\'])) {
+		return None;
+	}
+
+	let name = AsciiStrArr::from_bytes(name).ok()?;
+	let extension = match extension {
+		Some(extension) => Some(AsciiStrArr::from_bytes(extension).ok()?),
+		None => None,
+	};
+
+	Some((name, extension))
+}
+
+/// Returns if an entry matches a name and extension, ignoring case
+fn entry_matches(entry: &DirEntry, name: &str, extension: Option<&str>) -> bool {
+	entry.name.as_str().eq_ignore_ascii_case(name) &&
+		match (entry.kind, extension) {
+			(DirEntryKind::Dir { .. }, None) => true,
+			(DirEntryKind::File { extension: ext, .. }, Some(extension)) => {
+				ext.as_str().eq_ignore_ascii_case(extension)
+			},
+			_ => false,
+		}
+}
+
+/// Finds the index of an entry by it's name
+fn find_entry_idx(entries: &[DirEntry], name: &AsciiStr) -> Option<usize> {
+	let (name, extension) = self::split_name(name);
+	entries
+		.iter()
+		.position(|entry| self::entry_matches(entry, name, extension))
 }
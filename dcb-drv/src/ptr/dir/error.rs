@@ -60,6 +60,18 @@ pub enum FindError {
 	EmptyPath,
 }
 
+/// Error type for [`DirPtr::find_dir`](super::DirPtr::find_dir)
+#[derive(Debug, thiserror::Error)]
+pub enum FindDirError {
+	/// Unable to find directory
+	#[error("Unable to find directory")]
+	Find(#[source] FindError),
+
+	/// Found file
+	#[error("Found file")]
+	FoundFile,
+}
+
 /// Error for [`DirPtr::find_entry`](super::DirPtr::find_entry)
 #[derive(Debug, thiserror::Error)]
 pub enum FindEntryError {
@@ -75,3 +87,226 @@ pub enum FindEntryError {
 	#[error("Unable to find entry")]
 	FindEntry,
 }
+
+/// Error for [`DirPtr::read_all_entries`](super::DirPtr::read_all_entries)
+#[derive(Debug, thiserror::Error)]
+pub enum ReadAllEntriesError {
+	/// Unable to read entries
+	#[error("Unable to read entries")]
+	ReadEntries(#[source] ReadEntriesError),
+
+	/// Unable to read entry
+	#[error("Unable to read entry")]
+	ReadEntry(#[source] ReadEntryError),
+}
+
+/// Error for reserving space for an entry in a directory
+#[derive(Debug, thiserror::Error)]
+pub enum ReserveEntryError {
+	/// The root directory is full and cannot be relocated
+	#[error("The root directory is full and cannot be relocated")]
+	RootFull,
+
+	/// Unable to find parent directory
+	#[error("Unable to find parent directory")]
+	FindParent(#[source] ReadAllEntriesError),
+
+	/// Directory has no parent
+	#[error("Directory has no parent")]
+	NoParent,
+
+	/// Drive doesn't have enough free sectors to relocate the directory
+	#[error("Drive doesn't have {sectors_len} free sectors to relocate the directory")]
+	DriveFull {
+		/// Number of sectors needed
+		sectors_len: u32,
+	},
+}
+
+/// Error for adding an entry to a directory
+#[derive(Debug, thiserror::Error)]
+pub enum PushEntryError {
+	/// Unable to write entries
+	#[error("Unable to write entries")]
+	WriteEntries(#[source] WriteEntriesError),
+
+	/// Unable to read parent directory entries
+	#[error("Unable to read parent directory entries")]
+	ReadParentEntries(#[source] ReadAllEntriesError),
+
+	/// Unable to write parent directory entry
+	#[error("Unable to write parent directory entry")]
+	WriteParentEntry(#[source] crate::ptr::entry::WriteEntryError),
+}
+
+/// Error for [`DirPtr::create_file`](super::DirPtr::create_file)
+#[derive(Debug, thiserror::Error)]
+pub enum CreateFileError {
+	/// Invalid name
+	#[error("Invalid name")]
+	InvalidName,
+
+	/// Unable to read entries
+	#[error("Unable to read entries")]
+	ReadEntries(#[source] ReadAllEntriesError),
+
+	/// Entry already exists
+	#[error("Entry already exists")]
+	AlreadyExists,
+
+	/// Unable to read contents
+	#[error("Unable to read contents")]
+	ReadContents(#[source] io::Error),
+
+	/// File size was too large
+	#[error("File size was too large")]
+	FileTooLarge,
+
+	/// Unable to create sector allocator
+	#[error("Unable to create sector allocator")]
+	NewAllocator(#[source] crate::allocator::NewError),
+
+	/// Drive doesn't have enough free sectors
+	#[error("Drive only has {free_len} free sectors, but {sectors_len} are needed")]
+	DriveFull {
+		/// Number of sectors needed
+		sectors_len: u32,
+
+		/// Number of free sectors
+		free_len: u32,
+	},
+
+	/// Unable to reserve space for the entry
+	#[error("Unable to reserve space for the entry")]
+	ReserveEntry(#[source] ReserveEntryError),
+
+	/// Unable to seek to file
+	#[error("Unable to seek to file")]
+	SeekFile(#[source] io::Error),
+
+	/// Unable to write file
+	#[error("Unable to write file")]
+	WriteFile(#[source] io::Error),
+
+	/// Unable to add entry
+	#[error("Unable to add entry")]
+	PushEntry(#[source] PushEntryError),
+}
+
+/// Error for [`DirPtr::link_file`](super::DirPtr::link_file)
+#[derive(Debug, thiserror::Error)]
+pub enum LinkFileError {
+	/// Invalid name
+	#[error("Invalid name")]
+	InvalidName,
+
+	/// Unable to read entries
+	#[error("Unable to read entries")]
+	ReadEntries(#[source] ReadAllEntriesError),
+
+	/// Entry already exists
+	#[error("Entry already exists")]
+	AlreadyExists,
+
+	/// Unable to create sector allocator
+	#[error("Unable to create sector allocator")]
+	NewAllocator(#[source] crate::allocator::NewError),
+
+	/// Unable to reserve space for the entry
+	#[error("Unable to reserve space for the entry")]
+	ReserveEntry(#[source] ReserveEntryError),
+
+	/// Unable to add entry
+	#[error("Unable to add entry")]
+	PushEntry(#[source] PushEntryError),
+}
+
+/// Error for [`DirPtr::create_dir`](super::DirPtr::create_dir)
+#[derive(Debug, thiserror::Error)]
+pub enum CreateDirError {
+	/// Invalid name
+	#[error("Invalid name")]
+	InvalidName,
+
+	/// Unable to read entries
+	#[error("Unable to read entries")]
+	ReadEntries(#[source] ReadAllEntriesError),
+
+	/// Entry already exists
+	#[error("Entry already exists")]
+	AlreadyExists,
+
+	/// Unable to create sector allocator
+	#[error("Unable to create sector allocator")]
+	NewAllocator(#[source] crate::allocator::NewError),
+
+	/// Drive doesn't have enough free sectors
+	#[error("Drive only has {free_len} free sectors, but {sectors_len} are needed")]
+	DriveFull {
+		/// Number of sectors needed
+		sectors_len: u32,
+
+		/// Number of free sectors
+		free_len: u32,
+	},
+
+	/// Unable to reserve space for the entry
+	#[error("Unable to reserve space for the entry")]
+	ReserveEntry(#[source] ReserveEntryError),
+
+	/// Unable to write directory
+	#[error("Unable to write directory")]
+	WriteDir(#[source] WriteEntriesError),
+
+	/// Unable to add entry
+	#[error("Unable to add entry")]
+	PushEntry(#[source] PushEntryError),
+}
+
+/// Error for [`DirPtr::remove_entry`](super::DirPtr::remove_entry)
+#[derive(Debug, thiserror::Error)]
+pub enum RemoveEntryError {
+	/// Unable to read entries
+	#[error("Unable to read entries")]
+	ReadEntries(#[source] ReadAllEntriesError),
+
+	/// Unable to find entry
+	#[error("Unable to find entry")]
+	NotFound,
+
+	/// Unable to read directory entries
+	#[error("Unable to read directory entries")]
+	ReadDirEntries(#[source] ReadAllEntriesError),
+
+	/// Directory wasn't empty
+	#[error("Directory wasn't empty")]
+	DirNotEmpty,
+
+	/// Unable to write entries
+	#[error("Unable to write entries")]
+	WriteEntries(#[source] WriteEntriesError),
+}
+
+/// Error for [`DirPtr::rename_entry`](super::DirPtr::rename_entry)
+#[derive(Debug, thiserror::Error)]
+pub enum RenameEntryError {
+	/// Unable to read entries
+	#[error("Unable to read entries")]
+	ReadEntries(#[source] ReadAllEntriesError),
+
+	/// Unable to find entry
+	#[error("Unable to find entry")]
+	NotFound,
+
+	/// Entry already exists
+	#[error("Entry already exists")]
+	AlreadyExists,
+
+	/// Invalid name
+	#[error("Invalid name")]
+	InvalidName,
+
+	/// Unable to write entry
+	#[error("Unable to write entry")]
+	WriteEntry(#[source] crate::ptr::entry::WriteEntryError),
+}
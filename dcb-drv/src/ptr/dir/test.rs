@@ -1,9 +1,8 @@
 //! Tests
 
-use crate::FilePtr;
-
 // Imports
 use super::*;
+use crate::test_util::{self, ascii};
 use chrono::NaiveDateTime;
 use zutil::AsciiStrArr;
 
@@ -40,3 +39,192 @@ fn write_read_entries() {
 
 	assert_eq!(entries, read_entries);
 }
+
+#[test]
+fn create_rename_remove() {
+	let mut drive = test_util::create_drive(3, []);
+	let root = DirPtr::root();
+	let date = NaiveDateTime::from_timestamp(0, 0);
+
+	// Create a file and a directory with a file
+	let contents = [0xaa; 0x10];
+	let (_, _, file_entry) = root
+		.create_file(&mut drive, ascii("A.BIN"), date, &mut &contents[..])
+		.expect("Unable to create file");
+	assert_eq!(file_entry.kind.as_file_ptr(), Some(FilePtr::new(1, 0x10)));
+	let (_, _, dir_entry) = root
+		.create_dir(&mut drive, ascii("DIR"), date)
+		.expect("Unable to create directory");
+	let dir = dir_entry.kind.as_dir_ptr().expect("Entry wasn't a directory");
+	dir.create_file(&mut drive, ascii("B.BIN"), date, &mut io::empty())
+		.expect("Unable to create file");
+	assert!(matches!(
+		root.create_file(&mut drive, ascii("A.BIN"), date, &mut io::empty()),
+		Err(CreateFileError::AlreadyExists)
+	));
+
+	// Then rename the file
+	root.rename_entry(&mut drive, ascii("A.BIN"), ascii("C.BIN"))
+		.expect("Unable to rename file");
+	let (_, entry) = root
+		.find_entry(&mut drive, ascii("C.BIN"))
+		.expect("Unable to find renamed file");
+	assert_eq!(entry.kind, file_entry.kind);
+	assert!(root.find_entry(&mut drive, ascii("A.BIN")).is_err());
+
+	// And remove it and the directory
+	root.remove_entry(&mut drive, ascii("C.BIN"))
+		.expect("Unable to remove file");
+	assert!(matches!(
+		root.remove_entry(&mut drive, ascii("DIR")),
+		Err(RemoveEntryError::DirNotEmpty)
+	));
+	dir.remove_entry(&mut drive, ascii("B.BIN"))
+		.expect("Unable to remove file");
+	root.remove_entry(&mut drive, ascii("DIR"))
+		.expect("Unable to remove directory");
+	assert!(root
+		.read_all_entries(&mut drive)
+		.expect("Unable to read entries")
+		.is_empty());
+}
+
+#[test]
+fn grow_relocate() {
+	let mut drive = test_util::create_drive(5, []);
+	let root = DirPtr::root();
+	let date = NaiveDateTime::from_timestamp(0, 0);
+
+	// Create a directory followed by a file, so it can't grow in-place
+	let (_, _, dir_entry) = root
+		.create_dir(&mut drive, ascii("DIR"), date)
+		.expect("Unable to create directory");
+	let mut dir = dir_entry.kind.as_dir_ptr().expect("Entry wasn't a directory");
+	root.create_file(&mut drive, ascii("A.BIN"), date, &mut &[0; 0x10][..])
+		.expect("Unable to create file");
+
+	// Then fill the directory until it needs to grow
+	for idx in 0..64 {
+		let name = format!("F{idx}.BIN");
+		(dir, ..) = dir
+			.create_file(&mut drive, ascii(&name), date, &mut io::empty())
+			.expect("Unable to create file");
+	}
+
+	// And make sure it was relocated and all entries are still there
+	assert_ne!(dir, dir_entry.kind.as_dir_ptr().expect("Entry wasn't a directory"));
+	let (_, dir_entry) = root
+		.find_entry(&mut drive, ascii("DIR"))
+		.expect("Unable to find directory");
+	assert_eq!(dir_entry.kind.as_dir_ptr(), Some(dir));
+	assert_eq!(
+		dir.read_all_entries(&mut drive).expect("Unable to read entries").len(),
+		64
+	);
+}
+
+#[test]
+fn grow_past_end() {
+	// Create a directory at the end of the drive, with free sectors before it
+	let mut drive = test_util::create_drive(4, [test_util::entry("DIR", DirEntryKind::dir(DirPtr::new(3)))]);
+	let root = DirPtr::root();
+	let date = test_util::date();
+
+	// Then fill the directory until it needs to grow
+	let mut dir = DirPtr::new(3);
+	for idx in 0..64 {
+		let name = format!("F{idx}.BIN");
+		(dir, ..) = dir
+			.create_file(&mut drive, ascii(&name), date, &mut io::empty())
+			.expect("Unable to create file");
+	}
+
+	// And make sure it was relocated within the drive, instead of growing past it's end
+	assert_eq!(dir, DirPtr::new(1));
+	let (_, dir_entry) = root
+		.find_entry(&mut drive, ascii("DIR"))
+		.expect("Unable to find directory");
+	assert_eq!(dir_entry.kind.as_dir_ptr(), Some(dir));
+	assert_eq!(drive.get_ref().len(), 4 * 0x800);
+}
+
+#[test]
+fn create_drive_full() {
+	// Create a drive with a single free sector
+	let mut drive = test_util::create_drive(2, []);
+	let root = DirPtr::root();
+	let date = test_util::date();
+
+	// Then make sure files and directories that don't fit aren't written
+	let contents = [0xaa; 0x801];
+	assert!(matches!(
+		root.create_file(&mut drive, ascii("A.BIN"), date, &mut &contents[..]),
+		Err(CreateFileError::DriveFull {
+			sectors_len: 2,
+			free_len:    1,
+		})
+	));
+	assert!(drive.get_ref()[0x800..].iter().all(|&byte| byte == 0));
+
+	// And that they're created once they do
+	root.create_dir(&mut drive, ascii("DIR"), date)
+		.expect("Unable to create directory");
+	assert!(matches!(
+		root.create_dir(&mut drive, ascii("DIR2"), date),
+		Err(CreateDirError::DriveFull {
+			sectors_len: 1,
+			free_len:    0,
+		})
+	));
+	assert_eq!(drive.get_ref().len(), 2 * 0x800);
+}
+
+#[test]
+fn relocate_drive_full() {
+	// Create a directory at the end of the drive, with a single free sector before it
+	let mut drive = test_util::create_drive(3, [test_util::entry("DIR", DirEntryKind::dir(DirPtr::new(2)))]);
+	let date = test_util::date();
+
+	// Then fill the directory until it needs to grow
+	let mut dir = DirPtr::new(2);
+	for idx in 0..63 {
+		let name = format!("F{idx}.BIN");
+		(dir, ..) = dir
+			.create_file(&mut drive, ascii(&name), date, &mut io::empty())
+			.expect("Unable to create file");
+	}
+
+	// And make sure it can't be relocated
+	assert!(matches!(
+		dir.create_file(&mut drive, ascii("F63.BIN"), date, &mut io::empty()),
+		Err(CreateFileError::DriveFull {
+			sectors_len: 2,
+			free_len:    1,
+		})
+	));
+	assert_eq!(
+		dir.read_all_entries(&mut drive).expect("Unable to read entries").len(),
+		63
+	);
+	assert_eq!(drive.get_ref().len(), 3 * 0x800);
+}
+
+#[test]
+fn find_dir() {
+	let mut drive = test_util::create_drive(2, []);
+	let root = DirPtr::root();
+	let date = NaiveDateTime::from_timestamp(0, 0);
+
+	let (_, _, dir_entry) = root
+		.create_dir(&mut drive, ascii("DIR"), date)
+		.expect("Unable to create directory");
+	let dir = dir_entry.kind.as_dir_ptr().expect("Entry wasn't a directory");
+	root.create_file(&mut drive, ascii("A.BIN"), date, &mut io::empty())
+		.expect("Unable to create file");
+
+	let mut find_dir = |from: DirPtr, path: &str| from.find_dir(&mut drive, Path::new(ascii(path)));
+	assert_eq!(find_dir(root, "dir\\").ok(), Some(dir));
+	assert_eq!(find_dir(dir, "").ok(), Some(dir));
+	assert_eq!(find_dir(dir, "\\").ok(), Some(root));
+	assert!(matches!(find_dir(root, "A.BIN"), Err(FindDirError::FoundFile)));
+}
@@ -0,0 +1,109 @@
+//! File replacing
+
+// Modules
+#[cfg(test)]
+mod test;
+
+// Imports
+use crate::{
+	allocator::{self, Extent},
+	ptr, DirEntryKind, DirPtr, FilePtr, Path, SectorAllocator,
+};
+use std::{convert::TryFrom, io};
+
+/// Replaces the contents of a file.
+///
+/// If the new contents fit in the sectors currently used by the file, and no other file
+/// shares them, they are written in-place, else the file is relocated using a [`SectorAllocator`].
+///
+/// Returns the new pointer of the file.
+pub fn replace_file<T: io::Seek + io::Read + io::Write, R: io::Read>(
+	cursor: &mut T, path: &Path, reader: &mut R,
+) -> Result<FilePtr, ReplaceFileError> {
+	// Find the file
+	let (entry_ptr, mut entry) = DirPtr::root().find(cursor, path).map_err(ReplaceFileError::Find)?;
+	let (extension, old_ptr) = match entry.kind {
+		DirEntryKind::File { extension, ptr } => (extension, ptr),
+		DirEntryKind::Dir { .. } => return Err(ReplaceFileError::FoundDir),
+	};
+
+	// Read the new contents
+	let mut contents = vec![];
+	reader
+		.read_to_end(&mut contents)
+		.map_err(ReplaceFileError::ReadContents)?;
+	let size = u32::try_from(contents.len()).map_err(|_| ReplaceFileError::FileTooLarge)?;
+
+	// Then check where we can write them
+	// Note: When relocating, the sectors currently used by the file are also considered free,
+	//       unless they're shared with other files, such as deduplicated ones.
+	//       If there's no space left within the drive, it's grown by writing past it's end.
+	let old_extent = Extent::from_file_ptr(old_ptr);
+	let extent = Extent::from_file_ptr(FilePtr::new(old_ptr.sector_pos, size));
+	let mut allocator = SectorAllocator::new(cursor).map_err(ReplaceFileError::NewAllocator)?;
+	let (sector_pos, grows) = match extent.len <= old_extent.len && !allocator.is_shared(old_extent) {
+		true => (old_ptr.sector_pos, false),
+		false => {
+			allocator.free(old_extent);
+			match allocator.try_allocate(extent.len, allocator.len()) {
+				Some(extent) => (extent.sector_pos, false),
+				None => (allocator.allocate(extent.len).sector_pos, true),
+			}
+		},
+	};
+
+	// Write the contents
+	// Note: If the drive can't grow, writing past it's end won't write anything.
+	let ptr = FilePtr::new(sector_pos, size);
+	ptr.seek_to(cursor).map_err(ReplaceFileError::SeekFile)?;
+	cursor.write_all(&contents).map_err(|err| match err.kind() {
+		io::ErrorKind::WriteZero if grows => ReplaceFileError::DriveFull,
+		_ => ReplaceFileError::WriteFile(err),
+	})?;
+
+	// And update the entry
+	entry.kind = DirEntryKind::file(extension, ptr);
+	entry_ptr.write(cursor, &entry).map_err(ReplaceFileError::WriteEntry)?;
+
+	Ok(ptr)
+}
+
+/// Error type for [`replace_file`]
+#[derive(Debug, thiserror::Error)]
+pub enum ReplaceFileError {
+	/// Unable to find file
+	#[error("Unable to find file")]
+	Find(#[source] ptr::dir::FindError),
+
+	/// Found directory
+	#[error("Found directory")]
+	FoundDir,
+
+	/// Unable to read new contents
+	#[error("Unable to read new contents")]
+	ReadContents(#[source] io::Error),
+
+	/// File size was too large
+	#[error("File size was too large")]
+	FileTooLarge,
+
+	/// Unable to create sector allocator
+	#[error("Unable to create sector allocator")]
+	NewAllocator(#[source] allocator::NewError),
+
+	/// Unable to seek to file
+	#[error("Unable to seek to file")]
+	SeekFile(#[source] io::Error),
+
+	/// Unable to write file
+	#[error("Unable to write file")]
+	WriteFile(#[source] io::Error),
+
+	/// Drive was full
+	#[error("Drive was full")]
+	DriveFull,
+
+	/// Unable to write file entry
+	#[error("Unable to write file entry")]
+	WriteEntry(#[source] ptr::entry::WriteEntryError),
+}
@@ -0,0 +1,106 @@
+//! Tests
+
+// Imports
+use super::*;
+use crate::test_util::{self, ascii, entry_writer, file_writer, Lister};
+use std::io::Read;
+
+/// Creates a drive with a root directory and the files `A.BIN` and `B.BIN`, each with a sector
+fn create_drive() -> io::Cursor<Vec<u8>> {
+	test_util::create_drive(3, [
+		test_util::entry("A", test_util::file(1, 0x10)),
+		test_util::entry("B", test_util::file(2, 0x800)),
+	])
+}
+
+/// Creates a path from `path`
+fn path(path: &str) -> &Path {
+	Path::new(ascii(path))
+}
+
+/// Reads a file from a drive
+fn read_file(drive: &mut io::Cursor<Vec<u8>>, path: &Path) -> (FilePtr, Vec<u8>) {
+	let (_, entry) = DirPtr::root().find(drive, path).expect("Unable to find file");
+	let ptr = entry.kind.as_file_ptr().expect("Entry wasn't a file");
+
+	let mut contents = vec![];
+	ptr.cursor(drive)
+		.expect("Unable to open file")
+		.read_to_end(&mut contents)
+		.expect("Unable to read file");
+	(ptr, contents)
+}
+
+#[test]
+fn replace_in_place() {
+	let mut drive = self::create_drive();
+	let path = self::path("A.BIN");
+
+	let contents = vec![0xaa; 0x20];
+	let ptr = replace_file(&mut drive, path, &mut contents.as_slice()).expect("Unable to replace file");
+	assert_eq!(ptr, FilePtr::new(1, 0x20));
+	assert_eq!(self::read_file(&mut drive, path), (ptr, contents));
+}
+
+#[test]
+fn replace_relocate() {
+	let mut drive = self::create_drive();
+	let path = self::path("A.BIN");
+
+	let contents = vec![0xaa; 0x900];
+	let ptr = replace_file(&mut drive, path, &mut contents.as_slice()).expect("Unable to replace file");
+	assert_eq!(ptr, FilePtr::new(3, 0x900));
+	assert_eq!(self::read_file(&mut drive, path), (ptr, contents));
+
+	// Then make sure the other file wasn't touched
+	let other_path = self::path("B.BIN");
+	assert_eq!(self::read_file(&mut drive, other_path).0, FilePtr::new(2, 0x800));
+}
+
+#[test]
+fn replace_drive_full() {
+	// Note: A slice can't grow, so the drive is full
+	let mut bytes = self::create_drive().into_inner();
+	let mut drive = io::Cursor::new(bytes.as_mut_slice());
+	let path = self::path("A.BIN");
+
+	let contents = vec![0xaa; 0x900];
+	assert!(matches!(
+		replace_file(&mut drive, path, &mut contents.as_slice()),
+		Err(ReplaceFileError::DriveFull)
+	));
+
+	// And make sure the file wasn't changed
+	let (_, entry) = DirPtr::root().find(&mut drive, path).expect("Unable to find file");
+	assert_eq!(entry.kind.as_file_ptr(), Some(FilePtr::new(1, 0x10)));
+}
+
+#[test]
+fn replace_shared() {
+	// `A` and `B` are deduplicated, so they share their sectors
+	let mut drive = test_util::write_drive(
+		Lister(vec![
+			entry_writer("A", None, file_writer(&[0xaa; 0x10])),
+			entry_writer("B", None, file_writer(&[0xaa; 0x10])),
+		]),
+		true,
+	);
+	let path = self::path("A.BIN");
+	let other_path = self::path("B.BIN");
+	assert_eq!(
+		self::read_file(&mut drive, path).0,
+		self::read_file(&mut drive, other_path).0
+	);
+
+	// Even though the new contents fit, they should be relocated
+	let contents = vec![0xbb; 0x20];
+	let ptr = replace_file(&mut drive, path, &mut contents.as_slice()).expect("Unable to replace file");
+	assert_eq!(ptr, FilePtr::new(2, 0x20));
+	assert_eq!(self::read_file(&mut drive, path), (ptr, contents));
+
+	// And make sure the other copy wasn't touched
+	assert_eq!(
+		self::read_file(&mut drive, other_path),
+		(FilePtr::new(1, 0x10), vec![0xaa; 0x10])
+	);
+}
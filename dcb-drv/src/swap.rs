@@ -10,7 +10,7 @@ pub fn swap_files<T: io::Seek + io::Read + io::Write>(
 ) -> Result<(), SwapFilesError> {
 	// Find both files and their entry pointers
 	let (lhs_entry_ptr, mut lhs_entry) = DirPtr::root().find(cursor, lhs_path).map_err(SwapFilesError::FindLhs)?;
-	let (rhs_entry_ptr, mut rhs_entry) = DirPtr::root().find(cursor, rhs_path).map_err(SwapFilesError::FindLhs)?;
+	let (rhs_entry_ptr, mut rhs_entry) = DirPtr::root().find(cursor, rhs_path).map_err(SwapFilesError::FindRhs)?;
 
 	// Swap both entries' file pointers
 	match (&mut lhs_entry.kind, &mut rhs_entry.kind) {
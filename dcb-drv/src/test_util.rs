@@ -0,0 +1,101 @@
+//! Test utilities
+//!
+//! Fixtures shared by the tests of all modules.
+
+// Imports
+use crate::{DirEntry, DirEntryKind, DirEntryWriter, DirEntryWriterKind, DirPtr, DirWriter, DirWriterLister, FilePtr};
+use ascii::AsciiStr;
+use chrono::NaiveDateTime;
+use std::{
+	convert::{Infallible, TryFrom},
+	io, vec,
+};
+use zutil::AsciiStrArr;
+
+/// Lister over a list of entries
+pub struct Lister(pub Vec<DirEntryWriter<Self>>);
+
+impl IntoIterator for Lister {
+	type IntoIter = std::iter::Map<vec::IntoIter<DirEntryWriter<Self>>, fn(DirEntryWriter<Self>) -> Self::Item>;
+	type Item = Result<DirEntryWriter<Self>, Infallible>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.0.into_iter().map(Ok)
+	}
+}
+
+impl DirWriterLister for Lister {
+	type Error = Infallible;
+	type FileReader = io::Cursor<&'static [u8]>;
+}
+
+/// Creates an ascii string from `s`
+pub fn ascii(s: &str) -> &AsciiStr {
+	AsciiStr::from_ascii(s).expect("Invalid string")
+}
+
+/// Returns the date used for all entries
+pub fn date() -> NaiveDateTime {
+	NaiveDateTime::from_timestamp(0, 0)
+}
+
+/// Creates an entry named `name`
+pub fn entry(name: &str, kind: DirEntryKind) -> DirEntry {
+	DirEntry {
+		name: AsciiStrArr::from_bytes(name).expect("Invalid string"),
+		date: self::date(),
+		kind,
+	}
+}
+
+/// Creates a `BIN` file entry kind
+pub fn file(sector_pos: u32, size: u32) -> DirEntryKind {
+	DirEntryKind::file(
+		AsciiStrArr::from_bytes("BIN").expect("Invalid string"),
+		FilePtr::new(sector_pos, size),
+	)
+}
+
+/// Creates a drive of `len` sectors with a root directory with `entries`
+pub fn create_drive(len: usize, entries: impl IntoIterator<Item = DirEntry>) -> io::Cursor<Vec<u8>> {
+	let mut drive = io::Cursor::new(vec![0; len * 0x800]);
+	DirPtr::root()
+		.write_entries(&mut drive, entries)
+		.expect("Unable to write entries");
+
+	drive
+}
+
+/// Creates an entry writer
+pub fn entry_writer(name: &str, sector_pos: Option<u32>, kind: DirEntryWriterKind<Lister>) -> DirEntryWriter<Lister> {
+	DirEntryWriter {
+		name: AsciiStrArr::from_bytes(name).expect("Invalid string"),
+		date: self::date(),
+		sector_pos,
+		kind,
+	}
+}
+
+/// Creates a `BIN` file entry writer kind
+pub fn file_writer(contents: &'static [u8]) -> DirEntryWriterKind<Lister> {
+	DirEntryWriterKind::File {
+		extension: AsciiStrArr::from_bytes("BIN").expect("Invalid string"),
+		reader:    io::Cursor::new(contents),
+		size:      u32::try_from(contents.len()).expect("File was too large"),
+	}
+}
+
+/// Writes a drive with the root directory `root`, optionally deduplicating files
+pub fn write_drive(root: Lister, dedup: bool) -> io::Cursor<Vec<u8>> {
+	let mut drive = io::Cursor::new(vec![]);
+	DirWriter::new(root)
+		.with_dedup(dedup)
+		.write(DirPtr::root(), &mut drive)
+		.expect("Unable to write drive");
+
+	// Note: Only whole sectors are part of the drive, so we pad the last one
+	let len = drive.get_ref().len();
+	drive.get_mut().resize((len + 0x7ff) / 0x800 * 0x800, 0);
+
+	drive
+}
@@ -1,20 +1,40 @@
 //! Writer
+//!
+//! The writer first collects the whole directory tree, so that the location of every
+//! directory and file may be known before anything is written.
+//!
+//! Directories and files may request a sector position, in which case they are placed
+//! there, as long as they don't overlap anything else. Otherwise, or if they didn't
+//! request one, they are placed after everything else, with each directory followed
+//! by it's entries, recursively.
+//!
+//! Optionally, files with the same contents may be deduplicated, in which case only the
+//! first of them is written, with the rest pointing to it's sectors. To find them, each
+//! file is hashed before being written, and only files with the same hash are compared.
+
+// Modules
+#[cfg(test)]
+mod test;
 
 // Imports
-use crate::{DirEntry, DirEntryKind, DirPtr, FilePtr};
+use crate::{allocator::Extent, DirEntry, DirEntryKind, DirPtr, FilePtr, SectorAllocator};
 use chrono::NaiveDateTime;
 use std::{
-	convert::TryInto,
-	io::{self, SeekFrom},
+	collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+	convert::TryFrom,
+	hash::Hasher,
+	io::{self, Read, SeekFrom},
 };
-use zutil::{AsciiStrArr, MapBoxResult};
+use zutil::AsciiStrArr;
 
 /// A directory lister
 pub trait DirWriterLister:
 	Sized + IntoIterator<Item = Result<DirEntryWriter<Self>, Self::Error>, IntoIter: ExactSizeIterator>
 {
 	/// File type
-	type FileReader: io::Read;
+	///
+	/// Files are rewound after being read while deduplicating, so they may be read again.
+	type FileReader: io::Read + io::Seek;
 
 	/// Error type for each entry
 	type Error: std::error::Error + 'static;
@@ -27,22 +47,26 @@ pub enum WriteDirError<E: std::error::Error + 'static> {
 	#[error("Unable to get entry")]
 	GetEntry(#[source] E),
 
-	/// Unable to seek to entry
-	#[error("Unable to seek to entry")]
-	SeekToEntry(#[source] io::Error),
+	/// Filesystem was too large
+	#[error("Filesystem was too large")]
+	TooLarge,
+
+	/// Unable to read file
+	#[error("Unable to read file")]
+	ReadFile(#[source] io::Error),
+
+	/// Unable to rewind file
+	#[error("Unable to rewind file")]
+	RewindFile(#[source] io::Error),
+
+	/// Unable to seek to file
+	#[error("Unable to seek to file")]
+	SeekFile(#[source] io::Error),
 
 	/// Unable to write file
 	#[error("Unable to write file")]
 	WriteFile(#[source] io::Error),
 
-	/// File size was too large
-	#[error("File size was too large")]
-	FileTooLarge,
-
-	/// Unable to write directory
-	#[error("Unable to write directory")]
-	WriteDir(#[source] Box<Self>),
-
 	/// Unable to write all directory entries
 	#[error("Unable to write directory entries")]
 	WriteEntries(#[source] crate::ptr::dir::WriteEntriesError),
@@ -53,80 +77,140 @@ pub enum WriteDirError<E: std::error::Error + 'static> {
 pub struct DirWriter<L> {
 	/// All entries
 	entries: L,
+
+	/// If files with the same contents should be deduplicated
+	dedup: bool,
 }
 
 impl<L> DirWriter<L> {
 	/// Creates a new directory writer
 	#[must_use]
 	pub const fn new(entries: L) -> Self {
-		Self { entries }
+		Self { entries, dedup: false }
+	}
+
+	/// Sets if files with the same contents should be deduplicated.
+	///
+	/// All files are hashed before being written, and those with the same hash are compared,
+	/// so they're read more than once, but never kept in memory.
+	/// Duplicate files ignore their requested sector position.
+	///
+	/// Files are deduplicated across the whole tree, including all sub-directories.
+	#[must_use]
+	pub const fn with_dedup(mut self, dedup: bool) -> Self {
+		self.dedup = dedup;
+		self
 	}
 }
 
 impl<L: DirWriterLister> DirWriter<L> {
-	/// Writes `entries` to this directory recursively and returns the number of
-	/// sectors occupied
+	/// Writes this directory at `ptr` and all of it's entries recursively and returns the
+	/// number of sectors occupied after `ptr`.
 	pub fn write<W: io::Seek + io::Write>(self, ptr: DirPtr, writer: &mut W) -> Result<u32, WriteDirError<L::Error>> {
-		// Get the starting sector position for the first entry.
-		// Note: We on the directory after this directory.
-		// Note: `+1` for the null entry.
-		// Note: `+2047` is to pad this directory to the next sector, if not empty.
-		let entries = self.entries.into_iter();
-		let entries_len: u32 = entries
-			.len()
-			.try_into()
-			.expect("Number of entries didn't fit into a `u32`");
-		let mut cur_sector_pos = ptr.sector_pos + ((entries_len + 1) * 0x20 + 2047) / 2048;
-
-		// Write each entry and map it so we can write it later
-		let entries: Vec<_> = entries
-			.map(|entry| {
-				// Get the entry
-				let entry = entry.map_err(WriteDirError::GetEntry)?;
+		// Collect the whole tree, and deduplicate it, if requested
+		let mut dirs = self::collect_dirs(self.entries, ptr)?;
+		if self.dedup {
+			self::dedup_files(&mut dirs)?;
+		}
 
-				// Seek to the entry
-				writer
-					.seek(SeekFrom::Start(u64::from(cur_sector_pos) * 2048))
-					.map_err(WriteDirError::SeekToEntry)?;
-
-				// Then write it and get it's sector size
-				let (dir_entry_kind, sector_size) = match entry.kind {
-					DirEntryWriterKind::File { extension, mut reader } => {
-						// Write the file and get the size as `u32`
-						let size: u32 = io::copy(&mut reader, writer)
-							.map_err(WriteDirError::WriteFile)?
-							.try_into()
-							.map_err(|_| WriteDirError::FileTooLarge)?;
-						let sector_size = (size + 2047) / 2048;
-
-						let ptr = FilePtr::new(cur_sector_pos, sector_size);
-						(DirEntryKind::file(extension, ptr), sector_size)
-					},
-					DirEntryWriterKind::Dir(dir) => {
-						// Write all entries recursively
-						let ptr = DirPtr::new(cur_sector_pos);
-						let sector_size = dir.write(ptr, writer).box_map_err(WriteDirError::WriteDir)?;
-						(DirEntryKind::dir(ptr), sector_size)
-					},
-				};
+		// Then get all regions we need to place, in order
+		let mut regions = vec![];
+		self::collect_regions(&dirs, 0, &mut regions)?;
+
+		// And lay them out, first those that requested a position, then the rest
+		let mut allocator = SectorAllocator::empty();
+		let mut end = ptr.sector_pos;
+		let mut pending = vec![];
+		for (region, sector_pos, len) in regions {
+			match sector_pos {
+				Some(sector_pos) => {
+					let extent_end = sector_pos.checked_add(len).ok_or(WriteDirError::TooLarge)?;
+					match (sector_pos..extent_end).all(|sector_pos| allocator.is_free(sector_pos)) {
+						true => {
+							allocator.mark_used(Extent::new(sector_pos, len));
+							self::set_sector_pos(&mut dirs, region, sector_pos);
+							end = end.max(extent_end);
+						},
+						false => {
+							log::warn!(
+								"Unable to place {region:?} at requested sector {sector_pos}, placing it at the end"
+							);
+							pending.push((region, len));
+						},
+					}
+				},
+				None => pending.push((region, len)),
+			}
+		}
+		for (region, len) in pending {
+			let extent = allocator.allocate(len);
+			self::set_sector_pos(&mut dirs, region, extent.sector_pos);
+			end = end.max(extent.end());
+		}
 
-				// Update our sector pos
-				cur_sector_pos += sector_size;
+		// Then point all duplicates to their originals
+		for dir_idx in 0..dirs.len() {
+			for entry_idx in 0..dirs[dir_idx].entries.len() {
+				if let EntryKind::File {
+					duplicate_of: Some((original_dir_idx, original_entry_idx)),
+					..
+				} = dirs[dir_idx].entries[entry_idx].kind
+				{
+					let original_sector_pos = match dirs[original_dir_idx].entries[original_entry_idx].kind {
+						EntryKind::File { sector_pos, .. } => sector_pos,
+						EntryKind::Dir { .. } => unreachable!("Duplicate file pointed to a directory"),
+					};
+					self::set_sector_pos(&mut dirs, Region::File { dir_idx, entry_idx }, original_sector_pos);
+				}
+			}
+		}
 
-				Ok(DirEntry {
+		// Then write all directories
+		for dir in &dirs {
+			let entries = dir.entries.iter().map(|entry| {
+				let kind = match entry.kind {
+					EntryKind::File {
+						extension,
+						size,
+						sector_pos,
+						..
+					} => DirEntryKind::file(extension, FilePtr::new(sector_pos, size)),
+					EntryKind::Dir { idx } => DirEntryKind::dir(DirPtr::new(dirs[idx].sector_pos)),
+				};
+
+				DirEntry {
 					name: entry.name,
 					date: entry.date,
-					kind: dir_entry_kind,
-				})
-			})
-			.collect::<Result<_, _>>()?;
+					kind,
+				}
+			});
+			DirPtr::new(dir.sector_pos)
+				.write_entries(writer, entries)
+				.map_err(WriteDirError::WriteEntries)?;
+		}
 
-		// Then write the entries
-		ptr.write_entries(writer, entries)
-			.map_err(WriteDirError::WriteEntries)?;
+		// And all files, except duplicates
+		// Note: Each file is dropped once written, so file readers don't stay open.
+		for entry in dirs.into_iter().flat_map(|dir| dir.entries) {
+			if let EntryKind::File {
+				reader,
+				size,
+				sector_pos,
+				duplicate_of: None,
+				..
+			} = entry.kind
+			{
+				writer
+					.seek(SeekFrom::Start(u64::from(sector_pos) * 0x800))
+					.map_err(WriteDirError::SeekFile)?;
+				let written = io::copy(&mut reader.take(u64::from(size)), writer).map_err(WriteDirError::WriteFile)?;
+				if written != u64::from(size) {
+					return Err(WriteDirError::WriteFile(io::ErrorKind::UnexpectedEof.into()));
+				}
+			}
+		}
 
-		// And return the number of sectors we wrote.
-		Ok(cur_sector_pos - ptr.sector_pos)
+		Ok(end - ptr.sector_pos)
 	}
 }
 
@@ -138,6 +222,9 @@ pub struct DirEntryWriter<L: DirWriterLister> {
 	/// Entry date
 	pub date: NaiveDateTime,
 
+	/// Requested sector position
+	pub sector_pos: Option<u32>,
+
 	/// Kind
 	pub kind: DirEntryWriterKind<L>,
 }
@@ -151,8 +238,281 @@ pub enum DirEntryWriterKind<L: DirWriterLister> {
 
 		/// File reader
 		reader: L::FileReader,
+
+		/// File size
+		size: u32,
 	},
 
 	/// A directory
 	Dir(DirWriter<L>),
 }
+
+/// A region of the filesystem
+#[derive(Clone, Copy, Debug)]
+enum Region {
+	/// A directory
+	Dir {
+		/// Directory index
+		idx: usize,
+	},
+
+	/// A file
+	File {
+		/// Directory index
+		dir_idx: usize,
+
+		/// Entry index
+		entry_idx: usize,
+	},
+}
+
+/// A collected directory
+struct Dir<R> {
+	/// All entries
+	entries: Vec<Entry<R>>,
+
+	/// Requested sector position
+	requested_sector_pos: Option<u32>,
+
+	/// Sector position
+	sector_pos: u32,
+}
+
+/// A collected directory entry
+struct Entry<R> {
+	/// Name
+	name: AsciiStrArr<0x10>,
+
+	/// Date
+	date: NaiveDateTime,
+
+	/// Kind
+	kind: EntryKind<R>,
+}
+
+/// A collected directory entry kind
+enum EntryKind<R> {
+	/// A file
+	File {
+		/// Extension
+		extension: AsciiStrArr<0x3>,
+
+		/// File reader
+		reader: R,
+
+		/// File size
+		size: u32,
+
+		/// Requested sector position
+		requested_sector_pos: Option<u32>,
+
+		/// Sector position
+		sector_pos: u32,
+
+		/// Directory and entry index of the file this file is a duplicate of
+		duplicate_of: Option<(usize, usize)>,
+	},
+
+	/// A directory
+	Dir {
+		/// Directory index
+		idx: usize,
+	},
+}
+
+/// Collects all directories, breadth-first, with the root directory, at `ptr`, first.
+fn collect_dirs<L: DirWriterLister>(root: L, ptr: DirPtr) -> Result<Vec<Dir<L::FileReader>>, WriteDirError<L::Error>> {
+	let mut dirs = vec![Dir {
+		entries:              vec![],
+		requested_sector_pos: Some(ptr.sector_pos),
+		sector_pos:           0,
+	}];
+
+	let mut queue = VecDeque::from(vec![(0, root)]);
+	while let Some((dir_idx, lister)) = queue.pop_front() {
+		let entries = lister
+			.into_iter()
+			.map(|entry| {
+				let entry = entry.map_err(WriteDirError::GetEntry)?;
+				let kind = match entry.kind {
+					DirEntryWriterKind::File {
+						extension,
+						reader,
+						size,
+					} => EntryKind::File {
+						extension,
+						reader,
+						size,
+						requested_sector_pos: entry.sector_pos,
+						sector_pos: 0,
+						duplicate_of: None,
+					},
+					DirEntryWriterKind::Dir(dir) => {
+						let idx = dirs.len();
+						dirs.push(Dir {
+							entries:              vec![],
+							requested_sector_pos: entry.sector_pos,
+							sector_pos:           0,
+						});
+						queue.push_back((idx, dir.entries));
+						EntryKind::Dir { idx }
+					},
+				};
+
+				Ok(Entry {
+					name: entry.name,
+					date: entry.date,
+					kind,
+				})
+			})
+			.collect::<Result<_, _>>()?;
+		dirs[dir_idx].entries = entries;
+	}
+
+	Ok(dirs)
+}
+
+/// Marks all files with the same contents as a previous file as it's duplicate.
+///
+/// Files are first hashed, and only compared with previous files with the same size and hash.
+fn dedup_files<R: io::Read + io::Seek, E: std::error::Error + 'static>(
+	dirs: &mut [Dir<R>],
+) -> Result<(), WriteDirError<E>> {
+	let mut originals = HashMap::<(u32, u64), Vec<(usize, usize)>>::new();
+	for dir_idx in 0..dirs.len() {
+		for entry_idx in 0..dirs[dir_idx].entries.len() {
+			let size = match dirs[dir_idx].entries[entry_idx].kind {
+				EntryKind::File { size, .. } if size != 0 => size,
+				_ => continue,
+			};
+
+			let hash = self::hash_file(self::file_reader(dirs, dir_idx, entry_idx), size)?;
+			let candidates = originals.entry((size, hash)).or_default();
+			let mut original = None;
+			for &candidate in &*candidates {
+				if self::files_eq(dirs, candidate, (dir_idx, entry_idx), size)? {
+					original = Some(candidate);
+					break;
+				}
+			}
+
+			match original {
+				Some(original) => {
+					if let EntryKind::File { duplicate_of, .. } = &mut dirs[dir_idx].entries[entry_idx].kind {
+						*duplicate_of = Some(original);
+					}
+				},
+				None => candidates.push((dir_idx, entry_idx)),
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Returns the reader of a file
+fn file_reader<R>(dirs: &mut [Dir<R>], dir_idx: usize, entry_idx: usize) -> &mut R {
+	match &mut dirs[dir_idx].entries[entry_idx].kind {
+		EntryKind::File { reader, .. } => reader,
+		EntryKind::Dir { .. } => unreachable!("File index pointed to a directory"),
+	}
+}
+
+/// Hashes the `size` bytes of a file, then rewinds it
+fn hash_file<R: io::Read + io::Seek, E: std::error::Error + 'static>(
+	reader: &mut R, size: u32,
+) -> Result<u64, WriteDirError<E>> {
+	let mut hasher = DefaultHasher::new();
+	let mut buffer = [0; 0x800];
+	let mut remaining = size;
+	while remaining != 0 {
+		let len = u32::min(remaining, 0x800);
+		let buffer = &mut buffer[..usize::try_from(len).expect("Length didn't fit into a `usize`")];
+		reader.read_exact(buffer).map_err(WriteDirError::ReadFile)?;
+		hasher.write(buffer);
+		remaining -= len;
+	}
+	reader.seek(SeekFrom::Start(0)).map_err(WriteDirError::RewindFile)?;
+
+	Ok(hasher.finish())
+}
+
+/// Compares the `size` bytes of two files, then rewinds them
+fn files_eq<R: io::Read + io::Seek, E: std::error::Error + 'static>(
+	dirs: &mut [Dir<R>], (lhs_dir_idx, lhs_entry_idx): (usize, usize), (rhs_dir_idx, rhs_entry_idx): (usize, usize),
+	size: u32,
+) -> Result<bool, WriteDirError<E>> {
+	let mut lhs_buffer = [0; 0x800];
+	let mut rhs_buffer = [0; 0x800];
+	let mut remaining = size;
+	let mut eq = true;
+	while eq && remaining != 0 {
+		let len = usize::try_from(u32::min(remaining, 0x800)).expect("Length didn't fit into a `usize`");
+		self::file_reader(dirs, lhs_dir_idx, lhs_entry_idx)
+			.read_exact(&mut lhs_buffer[..len])
+			.map_err(WriteDirError::ReadFile)?;
+		self::file_reader(dirs, rhs_dir_idx, rhs_entry_idx)
+			.read_exact(&mut rhs_buffer[..len])
+			.map_err(WriteDirError::ReadFile)?;
+		eq = lhs_buffer[..len] == rhs_buffer[..len];
+		remaining -= u32::try_from(len).expect("Length didn't fit into a `u32`");
+	}
+
+	for (dir_idx, entry_idx) in [(lhs_dir_idx, lhs_entry_idx), (rhs_dir_idx, rhs_entry_idx)] {
+		self::file_reader(dirs, dir_idx, entry_idx)
+			.seek(SeekFrom::Start(0))
+			.map_err(WriteDirError::RewindFile)?;
+	}
+
+	Ok(eq)
+}
+
+/// Collects the regions of a directory and all of it's entries, with each directory followed by it's entries.
+///
+/// Each region is returned alongside it's requested sector position and number of sectors.
+/// Duplicate files don't have a region.
+fn collect_regions<R, E: std::error::Error + 'static>(
+	dirs: &[Dir<R>], idx: usize, regions: &mut Vec<(Region, Option<u32>, u32)>,
+) -> Result<(), WriteDirError<E>> {
+	let dir = &dirs[idx];
+	let entries_len = u32::try_from(dir.entries.len()).map_err(|_| WriteDirError::TooLarge)?;
+	let len = Extent::from_dir_ptr(DirPtr::root(), entries_len).len;
+	regions.push((Region::Dir { idx }, dir.requested_sector_pos, len));
+
+	for (entry_idx, entry) in dir.entries.iter().enumerate() {
+		match entry.kind {
+			EntryKind::File {
+				duplicate_of: Some(_), ..
+			} => (),
+			EntryKind::File {
+				size,
+				requested_sector_pos,
+				..
+			} => {
+				let len = Extent::from_file_ptr(FilePtr::new(0, size)).len;
+				regions.push((
+					Region::File {
+						dir_idx: idx,
+						entry_idx,
+					},
+					requested_sector_pos,
+					len,
+				));
+			},
+			EntryKind::Dir { idx } => self::collect_regions(dirs, idx, regions)?,
+		}
+	}
+
+	Ok(())
+}
+
+/// Sets the sector position of a region
+fn set_sector_pos<R>(dirs: &mut [Dir<R>], region: Region, sector_pos: u32) {
+	match region {
+		Region::Dir { idx } => dirs[idx].sector_pos = sector_pos,
+		Region::File { dir_idx, entry_idx } => match &mut dirs[dir_idx].entries[entry_idx].kind {
+			EntryKind::File { sector_pos: pos, .. } => *pos = sector_pos,
+			EntryKind::Dir { .. } => unreachable!("File region pointed to a directory"),
+		},
+	}
+}
@@ -0,0 +1,124 @@
+//! Tests
+
+// Imports
+use super::*;
+use crate::test_util::{entry_writer as entry, file_writer as file, Lister};
+
+#[test]
+fn write_requested() {
+	let root = Lister(vec![
+		entry("A", None, file(&[0xaa; 0x10])),
+		entry(
+			"DIR",
+			None,
+			DirEntryWriterKind::Dir(DirWriter::new(Lister(vec![entry("B", Some(10), file(&[0xbb; 0x900]))]))),
+		),
+		entry("C", Some(11), file(&[0xcc; 0x10])),
+	]);
+
+	let mut drive = io::Cursor::new(vec![]);
+	let sectors_len = DirWriter::new(root)
+		.write(DirPtr::root(), &mut drive)
+		.expect("Unable to write drive");
+	assert_eq!(sectors_len, 15);
+
+	// `B` gets it's requested position, while `C` overlaps it, so it's placed at the end, along with
+	// all entries that didn't request a position
+	let extension = AsciiStrArr::from_bytes("BIN").expect("Invalid string");
+	let kinds = |drive: &mut io::Cursor<Vec<u8>>, ptr: DirPtr| {
+		ptr.read_all_entries(drive)
+			.expect("Unable to read entries")
+			.into_iter()
+			.map(|entry| entry.kind)
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(kinds(&mut drive, DirPtr::root()), [
+		DirEntryKind::file(extension, FilePtr::new(12, 0x10)),
+		DirEntryKind::dir(DirPtr::new(13)),
+		DirEntryKind::file(extension, FilePtr::new(14, 0x10)),
+	]);
+	assert_eq!(kinds(&mut drive, DirPtr::new(13)), [DirEntryKind::file(
+		extension,
+		FilePtr::new(10, 0x900)
+	)]);
+}
+
+#[test]
+fn write_dedup() {
+	let root = Lister(vec![
+		entry("A", None, file(&[0xaa; 0x10])),
+		entry("B", None, file(&[0xaa; 0x10])),
+		entry("C", None, file(&[0xcc; 0x10])),
+	]);
+
+	let mut drive = io::Cursor::new(vec![]);
+	let sectors_len = DirWriter::new(root)
+		.with_dedup(true)
+		.write(DirPtr::root(), &mut drive)
+		.expect("Unable to write drive");
+	assert_eq!(sectors_len, 3);
+
+	// `B` should share `A`'s sectors
+	let ptrs = DirPtr::root()
+		.read_all_entries(&mut drive)
+		.expect("Unable to read entries")
+		.into_iter()
+		.map(|entry| entry.kind.as_file_ptr())
+		.collect::<Vec<_>>();
+	assert_eq!(ptrs, [
+		Some(FilePtr::new(1, 0x10)),
+		Some(FilePtr::new(1, 0x10)),
+		Some(FilePtr::new(2, 0x10)),
+	]);
+}
+
+#[test]
+fn write_dedup_streamed() {
+	// `B` only differs from `A` after the first sector, while `C`, in a sub-directory, is a duplicate of `A`
+	let a = vec![0xaa; 0x900];
+	let mut b = a.clone();
+	b[0x8ff] = 0xbb;
+	let a: &'static [u8] = Box::leak(a.into_boxed_slice());
+	let b: &'static [u8] = Box::leak(b.into_boxed_slice());
+	let root = Lister(vec![
+		entry("A", None, file(a)),
+		entry("B", None, file(b)),
+		entry(
+			"DIR",
+			None,
+			DirEntryWriterKind::Dir(DirWriter::new(Lister(vec![entry("C", None, file(a))]))),
+		),
+	]);
+
+	let mut drive = io::Cursor::new(vec![]);
+	DirWriter::new(root)
+		.with_dedup(true)
+		.write(DirPtr::root(), &mut drive)
+		.expect("Unable to write drive");
+
+	let kinds = DirPtr::root()
+		.read_all_entries(&mut drive)
+		.expect("Unable to read entries")
+		.into_iter()
+		.map(|entry| entry.kind)
+		.collect::<Vec<_>>();
+	let a_ptr = kinds[0].as_file_ptr().expect("`A` wasn't a file");
+	let b_ptr = kinds[1].as_file_ptr().expect("`B` wasn't a file");
+	let dir_ptr = kinds[2].as_dir_ptr().expect("`DIR` wasn't a directory");
+	let c_ptr = dir_ptr
+		.read_all_entries(&mut drive)
+		.expect("Unable to read entries")
+		.into_iter()
+		.find_map(|entry| entry.kind.as_file_ptr())
+		.expect("Sub-directory had no file");
+	assert_ne!(a_ptr, b_ptr);
+	assert_eq!(a_ptr, c_ptr);
+
+	// And both files should have been fully written, even after being read while deduplicating
+	let contents = |ptr: FilePtr| {
+		let start = usize::try_from(ptr.sector_pos).expect("Sector didn't fit into a `usize`") * 0x800;
+		&drive.get_ref()[start..start + 0x900]
+	};
+	assert_eq!(contents(a_ptr), a);
+	assert_eq!(contents(b_ptr), b);
+}
@@ -210,7 +210,7 @@ impl Executable for Inst {
 			Inst::Shift(inst) => inst.exec(state),
 			Inst::Store(inst) => inst.exec(state),
 			Inst::Sys  (inst) => inst.exec(state),
-			Inst::Co   (_) => todo!(),
+			Inst::Co   (inst) => inst.exec(state),
 		}
 	}
 }
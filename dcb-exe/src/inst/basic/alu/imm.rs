@@ -3,7 +3,7 @@
 // Imports
 use crate::inst::{
 	basic::{Decode, Encode, ModifiesReg},
-	exec::{ExecCtx, ExecError, Executable},
+	exec::{Exception, ExecCtx, ExecError, Executable},
 	parse::LineArg,
 	DisplayCtx, InstDisplay, InstFmtArg, Parsable, ParseCtx, ParseError, Register,
 };
@@ -196,7 +196,7 @@ impl Executable for Inst {
 				.load_reg(self.lhs)
 				.as_signed()
 				.checked_add(rhs.sign_extended::<i32>())
-				.ok_or(ExecError::Overflow)?
+				.ok_or(Exception::Overflow)?
 				.as_unsigned(),
 			Kind::AddUnsigned(rhs) => state
 				.load_reg(self.lhs)
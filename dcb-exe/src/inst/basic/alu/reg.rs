@@ -3,7 +3,7 @@
 // Imports
 use crate::inst::{
 	basic::{Decode, Encode, ModifiesReg},
-	exec::{ExecCtx, ExecError, Executable},
+	exec::{Exception, ExecCtx, ExecError, Executable},
 	parse::LineArg,
 	DisplayCtx, InstDisplay, InstFmtArg, Parsable, ParseCtx, ParseError, Register,
 };
@@ -210,13 +210,13 @@ impl Executable for Inst {
 			Kind::Add => lhs
 				.as_signed()
 				.checked_add(rhs.as_signed())
-				.ok_or(ExecError::Overflow)?
+				.ok_or(Exception::Overflow)?
 				.as_unsigned(),
 			Kind::AddUnsigned => lhs.as_signed().wrapping_add(rhs.as_signed()).as_unsigned(),
 			Kind::Sub => lhs
 				.as_signed()
 				.checked_sub(rhs.as_signed())
-				.ok_or(ExecError::Overflow)?
+				.ok_or(Exception::Overflow)?
 				.as_unsigned(),
 			Kind::SubUnsigned => lhs.as_signed().wrapping_sub(rhs.as_signed()).as_unsigned(),
 			Kind::And => lhs & rhs,
@@ -2,12 +2,16 @@
 
 // Imports
 use super::ModifiesReg;
-use crate::inst::{
-	basic::{Decode, Encode},
-	parse::LineArg,
-	DisplayCtx, InstDisplay, InstFmtArg, Parsable, ParseCtx, ParseError, Register,
+use crate::{
+	inst::{
+		basic::{cond, Decode, Encode},
+		exec::{Exception, ExecCtx, ExecError, Executable},
+		parse::LineArg,
+		DisplayCtx, InstDisplay, InstFmtArg, Parsable, ParseCtx, ParseError, Register,
+	},
+	Pos,
 };
-use int_conv::{Signed, Truncated, ZeroExtended};
+use int_conv::{SignExtended, Signed, Truncated, ZeroExtended};
 use std::{convert::TryInto, fmt};
 
 /// Co-processor register kind
@@ -316,3 +320,74 @@ impl ModifiesReg for Inst {
 		}
 	}
 }
+
+impl Executable for Inst {
+	fn exec<Ctx: ExecCtx>(&self, state: &mut Ctx) -> Result<(), ExecError> {
+		let n = self.n;
+		match self.kind {
+			Kind::CopN { imm } => match n {
+				// Note: The only command of co-processor 0 is `rfe`
+				0 => match imm & 0x3f {
+					0x10 => {
+						state.cop0_mut().rfe();
+						Ok(())
+					},
+					_ => Err(ExecError::UnknownCoCommand { n, cmd: imm }),
+				},
+				2 => state.gte_mut().exec(imm),
+				_ => Err(Exception::CoUnusable { n }.into()),
+			},
+			Kind::MoveFrom { dst, src, kind } => {
+				let value = match (n, kind) {
+					(0, RegisterKind::Data) => state.cop0().load(src)?,
+					(2, RegisterKind::Data) => state.gte().load_data(src)?,
+					(2, RegisterKind::Control) => state.gte().load_control(src)?,
+					(0, RegisterKind::Control) => return Err(ExecError::UnknownCoRegister { n, reg: src }),
+					_ => return Err(Exception::CoUnusable { n }.into()),
+				};
+				state.queue_load(dst, value);
+
+				Ok(())
+			},
+			Kind::MoveTo { dst, src, kind } => {
+				let value = state.load_reg(src);
+				match (n, kind) {
+					(0, RegisterKind::Data) => state.cop0_mut().store(dst, value),
+					(2, RegisterKind::Data) => state.gte_mut().store_data(dst, value),
+					(2, RegisterKind::Control) => state.gte_mut().store_control(dst, value),
+					(0, RegisterKind::Control) => Err(ExecError::UnknownCoRegister { n, reg: dst }),
+					_ => Err(Exception::CoUnusable { n }.into()),
+				}
+			},
+			Kind::Branch { offset, on } => match n {
+				// Note: The condition inputs of the co-processors aren't connected, so they're always false.
+				0 | 2 => match on {
+					true => Ok(()),
+					false => state.queue_jump(cond::Inst::target_of(offset, state.pc())),
+				},
+				_ => Err(Exception::CoUnusable { n }.into()),
+			},
+			Kind::Load { dst, src, offset } => match n {
+				2 => {
+					let value = state.read_word(self::addr(state, src, offset))?;
+					state.queue_gte_load(dst, value);
+
+					Ok(())
+				},
+				_ => Err(Exception::CoUnusable { n }.into()),
+			},
+			Kind::Store { dst, src, offset } => match n {
+				2 => {
+					let value = state.gte().load_data(dst)?;
+					state.write_word(self::addr(state, src, offset), value)
+				},
+				_ => Err(Exception::CoUnusable { n }.into()),
+			},
+		}
+	}
+}
+
+/// Returns the address of a load / store from it's register and offset
+fn addr<Ctx: ExecCtx>(state: &Ctx, reg: Register, offset: i16) -> Pos {
+	Pos(state.load_reg(reg)) + offset.sign_extended::<i32>()
+}
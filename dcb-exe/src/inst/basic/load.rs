@@ -183,24 +183,38 @@ impl ModifiesReg for Inst {
 
 impl Executable for Inst {
 	fn exec<Ctx: ExecCtx>(&self, state: &mut Ctx) -> Result<(), ExecError> {
+		let pos = Pos(state.load_reg(self.addr)) + self.offset.sign_extended::<i32>();
 		let value = match self.kind {
-			Kind::Byte => state
-				.read_byte(Pos(state.load_reg(self.addr)))?
-				.as_signed()
-				.sign_extended::<i32>()
-				.as_unsigned(),
+			Kind::Byte => state.read_byte(pos)?.as_signed().sign_extended::<i32>().as_unsigned(),
 			Kind::HalfWord => state
-				.read_half_word(Pos(state.load_reg(self.addr)))?
+				.read_half_word(pos)?
 				.as_signed()
 				.sign_extended::<i32>()
 				.as_unsigned(),
-			Kind::Word => state.read_word(Pos(state.load_reg(self.addr)))?,
-			Kind::ByteUnsigned => state.read_byte(Pos(state.load_reg(self.addr)))?.zero_extended(),
-			Kind::HalfWordUnsigned => state.read_half_word(Pos(state.load_reg(self.addr)))?.zero_extended(),
-			Kind::WordLeft | Kind::WordRight => todo!(),
+			Kind::Word => state.read_word(pos)?,
+			Kind::ByteUnsigned => state.read_byte(pos)?.zero_extended(),
+			Kind::HalfWordUnsigned => state.read_half_word(pos)?.zero_extended(),
+			Kind::WordLeft => {
+				let (word, prev, shift) = self::read_unaligned(state, pos, self.value)?;
+				(prev & (0x00ff_ffff >> shift)) | (word << (24 - shift))
+			},
+			Kind::WordRight => {
+				let (word, prev, shift) = self::read_unaligned(state, pos, self.value)?;
+				(prev & !(0xffff_ffff >> shift)) | (word >> shift)
+			},
 		};
-		state.store_reg(self.value, value);
+		state.queue_load(self.value, value);
 
 		Ok(())
 	}
 }
+
+/// Reads the aligned word containing `pos` for an unaligned load into `reg`.
+///
+/// Returns the word, the previous value of `reg`, including any load into it still
+/// in it's delay slot, and the offset of `pos` within the word, in bits.
+fn read_unaligned<Ctx: ExecCtx>(state: &mut Ctx, pos: Pos, reg: Register) -> Result<(u32, u32, u32), ExecError> {
+	let word = state.read_word(Pos(pos.0 & !0x3))?;
+	let prev = state.load_reg_delayed(reg);
+	Ok((word, prev, (pos.0 & 0x3) * 8))
+}
@@ -11,7 +11,7 @@ use crate::{
 	},
 	Pos,
 };
-use int_conv::{Signed, Truncated, ZeroExtended};
+use int_conv::{SignExtended, Signed, Truncated, ZeroExtended};
 
 /// Store instruction kind
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
@@ -170,13 +170,23 @@ impl ModifiesReg for Inst {
 
 impl Executable for Inst {
 	fn exec<Ctx: ExecCtx>(&self, state: &mut Ctx) -> Result<(), ExecError> {
+		let pos = Pos(state.load_reg(self.addr)) + self.offset.sign_extended::<i32>();
+		let value = state.load_reg(self.value);
 		match self.kind {
-			Kind::Byte => state.write_byte(Pos(state.load_reg(self.addr)), state.load_reg(self.value).truncated()),
-			Kind::HalfWord => {
-				state.write_half_word(Pos(state.load_reg(self.addr)), state.load_reg(self.value).truncated())
+			Kind::Byte => state.write_byte(pos, value.truncated()),
+			Kind::HalfWord => state.write_half_word(pos, value.truncated()),
+			Kind::Word => state.write_word(pos, value),
+			// Note: Unaligned stores only write the bytes of the aligned word they cover
+			Kind::WordLeft => {
+				let (aligned, shift) = (Pos(pos.0 & !0x3), pos.0 & 0x3);
+				(0..=shift)
+					.try_for_each(|idx| state.write_byte(aligned + idx, (value >> (8 * (3 - shift + idx))).truncated()))
+			},
+			Kind::WordRight => {
+				let (aligned, shift) = (Pos(pos.0 & !0x3), pos.0 & 0x3);
+				(shift..4)
+					.try_for_each(|idx| state.write_byte(aligned + idx, (value >> (8 * (idx - shift))).truncated()))
 			},
-			Kind::Word => state.write_word(Pos(state.load_reg(self.addr)), state.load_reg(self.value)),
-			Kind::WordLeft | Kind::WordRight => todo!(),
 		}
 	}
 }
@@ -4,7 +4,7 @@
 use super::ModifiesReg;
 use crate::inst::{
 	basic::{Decode, TryEncode},
-	exec::{ExecCtx, ExecError, Executable},
+	exec::{Exception, ExecCtx, ExecError, Executable},
 	parse::LineArg,
 	DisplayCtx, InstDisplay, InstFmtArg, Parsable, ParseCtx, ParseError, Register,
 };
@@ -125,7 +125,12 @@ impl ModifiesReg for Inst {
 }
 
 impl Executable for Inst {
-	fn exec<Ctx: ExecCtx>(&self, state: &mut Ctx) -> Result<(), ExecError> {
-		state.sys(*self)
+	fn exec<Ctx: ExecCtx>(&self, _state: &mut Ctx) -> Result<(), ExecError> {
+		let exception = match self.kind {
+			Kind::Sys => Exception::Syscall,
+			Kind::Break => Exception::Breakpoint,
+		};
+
+		Err(exception.into())
 	}
 }
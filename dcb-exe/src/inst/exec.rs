@@ -1,13 +1,22 @@
 //! Execution
 
 // Modules
+pub mod bus;
+pub mod cop0;
 mod error;
+pub mod exception;
+pub mod gte;
+pub mod load_delay;
 
 // Exports
+pub use bus::Bus;
+pub use cop0::Cop0;
 pub use error::ExecError;
+pub use exception::Exception;
+pub use gte::Gte;
+pub use load_delay::{LoadDelay, LoadTarget};
 
 // Imports
-use super::basic;
 use crate::{
 	inst::{basic::mult::MultReg, Register},
 	Pos,
@@ -21,6 +30,11 @@ pub trait ExecCtx {
 	/// Loads a register `reg`
 	fn load_reg(&self, reg: Register) -> u32;
 
+	/// Loads a register `reg`, including any load into it still in it's delay slot
+	///
+	/// Used by `lwl` / `lwr`, which merge with a previous load instead of waiting on it.
+	fn load_reg_delayed(&self, reg: Register) -> u32;
+
 	/// Stores a register `reg`
 	fn store_reg(&mut self, reg: Register, value: u32);
 
@@ -33,14 +47,27 @@ pub trait ExecCtx {
 	/// Queues a jump
 	fn queue_jump(&mut self, pos: Pos) -> Result<(), ExecError>;
 
+	/// Queues a load of `value` into `reg`
+	///
+	/// Loads have a delay slot, so `reg` should only be written after the next instruction
+	/// executes, unless the next instruction writes to it first.
+	fn queue_load(&mut self, reg: Register, value: u32);
+
+	/// Queues a load of `value` into the geometry transformation engine data register `reg`
+	///
+	/// Like with `queue_load`, `reg` should only be written after the next instruction executes.
+	fn queue_gte_load(&mut self, reg: u8, value: u32);
+
 	/// Reads a word
-	fn read_word(&self, pos: Pos) -> Result<u32, ExecError>;
+	///
+	/// Note: Takes `&mut self`, as reading from hardware registers may have side effects.
+	fn read_word(&mut self, pos: Pos) -> Result<u32, ExecError>;
 
 	/// Reads a half-word
-	fn read_half_word(&self, pos: Pos) -> Result<u16, ExecError>;
+	fn read_half_word(&mut self, pos: Pos) -> Result<u16, ExecError>;
 
 	/// Reads a byte
-	fn read_byte(&self, pos: Pos) -> Result<u8, ExecError>;
+	fn read_byte(&mut self, pos: Pos) -> Result<u8, ExecError>;
 
 	/// Writes a word
 	fn write_word(&mut self, pos: Pos, value: u32) -> Result<(), ExecError>;
@@ -51,8 +78,17 @@ pub trait ExecCtx {
 	/// Writes a byte
 	fn write_byte(&mut self, pos: Pos, value: u8) -> Result<(), ExecError>;
 
-	/// Executes a syscall
-	fn sys(&mut self, inst: basic::sys::Inst) -> Result<(), ExecError>;
+	/// Returns the system control co-processor
+	fn cop0(&self) -> &Cop0;
+
+	/// Returns the system control co-processor mutably
+	fn cop0_mut(&mut self) -> &mut Cop0;
+
+	/// Returns the geometry transformation engine
+	fn gte(&self) -> &Gte;
+
+	/// Returns the geometry transformation engine mutably
+	fn gte_mut(&mut self) -> &mut Gte;
 }
 
 /// An executable instruction
@@ -0,0 +1,271 @@
+//! Memory bus
+//!
+//! Maps the address space onto ram, the scratchpad, the bios and
+//! the devices mapped onto the i/o ports.
+//!
+//! `KUSEG`, `KSEG0` and `KSEG1` all mirror the physical address space, while
+//! `KSEG2` only contains the cache control register.
+//!
+//! | Physical address           | Region         |
+//! | -------------------------- | -------------- |
+//! | `0x0000_0000..0x0080_0000` | Ram (mirrored) |
+//! | `0x1f00_0000..0x1f80_0000` | Expansion 1    |
+//! | `0x1f80_0000..0x1f80_0400` | Scratchpad     |
+//! | `0x1f80_1000..0x1f80_4000` | I/O ports      |
+//! | `0x1fc0_0000..0x1fc8_0000` | Bios           |
+
+// Modules
+pub mod device;
+#[cfg(test)]
+mod test;
+
+// Exports
+pub use device::{AccessSize, Device};
+
+// Imports
+use self::device::{CdRom, Dma, Gpu, InterruptController, Registers, Spu, Timers};
+use super::{Exception, ExecError};
+use crate::Pos;
+use std::ops::Range;
+
+/// Memory bus
+pub struct Bus {
+	/// Ram
+	ram: Box<[u8]>,
+
+	/// Scratchpad
+	scratchpad: Box<[u8]>,
+
+	/// Bios
+	bios: Box<[u8]>,
+
+	/// Cache control register
+	cache_control: u32,
+
+	/// All mapped devices
+	devices: Vec<MappedDevice>,
+}
+
+/// A device mapped onto the bus
+struct MappedDevice {
+	/// Physical addresses
+	addrs: Range<u32>,
+
+	/// Device
+	device: Box<dyn Device>,
+}
+
+/// Bus region
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Region {
+	/// Ram
+	Ram(usize),
+
+	/// Expansion 1
+	Expansion1,
+
+	/// Scratchpad
+	Scratchpad(usize),
+
+	/// Device
+	Device {
+		/// Index of the device
+		idx: usize,
+
+		/// Offset into the device
+		offset: u32,
+	},
+
+	/// I/O port without any device mapped
+	UnmappedIo,
+
+	/// Bios
+	Bios(usize),
+
+	/// Cache control register
+	CacheControl,
+}
+
+impl Bus {
+	/// Bios size
+	pub const BIOS_SIZE: usize = 0x8_0000;
+	/// Ram size
+	pub const RAM_SIZE: usize = 0x20_0000;
+	/// Scratchpad size
+	pub const SCRATCHPAD_SIZE: usize = 0x400;
+
+	/// Creates a new bus, with stubs mapped for all hardware devices
+	#[must_use]
+	pub fn new() -> Self {
+		let mut bus = Self::empty();
+
+		bus.map_device(0x1f80_1000, 0x24, Box::new(Registers::new("Memory control", 0x24)));
+		bus.map_device(0x1f80_1040, 0x20, Box::new(Registers::new("Peripheral", 0x20)));
+		bus.map_device(0x1f80_1060, 0x4, Box::new(Registers::new("Ram size", 0x4)));
+		bus.map_device(0x1f80_1070, 0x8, Box::new(InterruptController::default()));
+		bus.map_device(0x1f80_1080, 0x80, Box::new(Dma::default()));
+		bus.map_device(0x1f80_1100, 0x30, Box::new(Timers::default()));
+		bus.map_device(0x1f80_1800, 0x4, Box::new(CdRom::default()));
+		bus.map_device(0x1f80_1810, 0x8, Box::new(Gpu::default()));
+		bus.map_device(0x1f80_1820, 0x8, Box::new(Registers::new("Mdec", 0x8)));
+		bus.map_device(0x1f80_1c00, 0x400, Box::new(Spu::default()));
+		bus.map_device(0x1f80_2000, 0x80, Box::new(Registers::new("Expansion 2", 0x80)));
+
+		bus
+	}
+
+	/// Creates a new bus without any devices mapped
+	#[must_use]
+	pub fn empty() -> Self {
+		Self {
+			ram: vec![0; Self::RAM_SIZE].into_boxed_slice(),
+			scratchpad: vec![0; Self::SCRATCHPAD_SIZE].into_boxed_slice(),
+			bios: vec![0; Self::BIOS_SIZE].into_boxed_slice(),
+			cache_control: 0,
+			devices: vec![],
+		}
+	}
+
+	/// Maps `device` onto `len` bytes starting at physical address `start`.
+	///
+	/// Devices mapped later take priority over devices already mapped.
+	pub fn map_device(&mut self, start: u32, len: u32, device: Box<dyn Device>) {
+		self.devices.push(MappedDevice {
+			addrs: start..(start + len),
+			device,
+		});
+	}
+
+	/// Returns the ram
+	#[must_use]
+	pub fn ram(&self) -> &[u8] {
+		&self.ram
+	}
+
+	/// Returns the ram mutably
+	pub fn ram_mut(&mut self) -> &mut [u8] {
+		&mut self.ram
+	}
+
+	/// Returns the bios
+	#[must_use]
+	pub fn bios(&self) -> &[u8] {
+		&self.bios
+	}
+
+	/// Returns the bios mutably
+	pub fn bios_mut(&mut self) -> &mut [u8] {
+		&mut self.bios
+	}
+
+	/// Reads `size` bytes from `pos`
+	pub fn read(&mut self, pos: Pos, size: AccessSize) -> Result<u32, ExecError> {
+		if !pos.is_aligned_to(size.bytes()) {
+			return Err(Exception::AddressErrorLoad { pos }.into());
+		}
+
+		let value = match self.region(pos).ok_or(Exception::BusErrorData { pos })? {
+			Region::Ram(offset) => size.read(&self.ram, offset),
+			Region::Scratchpad(offset) => size.read(&self.scratchpad, offset),
+			Region::Bios(offset) => size.read(&self.bios, offset),
+			Region::CacheControl => self.cache_control,
+
+			// Note: Nothing is connected to the expansion, so all bits read as set
+			Region::Expansion1 => size.mask(),
+
+			Region::Device { idx, offset } => {
+				let device = &mut self.devices[idx].device;
+				let value = device.read(offset, size);
+				log::trace!("[{}] Read {size} at {offset:#x}: {value:#x}", device.name());
+				value
+			},
+			Region::UnmappedIo => {
+				log::warn!("Read {size} from unmapped i/o port {pos}");
+				0
+			},
+		};
+
+		Ok(value)
+	}
+
+	/// Reads `size` bytes from `pos` without any side effects.
+	///
+	/// Returns `None` for i/o ports, as reading them might have side effects.
+	pub fn peek(&self, pos: Pos, size: AccessSize) -> Result<Option<u32>, ExecError> {
+		if !pos.is_aligned_to(size.bytes()) {
+			return Err(Exception::AddressErrorLoad { pos }.into());
+		}
+
+		let value = match self.region(pos).ok_or(Exception::BusErrorData { pos })? {
+			Region::Ram(offset) => size.read(&self.ram, offset),
+			Region::Scratchpad(offset) => size.read(&self.scratchpad, offset),
+			Region::Bios(offset) => size.read(&self.bios, offset),
+			Region::CacheControl => self.cache_control,
+			Region::Expansion1 => size.mask(),
+			Region::Device { .. } | Region::UnmappedIo => return Ok(None),
+		};
+
+		Ok(Some(value))
+	}
+
+	/// Writes `size` bytes of `value` to `pos`
+	pub fn write(&mut self, pos: Pos, size: AccessSize, value: u32) -> Result<(), ExecError> {
+		if !pos.is_aligned_to(size.bytes()) {
+			return Err(Exception::AddressErrorStore { pos }.into());
+		}
+
+		match self.region(pos).ok_or(Exception::BusErrorData { pos })? {
+			Region::Ram(offset) => size.write(&mut self.ram, offset, value),
+			Region::Scratchpad(offset) => size.write(&mut self.scratchpad, offset, value),
+			Region::CacheControl => self.cache_control = value,
+			Region::Bios(_) => log::warn!("Ignoring write of {size} {value:#x} to bios at {pos}"),
+			Region::Expansion1 => log::warn!("Ignoring write of {size} {value:#x} to expansion 1 at {pos}"),
+			Region::Device { idx, offset } => {
+				let device = &mut self.devices[idx].device;
+				log::trace!("[{}] Write {size} at {offset:#x}: {value:#x}", device.name());
+				device.write(offset, size, value);
+			},
+			Region::UnmappedIo => log::warn!("Ignoring write of {size} {value:#x} to unmapped i/o port {pos}"),
+		}
+
+		Ok(())
+	}
+
+	/// Returns the region of an access to `pos`, if anything is mapped there
+	fn region(&self, pos: Pos) -> Option<Region> {
+		// Get the physical address
+		let addr = match pos.0 {
+			0x0000_0000..=0x1fff_ffff | 0x8000_0000..=0xbfff_ffff => pos.0 & 0x1fff_ffff,
+			0xfffe_0130 => return Some(Region::CacheControl),
+			_ => return None,
+		};
+
+		let region = match addr {
+			0x0000_0000..=0x007f_ffff => Region::Ram(self::offset(addr, 0) % Self::RAM_SIZE),
+			0x1f00_0000..=0x1f7f_ffff => Region::Expansion1,
+			0x1f80_0000..=0x1f80_03ff => Region::Scratchpad(self::offset(addr, 0x1f80_0000)),
+			0x1f80_1000..=0x1f80_3fff => match self.devices.iter().rposition(|mapped| mapped.addrs.contains(&addr)) {
+				Some(idx) => Region::Device {
+					idx,
+					offset: addr - self.devices[idx].addrs.start,
+				},
+				None => Region::UnmappedIo,
+			},
+			0x1fc0_0000..=0x1fc7_ffff => Region::Bios(self::offset(addr, 0x1fc0_0000)),
+			_ => return None,
+		};
+
+		Some(region)
+	}
+}
+
+impl Default for Bus {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Returns the offset of `addr` from `start`
+fn offset(addr: u32, start: u32) -> usize {
+	usize::try_from(addr - start).expect("Offset didn't fit into a `usize`")
+}
@@ -0,0 +1,97 @@
+//! Bus devices
+//!
+//! Devices are mapped onto the i/o ports of the bus. Instead of emulating the
+//! hardware, the devices here are stubs that log their accesses and return
+//! plausible values, so an executable may be traced while it drives the hardware.
+
+// Modules
+pub mod cdrom;
+pub mod dma;
+pub mod gpu;
+pub mod interrupt;
+pub mod registers;
+pub mod spu;
+pub mod timers;
+
+// Exports
+pub use cdrom::CdRom;
+pub use dma::Dma;
+pub use gpu::Gpu;
+pub use interrupt::InterruptController;
+pub use registers::Registers;
+pub use spu::Spu;
+pub use timers::Timers;
+
+// Imports
+use byteorder::{ByteOrder, LittleEndian};
+use int_conv::Truncated;
+
+/// A device mapped onto the bus
+pub trait Device {
+	/// Returns the name of this device
+	fn name(&self) -> &'static str;
+
+	/// Reads `size` bytes at `offset`, relative to the start of the device
+	fn read(&mut self, offset: u32, size: AccessSize) -> u32;
+
+	/// Writes `size` bytes of `value` at `offset`, relative to the start of the device
+	fn write(&mut self, offset: u32, size: AccessSize, value: u32);
+}
+
+/// Access size
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(derive_more::Display)]
+pub enum AccessSize {
+	/// Byte
+	#[display(fmt = "byte")]
+	Byte,
+
+	/// Half-word
+	#[display(fmt = "half-word")]
+	HalfWord,
+
+	/// Word
+	#[display(fmt = "word")]
+	Word,
+}
+
+impl AccessSize {
+	/// Returns the number of bytes of this access
+	#[must_use]
+	pub const fn bytes(self) -> usize {
+		match self {
+			Self::Byte => 1,
+			Self::HalfWord => 2,
+			Self::Word => 4,
+		}
+	}
+
+	/// Returns a mask with all the bits of this access set
+	#[must_use]
+	pub const fn mask(self) -> u32 {
+		match self {
+			Self::Byte => 0xff,
+			Self::HalfWord => 0xffff,
+			Self::Word => 0xffff_ffff,
+		}
+	}
+
+	/// Reads a value of this size from `bytes` at `offset`
+	#[must_use]
+	pub fn read(self, bytes: &[u8], offset: usize) -> u32 {
+		match self {
+			Self::Byte => u32::from(bytes[offset]),
+			Self::HalfWord => u32::from(LittleEndian::read_u16(&bytes[offset..])),
+			Self::Word => LittleEndian::read_u32(&bytes[offset..]),
+		}
+	}
+
+	/// Writes `value` with this size to `bytes` at `offset`
+	pub fn write(self, bytes: &mut [u8], offset: usize, value: u32) {
+		match self {
+			Self::Byte => bytes[offset] = value.truncated(),
+			Self::HalfWord => LittleEndian::write_u16(&mut bytes[offset..], value.truncated()),
+			Self::Word => LittleEndian::write_u32(&mut bytes[offset..], value),
+		}
+	}
+}
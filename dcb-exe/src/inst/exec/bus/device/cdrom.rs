@@ -0,0 +1,42 @@
+//! Cd-rom controller
+
+// Imports
+use super::{AccessSize, Device};
+
+/// Cd-rom controller stub.
+///
+/// Logs all commands and parameters, but never responds to them.
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct CdRom {
+	/// Current index of the registers
+	index: u32,
+}
+
+impl CdRom {
+	/// Status bits reporting the parameter fifo as empty and not full
+	pub const STATUS_PARAM_FIFO: u32 = 0x18;
+}
+
+impl Device for CdRom {
+	fn name(&self) -> &'static str {
+		"Cd-rom controller"
+	}
+
+	fn read(&mut self, offset: u32, _size: AccessSize) -> u32 {
+		match offset {
+			0 => self.index | Self::STATUS_PARAM_FIFO,
+
+			// Note: The response and data fifos are always empty
+			_ => 0,
+		}
+	}
+
+	fn write(&mut self, offset: u32, _size: AccessSize, value: u32) {
+		match (offset, self.index) {
+			(0, _) => self.index = value & 0x3,
+			(1, 0) => log::debug!("[Cd-rom] Command {value:#04x}"),
+			(2, 0) => log::debug!("[Cd-rom] Parameter {value:#04x}"),
+			(offset, index) => log::debug!("[Cd-rom] Register {offset}.{index}: {value:#04x}"),
+		}
+	}
+}
@@ -0,0 +1,77 @@
+//! Dma
+
+// Imports
+use super::{AccessSize, Device, Registers};
+
+/// Dma stub.
+///
+/// Logs all transfers and finishes them as soon as they're started, without
+/// transferring anything.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Dma {
+	/// Registers
+	regs: Registers,
+}
+
+impl Dma {
+	/// Offset of the interrupt register, `DICR`
+	pub const DICR: u32 = 0x74;
+
+	/// Returns the name of channel `channel`
+	#[must_use]
+	pub const fn channel_name(channel: u32) -> &'static str {
+		match channel {
+			0 => "MDECin",
+			1 => "MDECout",
+			2 => "GPU",
+			3 => "CDROM",
+			4 => "SPU",
+			5 => "PIO",
+			6 => "OTC",
+			_ => "Unknown",
+		}
+	}
+}
+
+impl Default for Dma {
+	fn default() -> Self {
+		Self {
+			regs: Registers::new("Dma", 0x80),
+		}
+	}
+}
+
+impl Device for Dma {
+	fn name(&self) -> &'static str {
+		"Dma"
+	}
+
+	fn read(&mut self, offset: u32, size: AccessSize) -> u32 {
+		self.regs.load(offset, size)
+	}
+
+	fn write(&mut self, offset: u32, size: AccessSize, value: u32) {
+		// Note: Writing `1` to the flags of `DICR` acknowledges them
+		if offset == Self::DICR {
+			let flags = self.regs.load(Self::DICR, AccessSize::Word) & 0x7f00_0000 & !value;
+			self.regs
+				.store(Self::DICR, AccessSize::Word, (value & 0x00ff_ffff) | flags);
+			return;
+		}
+		self.regs.store(offset, size, value);
+
+		// If a channel was started, log it and finish it right away
+		let (channel, reg) = (offset / 0x10, offset % 0x10);
+		if channel < 7 && reg == 8 && value & (1 << 24) != 0 {
+			let madr = self.regs.load(channel * 0x10, AccessSize::Word);
+			let bcr = self.regs.load(channel * 0x10 + 4, AccessSize::Word);
+			log::debug!(
+				"[Dma] {} transfer: madr={madr:#x}, bcr={bcr:#x}, chcr={value:#x}",
+				Self::channel_name(channel)
+			);
+
+			self.regs
+				.store(offset, AccessSize::Word, value & !((1 << 24) | (1 << 28)));
+		}
+	}
+}
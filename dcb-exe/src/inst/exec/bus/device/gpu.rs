@@ -0,0 +1,66 @@
+//! Gpu
+
+// Imports
+use super::{AccessSize, Device};
+
+/// Gpu stub.
+///
+/// Logs all commands and always reports being ready to receive them.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Gpu {
+	/// Status, `GPUSTAT`
+	status: u32,
+}
+
+impl Gpu {
+	/// Status bits reporting the gpu as ready to receive commands, vram transfers and dma blocks
+	pub const STATUS_READY: u32 = 0x1c00_0000;
+	/// Status after a reset
+	pub const STATUS_RESET: u32 = 0x1480_2000;
+}
+
+impl Default for Gpu {
+	fn default() -> Self {
+		Self {
+			status: Self::STATUS_RESET,
+		}
+	}
+}
+
+impl Device for Gpu {
+	fn name(&self) -> &'static str {
+		"Gpu"
+	}
+
+	fn read(&mut self, offset: u32, _size: AccessSize) -> u32 {
+		match offset {
+			// `GPUSTAT`
+			4 => self.status | Self::STATUS_READY,
+
+			// `GPUREAD`
+			_ => 0,
+		}
+	}
+
+	fn write(&mut self, offset: u32, _size: AccessSize, value: u32) {
+		let cmd = value >> 24;
+		match offset {
+			0 => log::debug!("[Gpu] GP0({cmd:#04x}): {value:#010x}"),
+			_ => {
+				log::debug!("[Gpu] GP1({cmd:#04x}): {value:#010x}");
+				match cmd {
+					// Reset
+					0x00 => self.status = Self::STATUS_RESET,
+
+					// Display enable
+					0x03 => self.status = (self.status & !(1 << 23)) | ((value & 0x1) << 23),
+
+					// Dma direction
+					0x04 => self.status = (self.status & !(0x3 << 29)) | ((value & 0x3) << 29),
+
+					_ => (),
+				}
+			},
+		}
+	}
+}
@@ -0,0 +1,39 @@
+//! Interrupt controller
+
+// Imports
+use super::{AccessSize, Device};
+
+/// Interrupt controller stub.
+///
+/// No device ever requests an interrupt, but acknowledging and masking them works.
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct InterruptController {
+	/// Status, `I_STAT`
+	pub stat: u32,
+
+	/// Mask, `I_MASK`
+	pub mask: u32,
+}
+
+impl Device for InterruptController {
+	fn name(&self) -> &'static str {
+		"Interrupt controller"
+	}
+
+	fn read(&mut self, offset: u32, _size: AccessSize) -> u32 {
+		match offset {
+			0 => self.stat,
+			4 => self.mask,
+			_ => 0,
+		}
+	}
+
+	fn write(&mut self, offset: u32, _size: AccessSize, value: u32) {
+		match offset {
+			// Note: Writing `0` to a bit of the status acknowledges it
+			0 => self.stat &= value,
+			4 => self.mask = value & 0x7ff,
+			_ => (),
+		}
+	}
+}
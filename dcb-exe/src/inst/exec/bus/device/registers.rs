@@ -0,0 +1,57 @@
+//! Register file
+
+// Imports
+use super::{AccessSize, Device};
+
+/// Register file.
+///
+/// Stub for devices whose registers simply hold the last value written to them.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Registers {
+	/// Name
+	name: &'static str,
+
+	/// All registers
+	bytes: Box<[u8]>,
+}
+
+impl Registers {
+	/// Creates a new register file with `len` bytes of zeroed registers
+	#[must_use]
+	pub fn new(name: &'static str, len: usize) -> Self {
+		Self {
+			name,
+			bytes: vec![0; len].into_boxed_slice(),
+		}
+	}
+
+	/// Loads `size` bytes from the registers at `offset`
+	#[must_use]
+	pub fn load(&self, offset: u32, size: AccessSize) -> u32 {
+		size.read(&self.bytes, self::offset_idx(offset))
+	}
+
+	/// Stores `size` bytes of `value` in the registers at `offset`
+	pub fn store(&mut self, offset: u32, size: AccessSize, value: u32) {
+		size.write(&mut self.bytes, self::offset_idx(offset), value);
+	}
+}
+
+impl Device for Registers {
+	fn name(&self) -> &'static str {
+		self.name
+	}
+
+	fn read(&mut self, offset: u32, size: AccessSize) -> u32 {
+		self.load(offset, size)
+	}
+
+	fn write(&mut self, offset: u32, size: AccessSize, value: u32) {
+		self.store(offset, size, value);
+	}
+}
+
+/// Converts an offset into an index
+fn offset_idx(offset: u32) -> usize {
+	usize::try_from(offset).expect("Register offset didn't fit into a `usize`")
+}
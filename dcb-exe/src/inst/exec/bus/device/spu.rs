@@ -0,0 +1,55 @@
+//! Spu
+
+// Imports
+use super::{AccessSize, Device, Registers};
+
+/// Spu stub.
+///
+/// Holds all registers and logs voices being keyed on and off.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Spu {
+	/// Registers
+	regs: Registers,
+}
+
+impl Spu {
+	/// Offset of the control register, `SPUCNT`
+	pub const CONTROL: u32 = 0x1aa;
+	/// Offset of the key off register, `KOFF`
+	pub const KEY_OFF: u32 = 0x18c;
+	/// Offset of the key on register, `KON`
+	pub const KEY_ON: u32 = 0x188;
+	/// Offset of the status register, `SPUSTAT`
+	pub const STATUS: u32 = 0x1ae;
+}
+
+impl Default for Spu {
+	fn default() -> Self {
+		Self {
+			regs: Registers::new("Spu", 0x400),
+		}
+	}
+}
+
+impl Device for Spu {
+	fn name(&self) -> &'static str {
+		"Spu"
+	}
+
+	fn read(&mut self, offset: u32, size: AccessSize) -> u32 {
+		match offset {
+			// Note: The mode bits of the status are applied right away from the control
+			Self::STATUS => self.regs.load(Self::CONTROL, AccessSize::HalfWord) & 0x3f,
+			_ => self.regs.load(offset, size),
+		}
+	}
+
+	fn write(&mut self, offset: u32, size: AccessSize, value: u32) {
+		match offset {
+			Self::KEY_ON | 0x18a => log::debug!("[Spu] Key on {value:#x} at {offset:#x}"),
+			Self::KEY_OFF | 0x18e => log::debug!("[Spu] Key off {value:#x} at {offset:#x}"),
+			_ => (),
+		}
+		self.regs.store(offset, size, value);
+	}
+}
@@ -0,0 +1,80 @@
+//! Timers
+
+// Imports
+use super::{AccessSize, Device};
+
+/// Timers stub.
+///
+/// As no cycles are counted, the counters advance each time they're read, so
+/// that loops waiting on them still make progress.
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct Timers {
+	/// All timers
+	timers: [Timer; 3],
+}
+
+impl Timers {
+	/// Amount each counter advances per read
+	pub const COUNTER_STEP: u32 = 0x10;
+
+	/// Returns the timer of `offset`
+	fn timer_mut(&mut self, offset: u32) -> &mut Timer {
+		let idx = usize::try_from(offset / 0x10).expect("Timer index didn't fit into a `usize`");
+		&mut self.timers[idx]
+	}
+}
+
+/// Timer
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+struct Timer {
+	/// Counter
+	counter: u32,
+
+	/// Mode
+	mode: u32,
+
+	/// Target
+	target: u32,
+}
+
+impl Device for Timers {
+	fn name(&self) -> &'static str {
+		"Timers"
+	}
+
+	fn read(&mut self, offset: u32, _size: AccessSize) -> u32 {
+		let timer = self.timer_mut(offset);
+		match offset % 0x10 {
+			0 => {
+				timer.counter = (timer.counter + Self::COUNTER_STEP) & 0xffff;
+				timer.counter
+			},
+
+			// Note: Reading the mode resets the target / overflow reached bits
+			4 => {
+				let mode = timer.mode;
+				timer.mode &= !0x1800;
+				mode
+			},
+
+			8 => timer.target,
+			_ => 0,
+		}
+	}
+
+	fn write(&mut self, offset: u32, _size: AccessSize, value: u32) {
+		let timer = self.timer_mut(offset);
+		match offset % 0x10 {
+			0 => timer.counter = value & 0xffff,
+
+			// Note: Writing the mode resets the counter and the (active-low) interrupt request bit
+			4 => {
+				timer.mode = (value & 0x3ff) | 0x400;
+				timer.counter = 0;
+			},
+
+			8 => timer.target = value & 0xffff,
+			_ => (),
+		}
+	}
+}
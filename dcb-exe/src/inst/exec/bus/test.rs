@@ -0,0 +1,122 @@
+//! Tests
+
+// Imports
+use super::*;
+use std::{cell::Cell, rc::Rc};
+
+/// Device that returns the offset read and remembers the last write
+struct Echo {
+	/// Last write
+	last_write: Rc<Cell<Option<(u32, u32)>>>,
+}
+
+impl Device for Echo {
+	fn name(&self) -> &'static str {
+		"Echo"
+	}
+
+	fn read(&mut self, offset: u32, _size: AccessSize) -> u32 {
+		offset
+	}
+
+	fn write(&mut self, offset: u32, _size: AccessSize, value: u32) {
+		self.last_write.set(Some((offset, value)));
+	}
+}
+
+#[test]
+fn ram_mirrors() {
+	let mut bus = Bus::empty();
+	bus.write(Pos(0x8001_0000), AccessSize::Word, 0x1234_5678)
+		.expect("Unable to write word");
+
+	for pos in [0x0001_0000, 0x8001_0000, 0xa001_0000, 0x8021_0000, 0x0061_0000] {
+		assert_eq!(
+			bus.read(Pos(pos), AccessSize::Word).expect("Unable to read word"),
+			0x1234_5678
+		);
+	}
+	assert_eq!(
+		bus.read(Pos(0x8001_0002), AccessSize::HalfWord)
+			.expect("Unable to read half-word"),
+		0x1234
+	);
+	assert_eq!(
+		bus.read(Pos(0x8001_0003), AccessSize::Byte)
+			.expect("Unable to read byte"),
+		0x12
+	);
+}
+
+#[test]
+fn bios_read_only() {
+	let mut bus = Bus::empty();
+	bus.bios_mut()[..4].copy_from_slice(&[0x78, 0x56, 0x34, 0x12]);
+
+	bus.write(Pos(0xbfc0_0000), AccessSize::Word, 0)
+		.expect("Unable to write word");
+	assert_eq!(
+		bus.read(Pos(0xbfc0_0000), AccessSize::Word)
+			.expect("Unable to read word"),
+		0x1234_5678
+	);
+}
+
+#[test]
+fn invalid_accesses() {
+	let mut bus = Bus::empty();
+
+	assert!(matches!(
+		bus.read(Pos(0x8000_0002), AccessSize::Word),
+		Err(ExecError::Exception(Exception::AddressErrorLoad { .. }))
+	));
+	assert!(matches!(
+		bus.write(Pos(0x8000_0001), AccessSize::HalfWord, 0),
+		Err(ExecError::Exception(Exception::AddressErrorStore { .. }))
+	));
+	assert!(matches!(
+		bus.read(Pos(0x8080_0000), AccessSize::Word),
+		Err(ExecError::Exception(Exception::BusErrorData { .. }))
+	));
+	assert!(matches!(
+		bus.read(Pos(0xc000_0000), AccessSize::Word),
+		Err(ExecError::Exception(Exception::BusErrorData { .. }))
+	));
+}
+
+#[test]
+fn devices() {
+	let mut bus = Bus::new();
+	let last_write = Rc::default();
+	bus.map_device(
+		0x1f80_1810,
+		0x8,
+		Box::new(Echo {
+			last_write: Rc::clone(&last_write),
+		}),
+	);
+
+	// The latest device should take priority over the gpu
+	assert_eq!(
+		bus.read(Pos(0x1f80_1814), AccessSize::Word)
+			.expect("Unable to read word"),
+		4
+	);
+	assert_eq!(
+		bus.peek(Pos(0x1f80_1814), AccessSize::Word)
+			.expect("Unable to peek word"),
+		None
+	);
+	bus.write(Pos(0xbf80_1810), AccessSize::Word, 0xe100_0000)
+		.expect("Unable to write word");
+	assert_eq!(last_write.get(), Some((0, 0xe100_0000)));
+
+	// And the interrupt controller should still be there
+	bus.write(Pos(0x1f80_1074), AccessSize::Word, 0xffff_ffff)
+		.expect("Unable to write word");
+	assert_eq!(
+		bus.read(Pos(0x1f80_1074), AccessSize::Word)
+			.expect("Unable to read word"),
+		0x7ff
+	);
+}
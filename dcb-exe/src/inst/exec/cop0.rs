@@ -0,0 +1,148 @@
+//! System control co-processor
+
+// Modules
+#[cfg(test)]
+mod test;
+
+// Imports
+use super::{Exception, ExecError};
+use crate::Pos;
+
+/// System control co-processor (`cop0`)
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct Cop0 {
+	/// Breakpoint on execute address (`BPC`, `r3`)
+	pub bpc: u32,
+
+	/// Breakpoint on data access address (`BDA`, `r5`)
+	pub bda: u32,
+
+	/// Jump destination (`JUMPDEST`, `r6`)
+	pub jump_dest: u32,
+
+	/// Breakpoint control (`DCIC`, `r7`)
+	pub dcic: u32,
+
+	/// Bad virtual address (`BadVaddr`, `r8`)
+	pub bad_vaddr: u32,
+
+	/// Breakpoint on data access mask (`BDAM`, `r9`)
+	pub bdam: u32,
+
+	/// Breakpoint on execute mask (`BPCM`, `r11`)
+	pub bpcm: u32,
+
+	/// System status (`SR`, `r12`)
+	pub sr: u32,
+
+	/// Exception cause (`CAUSE`, `r13`)
+	pub cause: u32,
+
+	/// Exception return address (`EPC`, `r14`)
+	pub epc: u32,
+}
+
+impl Cop0 {
+	/// Bit of `CAUSE` set when the exception happened in a branch delay slot
+	pub const CAUSE_BRANCH_DELAY: u32 = 1 << 31;
+	/// Bits of `CAUSE` with the pending interrupts
+	pub const CAUSE_PENDING_INTERRUPTS: u32 = 0xff00;
+	/// Bits of `CAUSE` that may be written by software
+	pub const CAUSE_WRITE_MASK: u32 = 0x300;
+	/// Exception vector
+	pub const EXCEPTION_VECTOR: Pos = Pos(0x8000_0080);
+	/// Exception vector while the boot exception vectors are in use
+	pub const EXCEPTION_VECTOR_BOOT: Pos = Pos(0xbfc0_0180);
+	/// Processor id (`PRID`, `r15`)
+	pub const PRID: u32 = 0x2;
+	/// Bit of `SR` that selects the boot exception vectors
+	pub const SR_BOOT_EXCEPTION_VECTORS: u32 = 1 << 22;
+	/// Bit of `SR` that isolates the cache
+	pub const SR_ISOLATE_CACHE: u32 = 1 << 16;
+
+	/// Loads register `reg`
+	pub fn load(&self, reg: u8) -> Result<u32, ExecError> {
+		let value = match reg {
+			3 => self.bpc,
+			5 => self.bda,
+			6 => self.jump_dest,
+			7 => self.dcic,
+			8 => self.bad_vaddr,
+			9 => self.bdam,
+			11 => self.bpcm,
+			12 => self.sr,
+			13 => self.cause,
+			14 => self.epc,
+			15 => Self::PRID,
+			_ => return Err(ExecError::UnknownCoRegister { n: 0, reg }),
+		};
+
+		Ok(value)
+	}
+
+	/// Stores `value` in register `reg`
+	///
+	/// Writes to read-only registers are ignored.
+	pub fn store(&mut self, reg: u8, value: u32) -> Result<(), ExecError> {
+		match reg {
+			3 => self.bpc = value,
+			5 => self.bda = value,
+			6 => self.jump_dest = value,
+			7 => self.dcic = value,
+			9 => self.bdam = value,
+			11 => self.bpcm = value,
+			12 => self.sr = value,
+			13 => self.cause = (self.cause & !Self::CAUSE_WRITE_MASK) | (value & Self::CAUSE_WRITE_MASK),
+			8 | 14 | 15 => (),
+			_ => return Err(ExecError::UnknownCoRegister { n: 0, reg }),
+		}
+
+		Ok(())
+	}
+
+	/// Enters `exception`, raised by the instruction at `pc`.
+	///
+	/// Pushes the interrupt enable / kernel mode stack in `SR`, sets up `CAUSE`, `EPC`
+	/// and `BadVaddr` and returns the exception vector to jump to.
+	pub fn enter_exception(&mut self, exception: Exception, pc: Pos, in_delay_slot: bool) -> Pos {
+		self.sr = (self.sr & !0x3f) | ((self.sr << 2) & 0x3f);
+
+		// Note: `CE` is only set for co-processor unusable exceptions
+		let co_error = match exception {
+			Exception::CoUnusable { n } => n & 0x3,
+			_ => 0,
+		};
+		self.cause = (self.cause & Self::CAUSE_PENDING_INTERRUPTS) | (exception.code() << 2) | (co_error << 28);
+
+		// Note: Within a branch delay slot, `EPC` points to the branch, so it's executed again
+		self.epc = match in_delay_slot {
+			true => {
+				self.cause |= Self::CAUSE_BRANCH_DELAY;
+				(pc - 4u32).0
+			},
+			false => pc.0,
+		};
+
+		if let Some(pos) = exception.bad_vaddr() {
+			self.bad_vaddr = pos.0;
+		}
+
+		match self.sr & Self::SR_BOOT_EXCEPTION_VECTORS != 0 {
+			true => Self::EXCEPTION_VECTOR_BOOT,
+			false => Self::EXCEPTION_VECTOR,
+		}
+	}
+
+	/// Returns from an exception (`rfe`).
+	///
+	/// Pops the interrupt enable / kernel mode stack in `SR`.
+	pub fn rfe(&mut self) {
+		self.sr = (self.sr & !0xf) | ((self.sr >> 2) & 0xf);
+	}
+
+	/// Returns if the cache is isolated
+	#[must_use]
+	pub const fn is_cache_isolated(&self) -> bool {
+		self.sr & Self::SR_ISOLATE_CACHE != 0
+	}
+}
@@ -0,0 +1,40 @@
+//! Tests
+
+// Imports
+use super::*;
+
+#[test]
+fn exception_round_trip() {
+	let mut cop0 = Cop0 {
+		sr: 0b0000_0001,
+		cause: 0x0000_0400,
+		..Cop0::default()
+	};
+
+	let vector = cop0.enter_exception(
+		Exception::AddressErrorLoad { pos: Pos(0x8001_0001) },
+		Pos(0x8005_0000),
+		false,
+	);
+	assert_eq!(vector, Cop0::EXCEPTION_VECTOR);
+	assert_eq!(cop0.sr, 0b0000_0100);
+	assert_eq!(cop0.cause, 0x0000_0400 | (0x4 << 2));
+	assert_eq!(cop0.epc, 0x8005_0000);
+	assert_eq!(cop0.bad_vaddr, 0x8001_0001);
+
+	cop0.rfe();
+	assert_eq!(cop0.sr, 0b0000_0001);
+}
+
+#[test]
+fn exception_delay_slot() {
+	let mut cop0 = Cop0 {
+		sr: Cop0::SR_BOOT_EXCEPTION_VECTORS,
+		..Cop0::default()
+	};
+
+	let vector = cop0.enter_exception(Exception::CoUnusable { n: 2 }, Pos(0x8005_0004), true);
+	assert_eq!(vector, Cop0::EXCEPTION_VECTOR_BOOT);
+	assert_eq!(cop0.cause, Cop0::CAUSE_BRANCH_DELAY | (2 << 28) | (0xb << 2));
+	assert_eq!(cop0.epc, 0x8005_0000);
+}
@@ -1,41 +1,36 @@
 //! Errors
 
 // Imports
-use crate::Pos;
+use super::Exception;
 
 /// Executing error
 #[derive(Debug, thiserror::Error)]
 pub enum ExecError {
-	/// Memory address was out of bounds
-	#[error("Memory access for {pos} is out of bounds")]
-	MemoryOutOfBounds {
-		/// Position the instruction tried to access
-		pos: Pos,
-	},
-
-	/// Memory address was unaligned
-	#[error("Memory access for {pos} was unaligned")]
-	MemoryUnalignedAccess {
-		/// Position which is unaligned
-		pos: Pos,
-	},
-
-	/// Unable to decode instruction
-	#[error("Unable to decode instruction")]
-	DecodeInst,
-
-	/// Overflow
-	#[error("Overflow")]
-	Overflow,
+	/// Instruction raised an exception
+	#[error("Instruction raised an exception")]
+	Exception(#[from] Exception),
 
 	/// Attempted to jump while jumping
 	#[error("Cannot jump while jumping")]
 	JumpWhileJumping,
 
-	/// Unknown syscall
-	#[error("Unknown syscall {comment:#x}")]
-	UnknownSys {
-		/// Syscall comment
-		comment: u32,
+	/// Unknown co-processor register
+	#[error("Unknown co-processor {n} register {reg}")]
+	UnknownCoRegister {
+		/// Co-processor number
+		n: u32,
+
+		/// Register
+		reg: u8,
+	},
+
+	/// Unknown co-processor command
+	#[error("Unknown co-processor {n} command {cmd:#x}")]
+	UnknownCoCommand {
+		/// Co-processor number
+		n: u32,
+
+		/// Command
+		cmd: u32,
 	},
 }
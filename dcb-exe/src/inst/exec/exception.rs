@@ -0,0 +1,91 @@
+//! Exceptions
+
+// Imports
+use crate::Pos;
+
+/// Exception raised by an instruction
+#[derive(PartialEq, Eq, Clone, Copy, Debug, thiserror::Error)]
+pub enum Exception {
+	/// Interrupt
+	#[error("Interrupt")]
+	Interrupt,
+
+	/// Address error on a load or instruction fetch
+	#[error("Address error loading from {pos}")]
+	AddressErrorLoad {
+		/// Position accessed
+		pos: Pos,
+	},
+
+	/// Address error on a store
+	#[error("Address error storing to {pos}")]
+	AddressErrorStore {
+		/// Position accessed
+		pos: Pos,
+	},
+
+	/// Bus error on an instruction fetch
+	#[error("Bus error fetching instruction from {pos}")]
+	BusErrorInst {
+		/// Position accessed
+		pos: Pos,
+	},
+
+	/// Bus error on a data load / store
+	#[error("Bus error accessing {pos}")]
+	BusErrorData {
+		/// Position accessed
+		pos: Pos,
+	},
+
+	/// Syscall
+	#[error("Syscall")]
+	Syscall,
+
+	/// Breakpoint
+	#[error("Breakpoint")]
+	Breakpoint,
+
+	/// Reserved instruction
+	#[error("Reserved instruction")]
+	ReservedInst,
+
+	/// Co-processor is unusable
+	#[error("Co-processor {n} is unusable")]
+	CoUnusable {
+		/// Co-processor number
+		n: u32,
+	},
+
+	/// Arithmetic overflow
+	#[error("Overflow")]
+	Overflow,
+}
+
+impl Exception {
+	/// Returns the exception code of this exception, `Cause.ExcCode`
+	#[must_use]
+	pub const fn code(self) -> u32 {
+		match self {
+			Self::Interrupt => 0x0,
+			Self::AddressErrorLoad { .. } => 0x4,
+			Self::AddressErrorStore { .. } => 0x5,
+			Self::BusErrorInst { .. } => 0x6,
+			Self::BusErrorData { .. } => 0x7,
+			Self::Syscall => 0x8,
+			Self::Breakpoint => 0x9,
+			Self::ReservedInst => 0xa,
+			Self::CoUnusable { .. } => 0xb,
+			Self::Overflow => 0xc,
+		}
+	}
+
+	/// Returns the address to store in `BadVaddr`, if any
+	#[must_use]
+	pub const fn bad_vaddr(self) -> Option<Pos> {
+		match self {
+			Self::AddressErrorLoad { pos } | Self::AddressErrorStore { pos } => Some(pos),
+			_ => None,
+		}
+	}
+}
@@ -0,0 +1,747 @@
+//! Geometry transformation engine
+//!
+//! Software implementation of co-processor 2, the `GTE`, which performs
+//! the fixed-point vector and matrix operations used for 3D graphics.
+//!
+//! All commands set `FLAG` on overflow and saturation like the hardware does,
+//! and the perspective division uses the same unsigned newton-raphson
+//! division as the hardware, so results should match bit-for-bit.
+
+// Modules
+#[cfg(test)]
+mod test;
+
+// Imports
+use super::ExecError;
+use int_conv::{SignExtended, Signed, Split, Truncated};
+
+/// Data register `RGBC`
+const RGBC: usize = 6;
+/// Data register `OTZ`
+const OTZ: usize = 7;
+/// Data register `IR0`
+const IR0: usize = 8;
+/// Data register `SXY0`
+const SXY0: usize = 12;
+/// Data register `SXY1`
+const SXY1: usize = 13;
+/// Data register `SXY2`
+const SXY2: usize = 14;
+/// Data register `SZ0`
+const SZ0: usize = 16;
+/// Data register `RGB0`
+const RGB0: usize = 20;
+/// Data register `MAC0`
+const MAC0: usize = 24;
+/// Data register `IRGB`
+const IRGB: usize = 28;
+/// Data register `LZCS`
+const LZCS: usize = 30;
+
+/// Control register `TRX`
+const TR: usize = 5;
+/// Control register `RBK`
+const BK: usize = 13;
+/// Control register `RFC`
+const FC: usize = 21;
+/// Control register `OFX`
+const OFX: usize = 24;
+/// Control register `OFY`
+const OFY: usize = 25;
+/// Control register `H`
+const H: usize = 26;
+/// Control register `DQA`
+const DQA: usize = 27;
+/// Control register `DQB`
+const DQB: usize = 28;
+/// Control register `ZSF3`
+const ZSF3: usize = 29;
+/// Control register `ZSF4`
+const ZSF4: usize = 30;
+/// Control register `FLAG`
+const FLAG: usize = 31;
+
+/// Geometry transformation engine
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct Gte {
+	/// Data registers
+	data: [u32; 32],
+
+	/// Control registers
+	control: [u32; 32],
+}
+
+impl Gte {
+	/// Loads data register `reg`
+	pub fn load_data(&self, reg: u8) -> Result<u32, ExecError> {
+		let idx = usize::from(reg);
+		let value = match reg {
+			// `VZ0..=VZ2` and `IR0..=IR3` are sign-extended
+			1 | 3 | 5 | 8..=11 => self::sign_extend(self.data[idx]),
+
+			// `OTZ` and `SZ0..=SZ3` are zero-extended
+			7 | 16..=19 => self.data[idx] & 0xffff,
+
+			// `SXYP` mirrors `SXY2`
+			15 => self.data[SXY2],
+
+			// `IRGB` and `ORGB` both read `IR1..=IR3` as a 15-bit color
+			28 | 29 => self.orgb(),
+
+			// `LZCR` counts the leading bits of `LZCS`
+			31 => self.lzcr(),
+
+			0..=31 => self.data[idx],
+			_ => return Err(ExecError::UnknownCoRegister { n: 2, reg }),
+		};
+
+		Ok(value)
+	}
+
+	/// Stores `value` in data register `reg`
+	pub fn store_data(&mut self, reg: u8, value: u32) -> Result<(), ExecError> {
+		match reg {
+			// `SXYP` pushes onto the screen coordinate fifo
+			15 => self.push_sxy(value),
+
+			// `IRGB` expands the 15-bit color onto `IR1..=IR3`
+			28 => {
+				self.data[IRGB] = value & 0x7fff;
+				self.data[IR0 + 1] = (value & 0x1f) * 0x80;
+				self.data[IR0 + 2] = ((value >> 5) & 0x1f) * 0x80;
+				self.data[IR0 + 3] = ((value >> 10) & 0x1f) * 0x80;
+			},
+
+			// `ORGB` and `LZCR` are read-only
+			29 | 31 => (),
+
+			0..=31 => self.data[usize::from(reg)] = value,
+			_ => return Err(ExecError::UnknownCoRegister { n: 2, reg }),
+		}
+
+		Ok(())
+	}
+
+	/// Loads control register `reg`
+	pub fn load_control(&self, reg: u8) -> Result<u32, ExecError> {
+		let idx = usize::from(reg);
+		let value = match reg {
+			// `RT33`, `L33`, `LB3`, `H`, `DQA`, `ZSF3` and `ZSF4` are sign-extended.
+			// Note: `H` is unsigned, but is still sign-extended when read due to a hardware bug.
+			4 | 12 | 20 | 26 | 27 | 29 | 30 => self::sign_extend(self.control[idx]),
+
+			0..=31 => self.control[idx],
+			_ => return Err(ExecError::UnknownCoRegister { n: 2, reg }),
+		};
+
+		Ok(value)
+	}
+
+	/// Stores `value` in control register `reg`
+	pub fn store_control(&mut self, reg: u8, value: u32) -> Result<(), ExecError> {
+		match reg {
+			// Only bits `12..=30` of `FLAG` are writable, bit 31 is calculated from the others.
+			31 => {
+				self.control[FLAG] = value & 0x7fff_f000;
+				self.update_flag_error();
+			},
+
+			0..=31 => self.control[usize::from(reg)] = value,
+			_ => return Err(ExecError::UnknownCoRegister { n: 2, reg }),
+		}
+
+		Ok(())
+	}
+
+	/// Executes command `raw`
+	pub fn exec(&mut self, raw: u32) -> Result<(), ExecError> {
+		let cmd = Command::new(raw);
+
+		self.control[FLAG] = 0;
+		match cmd.opcode {
+			0x01 => self.rtps(0, cmd, true),
+			0x06 => self.nclip(),
+			0x0c => self.op(cmd),
+			0x10 => self.dpcs(cmd, self.data[RGBC]),
+			0x11 => self.intpl(cmd),
+			0x12 => self.mvmva(cmd),
+			0x13 => self.ncds(0, cmd),
+			0x14 => self.cdp(cmd),
+			0x16 => {
+				for v in 0..3 {
+					self.ncds(v, cmd);
+				}
+			},
+			0x1b => self.nccs(0, cmd),
+			0x1c => self.cc(cmd),
+			0x1e => self.ncs(0, cmd),
+			0x20 => {
+				for v in 0..3 {
+					self.ncs(v, cmd);
+				}
+			},
+			0x28 => self.sqr(cmd),
+			0x29 => self.dcpl(cmd),
+			0x2a => {
+				// Note: Each iteration uses the color pushed by the previous one
+				for _ in 0..3 {
+					self.dpcs(cmd, self.data[RGB0]);
+				}
+			},
+			0x2d => self.avsz3(),
+			0x2e => self.avsz4(),
+			0x30 => {
+				for v in 0..3 {
+					self.rtps(v, cmd, v == 2);
+				}
+			},
+			0x3d => self.gpf(cmd),
+			0x3e => self.gpl(cmd),
+			0x3f => {
+				for v in 0..3 {
+					self.nccs(v, cmd);
+				}
+			},
+			_ => return Err(ExecError::UnknownCoCommand { n: 2, cmd: raw }),
+		}
+		self.update_flag_error();
+
+		Ok(())
+	}
+}
+
+// Commands
+impl Gte {
+	/// `RTPS`: Perspective transformation of vector `v`
+	fn rtps(&mut self, v: usize, cmd: Command, last: bool) {
+		let rt = self.matrix(0);
+		let tr = self.control_vector(TR);
+		let vector = self.vector(v);
+
+		let mut z = 0;
+		for n in 0..3 {
+			let value = (tr[n] << 12) + self::dot(rt[n], vector);
+			let mac = self.set_mac(n + 1, value, cmd.shift);
+
+			match n {
+				// Note: Due to a hardware bug, the saturation flag of `IR3` is always
+				//       checked against `MAC3 >> 12`, regardless of `sf` and `lm`.
+				2 => {
+					self.set_ir(3, mac, cmd.lm);
+
+					z = self::truncate_44(value) >> 12;
+					self.control[FLAG] &= !(1 << 22);
+					if z != z.clamp(-0x8000, 0x7fff) {
+						self.set_flag(22);
+					}
+				},
+				_ => self.set_ir(n + 1, mac, cmd.lm),
+			}
+		}
+		self.push_sz(z);
+
+		let div = self.divide();
+		let x = self.set_mac0(div * self.ir(1) + i64::from(self.control[OFX].as_signed()));
+		let y = self.set_mac0(div * self.ir(2) + i64::from(self.control[OFY].as_signed()));
+		self.push_sxy_saturated(x >> 16, y >> 16);
+
+		if last {
+			let dqa = self::half(self.control[DQA], false);
+			let dqb = i64::from(self.control[DQB].as_signed());
+			let depth = self.set_mac0(div * dqa + dqb);
+			self.set_ir0(depth >> 12);
+		}
+	}
+
+	/// `NCLIP`: Normal clipping
+	fn nclip(&mut self) {
+		let [(x0, y0), (x1, y1), (x2, y2)] =
+			[SXY0, SXY1, SXY2].map(|reg| (self::half(self.data[reg], false), self::half(self.data[reg], true)));
+
+		self.set_mac0(x0 * y1 + x1 * y2 + x2 * y0 - x0 * y2 - x1 * y0 - x2 * y1);
+	}
+
+	/// `OP`: Outer product of `IR1..=IR3` and the diagonal of the rotation matrix
+	fn op(&mut self, cmd: Command) {
+		let rt = self.matrix(0);
+		let [d1, d2, d3] = [rt[0][0], rt[1][1], rt[2][2]];
+		let [ir1, ir2, ir3] = self.ir_vector();
+
+		self.set_mac_ir(1, ir3 * d2 - ir2 * d3, cmd);
+		self.set_mac_ir(2, ir1 * d3 - ir3 * d1, cmd);
+		self.set_mac_ir(3, ir2 * d1 - ir1 * d2, cmd);
+	}
+
+	/// `DPCS`: Depth cueing of `color`
+	fn dpcs(&mut self, cmd: Command, color: u32) {
+		let macs = self::color_components(color).map(|component| component << 16);
+		self.interpolate(macs, cmd);
+		self.push_color();
+	}
+
+	/// `INTPL`: Interpolation of `IR1..=IR3` and the far color
+	fn intpl(&mut self, cmd: Command) {
+		let macs = self.ir_vector().map(|ir| ir << 12);
+		self.interpolate(macs, cmd);
+		self.push_color();
+	}
+
+	/// `MVMVA`: Multiplies a vector by a matrix and adds a translation vector
+	fn mvmva(&mut self, cmd: Command) {
+		let matrix = self.matrix(cmd.mx);
+		let vector = self.vector(cmd.v);
+
+		match cmd.cv {
+			// Note: Due to a hardware bug, when using the far color, it and the first column
+			//       of the matrix only affect the flags.
+			2 => {
+				let fc = self.control_vector(FC);
+				for n in 0..3 {
+					let value = self.set_mac(n + 1, (fc[n] << 12) + matrix[n][0] * vector[0], cmd.shift);
+					self.set_ir(n + 1, value, false);
+
+					self.set_mac_ir(n + 1, matrix[n][1] * vector[1] + matrix[n][2] * vector[2], cmd);
+				}
+			},
+			cv => {
+				let translation = match cv {
+					0 => self.control_vector(TR),
+					1 => self.control_vector(BK),
+					_ => [0; 3],
+				};
+				self.mul_matrix(matrix, vector, translation, cmd);
+			},
+		}
+	}
+
+	/// `NCDS`: Normal color depth cue of vector `v`
+	fn ncds(&mut self, v: usize, cmd: Command) {
+		self.light(v, cmd);
+		self.interpolate(self.color_ir(), cmd);
+		self.push_color();
+	}
+
+	/// `CDP`: Color depth cue
+	fn cdp(&mut self, cmd: Command) {
+		self.light_color(cmd);
+		self.interpolate(self.color_ir(), cmd);
+		self.push_color();
+	}
+
+	/// `NCCS`: Normal color color of vector `v`
+	fn nccs(&mut self, v: usize, cmd: Command) {
+		self.light(v, cmd);
+		self.cc(cmd);
+	}
+
+	/// `CC`: Color color
+	fn cc(&mut self, cmd: Command) {
+		self.light_color(cmd);
+		for (n, value) in self.color_ir().into_iter().enumerate() {
+			self.set_mac_ir(n + 1, value, cmd);
+		}
+		self.push_color();
+	}
+
+	/// `NCS`: Normal color of vector `v`
+	fn ncs(&mut self, v: usize, cmd: Command) {
+		self.light(v, cmd);
+		self.push_color();
+	}
+
+	/// `SQR`: Square of `IR1..=IR3`
+	fn sqr(&mut self, cmd: Command) {
+		for (n, ir) in self.ir_vector().into_iter().enumerate() {
+			self.set_mac_ir(n + 1, ir * ir, cmd);
+		}
+	}
+
+	/// `DCPL`: Depth cue color light
+	fn dcpl(&mut self, cmd: Command) {
+		self.interpolate(self.color_ir(), cmd);
+		self.push_color();
+	}
+
+	/// `AVSZ3`: Average of 3 z values
+	fn avsz3(&mut self) {
+		let zsf3 = self::half(self.control[ZSF3], false);
+		let value = self.set_mac0(zsf3 * (self.sz(1) + self.sz(2) + self.sz(3)));
+		self.set_otz(value >> 12);
+	}
+
+	/// `AVSZ4`: Average of 4 z values
+	fn avsz4(&mut self) {
+		let zsf4 = self::half(self.control[ZSF4], false);
+		let value = self.set_mac0(zsf4 * (self.sz(0) + self.sz(1) + self.sz(2) + self.sz(3)));
+		self.set_otz(value >> 12);
+	}
+
+	/// `GPF`: General purpose interpolation
+	fn gpf(&mut self, cmd: Command) {
+		let ir0 = self.ir(0);
+		for (n, ir) in self.ir_vector().into_iter().enumerate() {
+			self.set_mac_ir(n + 1, ir0 * ir, cmd);
+		}
+		self.push_color();
+	}
+
+	/// `GPL`: General purpose interpolation with base
+	fn gpl(&mut self, cmd: Command) {
+		let ir0 = self.ir(0);
+		for (n, ir) in self.ir_vector().into_iter().enumerate() {
+			let base = self.mac(n + 1) << cmd.shift;
+			self.set_mac_ir(n + 1, base + ir0 * ir, cmd);
+		}
+		self.push_color();
+	}
+}
+
+// Common steps
+impl Gte {
+	/// Sets `MAC1..=MAC3` and `IR1..=IR3` to `(translation << 12) + matrix * vector`
+	fn mul_matrix(&mut self, matrix: [[i64; 3]; 3], vector: [i64; 3], translation: [i64; 3], cmd: Command) {
+		for n in 0..3 {
+			self.set_mac_ir(n + 1, (translation[n] << 12) + self::dot(matrix[n], vector), cmd);
+		}
+	}
+
+	/// Multiplies vector `v` by the light matrix and then by the light color matrix
+	fn light(&mut self, v: usize, cmd: Command) {
+		let llm = self.matrix(1);
+		let vector = self.vector(v);
+		self.mul_matrix(llm, vector, [0; 3], cmd);
+		self.light_color(cmd);
+	}
+
+	/// Multiplies `IR1..=IR3` by the light color matrix and adds the background color
+	fn light_color(&mut self, cmd: Command) {
+		let lcm = self.matrix(2);
+		let bk = self.control_vector(BK);
+		self.mul_matrix(lcm, self.ir_vector(), bk, cmd);
+	}
+
+	/// Returns `IR1..=IR3` multiplied by the color in `RGBC`
+	fn color_ir(&self) -> [i64; 3] {
+		let color = self::color_components(self.data[RGBC]);
+		let ir = self.ir_vector();
+		[0, 1, 2].map(|n| (color[n] << 4) * ir[n])
+	}
+
+	/// Interpolates between `macs` and the far color using `IR0`.
+	///
+	/// Sets `MAC1..=MAC3` and `IR1..=IR3` to the result.
+	fn interpolate(&mut self, macs: [i64; 3], cmd: Command) {
+		let fc = self.control_vector(FC);
+		for n in 0..3 {
+			let value = self.set_mac(n + 1, (fc[n] << 12) - macs[n], cmd.shift);
+			self.set_ir(n + 1, value, false);
+		}
+
+		let ir0 = self.ir(0);
+		for n in 0..3 {
+			self.set_mac_ir(n + 1, self.ir(n + 1) * ir0 + macs[n], cmd);
+		}
+	}
+
+	/// Performs the perspective division of `H` by `SZ3`
+	fn divide(&mut self) -> i64 {
+		let h = self.control[H] & 0xffff;
+		let sz3 = self.data[SZ0 + 3] & 0xffff;
+
+		// If the result would overflow, saturate it
+		if h >= sz3 * 2 {
+			self.set_flag(17);
+			return 0x1ffff;
+		}
+
+		// Else normalize both values and divide them using newton-raphson
+		let shift = sz3.leading_zeros() - 16;
+		let n = u64::from(h << shift);
+		let d = sz3 << shift;
+		let u = self::unr(((d & 0x7fff) + 0x40) >> 7) + 0x101;
+		let d = (0x0200_0080 - d * u) >> 8;
+		let d = (0x0000_0080 + d * u) >> 8;
+
+		let div = ((n * u64::from(d) + 0x8000) >> 16).min(0x1ffff);
+		i64::from(div.truncated::<u32>())
+	}
+}
+
+// Registers
+impl Gte {
+	/// Returns `IR{n}`
+	fn ir(&self, n: usize) -> i64 {
+		self::half(self.data[IR0 + n], false)
+	}
+
+	/// Returns `IR1..=IR3`
+	fn ir_vector(&self) -> [i64; 3] {
+		[self.ir(1), self.ir(2), self.ir(3)]
+	}
+
+	/// Returns `MAC{n}`
+	fn mac(&self, n: usize) -> i64 {
+		i64::from(self.data[MAC0 + n].as_signed())
+	}
+
+	/// Returns `SZ{n}`
+	fn sz(&self, n: usize) -> i64 {
+		i64::from(self.data[SZ0 + n] & 0xffff)
+	}
+
+	/// Returns vector `v`, where vector 3 is `IR1..=IR3`
+	fn vector(&self, v: usize) -> [i64; 3] {
+		match v {
+			3 => self.ir_vector(),
+			_ => {
+				let xy = self.data[2 * v];
+				let z = self.data[2 * v + 1];
+				[self::half(xy, false), self::half(xy, true), self::half(z, false)]
+			},
+		}
+	}
+
+	/// Returns matrix `mx`, where matrix 0 is the rotation matrix, 1 the light matrix,
+	/// 2 the light color matrix and 3 the garbage matrix used by `MVMVA`.
+	fn matrix(&self, mx: usize) -> [[i64; 3]; 3] {
+		let base = match mx {
+			0 => 0,
+			1 => 8,
+			2 => 16,
+			_ => {
+				let r = i64::from(self.data[RGBC] & 0xff) << 4;
+				let rt13 = self::half(self.control[1], false);
+				let rt22 = self::half(self.control[2], false);
+				return [[-r, r, self.ir(0)], [rt13; 3], [rt22; 3]];
+			},
+		};
+
+		let mut matrix = [[0; 3]; 3];
+		for idx in 0..9 {
+			matrix[idx / 3][idx % 3] = self::half(self.control[base + idx / 2], idx % 2 == 1);
+		}
+		matrix
+	}
+
+	/// Returns the vector in control registers `base..(base + 3)`
+	fn control_vector(&self, base: usize) -> [i64; 3] {
+		[0, 1, 2].map(|n| i64::from(self.control[base + n].as_signed()))
+	}
+
+	/// Returns `ORGB`
+	fn orgb(&self) -> u32 {
+		let [r, g, b] = self.ir_vector().map(|ir| self::truncate((ir / 0x80).clamp(0, 0x1f)));
+		r | (g << 5) | (b << 10)
+	}
+
+	/// Returns `LZCR`
+	fn lzcr(&self) -> u32 {
+		let lzcs = self.data[LZCS];
+		match lzcs.as_signed() < 0 {
+			true => lzcs.leading_ones(),
+			false => lzcs.leading_zeros(),
+		}
+	}
+
+	/// Sets bit `bit` of `FLAG`
+	fn set_flag(&mut self, bit: usize) {
+		self.control[FLAG] |= 1u32 << bit;
+	}
+
+	/// Updates the error bit of `FLAG`
+	fn update_flag_error(&mut self) {
+		let flag = self.control[FLAG] & 0x7fff_ffff;
+		let error = flag & 0x7f87_e000 != 0;
+		self.control[FLAG] = flag | (u32::from(error) << 31);
+	}
+
+	/// Sets `MAC{n}` to the 44-bit `value` shifted by `shift`, returning it.
+	fn set_mac(&mut self, n: usize, value: i64, shift: u32) -> i64 {
+		if value > (1 << 43) - 1 {
+			self.set_flag(31 - n);
+		}
+		if value < -(1 << 43) {
+			self.set_flag(28 - n);
+		}
+
+		let value = self::truncate_44(value) >> shift;
+		self.data[MAC0 + n] = self::truncate(value);
+		value
+	}
+
+	/// Sets `IR{n}` to `value`, saturated
+	fn set_ir(&mut self, n: usize, value: i64, lm: bool) {
+		let min = match lm {
+			true => 0,
+			false => -0x8000,
+		};
+
+		let saturated = value.clamp(min, 0x7fff);
+		if saturated != value {
+			self.set_flag(25 - n);
+		}
+		self.data[IR0 + n] = self::truncate(saturated);
+	}
+
+	/// Sets `MAC{n}` to `value` and `IR{n}` to it saturated
+	fn set_mac_ir(&mut self, n: usize, value: i64, cmd: Command) {
+		let value = self.set_mac(n, value, cmd.shift);
+		self.set_ir(n, value, cmd.lm);
+	}
+
+	/// Sets `MAC0` to `value`, returning it truncated to 32-bits
+	fn set_mac0(&mut self, value: i64) -> i64 {
+		if value > i64::from(i32::MAX) {
+			self.set_flag(16);
+		}
+		if value < i64::from(i32::MIN) {
+			self.set_flag(15);
+		}
+
+		self.data[MAC0] = self::truncate(value);
+		self.mac(0)
+	}
+
+	/// Sets `IR0` to `value`, saturated
+	fn set_ir0(&mut self, value: i64) {
+		let saturated = value.clamp(0, 0x1000);
+		if saturated != value {
+			self.set_flag(12);
+		}
+		self.data[IR0] = self::truncate(saturated);
+	}
+
+	/// Sets `OTZ` to `value`, saturated
+	fn set_otz(&mut self, value: i64) {
+		let saturated = value.clamp(0, 0xffff);
+		if saturated != value {
+			self.set_flag(18);
+		}
+		self.data[OTZ] = self::truncate(saturated);
+	}
+
+	/// Pushes `value`, saturated, onto the `SZ0..=SZ3` fifo
+	fn push_sz(&mut self, value: i64) {
+		let saturated = value.clamp(0, 0xffff);
+		if saturated != value {
+			self.set_flag(18);
+		}
+
+		self.data.copy_within((SZ0 + 1)..(SZ0 + 4), SZ0);
+		self.data[SZ0 + 3] = self::truncate(saturated);
+	}
+
+	/// Pushes `value` onto the `SXY0..=SXY2` fifo
+	fn push_sxy(&mut self, value: u32) {
+		self.data.copy_within(SXY1..=SXY2, SXY0);
+		self.data[SXY2] = value;
+	}
+
+	/// Pushes `x` and `y`, saturated, onto the `SXY0..=SXY2` fifo
+	fn push_sxy_saturated(&mut self, x: i64, y: i64) {
+		let saturated_x = x.clamp(-0x400, 0x3ff);
+		if saturated_x != x {
+			self.set_flag(14);
+		}
+		let saturated_y = y.clamp(-0x400, 0x3ff);
+		if saturated_y != y {
+			self.set_flag(13);
+		}
+
+		self.push_sxy((self::truncate(saturated_x) & 0xffff) | (self::truncate(saturated_y) << 16));
+	}
+
+	/// Pushes `MAC1..=MAC3` as a color onto the `RGB0..=RGB2` fifo
+	fn push_color(&mut self) {
+		// Note: The code is kept from `RGBC`
+		let mut color = self.data[RGBC] & 0xff00_0000;
+		for n in 0..3 {
+			let value = self.mac(n + 1) >> 4;
+			let saturated = value.clamp(0, 0xff);
+			if saturated != value {
+				self.set_flag(21 - n);
+			}
+			color |= self::truncate(saturated) << (8 * n);
+		}
+
+		self.data.copy_within((RGB0 + 1)..(RGB0 + 3), RGB0);
+		self.data[RGB0 + 2] = color;
+	}
+}
+
+/// A gte command
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+struct Command {
+	/// Opcode
+	opcode: u32,
+
+	/// Shift applied to `MAC1..=MAC3`, `sf`
+	shift: u32,
+
+	/// If `IR1..=IR3` saturate to `0..`, instead of `-0x8000..`
+	lm: bool,
+
+	/// `MVMVA` matrix
+	mx: usize,
+
+	/// `MVMVA` vector
+	v: usize,
+
+	/// `MVMVA` translation vector
+	cv: usize,
+}
+
+impl Command {
+	/// Parses a command from it's raw representation
+	fn new(raw: u32) -> Self {
+		let field = |shift: u32| usize::from((raw >> shift).truncated::<u8>() & 0x3);
+
+		Self {
+			opcode: raw & 0x3f,
+			shift: ((raw >> 19) & 0x1) * 12,
+			lm: (raw >> 10) & 0x1 != 0,
+			mx: field(17),
+			v: field(15),
+			cv: field(13),
+		}
+	}
+}
+
+/// Returns the dot product of `lhs` and `rhs`
+fn dot(lhs: [i64; 3], rhs: [i64; 3]) -> i64 {
+	lhs[0] * rhs[0] + lhs[1] * rhs[1] + lhs[2] * rhs[2]
+}
+
+/// Returns the color components of `color`
+fn color_components(color: u32) -> [i64; 3] {
+	[0, 8, 16].map(|shift| i64::from((color >> shift) & 0xff))
+}
+
+/// Returns the low (or high) signed half of `word`
+fn half(word: u32, high: bool) -> i64 {
+	let half = match high {
+		true => word.hi(),
+		false => word.lo(),
+	};
+	i64::from(half.as_signed())
+}
+
+/// Sign-extends the low half of `word`
+fn sign_extend(word: u32) -> u32 {
+	word.lo().as_signed().sign_extended::<i32>().as_unsigned()
+}
+
+/// Truncates `value` to 44-bits, sign-extending it
+const fn truncate_44(value: i64) -> i64 {
+	(value << 20) >> 20
+}
+
+/// Truncates `value` to 32-bits
+fn truncate(value: i64) -> u32 {
+	value.as_unsigned().truncated()
+}
+
+/// Returns entry `idx` of the table used for the perspective division
+const fn unr(idx: u32) -> u32 {
+	((0x40000 / (idx + 0x100) + 1) / 2).saturating_sub(0x101)
+}
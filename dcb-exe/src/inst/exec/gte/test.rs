@@ -0,0 +1,95 @@
+//! Tests
+
+// Imports
+use super::*;
+
+#[test]
+fn lzcr() {
+	let mut gte = Gte::default();
+
+	for (lzcs, lzcr) in [
+		(0, 32),
+		(0xffff_ffff, 32),
+		(0x0000_ffff, 16),
+		(0xff00_0000, 8),
+		(0x8000_0000, 1),
+	] {
+		gte.store_data(30, lzcs).expect("Unable to store `LZCS`");
+		assert_eq!(gte.load_data(31).expect("Unable to load `LZCR`"), lzcr);
+	}
+}
+
+#[test]
+fn irgb_orgb() {
+	let mut gte = Gte::default();
+	gte.store_data(28, 0x7c1f).expect("Unable to store `IRGB`");
+
+	assert_eq!(gte.load_data(9).expect("Unable to load `IR1`"), 0xf80);
+	assert_eq!(gte.load_data(10).expect("Unable to load `IR2`"), 0);
+	assert_eq!(gte.load_data(11).expect("Unable to load `IR3`"), 0xf80);
+	assert_eq!(gte.load_data(29).expect("Unable to load `ORGB`"), 0x7c1f);
+}
+
+#[test]
+fn sxyp_fifo() {
+	let mut gte = Gte::default();
+	for value in 1..=4 {
+		gte.store_data(15, value).expect("Unable to store `SXYP`");
+	}
+
+	let sxy = [12, 13, 14, 15].map(|reg| gte.load_data(reg).expect("Unable to load `SXY`"));
+	assert_eq!(sxy, [2, 3, 4, 4]);
+}
+
+#[test]
+fn nclip() {
+	let mut gte = Gte::default();
+	for sxy in [0x0000_0000, 0x0000_000a, 0x000a_0000] {
+		gte.store_data(15, sxy).expect("Unable to store `SXYP`");
+	}
+
+	gte.exec(0x0140_0006).expect("Unable to execute `NCLIP`");
+	assert_eq!(gte.load_data(24).expect("Unable to load `MAC0`"), 100);
+}
+
+#[test]
+fn rtps() {
+	let mut gte = Gte::default();
+
+	// Identity rotation, translated by `0x100` on the z axis, with `H = 0x100`
+	for (reg, value) in [(0, 0x1000), (2, 0x1000), (4, 0x1000), (7, 0x100), (26, 0x100)] {
+		gte.store_control(reg, value).expect("Unable to store control register");
+	}
+	gte.store_data(0, 0x0020_0010).expect("Unable to store `VXY0`");
+
+	gte.exec(0x0018_0001).expect("Unable to execute `RTPS`");
+	assert_eq!(gte.load_data(19).expect("Unable to load `SZ3`"), 0x100);
+	assert_eq!(gte.load_data(14).expect("Unable to load `SXY2`"), 0x0020_0010);
+	assert_eq!(gte.load_control(31).expect("Unable to load `FLAG`"), 0);
+}
+
+#[test]
+fn avsz3() {
+	let mut gte = Gte::default();
+	for reg in 17..=19 {
+		gte.store_data(reg, 0x100).expect("Unable to store `SZ`");
+	}
+	gte.store_control(29, 0x555).expect("Unable to store `ZSF3`");
+
+	gte.exec(0x0158_002d).expect("Unable to execute `AVSZ3`");
+	assert_eq!(gte.load_data(7).expect("Unable to load `OTZ`"), 0xff);
+}
+
+#[test]
+fn sqr_saturates() {
+	let mut gte = Gte::default();
+	gte.store_data(9, 0x7fff).expect("Unable to store `IR1`");
+
+	gte.exec(0x00a0_0428).expect("Unable to execute `SQR`");
+	assert_eq!(gte.load_data(25).expect("Unable to load `MAC1`"), 0x3fff_0001);
+	assert_eq!(gte.load_data(9).expect("Unable to load `IR1`"), 0x7fff);
+	assert_eq!(
+		gte.load_control(31).expect("Unable to load `FLAG`"),
+		(1 << 31) | (1 << 24)
+	);
+}
@@ -0,0 +1,70 @@
+//! Load delay
+
+// Modules
+#[cfg(test)]
+mod test;
+
+// Imports
+use crate::inst::Register;
+
+/// Target of a load
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum LoadTarget {
+	/// Register
+	Register(Register),
+
+	/// Geometry transformation engine data register
+	GteData(u8),
+}
+
+/// Load delay pipeline
+///
+/// Loads only reach their target after the instruction in their
+/// delay slot executes, unless that instruction writes to it first.
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct LoadDelay {
+	/// Load queued by the current instruction
+	queued: Option<(LoadTarget, u32)>,
+
+	/// Load queued by the previous instruction, in it's delay slot
+	delayed: Option<(LoadTarget, u32)>,
+}
+
+impl LoadDelay {
+	/// Starts executing an instruction
+	///
+	/// Moves the load queued by the previous instruction into the delay slot.
+	pub fn start(&mut self) {
+		self.delayed = self.queued.take();
+	}
+
+	/// Finishes executing an instruction, returning the load that should be applied, if any
+	pub fn finish(&mut self) -> Option<(LoadTarget, u32)> {
+		self.delayed.take()
+	}
+
+	/// Queues a load of `value` into `target`
+	pub fn queue(&mut self, target: LoadTarget, value: u32) {
+		// Note: If the load in the delay slot has the same target, only this load is applied
+		self.cancel(target);
+		self.queued = Some((target, value));
+	}
+
+	/// Cancels the load in the delay slot, if it's into `target`
+	///
+	/// Should be called whenever the current instruction writes to `target`.
+	pub fn cancel(&mut self, target: LoadTarget) {
+		if matches!(self.delayed, Some((delayed_target, _)) if delayed_target == target) {
+			self.delayed = None;
+		}
+	}
+
+	/// Returns the value being loaded into `reg` by the load in the delay slot, if any
+	#[must_use]
+	pub fn delayed_value(&self, reg: Register) -> Option<u32> {
+		match self.delayed {
+			Some((LoadTarget::Register(delayed_reg), value)) if delayed_reg == reg => Some(value),
+			_ => None,
+		}
+	}
+}
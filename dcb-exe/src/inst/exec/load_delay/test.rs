@@ -0,0 +1,130 @@
+//! Tests
+
+// Imports
+use super::*;
+
+/// Executes an instruction that runs `f`, applying any finished load into `regs`
+fn step(loads: &mut LoadDelay, regs: &mut [u32; 32], f: impl FnOnce(&mut LoadDelay, &mut [u32; 32])) {
+	loads.start();
+	f(loads, regs);
+	if let Some((target, value)) = loads.finish() {
+		match target {
+			LoadTarget::Register(reg) => regs[self::idx(reg)] = value,
+			LoadTarget::GteData(_) => panic!("Unexpected gte load"),
+		}
+	}
+}
+
+/// Returns the index of `reg`
+fn idx(reg: Register) -> usize {
+	usize::try_from(reg.idx()).expect("Register index didn't fit into `usize`")
+}
+
+/// Writes `value` into `reg`, cancelling any load into it
+fn write(loads: &mut LoadDelay, regs: &mut [u32; 32], reg: Register, value: u32) {
+	loads.cancel(LoadTarget::Register(reg));
+	regs[self::idx(reg)] = value;
+}
+
+#[test]
+fn delay_slot_reads_old_value() {
+	let mut loads = LoadDelay::default();
+	let mut regs = [0; 32];
+	regs[self::idx(Register::T0)] = 1;
+
+	// `lw $t0, ...`
+	self::step(&mut loads, &mut regs, |loads, _| {
+		loads.queue(LoadTarget::Register(Register::T0), 2)
+	});
+	assert_eq!(regs[self::idx(Register::T0)], 1);
+
+	// Delay slot still sees the old value
+	self::step(&mut loads, &mut regs, |loads, regs| {
+		assert_eq!(loads.delayed_value(Register::T0), Some(2));
+		assert_eq!(regs[self::idx(Register::T0)], 1);
+	});
+
+	// And afterwards the new one
+	assert_eq!(regs[self::idx(Register::T0)], 2);
+	assert_eq!(loads.delayed_value(Register::T0), None);
+}
+
+#[test]
+fn delay_slot_write_cancels_load() {
+	let mut loads = LoadDelay::default();
+	let mut regs = [0; 32];
+
+	// `lw $t0, ...`
+	self::step(&mut loads, &mut regs, |loads, _| {
+		loads.queue(LoadTarget::Register(Register::T0), 2)
+	});
+
+	// `addiu $t0, $zr, 3` in the delay slot
+	self::step(&mut loads, &mut regs, |loads, regs| {
+		self::write(loads, regs, Register::T0, 3);
+	});
+	assert_eq!(regs[self::idx(Register::T0)], 3);
+
+	// And the load never reaches the register
+	self::step(&mut loads, &mut regs, |_, _| ());
+	assert_eq!(regs[self::idx(Register::T0)], 3);
+}
+
+#[test]
+fn delay_slot_write_other_reg() {
+	let mut loads = LoadDelay::default();
+	let mut regs = [0; 32];
+
+	// `lw $t0, ...`, then `addiu $t1, $zr, 3` in the delay slot
+	self::step(&mut loads, &mut regs, |loads, _| {
+		loads.queue(LoadTarget::Register(Register::T0), 2)
+	});
+	self::step(&mut loads, &mut regs, |loads, regs| {
+		self::write(loads, regs, Register::T1, 3);
+	});
+
+	assert_eq!(regs[self::idx(Register::T0)], 2);
+	assert_eq!(regs[self::idx(Register::T1)], 3);
+}
+
+#[test]
+fn back_to_back_loads() {
+	let mut loads = LoadDelay::default();
+	let mut regs = [0; 32];
+
+	// `lw $t0, ...`, then `lw $t0, ...` in the delay slot
+	self::step(&mut loads, &mut regs, |loads, _| {
+		loads.queue(LoadTarget::Register(Register::T0), 1)
+	});
+	self::step(&mut loads, &mut regs, |loads, _| {
+		loads.queue(LoadTarget::Register(Register::T0), 2)
+	});
+
+	// The first load is discarded
+	assert_eq!(regs[self::idx(Register::T0)], 0);
+
+	// And the second one applied after it's own delay slot
+	self::step(&mut loads, &mut regs, |_, regs| {
+		assert_eq!(regs[self::idx(Register::T0)], 0)
+	});
+	assert_eq!(regs[self::idx(Register::T0)], 2);
+}
+
+#[test]
+fn back_to_back_loads_other_reg() {
+	let mut loads = LoadDelay::default();
+	let mut regs = [0; 32];
+
+	// `lw $t0, ...`, then `lw $t1, ...` in the delay slot
+	self::step(&mut loads, &mut regs, |loads, _| {
+		loads.queue(LoadTarget::Register(Register::T0), 1)
+	});
+	self::step(&mut loads, &mut regs, |loads, _| {
+		loads.queue(LoadTarget::Register(Register::T1), 2)
+	});
+	assert_eq!(regs[self::idx(Register::T0)], 1);
+	assert_eq!(regs[self::idx(Register::T1)], 0);
+
+	self::step(&mut loads, &mut regs, |_, _| ());
+	assert_eq!(regs[self::idx(Register::T1)], 2);
+}
@@ -3,62 +3,48 @@
 //! See [`GameFile`] for details
 
 // Modules
+pub mod drives;
 mod error;
 pub mod path;
+#[cfg(test)]
+mod test;
 
 // Exports
-pub use error::{OpenFileError, SwapFilesError};
+pub use drives::{DriveLocation, Drives};
+pub use error::{
+	CheckDrivesError, CopyFileError, GlobError, MoveFileError, NewError, OpenFileError, RepairDriveError,
+	ReplaceFileError, SwapFilesError,
+};
 pub use path::Path;
 
 // Imports
-use dcb_drv::DirEntryKind;
-use std::io;
+use dcb_drv::{fsck::Issue, DirEntryKind, DirPtr, Glob};
+use std::io::{self, Read};
 use zutil::IoSlice;
 
 /// Game file.
+///
+/// The drives are located from the filesystem of the cdrom when created.
 #[derive(PartialEq, Clone, Debug)]
 pub struct GameFile<T> {
 	/// CD-Rom
 	cdrom: T,
-}
 
-// Constants
-impl<T> GameFile<T> {
-	/// `A.DRV` Offset
-	pub const A_OFFSET: u64 = 0xa1000;
-	/// `A.DRV` Size
-	pub const A_SIZE: u64 = 0xa78800;
-	/// `B.DRV` Offset
-	pub const B_OFFSET: u64 = 0xb19800;
-	/// `B.DRV` Size
-	pub const B_SIZE: u64 = 0x17ea800;
-	/// `C.DRV` Offset
-	pub const C_OFFSET: u64 = 0x2304000;
-	/// `C.DRV` Size
-	pub const C_SIZE: u64 = 0xb6c000;
-	/// `E.DRV` Offset
-	pub const E_OFFSET: u64 = 0x2e70000;
-	/// `E.DRV` Size
-	pub const E_SIZE: u64 = 0x1886800;
-	/// `F.DRV` Offset
-	pub const F_OFFSET: u64 = 0x46f6800;
-	/// `F.DRV` Size
-	pub const F_SIZE: u64 = 0xf2f800;
-	/// `G.DRV` Offset
-	pub const G_OFFSET: u64 = 0x5626000;
-	/// `G.DRV` Size
-	pub const G_SIZE: u64 = 0x293000;
-	/// `P.DRV` Offset
-	pub const P_OFFSET: u64 = 0xc000;
-	/// `P.DRV` Size
-	pub const P_SIZE: u64 = 0x95000;
+	/// Drives
+	drives: Drives,
 }
 
 // Constructors
 impl<T: io::Read + io::Seek> GameFile<T> {
-	/// Creates a new game file
-	pub fn new(cdrom: T) -> Self {
-		Self { cdrom }
+	/// Creates a new game file, locating all drives
+	pub fn new(mut cdrom: T) -> Result<Self, NewError> {
+		let drives = Drives::locate(&mut cdrom).map_err(NewError::LocateDrives)?;
+		Ok(Self { cdrom, drives })
+	}
+
+	/// Creates a new game file with already located drives
+	pub fn with_drives(cdrom: T, drives: Drives) -> Self {
+		Self { cdrom, drives }
 	}
 }
 
@@ -68,64 +54,60 @@ impl<T> GameFile<T> {
 	pub fn cdrom(&mut self) -> &mut T {
 		&mut self.cdrom
 	}
+
+	/// Returns the location of all drives
+	pub fn drives(&self) -> &Drives {
+		&self.drives
+	}
 }
 
 // Drive getters
 impl<T: io::Seek> GameFile<T> {
-	/// Returns the `A.DRV` file alongside it's cursor
-	pub fn a_drv(&mut self) -> Result<DriveCursor<&mut T>, io::Error> {
-		match DriveCursor::new_with_offset_len(&mut self.cdrom, Self::A_OFFSET, Self::A_SIZE) {
-			Ok(cursor) => Ok(cursor),
-			Err(err) => Err(err),
-		}
+	/// Returns a drive's cursor given it's letter, ignoring case
+	///
+	/// Returns `None` if the letter is unknown or the drive doesn't exist.
+	pub fn drive(&mut self, drive: char) -> Option<Result<DriveCursor<&mut T>, io::Error>> {
+		let location = self.drives.get(drive.to_ascii_uppercase())?;
+		Some(DriveCursor::new_with_offset_len(
+			&mut self.cdrom,
+			location.offset,
+			location.size,
+		))
 	}
 
-	/// Returns the `B.DRV` file alongside it's cursor
-	pub fn b_drv(&mut self) -> Result<DriveCursor<&mut T>, io::Error> {
-		match DriveCursor::new_with_offset_len(&mut self.cdrom, Self::B_OFFSET, Self::B_SIZE) {
-			Ok(cursor) => Ok(cursor),
-			Err(err) => Err(err),
-		}
+	/// Returns the `A.DRV` file alongside it's cursor, if it exists
+	pub fn a_drv(&mut self) -> Option<Result<DriveCursor<&mut T>, io::Error>> {
+		self.drive('A')
 	}
 
-	/// Returns the `C.DRV` file alongside it's cursor
-	pub fn c_drv(&mut self) -> Result<DriveCursor<&mut T>, io::Error> {
-		match DriveCursor::new_with_offset_len(&mut self.cdrom, Self::C_OFFSET, Self::C_SIZE) {
-			Ok(cursor) => Ok(cursor),
-			Err(err) => Err(err),
-		}
+	/// Returns the `B.DRV` file alongside it's cursor, if it exists
+	pub fn b_drv(&mut self) -> Option<Result<DriveCursor<&mut T>, io::Error>> {
+		self.drive('B')
 	}
 
-	/// Returns the `E.DRV` file alongside it's cursor
-	pub fn e_drv(&mut self) -> Result<DriveCursor<&mut T>, io::Error> {
-		match DriveCursor::new_with_offset_len(&mut self.cdrom, Self::E_OFFSET, Self::E_SIZE) {
-			Ok(cursor) => Ok(cursor),
-			Err(err) => Err(err),
-		}
+	/// Returns the `C.DRV` file alongside it's cursor, if it exists
+	pub fn c_drv(&mut self) -> Option<Result<DriveCursor<&mut T>, io::Error>> {
+		self.drive('C')
 	}
 
-	/// Returns the `F.DRV` file alongside it's cursor
-	pub fn f_drv(&mut self) -> Result<DriveCursor<&mut T>, io::Error> {
-		match DriveCursor::new_with_offset_len(&mut self.cdrom, Self::F_OFFSET, Self::F_SIZE) {
-			Ok(cursor) => Ok(cursor),
-			Err(err) => Err(err),
-		}
+	/// Returns the `E.DRV` file alongside it's cursor, if it exists
+	pub fn e_drv(&mut self) -> Option<Result<DriveCursor<&mut T>, io::Error>> {
+		self.drive('E')
 	}
 
-	/// Returns the `G.DRV` file alongside it's cursor
-	pub fn g_drv(&mut self) -> Result<DriveCursor<&mut T>, io::Error> {
-		match DriveCursor::new_with_offset_len(&mut self.cdrom, Self::G_OFFSET, Self::G_SIZE) {
-			Ok(cursor) => Ok(cursor),
-			Err(err) => Err(err),
-		}
+	/// Returns the `F.DRV` file alongside it's cursor, if it exists
+	pub fn f_drv(&mut self) -> Option<Result<DriveCursor<&mut T>, io::Error>> {
+		self.drive('F')
 	}
 
-	/// Returns the `P.DRV` file alongside it's cursor
-	pub fn p_drv(&mut self) -> Result<DriveCursor<&mut T>, io::Error> {
-		match DriveCursor::new_with_offset_len(&mut self.cdrom, Self::P_OFFSET, Self::P_SIZE) {
-			Ok(cursor) => Ok(cursor),
-			Err(err) => Err(err),
-		}
+	/// Returns the `G.DRV` file alongside it's cursor, if it exists
+	pub fn g_drv(&mut self) -> Option<Result<DriveCursor<&mut T>, io::Error>> {
+		self.drive('G')
+	}
+
+	/// Returns the `P.DRV` file alongside it's cursor, if it exists
+	pub fn p_drv(&mut self) -> Option<Result<DriveCursor<&mut T>, io::Error>> {
+		self.drive('P')
 	}
 }
 
@@ -135,15 +117,9 @@ impl<T: io::Seek + io::Read> GameFile<T> {
 	pub fn open_file(&mut self, path: &Path) -> Result<FileCursor<DriveCursor<&mut T>>, OpenFileError> {
 		// Check the drive we're accessing.
 		let (drive, path) = path.drive().ok_or(OpenFileError::NoDrive)?;
-		let mut cursor = match drive.as_char() {
-			'A' => self.a_drv().map_err(OpenFileError::OpenDrive)?,
-			'B' => self.b_drv().map_err(OpenFileError::OpenDrive)?,
-			'C' => self.c_drv().map_err(OpenFileError::OpenDrive)?,
-			'E' => self.e_drv().map_err(OpenFileError::OpenDrive)?,
-			'F' => self.f_drv().map_err(OpenFileError::OpenDrive)?,
-			'G' => self.g_drv().map_err(OpenFileError::OpenDrive)?,
-			'P' => self.p_drv().map_err(OpenFileError::OpenDrive)?,
-			drive => return Err(OpenFileError::UnknownDrive { drive }),
+		let mut cursor = match self.drive(drive.as_char()) {
+			Some(cursor) => cursor.map_err(OpenFileError::OpenDrive)?,
+			None => return Err(OpenFileError::UnknownDrive { drive: drive.as_char() }),
 		};
 
 		// Then get the entry
@@ -156,34 +132,238 @@ impl<T: io::Seek + io::Read> GameFile<T> {
 			_ => Err(OpenFileError::FoundDir),
 		}
 	}
+
+	/// Returns an iterator over all entries matching a pattern, e.g. `B:\CARD\LC*.TIM`.
+	///
+	/// The paths of all entries are relative to the drive. See [`Glob`] for more details.
+	pub fn glob(&mut self, pattern: &Path) -> Result<Glob<DriveCursor<&mut T>>, GlobError> {
+		// Check the drive we're accessing.
+		let (drive, pattern) = pattern.drive().ok_or(GlobError::NoDrive)?;
+		let cursor = match self.drive(drive.as_char()) {
+			Some(cursor) => cursor.map_err(GlobError::OpenDrive)?,
+			None => return Err(GlobError::UnknownDrive { drive: drive.as_char() }),
+		};
+
+		// Then search it
+		dcb_drv::DirPtr::root().glob(cursor, pattern).map_err(GlobError::Glob)
+	}
+
+	/// Checks all existing drives for issues, returning the issues of each drive
+	pub fn check_drives(&mut self) -> Result<Vec<(char, Vec<Issue>)>, CheckDrivesError> {
+		Drives::LETTERS
+			.iter()
+			.filter_map(|&drive| {
+				let cursor = self.drive(drive)?;
+				let issues = cursor
+					.map_err(|err| CheckDrivesError::OpenDrive(drive, err))
+					.and_then(|mut cursor| {
+						dcb_drv::fsck::check(&mut cursor).map_err(|err| CheckDrivesError::Check(drive, err))
+					});
+				Some(issues.map(|issues| (drive, issues)))
+			})
+			.collect()
+	}
 }
 
 impl<T: io::Seek + io::Read + io::Write> GameFile<T> {
-	/// Swaps two files
+	/// Swaps two files, which may be in different drives.
+	///
+	/// Within the same drive, only the file's entries are swapped.
+	/// Across drives, the contents of each file are replaced with the other's using [`Self::replace_file`].
 	pub fn swap_files(&mut self, lhs: &Path, rhs: &Path) -> Result<(), SwapFilesError> {
 		// Check the drive we're accessing.
 		let (lhs_drive, lhs_path) = lhs.drive().ok_or(SwapFilesError::NoDrive)?;
 		let (rhs_drive, rhs_path) = rhs.drive().ok_or(SwapFilesError::NoDrive)?;
-		let mut cursor = match (lhs_drive.as_char(), rhs_drive.as_char()) {
-			('A', 'A') => self.a_drv().map_err(SwapFilesError::OpenDrive)?,
-			('B', 'B') => self.b_drv().map_err(SwapFilesError::OpenDrive)?,
-			('C', 'C') => self.c_drv().map_err(SwapFilesError::OpenDrive)?,
-			('E', 'E') => self.e_drv().map_err(SwapFilesError::OpenDrive)?,
-			('F', 'F') => self.f_drv().map_err(SwapFilesError::OpenDrive)?,
-			('G', 'G') => self.g_drv().map_err(SwapFilesError::OpenDrive)?,
-			('P', 'P') => self.p_drv().map_err(SwapFilesError::OpenDrive)?,
-			(drive, _) if lhs_drive == rhs_drive => return Err(SwapFilesError::UnknownDrive { drive }),
-			_ => return Err(SwapFilesError::AcrossDrives),
+		if !lhs_drive.eq_ignore_ascii_case(&rhs_drive) {
+			return self.swap_files_across_drives(lhs, rhs);
+		}
+		let mut cursor = match self.drive(lhs_drive.as_char()) {
+			Some(cursor) => cursor.map_err(SwapFilesError::OpenDrive)?,
+			None => {
+				return Err(SwapFilesError::UnknownDrive {
+					drive: lhs_drive.as_char(),
+				})
+			},
 		};
 
 		// Then swap both files
 		dcb_drv::swap_files(&mut cursor, lhs_path, rhs_path).map_err(SwapFilesError::SwapFiles)
 	}
+
+	/// Swaps the contents of two files in different drives
+	fn swap_files_across_drives(&mut self, lhs: &Path, rhs: &Path) -> Result<(), SwapFilesError> {
+		// Read both files
+		// Note: We must read them whole, as both drives share the cdrom.
+		let mut lhs_contents = vec![];
+		self.open_file(lhs)
+			.map_err(SwapFilesError::OpenLhs)?
+			.read_to_end(&mut lhs_contents)
+			.map_err(SwapFilesError::ReadLhs)?;
+		let mut rhs_contents = vec![];
+		self.open_file(rhs)
+			.map_err(SwapFilesError::OpenRhs)?
+			.read_to_end(&mut rhs_contents)
+			.map_err(SwapFilesError::ReadRhs)?;
+
+		// Then replace them with each other
+		self.replace_file(lhs, &mut rhs_contents.as_slice())
+			.map_err(SwapFilesError::ReplaceLhs)?;
+		if let Err(err) = self.replace_file(rhs, &mut lhs_contents.as_slice()) {
+			// Note: `lhs` was only relocated if it's old sectors didn't fit the new contents, so
+			//       they're free again for the old contents.
+			self.replace_file(lhs, &mut lhs_contents.as_slice())
+				.map_err(SwapFilesError::RestoreLhs)?;
+			return Err(SwapFilesError::ReplaceRhs(err));
+		}
+
+		Ok(())
+	}
+
+	/// Replaces the contents of a file, relocating it within it's drive if it grows.
+	pub fn replace_file<R: io::Read>(&mut self, path: &Path, reader: &mut R) -> Result<(), ReplaceFileError> {
+		// Check the drive we're accessing.
+		let (drive, path) = path.drive().ok_or(ReplaceFileError::NoDrive)?;
+		let mut cursor = match self.drive(drive.as_char()) {
+			Some(cursor) => cursor.map_err(ReplaceFileError::OpenDrive)?,
+			None => return Err(ReplaceFileError::UnknownDrive { drive: drive.as_char() }),
+		};
+
+		// Then replace the file
+		dcb_drv::replace_file(&mut cursor, path, reader)
+			.map(|_| ())
+			.map_err(ReplaceFileError::ReplaceFile)
+	}
+
+	/// Copies a file to `dst`, which may be in another drive.
+	///
+	/// `dst` must be the full path of the new file, which must not exist yet.
+	/// Space for it is allocated in the destination drive, and it keeps the date of the source file.
+	///
+	/// As drives can't grow, returns an error if there isn't enough space left in the destination drive.
+	pub fn copy_file(&mut self, src: &Path, dst: &Path) -> Result<(), CopyFileError> {
+		// Read the source file
+		// Note: We must read it whole, as both drives share the cdrom.
+		let (src_drive, src_path) = src.drive().ok_or(CopyFileError::NoDrive)?;
+		let (entry, contents) = {
+			let mut cursor = match self.drive(src_drive.as_char()) {
+				Some(cursor) => cursor.map_err(CopyFileError::OpenDrive)?,
+				None => {
+					return Err(CopyFileError::UnknownDrive {
+						drive: src_drive.as_char(),
+					})
+				},
+			};
+
+			let (_, entry) = DirPtr::root()
+				.find(&mut cursor, src_path)
+				.map_err(CopyFileError::FindSrc)?;
+			let ptr = entry.kind.as_file_ptr().ok_or(CopyFileError::FoundDir)?;
+
+			let mut contents = vec![];
+			ptr.cursor(&mut cursor)
+				.map_err(CopyFileError::OpenSrc)?
+				.read_to_end(&mut contents)
+				.map_err(CopyFileError::ReadSrc)?;
+			(entry, contents)
+		};
+
+		// Then create it in the destination
+		let (dst_drive, dst_path) = dst.drive().ok_or(CopyFileError::NoDrive)?;
+		let (dst_dir_path, dst_name) = dst_path.split_file_name().ok_or(CopyFileError::NoDstName)?;
+		let mut cursor = match self.drive(dst_drive.as_char()) {
+			Some(cursor) => cursor.map_err(CopyFileError::OpenDrive)?,
+			None => {
+				return Err(CopyFileError::UnknownDrive {
+					drive: dst_drive.as_char(),
+				})
+			},
+		};
+		let dst_dir = DirPtr::root()
+			.find_dir(&mut cursor, dst_dir_path)
+			.map_err(CopyFileError::FindDstDir)?;
+
+		// Note: The file and any growth of the destination directory are allocated before anything
+		//       is written, so if the drive is full, nothing is changed.
+		dst_dir
+			.create_file(&mut cursor, dst_name, entry.date, &mut contents.as_slice())
+			.map_err(|err| match err {
+				dcb_drv::ptr::dir::CreateFileError::DriveFull { sectors_len, free_len } => CopyFileError::DriveFull {
+					drive: dst_drive.as_char(),
+					sectors_len,
+					free_len,
+				},
+				err => CopyFileError::CreateDst(err),
+			})?;
+
+		Ok(())
+	}
+
+	/// Moves a file to `dst`, which may be in another drive.
+	///
+	/// Within the same drive, the file's entry is moved, without copying it's contents.
+	/// Across drives, the file is copied with [`Self::copy_file`], and then removed.
+	pub fn move_file(&mut self, src: &Path, dst: &Path) -> Result<(), MoveFileError> {
+		let (src_drive, src_path) = src.drive().ok_or(MoveFileError::NoDrive)?;
+		let (dst_drive, dst_path) = dst.drive().ok_or(MoveFileError::NoDrive)?;
+		match src_drive.eq_ignore_ascii_case(&dst_drive) {
+			true => self.link_file(src_drive.as_char(), src_path, dst_path)?,
+			false => self.copy_file(src, dst).map_err(MoveFileError::Copy)?,
+		}
+
+		// Then remove the source file
+		// Note: We must find it's directory after adding the destination, as it may have been
+		//       relocated if the destination is within it.
+		let (src_dir_path, src_name) = src_path.split_file_name().ok_or(MoveFileError::NoSrcName)?;
+		let mut cursor = self
+			.drive(src_drive.as_char())
+			.expect("Source drive was unknown after adding the destination")
+			.map_err(MoveFileError::OpenDrive)?;
+		DirPtr::root()
+			.find_dir(&mut cursor, src_dir_path)
+			.map_err(MoveFileError::FindSrcDir)?
+			.remove_entry(&mut cursor, src_name)
+			.map_err(MoveFileError::RemoveSrc)?;
+
+		Ok(())
+	}
+
+	/// Adds an entry at `dst` for the file at `src`, both within `drive`, sharing it's sectors
+	fn link_file(&mut self, drive: char, src: &dcb_drv::Path, dst: &dcb_drv::Path) -> Result<(), MoveFileError> {
+		let mut cursor = match self.drive(drive) {
+			Some(cursor) => cursor.map_err(MoveFileError::OpenDrive)?,
+			None => return Err(MoveFileError::UnknownDrive { drive }),
+		};
+
+		let (_, entry) = DirPtr::root().find(&mut cursor, src).map_err(MoveFileError::FindSrc)?;
+		let ptr = entry.kind.as_file_ptr().ok_or(MoveFileError::FoundDir)?;
+
+		let (dst_dir_path, dst_name) = dst.split_file_name().ok_or(MoveFileError::NoDstName)?;
+		DirPtr::root()
+			.find_dir(&mut cursor, dst_dir_path)
+			.map_err(MoveFileError::FindDstDir)?
+			.link_file(&mut cursor, dst_name, entry.date, ptr)
+			.map_err(MoveFileError::LinkDst)?;
+
+		Ok(())
+	}
+
+	/// Repairs all repairable issues of a drive, returning how many were repaired.
+	///
+	/// See [`dcb_drv::fsck::repair`] for details.
+	pub fn repair_drive(&mut self, drive: char, issues: &[Issue]) -> Result<usize, RepairDriveError> {
+		let mut cursor = match self.drive(drive) {
+			Some(cursor) => cursor.map_err(RepairDriveError::OpenDrive)?,
+			None => return Err(RepairDriveError::UnknownDrive { drive }),
+		};
+
+		dcb_drv::fsck::repair(&mut cursor, issues).map_err(RepairDriveError::Repair)
+	}
 }
 
 /// Driver cursor
 pub type DriveCursor<T> = IoSlice<T>;
 
-/// File cursor
-// TODO: Make proper file cursor in `dcb-drv` that allows expanding
+/// File cursor.
+///
+/// Can't write past the end of the file, use [`GameFile::replace_file`] to grow it.
 pub type FileCursor<T> = IoSlice<T>;
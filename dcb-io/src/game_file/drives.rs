@@ -0,0 +1,185 @@
+//! Drive locations
+
+// Modules
+mod error;
+#[cfg(test)]
+mod test;
+
+// Exports
+pub use error::LocateError;
+
+// Imports
+use dcb_bytes::Bytes;
+use dcb_cdrom_xa::{
+	sector::header::{subheader::SubMode, SubHeader},
+	CdRomReader, Sector,
+};
+use dcb_iso9660::{FilesystemReader, FindError};
+use std::{
+	convert::TryFrom,
+	io::{self, SeekFrom},
+};
+
+/// Location of a drive within the cdrom
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct DriveLocation {
+	/// Offset
+	pub offset: u64,
+
+	/// Size
+	pub size: u64,
+}
+
+/// Location of all drives within the cdrom
+///
+/// Drives that weren't found in the filesystem are `None`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Drives {
+	/// `A.DRV`
+	pub a: Option<DriveLocation>,
+
+	/// `B.DRV`
+	pub b: Option<DriveLocation>,
+
+	/// `C.DRV`
+	pub c: Option<DriveLocation>,
+
+	/// `E.DRV`
+	pub e: Option<DriveLocation>,
+
+	/// `F.DRV`
+	pub f: Option<DriveLocation>,
+
+	/// `G.DRV`
+	pub g: Option<DriveLocation>,
+
+	/// `P.DRV`
+	pub p: Option<DriveLocation>,
+}
+
+impl Drives {
+	/// Letters of all drives
+	pub const LETTERS: [char; 7] = ['A', 'B', 'C', 'E', 'F', 'G', 'P'];
+	/// Sector size
+	pub const SECTOR_SIZE: u64 = 0x800;
+
+	/// Locates all drives from the root directory of the filesystem in `cdrom`
+	///
+	/// Any drives not found are logged and left as `None`.
+	pub fn locate<T: io::Read + io::Seek>(cdrom: &mut T) -> Result<Self, LocateError> {
+		let mut cdrom = CdRomReader::new(RawSectors::new(cdrom));
+		let filesystem = FilesystemReader::new(&mut cdrom).map_err(LocateError::ReadFilesystem)?;
+
+		let mut find = |name: &'static str| match filesystem.find(&mut cdrom, name) {
+			Ok(entry) if entry.is_file() => Ok(Some(DriveLocation {
+				offset: u64::from(entry.sector_pos) * Self::SECTOR_SIZE,
+				size:   u64::from(entry.size),
+			})),
+			Ok(_) => {
+				log::warn!("Drive {name} was a directory, ignoring it");
+				Ok(None)
+			},
+			Err(FindError::NotFound { .. }) => {
+				log::warn!("Unable to find drive {name}");
+				Ok(None)
+			},
+			Err(err) => Err(LocateError::FindDrive { name, err }),
+		};
+
+		Ok(Self {
+			a: find("A.DRV")?,
+			b: find("B.DRV")?,
+			c: find("C.DRV")?,
+			e: find("E.DRV")?,
+			f: find("F.DRV")?,
+			g: find("G.DRV")?,
+			p: find("P.DRV")?,
+		})
+	}
+
+	/// Returns the location of a drive given it's letter
+	///
+	/// Returns `None` if the letter is unknown or the drive wasn't found.
+	#[must_use]
+	pub const fn get(&self, drive: char) -> Option<DriveLocation> {
+		match drive {
+			'A' => self.a,
+			'B' => self.b,
+			'C' => self.c,
+			'E' => self.e,
+			'F' => self.f,
+			'G' => self.g,
+			'P' => self.p,
+			_ => None,
+		}
+	}
+}
+
+/// Raw sectors of a cdrom, from a reader of it's sector data.
+///
+/// Rebuilds each raw sector, as a form 1 data sector, so the filesystem
+/// may be read with [`FilesystemReader`].
+struct RawSectors<'a, T> {
+	/// Sector data reader
+	cdrom: &'a mut T,
+
+	/// Current position
+	pos: u64,
+}
+
+impl<'a, T> RawSectors<'a, T> {
+	/// Creates raw sectors from a reader of their data
+	fn new(cdrom: &'a mut T) -> Self {
+		Self { cdrom, pos: 0 }
+	}
+}
+
+impl<'a, T: io::Read + io::Seek> io::Read for RawSectors<'a, T> {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+		// Read the data of the sector we're in
+		let raw_sector_size = CdRomReader::<()>::SECTOR_SIZE;
+		let sector_pos = self.pos / raw_sector_size;
+		let sector_offset =
+			usize::try_from(self.pos % raw_sector_size).expect("Sector offset didn't fit into a `usize`");
+		let mut data = [0; 0x800];
+		self.cdrom.seek(SeekFrom::Start(sector_pos * Drives::SECTOR_SIZE))?;
+		match self.cdrom.read_exact(&mut data) {
+			Ok(()) => (),
+			Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(0),
+			Err(err) => return Err(err),
+		}
+
+		// Then rebuild it and read from it
+		let subheader = SubHeader {
+			submode: SubMode::DATA,
+			..SubHeader::new()
+		};
+		let sector_pos = usize::try_from(sector_pos).expect("Sector position didn't fit into a `usize`");
+		let sector = Sector::new(data, sector_pos, subheader)
+			.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+			.to_bytes()
+			.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+		let len = usize::min(buf.len(), sector.len() - sector_offset);
+		buf[..len].copy_from_slice(&sector[sector_offset..(sector_offset + len)]);
+		self.pos += u64::try_from(len).expect("Length didn't fit into a `u64`");
+
+		Ok(len)
+	}
+}
+
+impl<'a, T> io::Seek for RawSectors<'a, T> {
+	fn seek(&mut self, pos: SeekFrom) -> Result<u64, io::Error> {
+		self.pos = match pos {
+			SeekFrom::Start(pos) => pos,
+			SeekFrom::Current(offset) => zutil::signed_offset(self.pos, offset),
+			SeekFrom::End(_) => {
+				return Err(io::Error::new(
+					io::ErrorKind::Unsupported,
+					"Unable to seek from the end of raw sectors",
+				))
+			},
+		};
+
+		Ok(self.pos)
+	}
+}
@@ -0,0 +1,23 @@
+//! Errors
+
+// Imports
+use dcb_iso9660::{FindError, NewError};
+
+/// Error for [`Drives::locate`](super::Drives::locate)
+#[derive(Debug, thiserror::Error)]
+pub enum LocateError {
+	/// Unable to read filesystem
+	#[error("Unable to read filesystem")]
+	ReadFilesystem(#[source] NewError),
+
+	/// Unable to find drive
+	#[error("Unable to find drive {name}")]
+	FindDrive {
+		/// Drive name
+		name: &'static str,
+
+		/// Underlying error
+		#[source]
+		err: FindError,
+	},
+}
@@ -0,0 +1,139 @@
+//! Tests
+
+// Imports
+use super::*;
+use crate::GameFile;
+use dcb_cdrom_xa::{CdRomCursor, CdRomWriter};
+use dcb_iso9660::{
+	date_time::{DecDateTime, DirDateTime},
+	string::FileString,
+	writer::{DirEntryWriter, DirEntryWriterKind, DirWriterLister, VolumeInfo},
+	FilesystemWriter, StrArrA, StrArrD,
+};
+use std::{convert::Infallible, io::Read};
+
+/// Directory lister over in-memory files
+struct Lister(Vec<DirEntryWriter<Lister>>);
+
+impl DirWriterLister for Lister {
+	type Error = Infallible;
+	type FileReader = io::Cursor<Vec<u8>>;
+}
+
+impl IntoIterator for Lister {
+	type IntoIter = std::iter::Map<std::vec::IntoIter<DirEntryWriter<Self>>, fn(DirEntryWriter<Self>) -> Self::Item>;
+	type Item = Result<DirEntryWriter<Self>, Infallible>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.0.into_iter().map(Ok as fn(_) -> _)
+	}
+}
+
+/// Date used for all entries
+const DATE: DirDateTime = DirDateTime {
+	year:      100,
+	month:     1,
+	day:       1,
+	hour:      0,
+	minutes:   0,
+	seconds:   0,
+	time_zone: 0,
+};
+
+/// Returns the contents of the drive `drive`
+fn drive_contents(drive: char) -> Vec<u8> {
+	let byte = u8::try_from(drive).expect("Drive letter wasn't ascii");
+	vec![byte; 0x800 * usize::from(byte - b'A' + 1)]
+}
+
+/// Creates a cdrom with the drives `drives` in it's root directory
+fn create_cdrom(drives: &[char]) -> CdRomCursor<io::Cursor<Vec<u8>>> {
+	let entries = drives
+		.iter()
+		.map(|&drive| {
+			let contents = self::drive_contents(drive);
+			DirEntryWriter {
+				name:       FileString::from_bytes(format!("{drive}.DRV;1").as_bytes()).expect("Invalid name"),
+				date:       self::DATE,
+				system_use: vec![],
+				sector_pos: None,
+				kind:       DirEntryWriterKind::File {
+					size:       u32::try_from(contents.len()).expect("Drive size didn't fit into a `u32`"),
+					reader:     io::Cursor::new(contents),
+					subheaders: None,
+				},
+			}
+		})
+		.collect();
+
+	let date_time = DecDateTime::deserialize_bytes(b"0000000000000000\0").expect("Unable to create date time");
+	let info = VolumeInfo {
+		system_id:                     StrArrA::from_bytes(&[b' '; 0x20]).expect("Invalid system id"),
+		volume_id:                     StrArrD::from_bytes(&[b' '; 0x20]).expect("Invalid volume id"),
+		volume_set_id:                 StrArrD::from_bytes(&[b' '; 0x80]).expect("Invalid volume set id"),
+		publisher_id:                  StrArrA::from_bytes(&[b' '; 0x80]).expect("Invalid publisher id"),
+		data_preparer_id:              StrArrA::from_bytes(&[b' '; 0x80]).expect("Invalid data preparer id"),
+		application_id:                StrArrA::from_bytes(&[b' '; 0x80]).expect("Invalid application id"),
+		copyright_file_id:             StrArrD::from_bytes(&[b' '; 0x26]).expect("Invalid copyright file id"),
+		abstract_file_id:              StrArrD::from_bytes(&[b' '; 0x24]).expect("Invalid abstract file id"),
+		bibliographic_file_id:         StrArrD::from_bytes(&[b' '; 0x25]).expect("Invalid bibliographic file id"),
+		volume_creation_date_time:     date_time,
+		volume_modification_date_time: date_time,
+		volume_expiration_date_time:   date_time,
+		volume_effective_date_time:    date_time,
+		application_use:               [0; 0x200],
+		root_date:                     self::DATE,
+		root_system_use:               vec![],
+		root_sector_pos:               None,
+		path_table_locations:          None,
+		volume_space_size:             None,
+	};
+
+	// Note: The system area is left empty
+	let mut image = vec![];
+	let mut writer = CdRomWriter::new(&mut image, 0);
+	for _ in 0..FilesystemWriter::<Lister>::PRIMARY_VOLUME_DESCRIPTOR_SECTOR {
+		writer
+			.write_sector([0; 0x800], SubHeader::new())
+			.expect("Unable to write system area");
+	}
+	FilesystemWriter::new(info, Lister(entries))
+		.write(&mut writer)
+		.expect("Unable to write filesystem");
+
+	CdRomCursor::new(io::Cursor::new(image))
+}
+
+#[test]
+fn locate_all() {
+	let mut cdrom = self::create_cdrom(&Drives::LETTERS);
+	let mut game_file = GameFile::new(&mut cdrom).expect("Unable to locate drives");
+
+	for &drive in &Drives::LETTERS {
+		let location = game_file.drives().get(drive).expect("Drive wasn't found");
+		assert_eq!(
+			location.size,
+			u64::try_from(self::drive_contents(drive).len()).expect("Size didn't fit")
+		);
+
+		let mut contents = vec![];
+		game_file
+			.drive(drive)
+			.expect("Drive wasn't found")
+			.expect("Unable to open drive")
+			.read_to_end(&mut contents)
+			.expect("Unable to read drive");
+		assert_eq!(contents, self::drive_contents(drive));
+	}
+}
+
+#[test]
+fn locate_missing() {
+	let mut cdrom = self::create_cdrom(&['A', 'C']);
+	let mut game_file = GameFile::new(&mut cdrom).expect("Unable to locate drives");
+
+	assert!(game_file.drives().get('A').is_some());
+	assert!(game_file.drives().get('B').is_none());
+	assert!(game_file.drives().get('C').is_some());
+	assert!(game_file.drive('B').is_none());
+}
@@ -1,9 +1,17 @@
 //! Errors
 
 // Imports
+use super::drives;
 use dcb_drv::ptr;
 use std::io;
 
+/// Error for [`GameFile::new`](super::GameFile::new)
+#[derive(Debug, thiserror::Error)]
+pub enum NewError {
+	/// Unable to locate drives
+	#[error("Unable to locate drives")]
+	LocateDrives(#[source] drives::LocateError),
+}
 
 /// Error for [`GameFile::open_file`](super::GameFile::open_file)
 #[derive(Debug, thiserror::Error)]
@@ -51,10 +59,6 @@ pub enum SwapFilesError {
 		drive: char,
 	},
 
-	/// Swapping can only be done across the same drive currently
-	#[error("Swapping can only be done across the same drive currently")]
-	AcrossDrives,
-
 	/// Unable to open drive
 	#[error("Unable to open drive")]
 	OpenDrive(#[source] io::Error),
@@ -62,4 +66,224 @@ pub enum SwapFilesError {
 	/// Unable to swap files
 	#[error("Unable to swap files")]
 	SwapFiles(#[source] dcb_drv::swap::SwapFilesError),
+
+	/// Unable to open lhs file
+	#[error("Unable to open lhs file")]
+	OpenLhs(#[source] OpenFileError),
+
+	/// Unable to read lhs file
+	#[error("Unable to read lhs file")]
+	ReadLhs(#[source] io::Error),
+
+	/// Unable to open rhs file
+	#[error("Unable to open rhs file")]
+	OpenRhs(#[source] OpenFileError),
+
+	/// Unable to read rhs file
+	#[error("Unable to read rhs file")]
+	ReadRhs(#[source] io::Error),
+
+	/// Unable to replace lhs file
+	#[error("Unable to replace lhs file")]
+	ReplaceLhs(#[source] ReplaceFileError),
+
+	/// Unable to replace rhs file
+	#[error("Unable to replace rhs file")]
+	ReplaceRhs(#[source] ReplaceFileError),
+
+	/// Unable to restore lhs file after failing to replace rhs file
+	#[error("Unable to restore lhs file after failing to replace rhs file")]
+	RestoreLhs(#[source] ReplaceFileError),
+}
+
+/// Error for [`GameFile::replace_file`](super::GameFile::replace_file)
+#[derive(Debug, thiserror::Error)]
+pub enum ReplaceFileError {
+	/// No drive specified
+	#[error("No drive specified")]
+	NoDrive,
+
+	/// Unknown drive specified
+	#[error("Unknown drive {drive} specified")]
+	UnknownDrive {
+		/// Drive found
+		drive: char,
+	},
+
+	/// Unable to open drive
+	#[error("Unable to open drive")]
+	OpenDrive(#[source] io::Error),
+
+	/// Unable to replace file
+	#[error("Unable to replace file")]
+	ReplaceFile(#[source] dcb_drv::replace::ReplaceFileError),
+}
+
+/// Error for [`GameFile::glob`](super::GameFile::glob)
+#[derive(Debug, thiserror::Error)]
+pub enum GlobError {
+	/// No drive specified
+	#[error("No drive specified")]
+	NoDrive,
+
+	/// Unknown drive specified
+	#[error("Unknown drive {drive} specified")]
+	UnknownDrive {
+		/// Drive found
+		drive: char,
+	},
+
+	/// Unable to open drive
+	#[error("Unable to open drive")]
+	OpenDrive(#[source] io::Error),
+
+	/// Unable to search drive
+	#[error("Unable to search drive")]
+	Glob(#[source] dcb_drv::glob::GlobError),
+}
+
+/// Error for [`GameFile::check_drives`](super::GameFile::check_drives)
+#[derive(Debug, thiserror::Error)]
+pub enum CheckDrivesError {
+	/// Unable to open drive
+	#[error("Unable to open drive {_0}")]
+	OpenDrive(char, #[source] io::Error),
+
+	/// Unable to check drive
+	#[error("Unable to check drive {_0}")]
+	Check(char, #[source] dcb_drv::fsck::CheckError),
+}
+
+/// Error for [`GameFile::repair_drive`](super::GameFile::repair_drive)
+#[derive(Debug, thiserror::Error)]
+pub enum RepairDriveError {
+	/// Unknown drive specified
+	#[error("Unknown drive {drive} specified")]
+	UnknownDrive {
+		/// Drive found
+		drive: char,
+	},
+
+	/// Unable to open drive
+	#[error("Unable to open drive")]
+	OpenDrive(#[source] io::Error),
+
+	/// Unable to repair drive
+	#[error("Unable to repair drive")]
+	Repair(#[source] dcb_drv::fsck::RepairError),
+}
+
+/// Error for [`GameFile::copy_file`](super::GameFile::copy_file)
+#[derive(Debug, thiserror::Error)]
+pub enum CopyFileError {
+	/// No drive specified
+	#[error("No drive specified")]
+	NoDrive,
+
+	/// Unknown drive specified
+	#[error("Unknown drive {drive} specified")]
+	UnknownDrive {
+		/// Drive found
+		drive: char,
+	},
+
+	/// Unable to open drive
+	#[error("Unable to open drive")]
+	OpenDrive(#[source] io::Error),
+
+	/// Unable to find source file
+	#[error("Unable to find source file")]
+	FindSrc(#[source] ptr::dir::FindError),
+
+	/// Found directory
+	#[error("Found directory")]
+	FoundDir,
+
+	/// Unable to open source file
+	#[error("Unable to open source file")]
+	OpenSrc(#[source] ptr::file::FileCursorError),
+
+	/// Unable to read source file
+	#[error("Unable to read source file")]
+	ReadSrc(#[source] io::Error),
+
+	/// Destination path had no file name
+	#[error("Destination path had no file name")]
+	NoDstName,
+
+	/// Unable to find destination directory
+	#[error("Unable to find destination directory")]
+	FindDstDir(#[source] ptr::dir::FindDirError),
+
+	/// Destination drive was full
+	#[error("Drive {drive} was full, {sectors_len} sectors were needed, but only {free_len} were free")]
+	DriveFull {
+		/// Destination drive
+		drive: char,
+
+		/// Number of sectors needed
+		sectors_len: u32,
+
+		/// Number of free sectors
+		free_len: u32,
+	},
+
+	/// Unable to create destination file
+	#[error("Unable to create destination file")]
+	CreateDst(#[source] ptr::dir::CreateFileError),
+}
+
+/// Error for [`GameFile::move_file`](super::GameFile::move_file)
+#[derive(Debug, thiserror::Error)]
+pub enum MoveFileError {
+	/// No drive specified
+	#[error("No drive specified")]
+	NoDrive,
+
+	/// Unknown drive specified
+	#[error("Unknown drive {drive} specified")]
+	UnknownDrive {
+		/// Drive found
+		drive: char,
+	},
+
+	/// Unable to copy file
+	#[error("Unable to copy file")]
+	Copy(#[source] CopyFileError),
+
+	/// Unable to find source file
+	#[error("Unable to find source file")]
+	FindSrc(#[source] ptr::dir::FindError),
+
+	/// Found directory
+	#[error("Found directory")]
+	FoundDir,
+
+	/// Destination path had no file name
+	#[error("Destination path had no file name")]
+	NoDstName,
+
+	/// Unable to find destination directory
+	#[error("Unable to find destination directory")]
+	FindDstDir(#[source] ptr::dir::FindDirError),
+
+	/// Unable to add destination file
+	#[error("Unable to add destination file")]
+	LinkDst(#[source] ptr::dir::LinkFileError),
+
+	/// Source path had no file name
+	#[error("Source path had no file name")]
+	NoSrcName,
+
+	/// Unable to open drive
+	#[error("Unable to open drive")]
+	OpenDrive(#[source] io::Error),
+
+	/// Unable to find source directory
+	#[error("Unable to find source directory")]
+	FindSrcDir(#[source] ptr::dir::FindDirError),
+
+	/// Unable to remove source file
+	#[error("Unable to remove source file")]
+	RemoveSrc(#[source] ptr::dir::RemoveEntryError),
 }
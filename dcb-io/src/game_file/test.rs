@@ -0,0 +1,128 @@
+//! Tests
+
+// Imports
+use super::*;
+use ascii::AsciiStr;
+use chrono::NaiveDateTime;
+
+/// Creates a full drive with the file `name`, with a sector of `byte`, and, if `with_dir`, an empty `DIR` directory
+fn create_drive(name: &str, byte: u8, with_dir: bool) -> Vec<u8> {
+	// Note: One sector for the root directory, the file and, if `with_dir`, the directory.
+	let sectors_len = 2 + usize::from(with_dir);
+	let mut drive = io::Cursor::new(vec![0; sectors_len * 0x800]);
+	let root = DirPtr::root();
+	let date = NaiveDateTime::from_timestamp(0, 0);
+
+	let name = AsciiStr::from_ascii(name).expect("Invalid name");
+	root.create_file(&mut drive, name, date, &mut [byte; 0x800].as_slice())
+		.expect("Unable to create file");
+	if with_dir {
+		let name = AsciiStr::from_ascii("DIR").expect("Invalid name");
+		root.create_dir(&mut drive, name, date)
+			.expect("Unable to create directory");
+	}
+
+	drive.into_inner()
+}
+
+/// Creates a game file with the full drives `A`, with `X.BIN` and `DIR`, and `B`, with `Y.BIN`
+fn create_game_file() -> GameFile<io::Cursor<Vec<u8>>> {
+	let a_drv = self::create_drive("X.BIN", 0xaa, true);
+	let b_drv = self::create_drive("Y.BIN", 0xbb, false);
+
+	let location = |offset: usize, drive: &[u8]| DriveLocation {
+		offset: u64::try_from(offset).expect("Offset didn't fit into a `u64`"),
+		size:   u64::try_from(drive.len()).expect("Size didn't fit into a `u64`"),
+	};
+	let drives = Drives {
+		a: Some(location(0, &a_drv)),
+		b: Some(location(a_drv.len(), &b_drv)),
+		c: None,
+		e: None,
+		f: None,
+		g: None,
+		p: None,
+	};
+
+	GameFile::with_drives(io::Cursor::new([a_drv, b_drv].concat()), drives)
+}
+
+/// Creates a path from `path`
+fn path(path: &str) -> &Path {
+	Path::from_ascii(path).expect("Invalid path")
+}
+
+/// Reads a file from a game file
+fn read_file(game_file: &mut GameFile<io::Cursor<Vec<u8>>>, path: &str) -> Vec<u8> {
+	let mut contents = vec![];
+	game_file
+		.open_file(self::path(path))
+		.expect("Unable to open file")
+		.read_to_end(&mut contents)
+		.expect("Unable to read file");
+	contents
+}
+
+#[test]
+fn copy_drive_full() {
+	let mut game_file = self::create_game_file();
+
+	let is_drive_full = |err: &CopyFileError| {
+		matches!(err, CopyFileError::DriveFull {
+			drive: 'B',
+			sectors_len: 1,
+			free_len: 0,
+		})
+	};
+	let err = game_file
+		.copy_file(self::path("A:\\X.BIN"), self::path("B:\\X.BIN"))
+		.expect_err("File was copied");
+	assert!(is_drive_full(&err));
+	let err = game_file
+		.move_file(self::path("A:\\X.BIN"), self::path("B:\\X.BIN"))
+		.expect_err("File was moved");
+	assert!(matches!(&err, MoveFileError::Copy(err) if is_drive_full(err)));
+
+	// And make sure nothing was changed
+	assert_eq!(self::read_file(&mut game_file, "A:\\X.BIN"), [0xaa; 0x800]);
+	assert!(game_file.open_file(self::path("B:\\X.BIN")).is_err());
+}
+
+#[test]
+fn move_same_drive() {
+	let mut game_file = self::create_game_file();
+
+	// Even though the drive is full, moving within it doesn't need any space
+	game_file
+		.move_file(self::path("A:\\X.BIN"), self::path("A:\\DIR\\Z.BIN"))
+		.expect("Unable to move file");
+	assert!(game_file.open_file(self::path("A:\\X.BIN")).is_err());
+	assert_eq!(self::read_file(&mut game_file, "A:\\DIR\\Z.BIN"), [0xaa; 0x800]);
+
+	// And make sure the file kept it's sectors
+	let mut cursor = game_file
+		.a_drv()
+		.expect("Drive wasn't found")
+		.expect("Unable to open drive");
+	let (_, entry) = DirPtr::root()
+		.find(
+			&mut cursor,
+			dcb_drv::Path::new(AsciiStr::from_ascii("DIR\\Z.BIN").expect("Invalid path")),
+		)
+		.expect("Unable to find file");
+	assert_eq!(entry.kind.as_file_ptr(), Some(FilePtr::new(1, 0x800)));
+}
+
+#[test]
+fn swap_across_drives() {
+	let mut game_file = self::create_game_file();
+	game_file
+		.replace_file(self::path("A:\\X.BIN"), &mut [0xcc; 0x10].as_slice())
+		.expect("Unable to replace file");
+
+	game_file
+		.swap_files(self::path("A:\\X.BIN"), self::path("B:\\Y.BIN"))
+		.expect("Unable to swap files");
+	assert_eq!(self::read_file(&mut game_file, "A:\\X.BIN"), [0xbb; 0x800]);
+	assert_eq!(self::read_file(&mut game_file, "B:\\Y.BIN"), [0xcc; 0x10]);
+}
@@ -129,6 +129,83 @@ impl fmt::Debug for DecDateTime {
 	}
 }
 
+/// Directory record date time
+///
+/// Unlike [`DecDateTime`], this is stored in binary, with no validation.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct DirDateTime {
+	/// Years since 1900
+	pub year: u8,
+
+	/// Month
+	pub month: u8,
+
+	/// Day
+	pub day: u8,
+
+	/// Hour
+	pub hour: u8,
+
+	/// Minute
+	pub minutes: u8,
+
+	/// Second
+	pub seconds: u8,
+
+	/// Time zone
+	pub time_zone: u8,
+}
+
+impl Bytes for DirDateTime {
+	type ByteArray = [u8; 0x7];
+	type DeserializeError = !;
+	type SerializeError = !;
+
+	fn deserialize_bytes(bytes: &Self::ByteArray) -> Result<Self, Self::DeserializeError> {
+		let bytes = zutil::array_split!(bytes,
+			year     : 0x1,
+			month    : 0x1,
+			day      : 0x1,
+			hour     : 0x1,
+			minutes  : 0x1,
+			seconds  : 0x1,
+			time_zone: 0x1,
+		);
+
+		Ok(Self {
+			year:      *bytes.year,
+			month:     *bytes.month,
+			day:       *bytes.day,
+			hour:      *bytes.hour,
+			minutes:   *bytes.minutes,
+			seconds:   *bytes.seconds,
+			time_zone: *bytes.time_zone,
+		})
+	}
+
+	fn serialize_bytes(&self, bytes: &mut Self::ByteArray) -> Result<(), Self::SerializeError> {
+		let bytes = zutil::array_split_mut!(bytes,
+			year     : 0x1,
+			month    : 0x1,
+			day      : 0x1,
+			hour     : 0x1,
+			minutes  : 0x1,
+			seconds  : 0x1,
+			time_zone: 0x1,
+		);
+
+		*bytes.year = self.year;
+		*bytes.month = self.month;
+		*bytes.day = self.day;
+		*bytes.hour = self.hour;
+		*bytes.minutes = self.minutes;
+		*bytes.seconds = self.seconds;
+		*bytes.time_zone = self.time_zone;
+
+		Ok(())
+	}
+}
+
 /// Ensures a decimal encoded string is valid up to a certain value
 #[must_use]
 #[allow(clippy::needless_range_loop)] // We want to index both strings
@@ -1,7 +1,14 @@
 //! A directory
 
+// Modules
+pub mod walk;
+
+// Exports
+pub use walk::{Walk, WalkEntry};
+
 // Imports
-use crate::DirEntry;
+use crate::{entry::FromReaderError, string::FileStrWithoutVersion, DirEntry};
+use std::{convert::TryFrom, io};
 
 /// A directory
 #[derive(PartialEq, Eq, Clone, Debug)]
@@ -18,16 +25,51 @@ impl Dir {
 		}
 	}
 
+	/// Parses a directory from it's bytes.
+	///
+	/// Entries never cross sectors, so each sector is parsed until the padding at it's end.
+	/// The current and parent directory entries are skipped.
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, FromReaderError> {
+		let mut entries = vec![];
+		for sector in bytes.chunks(0x800) {
+			let mut reader = io::Cursor::new(sector);
+			loop {
+				// Note: The padding may be smaller than a record header, so we check for
+				//       it before reading the entry.
+				let pos = usize::try_from(reader.position()).expect("Position didn't fit into a `usize`");
+				if sector.get(pos).map_or(true, |&record_size| record_size == 0) {
+					break;
+				}
+
+				let entry = DirEntry::from_reader(&mut reader)?;
+				if *entry.name != [b'\x00'] && *entry.name != [b'\x01'] {
+					entries.push(entry);
+				}
+			}
+		}
+
+		Ok(Self::new(entries))
+	}
+
 	/// Returns all entries in this directory
 	#[must_use]
 	pub fn entries(&self) -> &[DirEntry] {
 		&self.entries
 	}
 
+	/// Returns all entries in this directory
+	#[must_use]
+	pub fn into_entries(self) -> Vec<DirEntry> {
+		self.entries
+	}
+
 	/// Finds an entry in this directory
+	///
+	/// Files may be found either with or without their version.
 	#[must_use]
 	pub fn find<'a>(&'a self, name: &str) -> Option<&'a DirEntry> {
-		// TODO: Avoid allocation
-		self.entries.iter().find(|entry| entry.name.to_string() == name)
+		self.entries
+			.iter()
+			.find(|entry| entry.name.as_bytes() == name.as_bytes() || entry.name.without_version() == name)
 	}
 }
@@ -0,0 +1,93 @@
+//! Recursive directory iteration
+
+// Imports
+use crate::{entry::ReadDirError, string::FileStrWithoutVersion, DirEntry};
+use dcb_cdrom_xa::CdRomReader;
+use std::{io, vec};
+
+/// Recursive iterator over all entries of a directory.
+///
+/// Entries are returned depth-first, with each directory returned before it's entries.
+#[derive(Debug)]
+pub struct Walk<'a, R> {
+	/// Cd-rom reader
+	cdrom: &'a mut CdRomReader<R>,
+
+	/// All directories being walked, with their path and remaining entries
+	stack: Vec<(String, vec::IntoIter<DirEntry>)>,
+}
+
+impl<'a, R: io::Read + io::Seek> Walk<'a, R> {
+	/// Creates a new iterator over all entries of the directory `entry`
+	pub fn new(cdrom: &'a mut CdRomReader<R>, entry: &DirEntry) -> Result<Self, ReadDirError> {
+		let dir = entry.read_dir(cdrom)?;
+
+		Ok(Self {
+			cdrom,
+			stack: vec![(String::new(), dir.into_entries().into_iter())],
+		})
+	}
+
+	/// Returns the cd-rom reader.
+	///
+	/// May be used to read files while walking.
+	pub fn cdrom(&mut self) -> &mut CdRomReader<R> {
+		self.cdrom
+	}
+}
+
+impl<'a, R: io::Read + io::Seek> Iterator for Walk<'a, R> {
+	type Item = Result<WalkEntry, ReadDirError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			// Get the next entry of the current directory, or go back to it's parent
+			let (dir, entries) = self.stack.last_mut()?;
+			let entry = match entries.next() {
+				Some(entry) => entry,
+				None => {
+					self.stack.pop();
+					continue;
+				},
+			};
+			let entry = WalkEntry {
+				dir: dir.clone(),
+				entry,
+			};
+
+			// If it's a directory, walk it next
+			if entry.entry.is_dir() {
+				match entry.entry.read_dir(self.cdrom) {
+					Ok(dir) => self.stack.push((entry.path(), dir.into_entries().into_iter())),
+					Err(err) => return Some(Err(err)),
+				}
+			}
+
+			return Some(Ok(entry));
+		}
+	}
+}
+
+/// An entry returned by [`Walk`]
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct WalkEntry {
+	/// Path of the directory containing this entry, separated by `/`.
+	///
+	/// Empty for entries in the directory being walked.
+	pub dir: String,
+
+	/// Entry
+	pub entry: DirEntry,
+}
+
+impl WalkEntry {
+	/// Returns the path of this entry, separated by `/`, without it's version
+	#[must_use]
+	pub fn path(&self) -> String {
+		let name = self.entry.name.without_version();
+		match self.dir.is_empty() {
+			true => name.to_owned(),
+			false => format!("{}/{name}", self.dir),
+		}
+	}
+}
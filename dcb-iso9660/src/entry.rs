@@ -3,6 +3,8 @@
 // Modules
 mod error;
 pub mod file;
+#[cfg(test)]
+mod test;
 
 // Exports
 pub use error::{FromReaderError, ReadDirError, ReadFileError, ToWriterError};
@@ -10,8 +12,9 @@ pub use file::FileReader;
 
 // Imports
 use super::string::FileString;
-use crate::Dir;
+use crate::{date_time::DirDateTime, Dir};
 use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use dcb_bytes::Bytes;
 use dcb_cdrom_xa::CdRomReader;
 use std::{
 	convert::{TryFrom, TryInto},
@@ -32,6 +35,14 @@ pub struct DirEntry {
 
 	/// Entry flags
 	pub flags: Flags,
+
+	/// Entry date
+	pub date: DirDateTime,
+
+	/// System use area
+	///
+	/// On CD-ROM/XA discs this holds the XA attributes of the entry.
+	pub system_use: Vec<u8>,
 }
 
 bitflags::bitflags! {
@@ -46,6 +57,20 @@ bitflags::bitflags! {
 }
 
 impl DirEntry {
+	/// Returns the size of this entry's record, in bytes
+	#[must_use]
+	pub fn record_size(&self) -> usize {
+		Self::record_size_of(self.name.len(), self.system_use.len())
+	}
+
+	/// Returns the size of a record given it's name and system use lengths, in bytes
+	///
+	/// Note: Names with an even length get a padding byte after them.
+	#[must_use]
+	pub const fn record_size_of(name_len: usize, system_use_len: usize) -> usize {
+		0x21 + name_len + (1 - name_len % 2) + system_use_len
+	}
+
 	/// Returns if this entry is a directory
 	#[must_use]
 	pub const fn is_dir(&self) -> bool {
@@ -83,37 +108,21 @@ impl DirEntry {
 			return Err(ReadDirError::NotADirectory);
 		}
 
-		// We don't currently support directories larger than a sector
+		// Read all sectors of the directory
 		let size = usize::try_from(self.size).expect("Directory size didn't fit into a `usize`");
-		if size > 2048 {
-			todo!("Directory sizes larger than a sector are not supported yet.");
+		let sectors_len = u64::from(self.size / 0x800 + u32::from(self.size % 0x800 != 0));
+		let mut bytes = Vec::with_capacity(size);
+		cdrom
+			.seek_sector(u64::from(self.sector_pos))
+			.map_err(ReadDirError::SeekSector)?;
+		for _ in 0..sectors_len {
+			let sector = cdrom.read_sector().map_err(ReadDirError::ReadSector)?;
+			let data = sector.data.as_form1().ok_or(ReadDirError::DirSectorWrongForm)?;
+			bytes.extend_from_slice(data);
 		}
 
-		// Read the sector
-		let sector = cdrom
-			.read_nth_sector(u64::from(self.sector_pos))
-			.map_err(ReadDirError::ReadSector)?;
-
-		// Then keep parsing until we run out.
-		let data = sector.data.as_form1().ok_or(ReadDirError::DirSectorWrongForm)?;
-		let mut bytes = std::io::Cursor::new(&data[..size]);
-		let dirs = std::iter::from_fn(move || match Self::from_reader(&mut bytes) {
-			// Note: If it fails due to the record size being 0, return None
-			Err(FromReaderError::RecordSizeTooSmall(0)) => None,
-			Ok(entry) => {
-				// If it's the current or parent directory, skip
-				if *entry.name == [b'\x00'] || *entry.name == [b'\x01'] {
-					Some(Ok(None))
-				} else {
-					Some(Ok(Some(entry)))
-				}
-			},
-			Err(err) => Some(Err(ReadDirError::ParseEntry(err))),
-		})
-		.filter_map(Result::transpose)
-		.collect::<Result<Vec<_>, _>>()?;
-
-		Ok(Dir::new(dirs))
+		// Then parse all entries
+		Dir::from_bytes(&bytes[..size]).map_err(ReadDirError::ParseEntry)
 	}
 }
 
@@ -158,17 +167,23 @@ impl DirEntry {
 		reader.read_exact(&mut name_bytes).map_err(FromReaderError::ReadName)?;
 		let name = FileString::from_bytes(&name_bytes).map_err(FromReaderError::ParseName)?;
 
-		// Then skip the remaining bytes
-		let mut remaining = vec![0; usize::from(header_bytes.record_size - 0x21 - header_bytes.name_len)];
+		// Then read the remaining bytes, skipping the padding after the name, if any.
+		let mut remaining = vec![0; usize::from(*header_bytes.record_size - 0x21 - *header_bytes.name_len)];
 		reader
 			.read_exact(&mut remaining)
 			.map_err(FromReaderError::ReadRemaining)?;
+		let system_use = match header_bytes.name_len % 2 {
+			0 if !remaining.is_empty() => remaining.split_off(1),
+			_ => remaining,
+		};
 
 		Ok(Self {
 			name,
 			sector_pos: LittleEndian::read_u32(header_bytes.extent_location_lsb),
 			size: LittleEndian::read_u32(header_bytes.extent_size_lsb),
 			flags: Flags::from_bits(*header_bytes.file_flags).ok_or(FromReaderError::InvalidFlags)?,
+			date: DirDateTime::deserialize_bytes(header_bytes.recording_date_time).into_ok(),
+			system_use,
 		})
 	}
 
@@ -193,24 +208,38 @@ impl DirEntry {
 		);
 
 		// Fill the header
-		*header.record_size = (0x21 + self.name.len()).try_into().expect("Name was too large");
+		*header.record_size = self
+			.record_size()
+			.try_into()
+			.map_err(|_| ToWriterError::RecordTooLarge)?;
 		*header.extended_attribute_record_len = 0;
 		LittleEndian::write_u32(header.extent_location_lsb, self.sector_pos);
-		BigEndian::write_u32(header.extent_location_lsb, self.sector_pos);
+		BigEndian::write_u32(header.extent_location_msb, self.sector_pos);
 		LittleEndian::write_u32(header.extent_size_lsb, self.size);
 		BigEndian::write_u32(header.extent_size_msb, self.size);
+		self.date.serialize_bytes(header.recording_date_time).into_ok();
 		*header.file_flags = self.flags.bits();
 		*header.file_unit_size = 0;
 		*header.interleave_gap_size = 0;
-		*header.name_len = *header.record_size - 0x21;
+		LittleEndian::write_u16(header.volume_sequence_number_lsb, 1);
+		BigEndian::write_u16(header.volume_sequence_number_msb, 1);
+		*header.name_len = self.name.len().try_into().map_err(|_| ToWriterError::RecordTooLarge)?;
 
 		// Write the header
 		writer.write_all(&header_bytes).map_err(ToWriterError::WriteHeader)?;
 
-		// Then write the name
+		// Then write the name and pad it, if it's length is even
 		writer
 			.write_all(self.name.as_bytes())
 			.map_err(ToWriterError::WriteName)?;
+		if self.name.len() % 2 == 0 {
+			writer.write_all(&[0]).map_err(ToWriterError::WriteName)?;
+		}
+
+		// And finally the system use area
+		writer
+			.write_all(&self.system_use)
+			.map_err(ToWriterError::WriteSystemUse)?;
 
 		Ok(())
 	}
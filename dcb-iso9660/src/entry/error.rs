@@ -2,7 +2,7 @@
 
 // Imports
 use crate::string;
-use dcb_cdrom_xa::reader::{ReadNthSectorError, SeekSectorError};
+use dcb_cdrom_xa::reader::{ReadSectorError, SeekSectorError};
 use std::io;
 
 /// Error type for [`Bytes::deserialize_bytes`](dcb_bytes::Bytes::deserialize_bytes)
@@ -37,6 +37,10 @@ pub enum FromReaderError {
 /// Error type for [`DirEntry::to_writer`](super::DirEntry::to_writer)
 #[derive(Debug, thiserror::Error)]
 pub enum ToWriterError {
+	/// Record was too large
+	#[error("Record was too large")]
+	RecordTooLarge,
+
 	/// Unable to write header
 	#[error("Unable to write header")]
 	WriteHeader(#[source] io::Error),
@@ -44,6 +48,10 @@ pub enum ToWriterError {
 	/// Unable to write name
 	#[error("Unable to write name")]
 	WriteName(#[source] io::Error),
+
+	/// Unable to write system use area
+	#[error("Unable to write system use area")]
+	WriteSystemUse(#[source] io::Error),
 }
 
 
@@ -66,9 +74,13 @@ pub enum ReadDirError {
 	#[error("Not a directory")]
 	NotADirectory,
 
+	/// Unable to seek to sector
+	#[error("Unable to seek to sector")]
+	SeekSector(#[source] SeekSectorError),
+
 	/// Unable to read sector
 	#[error("Unable to read sector")]
-	ReadSector(#[source] ReadNthSectorError),
+	ReadSector(#[source] ReadSectorError),
 
 	/// Unable to parse an entry
 	#[error("Unable to parse an entry")]
@@ -1,8 +1,23 @@
 //! File reader
+//!
+//! Files are read sector by sector, exposing the data of each sector depending on it's form.
+//! Form 1 sectors have `0x800` bytes of data, while form 2 sectors, such as those of streamed
+//! audio and video, have `0x914` bytes.
+//!
+//! As the size recorded for a file always counts `0x800` bytes per sector, the length of files
+//! with form 2 sectors is larger than their recorded size. The form of each sector is read lazily,
+//! as they are seeked over.
 
 // Imports
-use dcb_cdrom_xa::{reader::ReadSectorError, CdRomReader, Sector};
-use std::{convert::TryFrom, io};
+use dcb_cdrom_xa::{
+	reader::ReadSectorError,
+	sector::{header::subheader::SubMode, Form},
+	CdRomReader, Sector,
+};
+use std::{
+	convert::TryFrom,
+	io::{self, Read, Seek},
+};
 
 /// A file reader
 #[derive(PartialEq, Eq, Debug)]
@@ -13,12 +28,22 @@ pub struct FileReader<'a, R> {
 	/// File sector
 	sector_pos: u64,
 
-	/// File size
+	/// File size, as recorded
 	size: u64,
 
+	/// Position of the data of each sector whose form was read so far, followed by
+	/// the end of the last one.
+	sector_offsets: Vec<u64>,
+
 	/// Current position in the file
 	cur_pos: u64,
 
+	/// Current sector
+	cur_sector: u64,
+
+	/// Current position in the current sector
+	cur_sector_pos: u64,
+
 	/// Last cached sector
 	///
 	/// Note: Flushed on seeks.
@@ -35,21 +60,62 @@ impl<'a, R> FileReader<'a, R> {
 			cdrom,
 			sector_pos,
 			size,
+			sector_offsets: vec![0],
 			cur_pos: 0,
+			cur_sector: 0,
+			cur_sector_pos: 0,
 			cached: None,
 		}
 	}
 
-	/// Returns the file size
+	/// Returns the file size, as recorded.
+	///
+	/// Note: Form 2 sectors have more data than is recorded, see the module documentation for details.
 	#[must_use]
 	pub const fn size(&self) -> u64 {
 		self.size
 	}
 
-	/// Returns the remaining bytes from this file
-	#[must_use]
-	pub const fn remaining(&self) -> u64 {
-		self.size - self.cur_pos
+	/// Returns the number of sectors of this file
+	fn sectors_len(&self) -> u64 {
+		self.size / 0x800 + u64::from(self.size % 0x800 != 0)
+	}
+}
+
+impl<'a, R: io::Read + io::Seek> FileReader<'a, R> {
+	/// Reads the form of sectors until the data at `pos` is within a sector whose
+	/// form is known, or until the end of the file.
+	fn read_forms(&mut self, pos: u64) -> Result<(), io::Error> {
+		let sectors_len = self.sectors_len();
+		let is_done = |sector_offsets: &[u64]| {
+			let indexed_len = u64::try_from(sector_offsets.len() - 1).expect("Sector count didn't fit into a `u64`");
+			indexed_len >= sectors_len || sector_offsets.last().map_or(false, |&end| end > pos)
+		};
+		if is_done(&self.sector_offsets) {
+			return Ok(());
+		}
+
+		// Note: We restore the position after, so any cached sector stays valid
+		let reader = self.cdrom.reader_mut();
+		let prev_pos = reader.stream_position()?;
+		while !is_done(&self.sector_offsets) {
+			let sector_idx =
+				u64::try_from(self.sector_offsets.len() - 1).expect("Sector index didn't fit into a `u64`");
+			let reader = self.cdrom.reader_mut();
+			reader.seek(io::SeekFrom::Start(
+				(self.sector_pos + sector_idx) * CdRomReader::<R>::SECTOR_SIZE + 0x12,
+			))?;
+			let mut submode = [0; 1];
+			reader.read_exact(&mut submode)?;
+
+			let form = Form::from_submode(SubMode::from_bits_truncate(submode[0]));
+			let end = self.sector_offsets.last().expect("Sector offsets were empty") +
+				self::sector_len(self.size, sector_idx, form);
+			self.sector_offsets.push(end);
+		}
+		self.cdrom.reader_mut().seek(io::SeekFrom::Start(prev_pos))?;
+
+		Ok(())
 	}
 }
 
@@ -71,68 +137,82 @@ impl<'a, R: io::Read> FileReader<'a, R> {
 	}
 }
 
-
 impl<'a, R: io::Read> io::Read for FileReader<'a, R> {
-	fn read(&mut self, mut buf: &mut [u8]) -> io::Result<usize> {
-		// If buffer would go past the end of the file, cut it.
-		let remaining_file_bytes =
-			usize::try_from(self.remaining()).expect("Unable to get remaining file bytes as `usize`");
-		if buf.len() > remaining_file_bytes {
-			buf = &mut buf[..remaining_file_bytes];
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		// If we're past the last sector, we're at the end of the file
+		let (cur_sector, cur_sector_pos) = (self.cur_sector, self.cur_sector_pos);
+		if cur_sector >= self.sectors_len() {
+			return Ok(0);
 		}
 
-		// Get the sector in cache
-		let cur_pos = self.cur_pos;
+		// Get the sector in cache and it's data
+		let size = self.size;
 		let sector = self.cached()?;
-		let sector_data = sector
-			.data
-			.as_form1()
-			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Sector must be in form 1"))?;
-
-		// If we have enough bytes remaining in it to fill the buffer, read and return
-		let pos_in_sector = usize::try_from(cur_pos % 2048).expect("Unable to get current sector position as `usize`");
-		let remaining_sector_bytes = 2048 - pos_in_sector;
-		if buf.len() < remaining_sector_bytes {
-			buf.copy_from_slice(&sector_data[pos_in_sector..pos_in_sector + buf.len()]);
-			self.cur_pos += u64::try_from(buf.len()).expect("Buffer size didn't fit into `u64`");
-			return Ok(buf.len());
+		let sector_len = self::sector_len(size, cur_sector, sector.data.form());
+		let data_start = usize::try_from(cur_sector_pos).expect("Sector position didn't fit into a `usize`");
+		let data_end = usize::try_from(sector_len).expect("Sector length didn't fit into a `usize`");
+		let sector_data = &sector.data.as_ref()[data_start..data_end];
+
+		// Then read as much as we can from it
+		let len = usize::min(buf.len(), sector_data.len());
+		buf[..len].copy_from_slice(&sector_data[..len]);
+		let len_u64 = u64::try_from(len).expect("Buffer size didn't fit into `u64`");
+		self.cur_pos += len_u64;
+		self.cur_sector_pos += len_u64;
+
+		// And go to the next sector if we read all of it
+		if self.cur_sector_pos == sector_len {
+			self.cur_sector += 1;
+			self.cur_sector_pos = 0;
+			self.cached = None;
 		}
 
-		// Else write all we have currently, clear our buffer and return
-		// Note: This also covers the case in which we have just enough bytes to
-		//       fill it.
-		buf[..remaining_sector_bytes].copy_from_slice(&sector_data[pos_in_sector..]);
-		self.cur_pos += u64::try_from(remaining_sector_bytes).expect("Unable to get remaining sector bytes as `u64`");
-		self.cached = None;
-		Ok(remaining_sector_bytes)
+		Ok(len)
 	}
 }
 
-impl<'a, R: io::Seek> io::Seek for FileReader<'a, R> {
+impl<'a, R: io::Read + io::Seek> io::Seek for FileReader<'a, R> {
 	fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
 		// Get the position
+		// Note: Seeking before the start results in going to the start, and past the end to the end.
 		let next_pos = match pos {
-			// Seeking past the end results in going to the end
-			io::SeekFrom::Start(pos) => pos.min(self.size),
-
-			// Seeking before start results in going to start
-			// Note: We're fine with an overflow if `pos == i64::MIN`.
-			io::SeekFrom::End(pos) => zutil::saturating_signed_offset(self.size, -pos).clamp(0, self.size),
-
-			// Both combined
-			io::SeekFrom::Current(pos) => zutil::saturating_signed_offset(self.cur_pos, pos).clamp(0, self.size),
+			io::SeekFrom::Start(pos) => pos,
+			io::SeekFrom::End(offset) => {
+				self.read_forms(u64::MAX)?;
+				let len = *self.sector_offsets.last().expect("Sector offsets were empty");
+				zutil::saturating_signed_offset(len, offset)
+			},
+			io::SeekFrom::Current(offset) => zutil::saturating_signed_offset(self.cur_pos, offset),
 		};
 
+		// Then find the sector we end up in
+		self.read_forms(next_pos)?;
+		let len = *self.sector_offsets.last().expect("Sector offsets were empty");
+		let next_pos = next_pos.min(len);
+		let next_sector = self.sector_offsets.partition_point(|&offset| offset <= next_pos) - 1;
+		let next_sector_pos = next_pos - self.sector_offsets[next_sector];
+		let next_sector = u64::try_from(next_sector).expect("Sector index didn't fit into a `u64`");
+
 		// If we don't end up in the same sector, flush our sector and seek to the next sector
-		if next_pos / 2048 != self.cur_pos / 2048 {
+		if next_sector != self.cur_sector {
 			self.cached = None;
 			self.cdrom
-				.seek_sector(self.sector_pos + next_pos / 2048)
+				.seek_sector(self.sector_pos + next_sector)
 				.map_err(|err| err.err)?;
 		}
 
 		// And set our position
 		self.cur_pos = next_pos;
+		self.cur_sector = next_sector;
+		self.cur_sector_pos = next_sector_pos;
 		Ok(self.cur_pos)
 	}
 }
+
+/// Returns the data length of the `sector_idx`th sector of a file with size `size`, given it's form
+pub(crate) fn sector_len(size: u64, sector_idx: u64, form: Form) -> u64 {
+	match form {
+		Form::Form1 => u64::min(size - sector_idx * 0x800, 0x800),
+		Form::Form2 => 0x914,
+	}
+}
@@ -0,0 +1,105 @@
+//! Tests
+
+// Imports
+use super::*;
+use dcb_cdrom_xa::{
+	sector::{header::SubHeader, Data},
+	CdRomWriter,
+};
+use std::io::{Read, Seek};
+
+/// Date used for all entries
+const DATE: DirDateTime = DirDateTime {
+	year:      100,
+	month:     1,
+	day:       1,
+	hour:      0,
+	minutes:   0,
+	seconds:   0,
+	time_zone: 0,
+};
+
+/// Creates an entry
+fn entry(name: &str, sector_pos: u32, size: u32, flags: Flags) -> DirEntry {
+	DirEntry {
+		name: FileString::from_bytes(name.as_bytes()).expect("Invalid name"),
+		sector_pos,
+		size,
+		flags,
+		date: self::DATE,
+		system_use: vec![],
+	}
+}
+
+#[test]
+fn read_dir_multiple_sectors() {
+	// Create the records of a directory at sector 0, with enough files to span 2 sectors
+	let files = (0..60)
+		.map(|idx| self::entry(&format!("FILE{idx:02}.BIN;1"), 10 + idx, 0x800, Flags::empty()))
+		.collect::<Vec<_>>();
+	let dir = self::entry("\x00", 0, 0x1000, Flags::DIR);
+	let records = vec![
+		self::entry("\x00", 0, 0x1000, Flags::DIR),
+		self::entry("\x01", 0, 0x1000, Flags::DIR),
+	]
+	.into_iter()
+	.chain(files.iter().cloned());
+
+	// Write them, without letting any record cross a sector
+	let mut dir_bytes = io::Cursor::new(vec![0; 0x1000]);
+	for record in records {
+		let pos = usize::try_from(dir_bytes.position()).expect("Position didn't fit into a `usize`");
+		if pos % 0x800 + record.record_size() > 0x800 {
+			dir_bytes.set_position(u64::try_from(pos + 0x800 - pos % 0x800).expect("Position didn't fit into a `u64`"));
+		}
+		record.to_writer(&mut dir_bytes).expect("Unable to write record");
+	}
+	let dir_bytes = dir_bytes.into_inner();
+	assert_ne!(dir_bytes[0x800], 0, "Directory didn't span 2 sectors");
+
+	let mut image = vec![];
+	let mut writer = CdRomWriter::new(&mut image, 0);
+	for data in dir_bytes.chunks(0x800) {
+		let data = <[u8; 0x800]>::try_from(data).expect("Sector data size was wrong");
+		writer
+			.write_sector(data, SubHeader::new())
+			.expect("Unable to write sector");
+	}
+
+	// Then read it back
+	let mut cdrom = CdRomReader::new(io::Cursor::new(image));
+	let read = dir.read_dir(&mut cdrom).expect("Unable to read directory");
+	assert_eq!(read.entries(), files);
+}
+
+#[test]
+fn read_file_mixed_forms() {
+	// Write a file with a form 2 sector between form 1 sectors, with the last one incomplete
+	let data: [Data; 3] = [[0x12; 0x800].into(), [0x34; 0x914].into(), [0x56; 0x800].into()];
+	let mut image = vec![];
+	let mut writer = CdRomWriter::new(&mut image, 0);
+	for data in &data {
+		writer
+			.write_sector(data.clone(), SubHeader::new())
+			.expect("Unable to write sector");
+	}
+	let file = self::entry("FILE.STR;1", 0, 3 * 0x800 - 0x100, Flags::empty());
+
+	// Then make sure we read all of the form 2 sector
+	let mut cdrom = CdRomReader::new(io::Cursor::new(image));
+	let mut reader = file.read_file(&mut cdrom).expect("Unable to read file");
+	let mut contents = vec![];
+	reader.read_to_end(&mut contents).expect("Unable to read file");
+	let expected = [&data[0].as_ref()[..], &data[1].as_ref()[..], &data[2].as_ref()[..0x700]].concat();
+	assert_eq!(contents, expected);
+
+	// And that we can seek within it
+	assert_eq!(
+		reader.seek(io::SeekFrom::End(-0x10)).expect("Unable to seek"),
+		0x800 + 0x914 + 0x6f0
+	);
+	assert_eq!(reader.seek(io::SeekFrom::Start(0x900)).expect("Unable to seek"), 0x900);
+	let mut bytes = [0; 0x20];
+	reader.read_exact(&mut bytes).expect("Unable to read file");
+	assert_eq!(bytes, [0x34; 0x20]);
+}
@@ -1,7 +1,7 @@
 //! Errors
 
 // Imports
-use super::volume_descriptor;
+use super::{entry, volume_descriptor};
 use dcb_cdrom_xa::reader::{ReadSectorError, SeekSectorError};
 
 /// Error type for [`FilesystemReader::new`](super::FilesystemReader::new)
@@ -31,3 +31,37 @@ pub enum NewError {
 	#[error("Primary volume must be in form 1")]
 	PrimaryFormatWrongForm,
 }
+
+/// Error type for [`FilesystemReader::find`](super::FilesystemReader::find)
+#[derive(Debug, thiserror::Error)]
+pub enum FindError {
+	/// Unable to read directory
+	#[error("Unable to read directory {path:?}")]
+	ReadDir {
+		/// Path of the directory
+		path: String,
+
+		/// Underlying error
+		#[source]
+		err: entry::ReadDirError,
+	},
+
+	/// Entry wasn't found
+	#[error("Unable to find {path:?}")]
+	NotFound {
+		/// Path of the entry
+		path: String,
+	},
+}
+
+/// Error type for [`FilesystemReader::open`](super::FilesystemReader::open)
+#[derive(Debug, thiserror::Error)]
+pub enum OpenError {
+	/// Unable to find file
+	#[error("Unable to find file")]
+	Find(#[source] FindError),
+
+	/// Unable to read file
+	#[error("Unable to read file")]
+	ReadFile(#[source] entry::ReadFileError),
+}
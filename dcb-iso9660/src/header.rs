@@ -0,0 +1,143 @@
+//! Extracted filesystem header
+//!
+//! When extracting a filesystem, everything that can't be represented by the extracted
+//! files themselves, such as the volume information and the location of each entry, is
+//! stored in a header, so that the filesystem may be rebuilt from it.
+
+// Imports
+use dcb_bytes::Bytes;
+use dcb_cdrom_xa::sector::header::{subheader, SubHeader};
+use std::path::{Path, PathBuf};
+
+/// Header
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Header {
+	/// System Id
+	pub system_id: String,
+
+	/// Volume Id
+	pub volume_id: String,
+
+	/// Volume space size
+	pub volume_space_size: u32,
+
+	/// Volume sequence_number
+	pub volume_sequence_number: u16,
+
+	/// Logical block size
+	pub logical_block_size: u16,
+
+	/// Path table location
+	pub path_table_location: u32,
+
+	/// Path table optional location
+	pub path_table_opt_location: u32,
+
+	/// Big endian path table location
+	pub path_table_msb_location: u32,
+
+	/// Big endian path table optional location
+	pub path_table_msb_opt_location: u32,
+
+	/// Volume set identifier
+	pub volume_set_id: String,
+
+	/// Publisher identifier
+	pub publisher_id: String,
+
+	/// Data preparer identifier
+	pub data_preparer_id: String,
+
+	/// Application identifier
+	pub application_id: String,
+
+	/// Copyright file identifier
+	pub copyright_file_id: String,
+
+	/// Abstract file identifier
+	pub abstract_file_id: String,
+
+	/// Bibliographic file identifier
+	pub bibliographic_file_id: String,
+
+	/// Volume creation date time
+	pub volume_creation_date_time: String,
+
+	/// Volume modification date time
+	pub volume_modification_date_time: String,
+
+	/// Volume expiration date time
+	pub volume_expiration_date_time: String,
+
+	/// Volume effective date time
+	pub volume_effective_date_time: String,
+
+	/// Application use
+	pub application_use: Vec<u8>,
+
+	/// Root directory sector position
+	pub root_sector_pos: u32,
+
+	/// Root directory date
+	pub root_date: [u8; 7],
+
+	/// Root directory system use area
+	pub root_system_use: Vec<u8>,
+
+	/// All entries
+	pub entries: Vec<EntryHeader>,
+}
+
+/// Entry header
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct EntryHeader {
+	/// Path, relative to the root directory, without versions
+	pub path: String,
+
+	/// Name, with version
+	pub name: String,
+
+	/// Sector position
+	pub sector_pos: u32,
+
+	/// Date
+	pub date: [u8; 7],
+
+	/// System use area
+	pub system_use: Vec<u8>,
+
+	/// Recorded file size
+	///
+	/// Only present for files with form 2 sectors, as their extracted size
+	/// is larger than their recorded size.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub size: Option<u32>,
+
+	/// Sub-header of each file sector
+	///
+	/// Only present for files whose sectors don't all have the default sub-headers.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub subheaders: Option<Vec<[u8; 4]>>,
+}
+
+impl EntryHeader {
+	/// Returns the sub-header of each file sector, if any
+	pub fn subheaders(&self) -> Result<Option<Vec<SubHeader>>, subheader::DeserializeBytesError> {
+		self.subheaders
+			.as_ref()
+			.map(|subheaders| subheaders.iter().map(SubHeader::deserialize_bytes).collect())
+			.transpose()
+	}
+}
+
+/// Returns `path` with `suffix` appended to it
+///
+/// Used for the files extracted alongside the filesystem, such as it's header.
+#[must_use]
+pub fn path_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+	let mut path = path.as_os_str().to_os_string();
+	path.push(suffix);
+	PathBuf::from(path)
+}
@@ -7,18 +7,21 @@ pub mod date_time;
 pub mod dir;
 pub mod entry;
 mod error;
+pub mod header;
 pub mod string;
 pub mod volume_descriptor;
+pub mod writer;
 
 // Exports
-pub use dir::Dir;
-pub use entry::DirEntry;
-pub use error::NewError;
+pub use dir::{Dir, Walk, WalkEntry};
+pub use entry::{DirEntry, FileReader};
+pub use error::{FindError, NewError, OpenError};
 pub use string::{StrArrA, StrArrD};
 pub use volume_descriptor::VolumeDescriptor;
+pub use writer::FilesystemWriter;
 
 // Imports
-use self::volume_descriptor::PrimaryVolumeDescriptor;
+use self::{entry::ReadDirError, volume_descriptor::PrimaryVolumeDescriptor};
 use dcb_bytes::Bytes;
 use dcb_cdrom_xa::CdRomReader;
 use std::io;
@@ -75,43 +78,43 @@ impl FilesystemReader {
 	pub const fn root_dir(&self) -> &DirEntry {
 		&self.primary_volume_descriptor.root_dir_entry
 	}
-}
 
-/// A filesystem reader
-#[derive(PartialEq, Eq, Clone, Debug)]
-pub struct FilesystemWriter {}
+	/// Finds an entry given it's path.
+	///
+	/// The path is separated by `/`, relative to the root directory, and files may
+	/// be given with or without their version, e.g. `/SYSTEM.CNF;1` or `SYSTEM.CNF`.
+	pub fn find<R: io::Read + io::Seek>(&self, cdrom: &mut CdRomReader<R>, path: &str) -> Result<DirEntry, FindError> {
+		let mut entry = self.root_dir().clone();
+		let mut cur_path = String::new();
+		for name in path.split('/').filter(|name| !name.is_empty()) {
+			let dir = entry.read_dir(cdrom).map_err(|err| FindError::ReadDir {
+				path: cur_path.clone(),
+				err,
+			})?;
 
-impl FilesystemWriter {
-	/// Creates a new filesystem writer
-	#[must_use]
-	pub const fn new() -> Self {
-		/*
-		// Create the primary volume descriptor and write it
-		// Note: W
-		// 22
-		let primary_volume_descriptor = PrimaryVolumeDescriptor {
-			system_id: (),
-			volume_id: (),
-			volume_space_size: (),
-			volume_sequence_number: (),
-			logical_block_size: (),
-			path_table_size: (),
-			path_table_location: (),
-			path_table_opt_location: (),
-			root_dir_entry: (),
-			volume_set_id: (),
-			publisher_id: (),
-			data_preparer_id: (),
-			application_id: (),
-			copyright_file_id: (),
-			abstract_file_id: (),
-			bibliographic_file_id: (),
-			volume_creation_date_time: (),
-			volume_modification_date_time: (),
-			volume_expiration_date_time: (),
-			volume_effective_date_time: (),
-		};
-		*/
-		Self {}
+			cur_path.push('/');
+			cur_path.push_str(name);
+			entry = dir
+				.find(name)
+				.ok_or_else(|| FindError::NotFound { path: cur_path.clone() })?
+				.clone();
+		}
+
+		Ok(entry)
+	}
+
+	/// Opens a file given it's path.
+	///
+	/// See [`Self::find`] for the format of `path`.
+	pub fn open<'a, R: io::Read + io::Seek>(
+		&self, cdrom: &'a mut CdRomReader<R>, path: &str,
+	) -> Result<FileReader<'a, R>, OpenError> {
+		let entry = self.find(cdrom, path).map_err(OpenError::Find)?;
+		entry.read_file(cdrom).map_err(OpenError::ReadFile)
+	}
+
+	/// Returns an iterator over all entries, recursively.
+	pub fn walk<'a, R: io::Read + io::Seek>(&self, cdrom: &'a mut CdRomReader<R>) -> Result<Walk<'a, R>, ReadDirError> {
+		Walk::new(cdrom, self.root_dir())
 	}
 }
@@ -61,6 +61,9 @@ impl OnlyValidCharsAlphabet for AlphabetD {
 /// There are 3 exceptions to this, which are the root directory
 /// name, current directory name and parent directory name, which
 /// are, "\0", "" and "\x01", respectively.
+///
+/// Directory names, which have no extension nor version, are also
+/// accepted, as long as they are a D-character string.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub struct FileAlphabet;
 
@@ -75,6 +78,11 @@ impl Alphabet for FileAlphabet {
 			return Ok(bytes);
 		}
 
+		// If we don't have any `.` or `;`, this is a directory name
+		if !bytes.iter().any(|&b| b == b'.' || b == b';') {
+			return AlphabetD::validate(bytes).map_err(ValidateFileAlphabetError::InvalidNameChar);
+		}
+
 		// Separate into `<name>.<extension>;<version>`
 		let (name, extension, version) = {
 			// Separate into `<name>.<rest>` and ignore the `.` in `<rest>`
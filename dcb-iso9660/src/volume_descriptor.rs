@@ -89,7 +89,24 @@ impl Bytes for VolumeDescriptor {
 		}
 	}
 
-	fn serialize_bytes(&self, _bytes: &mut Self::ByteArray) -> Result<(), Self::SerializeError> {
-		todo!()
+	fn serialize_bytes(&self, bytes: &mut Self::ByteArray) -> Result<(), Self::SerializeError> {
+		let bytes = zutil::array_split_mut!(bytes,
+			kind      :  0x1,
+			magic     : [0x5],
+			version   :  0x1,
+			descriptor: [0x7f9],
+		);
+
+		self.kind().serialize_bytes(bytes.kind).into_ok();
+		*bytes.magic = Self::MAGIC;
+		*bytes.version = Self::VERSION;
+
+		match self {
+			Self::BootRecord(descriptor) => descriptor.serialize_bytes(bytes.descriptor).into_ok(),
+			Self::Primary(descriptor) => descriptor.serialize_bytes(bytes.descriptor).into_ok(),
+			Self::SetTerminator => *bytes.descriptor = [0; 0x7f9],
+		}
+
+		Ok(())
 	}
 }
@@ -46,7 +46,17 @@ impl Bytes for BootRecordVolumeDescriptor {
 		})
 	}
 
-	fn serialize_bytes(&self, _bytes: &mut Self::ByteArray) -> Result<(), Self::SerializeError> {
-		todo!()
+	fn serialize_bytes(&self, bytes: &mut Self::ByteArray) -> Result<(), Self::SerializeError> {
+		let bytes = zutil::array_split_mut!(bytes,
+			system_id: [0x20],
+			boot_id  : [0x20],
+			data     : [0x7b9],
+		);
+
+		self.system_id.write_bytes(bytes.system_id);
+		self.boot_id.write_bytes(bytes.boot_id);
+		*bytes.data = self.data;
+
+		Ok(())
 	}
 }
@@ -8,7 +8,7 @@ pub use error::DeserializeBytesError;
 
 // Imports
 use crate::{date_time::DecDateTime, entry::DirEntry, StrArrA, StrArrD};
-use byteorder::{ByteOrder, LittleEndian};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
 use dcb_bytes::Bytes;
 
 /// Primary volume descriptor
@@ -38,6 +38,12 @@ pub struct PrimaryVolumeDescriptor {
 	/// Path table optional location
 	pub path_table_opt_location: u32,
 
+	/// Big endian path table location
+	pub path_table_msb_location: u32,
+
+	/// Big endian path table optional location
+	pub path_table_msb_opt_location: u32,
+
 	/// Root directory entry
 	pub root_dir_entry: DirEntry,
 
@@ -73,6 +79,11 @@ pub struct PrimaryVolumeDescriptor {
 
 	/// Volume effective date time
 	pub volume_effective_date_time: DecDateTime,
+
+	/// Application use
+	///
+	/// On CD-ROM/XA discs this contains the `CD-XA001` signature.
+	pub application_use: [u8; 0x200],
 }
 
 impl Bytes for PrimaryVolumeDescriptor {
@@ -130,6 +141,8 @@ impl Bytes for PrimaryVolumeDescriptor {
 			path_table_size:               LittleEndian::read_u32(bytes.path_table_size_lsb),
 			path_table_location:           LittleEndian::read_u32(bytes.path_table_lsb_location),
 			path_table_opt_location:       LittleEndian::read_u32(bytes.path_table_lsb_opt_location),
+			path_table_msb_location:       BigEndian::read_u32(bytes.path_table_msb_location),
+			path_table_msb_opt_location:   BigEndian::read_u32(bytes.path_table_msb_opt_location),
 			root_dir_entry:                DirEntry::from_reader(&mut std::io::Cursor::new(bytes.root_dir_entry))
 				.map_err(DeserializeBytesError::RootDirEntry)?,
 			volume_set_id:                 StrArrD::from_bytes(bytes.volume_set_id)
@@ -154,6 +167,7 @@ impl Bytes for PrimaryVolumeDescriptor {
 				.map_err(DeserializeBytesError::VolumeExpirationDateTime)?,
 			volume_effective_date_time:    DecDateTime::deserialize_bytes(bytes.volume_effective_date_time)
 				.map_err(DeserializeBytesError::VolumeEffectiveDateTime)?,
+			application_use:               *bytes.data,
 		})
 	}
 
@@ -196,14 +210,25 @@ impl Bytes for PrimaryVolumeDescriptor {
 			reserved                     : [0x28d],
 		);
 
+		*bytes.zeroes0 = 0;
 		self.system_id.write_bytes(bytes.system_id);
 		self.volume_id.write_bytes(bytes.volume_id);
+		*bytes.zeroes1 = [0; 0x8];
 		LittleEndian::write_u32(bytes.volume_space_size_lsb, self.volume_space_size);
+		BigEndian::write_u32(bytes.volume_space_size_msb, self.volume_space_size);
+		*bytes.zeroes2 = [0; 0x20];
+		LittleEndian::write_u16(bytes.volume_set_size_lsb, 1);
+		BigEndian::write_u16(bytes.volume_set_size_msb, 1);
 		LittleEndian::write_u16(bytes.volume_sequence_number_lsb, self.volume_sequence_number);
+		BigEndian::write_u16(bytes.volume_sequence_number_msb, self.volume_sequence_number);
 		LittleEndian::write_u16(bytes.logical_block_size_lsb, self.logical_block_size);
+		BigEndian::write_u16(bytes.logical_block_size_msb, self.logical_block_size);
 		LittleEndian::write_u32(bytes.path_table_size_lsb, self.path_table_size);
+		BigEndian::write_u32(bytes.path_table_size_msb, self.path_table_size);
 		LittleEndian::write_u32(bytes.path_table_lsb_location, self.path_table_location);
 		LittleEndian::write_u32(bytes.path_table_lsb_opt_location, self.path_table_opt_location);
+		BigEndian::write_u32(bytes.path_table_msb_location, self.path_table_msb_location);
+		BigEndian::write_u32(bytes.path_table_msb_opt_location, self.path_table_msb_opt_location);
 		self.root_dir_entry
 			.to_writer(&mut std::io::Cursor::<&mut [u8]>::new(bytes.root_dir_entry))
 			.expect("Couldn't write root entry"); // TODO: Error handling
@@ -226,6 +251,10 @@ impl Bytes for PrimaryVolumeDescriptor {
 		self.volume_effective_date_time
 			.serialize_bytes(bytes.volume_effective_date_time)
 			.into_ok();
+		*bytes.file_structure_version = 1;
+		*bytes.zeroes3 = 0;
+		*bytes.data = self.application_use;
+		*bytes.reserved = [0; 0x28d];
 
 		Ok(())
 	}
@@ -0,0 +1,821 @@
+//! Filesystem writer
+//!
+//! The writer first collects the whole directory tree, so that the location of every
+//! directory and file may be known before anything is written, and then writes the
+//! filesystem sequentially, as [`CdRomWriter`] can't seek.
+//!
+//! The system area isn't written by the writer, as it's contents are arbitrary.
+//!
+//! The filesystem is laid out as follows:
+//! - The primary volume descriptor, followed by the set terminator.
+//! - The little endian path table and it's optional copy.
+//! - The big endian path table and it's optional copy.
+//! - All directories, in the same order as the path table.
+//! - All files, in the same order as the directories.
+//!
+//! Directories, files and path tables may request a sector position, in which case they
+//! are placed there, as long as they don't overlap anything else. Otherwise, or if they
+//! didn't request one, they are placed after everything else. Any sectors left unused
+//! between them are written as empty sectors.
+
+// Modules
+mod error;
+#[cfg(test)]
+mod test;
+
+// Exports
+pub use error::{WriteError, WriteFileError};
+
+// Imports
+use crate::{
+	date_time::{DecDateTime, DirDateTime},
+	entry::{file, Flags},
+	string::FileString,
+	volume_descriptor::PrimaryVolumeDescriptor,
+	DirEntry, StrArrA, StrArrD, VolumeDescriptor,
+};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use dcb_bytes::Bytes;
+use dcb_cdrom_xa::{
+	sector::{
+		header::{subheader::SubMode, SubHeader},
+		Data, Form,
+	},
+	CdRomWriter,
+};
+use std::{
+	collections::VecDeque,
+	convert::TryFrom,
+	io::{self, Read},
+	ops::Range,
+};
+
+/// A directory lister
+pub trait DirWriterLister: Sized + IntoIterator<Item = Result<DirEntryWriter<Self>, Self::Error>> {
+	/// File type
+	type FileReader: io::Read;
+
+	/// Error type for each entry
+	type Error: std::error::Error + 'static;
+}
+
+/// A directory entry writer
+pub struct DirEntryWriter<L: DirWriterLister> {
+	/// Entry name
+	pub name: FileString,
+
+	/// Entry date
+	pub date: DirDateTime,
+
+	/// System use area
+	pub system_use: Vec<u8>,
+
+	/// Requested sector position
+	pub sector_pos: Option<u32>,
+
+	/// Kind
+	pub kind: DirEntryWriterKind<L>,
+}
+
+/// A directory entry writer kind
+pub enum DirEntryWriterKind<L: DirWriterLister> {
+	/// A file
+	File {
+		/// File reader
+		reader: L::FileReader,
+
+		/// File size
+		size: u32,
+
+		/// Sub-header of each sector, if not the default ones
+		///
+		/// Form 2 sectors read `0x914` bytes from the reader, regardless of the file size.
+		subheaders: Option<Vec<SubHeader>>,
+	},
+
+	/// A directory
+	Dir(L),
+}
+
+/// Volume information
+///
+/// Contains everything in the primary volume descriptor that isn't
+/// derived from the layout of the filesystem.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct VolumeInfo {
+	/// System Id
+	pub system_id: StrArrA<0x20>,
+
+	/// Volume Id
+	pub volume_id: StrArrD<0x20>,
+
+	/// Volume set identifier
+	pub volume_set_id: StrArrD<0x80>,
+
+	/// Publisher identifier
+	pub publisher_id: StrArrA<0x80>,
+
+	/// Data preparer identifier
+	pub data_preparer_id: StrArrA<0x80>,
+
+	/// Application identifier
+	pub application_id: StrArrA<0x80>,
+
+	/// Copyright file identifier
+	pub copyright_file_id: StrArrD<0x26>,
+
+	/// Abstract file identifier
+	pub abstract_file_id: StrArrD<0x24>,
+
+	/// Bibliographic file identifier
+	pub bibliographic_file_id: StrArrD<0x25>,
+
+	/// Volume creation date time
+	pub volume_creation_date_time: DecDateTime,
+
+	/// Volume modification date time
+	pub volume_modification_date_time: DecDateTime,
+
+	/// Volume expiration date time
+	pub volume_expiration_date_time: DecDateTime,
+
+	/// Volume effective date time
+	pub volume_effective_date_time: DecDateTime,
+
+	/// Application use
+	pub application_use: [u8; 0x200],
+
+	/// Root directory date
+	pub root_date: DirDateTime,
+
+	/// Root directory system use area
+	pub root_system_use: Vec<u8>,
+
+	/// Requested root directory sector position
+	pub root_sector_pos: Option<u32>,
+
+	/// Requested path table locations
+	///
+	/// In order, the little endian path table, it's optional copy,
+	/// the big endian path table and it's optional copy.
+	pub path_table_locations: Option<[u32; 4]>,
+
+	/// Requested volume space size
+	///
+	/// If larger than the filesystem, empty sectors are written until it.
+	pub volume_space_size: Option<u32>,
+}
+
+/// A filesystem writer
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct FilesystemWriter<L> {
+	/// Volume information
+	info: VolumeInfo,
+
+	/// Root directory
+	root: L,
+}
+
+impl<L> FilesystemWriter<L> {
+	/// Creates a new filesystem writer
+	#[must_use]
+	pub const fn new(info: VolumeInfo, root: L) -> Self {
+		Self { info, root }
+	}
+}
+
+impl<L: DirWriterLister> FilesystemWriter<L> {
+	/// Sector of the primary volume descriptor
+	pub const PRIMARY_VOLUME_DESCRIPTOR_SECTOR: u32 = 0x10;
+
+	/// Writes the filesystem
+	///
+	/// `cdrom` is expected to be right after the system area, at the primary volume descriptor.
+	pub fn write<W: io::Write>(self, cdrom: &mut CdRomWriter<W>) -> Result<(), WriteError<L::Error>> {
+		let Self { info, root } = self;
+
+		// Collect the whole tree
+		let mut dirs = self::collect_dirs(root, &info)?;
+		for dir_idx in 0..dirs.len() {
+			dirs[dir_idx].sectors_len = self::dir_sectors_len(&dirs[dir_idx], &dirs[dirs[dir_idx].parent_idx])?;
+		}
+
+		// Then create the path tables
+		let mut path_table = self::path_table(&dirs)?;
+		let path_table_size = u32::try_from(path_table.len()).map_err(|_| WriteError::TooLarge)?;
+		let path_table_sectors = self::sector_count(path_table_size).max(1);
+
+		// Then get all regions we need to place, in order
+		// Note: An optional path table at sector 0 means it doesn't exist, so we don't place it
+		//       and it's location stays 0.
+		let path_table_regions = (0..4).filter_map(|idx| {
+			let sector_pos = info.path_table_locations.map(|locations| locations[idx]);
+			match (idx, sector_pos) {
+				(1 | 3, Some(0)) => None,
+				_ => Some((Region::PathTable { idx }, sector_pos, path_table_sectors)),
+			}
+		});
+		let dir_regions = dirs
+			.iter()
+			.enumerate()
+			.map(|(idx, dir)| (Region::Dir { idx }, dir.requested_sector_pos, dir.sectors_len));
+		let file_regions = dirs.iter().enumerate().flat_map(|(dir_idx, dir)| {
+			dir.entries
+				.iter()
+				.enumerate()
+				.filter_map(move |(entry_idx, entry)| match entry.kind {
+					EntryKind::File {
+						size,
+						requested_sector_pos,
+						..
+					} => Some((
+						Region::File { dir_idx, entry_idx },
+						requested_sector_pos,
+						self::sector_count(size),
+					)),
+					EntryKind::Dir { .. } => None,
+				})
+		});
+		let regions: Vec<_> = path_table_regions.chain(dir_regions).chain(file_regions).collect();
+
+		// And lay them out, first those that requested a position, then the rest
+		let mut allocator = Allocator::new(Self::PRIMARY_VOLUME_DESCRIPTOR_SECTOR + 2);
+		let mut placed = Vec::with_capacity(regions.len());
+		let mut pending = vec![];
+		for (region, sector_pos, sectors_len) in regions {
+			match sector_pos {
+				Some(sector_pos) if allocator.try_place(sector_pos, sectors_len)? => {
+					placed.push((region, sector_pos, sectors_len));
+				},
+				Some(sector_pos) => {
+					log::warn!("Unable to place {region:?} at requested sector {sector_pos}, placing it at the end");
+					pending.push((region, sectors_len));
+				},
+				None => pending.push((region, sectors_len)),
+			}
+		}
+		for (region, sectors_len) in pending {
+			let sector_pos = allocator.alloc(sectors_len)?;
+			placed.push((region, sector_pos, sectors_len));
+		}
+
+		// Then set all the positions
+		let mut path_table_locations = [0; 4];
+		for &(region, sector_pos, _) in &placed {
+			match region {
+				Region::PathTable { idx } => path_table_locations[idx] = sector_pos,
+				Region::Dir { idx } => dirs[idx].sector_pos = sector_pos,
+				Region::File { dir_idx, entry_idx } => match &mut dirs[dir_idx].entries[entry_idx].kind {
+					EntryKind::File { sector_pos: pos, .. } => *pos = sector_pos,
+					EntryKind::Dir { .. } => unreachable!("File region pointed to a directory"),
+				},
+			}
+		}
+		self::fill_path_table(&mut path_table, &dirs);
+		let mut path_table_msb = path_table.clone();
+		self::path_table_to_msb(&mut path_table_msb);
+		let volume_space_size = allocator.end.max(info.volume_space_size.unwrap_or(0));
+
+		// Write the volume descriptors
+		let root_dir = &dirs[0];
+		let primary_volume_descriptor = PrimaryVolumeDescriptor {
+			system_id: info.system_id,
+			volume_id: info.volume_id,
+			volume_space_size,
+			volume_sequence_number: 1,
+			logical_block_size: 0x800,
+			path_table_size,
+			path_table_location: path_table_locations[0],
+			path_table_opt_location: path_table_locations[1],
+			path_table_msb_location: path_table_locations[2],
+			path_table_msb_opt_location: path_table_locations[3],
+			root_dir_entry: DirEntry {
+				name:       root_dir.name.clone(),
+				sector_pos: root_dir.sector_pos,
+				size:       root_dir.sectors_len * 0x800,
+				flags:      Flags::DIR,
+				date:       root_dir.date,
+				system_use: vec![],
+			},
+			volume_set_id: info.volume_set_id,
+			publisher_id: info.publisher_id,
+			data_preparer_id: info.data_preparer_id,
+			application_id: info.application_id,
+			copyright_file_id: info.copyright_file_id,
+			abstract_file_id: info.abstract_file_id,
+			bibliographic_file_id: info.bibliographic_file_id,
+			volume_creation_date_time: info.volume_creation_date_time,
+			volume_modification_date_time: info.volume_modification_date_time,
+			volume_expiration_date_time: info.volume_expiration_date_time,
+			volume_effective_date_time: info.volume_effective_date_time,
+			application_use: info.application_use,
+		};
+		let volume_descriptors = [
+			(
+				VolumeDescriptor::Primary(primary_volume_descriptor),
+				SubMode::DATA | SubMode::END_OF_RECORD,
+			),
+			(
+				VolumeDescriptor::SetTerminator,
+				SubMode::DATA | SubMode::END_OF_RECORD | SubMode::END_OF_FILE,
+			),
+		];
+		for (volume_descriptor, submode) in &volume_descriptors {
+			let mut bytes = [0; 0x800];
+			volume_descriptor.serialize_bytes(&mut bytes).into_ok();
+
+			let subheader = SubHeader {
+				submode: *submode,
+				..SubHeader::new()
+			};
+			cdrom
+				.write_sector(bytes, subheader)
+				.map_err(WriteError::WriteVolumeDescriptor)?;
+		}
+
+		// Then write all regions in order, filling any gaps between them
+		placed.sort_by_key(|&(_, sector_pos, _)| sector_pos);
+		let mut cur_sector_pos = Self::PRIMARY_VOLUME_DESCRIPTOR_SECTOR + 2;
+		for (region, sector_pos, sectors_len) in placed {
+			// Note: Empty files may be placed anywhere, so we don't write them
+			if sectors_len == 0 {
+				continue;
+			}
+
+			self::write_empty(cdrom, sector_pos - cur_sector_pos).map_err(WriteError::WriteGap)?;
+			match region {
+				Region::PathTable { idx } => {
+					let table = match idx {
+						0 | 1 => &path_table,
+						_ => &path_table_msb,
+					};
+					self::write_bytes(cdrom, table, sectors_len).map_err(WriteError::WritePathTable)?;
+				},
+				Region::Dir { idx } => {
+					let bytes = self::dir_bytes(&dirs[idx], &dirs)?;
+					self::write_bytes(cdrom, &bytes, sectors_len).map_err(WriteError::WriteDir)?;
+				},
+				Region::File { dir_idx, entry_idx } => {
+					let entry = &mut dirs[dir_idx].entries[entry_idx];
+					match &mut entry.kind {
+						EntryKind::File {
+							reader,
+							size,
+							subheaders,
+							..
+						} => self::write_file(cdrom, reader, *size, subheaders.as_deref()).map_err(|err| {
+							WriteError::WriteFile {
+								name: entry.name.clone(),
+								err,
+							}
+						})?,
+						EntryKind::Dir { .. } => unreachable!("File region pointed to a directory"),
+					}
+				},
+			}
+			cur_sector_pos = sector_pos + sectors_len;
+		}
+		self::write_empty(cdrom, volume_space_size - cur_sector_pos).map_err(WriteError::WriteGap)?;
+
+		Ok(())
+	}
+}
+
+/// A region of the filesystem
+#[derive(Clone, Copy, Debug)]
+enum Region {
+	/// A path table
+	PathTable {
+		/// Path table index
+		idx: usize,
+	},
+
+	/// A directory
+	Dir {
+		/// Directory index
+		idx: usize,
+	},
+
+	/// A file
+	File {
+		/// Directory index
+		dir_idx: usize,
+
+		/// Entry index
+		entry_idx: usize,
+	},
+}
+
+/// Sector allocator
+struct Allocator {
+	/// All used ranges
+	used: Vec<Range<u32>>,
+
+	/// End of all used ranges
+	end: u32,
+}
+
+impl Allocator {
+	/// Creates a new allocator with the first `start` sectors used
+	fn new(start: u32) -> Self {
+		Self {
+			used: vec![0..start],
+			end:  start,
+		}
+	}
+
+	/// Tries to place `sectors_len` sectors at `sector_pos`.
+	///
+	/// Returns if successful.
+	fn try_place<E: std::error::Error + 'static>(
+		&mut self, sector_pos: u32, sectors_len: u32,
+	) -> Result<bool, WriteError<E>> {
+		let end = sector_pos.checked_add(sectors_len).ok_or(WriteError::TooLarge)?;
+		if sectors_len == 0 {
+			return Ok(true);
+		}
+		if self
+			.used
+			.iter()
+			.any(|range| sector_pos < range.end && range.start < end)
+		{
+			return Ok(false);
+		}
+
+		self.used.push(sector_pos..end);
+		self.end = self.end.max(end);
+		Ok(true)
+	}
+
+	/// Allocates `sectors_len` sectors after all used sectors
+	fn alloc<E: std::error::Error + 'static>(&mut self, sectors_len: u32) -> Result<u32, WriteError<E>> {
+		let sector_pos = self.end;
+		let end = sector_pos.checked_add(sectors_len).ok_or(WriteError::TooLarge)?;
+
+		self.used.push(sector_pos..end);
+		self.end = end;
+		Ok(sector_pos)
+	}
+}
+
+/// A collected directory
+struct Dir<R> {
+	/// Name
+	name: FileString,
+
+	/// Date
+	date: DirDateTime,
+
+	/// System use area
+	system_use: Vec<u8>,
+
+	/// Parent directory index
+	parent_idx: usize,
+
+	/// All entries
+	entries: Vec<Entry<R>>,
+
+	/// Requested sector position
+	requested_sector_pos: Option<u32>,
+
+	/// Sector position
+	sector_pos: u32,
+
+	/// Number of sectors
+	sectors_len: u32,
+}
+
+/// A collected directory entry
+struct Entry<R> {
+	/// Name
+	name: FileString,
+
+	/// Date
+	date: DirDateTime,
+
+	/// System use area
+	system_use: Vec<u8>,
+
+	/// Kind
+	kind: EntryKind<R>,
+}
+
+/// A collected directory entry kind
+enum EntryKind<R> {
+	/// A file
+	File {
+		/// File reader
+		reader: R,
+
+		/// File size
+		size: u32,
+
+		/// Sub-header of each sector
+		subheaders: Option<Vec<SubHeader>>,
+
+		/// Requested sector position
+		requested_sector_pos: Option<u32>,
+
+		/// Sector position
+		sector_pos: u32,
+	},
+
+	/// A directory
+	Dir {
+		/// Directory index
+		idx: usize,
+	},
+}
+
+/// Collects all directories, breadth-first, with the root directory first
+fn collect_dirs<L: DirWriterLister>(
+	root: L, info: &VolumeInfo,
+) -> Result<Vec<Dir<L::FileReader>>, WriteError<L::Error>> {
+	let mut dirs = vec![Dir {
+		name:                 FileString::from_bytes(&[0]).expect("Root directory name is valid"),
+		date:                 info.root_date,
+		system_use:           info.root_system_use.clone(),
+		parent_idx:           0,
+		entries:              vec![],
+		requested_sector_pos: info.root_sector_pos,
+		sector_pos:           0,
+		sectors_len:          0,
+	}];
+
+	let mut queue = VecDeque::from(vec![(0, root)]);
+	while let Some((dir_idx, lister)) = queue.pop_front() {
+		let mut entries = lister
+			.into_iter()
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(WriteError::GetEntry)?;
+
+		// Note: Entries must be sorted by name, both in the directory
+		//       records and the path table.
+		entries.sort_by(|lhs, rhs| lhs.name.as_bytes().cmp(rhs.name.as_bytes()));
+
+		let entries = entries
+			.into_iter()
+			.map(|entry| {
+				let kind = match entry.kind {
+					DirEntryWriterKind::File {
+						reader,
+						size,
+						subheaders,
+					} => EntryKind::File {
+						reader,
+						size,
+						subheaders,
+						requested_sector_pos: entry.sector_pos,
+						sector_pos: 0,
+					},
+					DirEntryWriterKind::Dir(lister) => {
+						let idx = dirs.len();
+						dirs.push(Dir {
+							name:                 entry.name.clone(),
+							date:                 entry.date,
+							system_use:           entry.system_use.clone(),
+							parent_idx:           dir_idx,
+							entries:              vec![],
+							requested_sector_pos: entry.sector_pos,
+							sector_pos:           0,
+							sectors_len:          0,
+						});
+						queue.push_back((idx, lister));
+						EntryKind::Dir { idx }
+					},
+				};
+
+				Entry {
+					name: entry.name,
+					date: entry.date,
+					system_use: entry.system_use,
+					kind,
+				}
+			})
+			.collect();
+		dirs[dir_idx].entries = entries;
+	}
+
+	Ok(dirs)
+}
+
+/// Creates the little endian path table
+fn path_table<R, E: std::error::Error + 'static>(dirs: &[Dir<R>]) -> Result<Vec<u8>, WriteError<E>> {
+	let mut bytes = vec![];
+	for dir in dirs {
+		let name = dir.name.as_bytes();
+		let parent_num = u16::try_from(dir.parent_idx + 1).map_err(|_| WriteError::TooLarge)?;
+
+		// Note: The location is filled later, once we know it, see `fill_path_table`.
+		let mut header = [0; 0x8];
+		header[0] = u8::try_from(name.len()).map_err(|_| WriteError::TooLarge)?;
+		LittleEndian::write_u16(&mut header[0x6..0x8], parent_num);
+
+		bytes.extend_from_slice(&header);
+		bytes.extend_from_slice(name);
+		if name.len() % 2 == 1 {
+			bytes.push(0);
+		}
+	}
+
+	Ok(bytes)
+}
+
+/// Fills the little endian path table with the location of each directory
+fn fill_path_table<R>(bytes: &mut [u8], dirs: &[Dir<R>]) {
+	let mut offset = 0;
+	for dir in dirs {
+		let name_len = usize::from(bytes[offset]);
+		LittleEndian::write_u32(&mut bytes[offset + 0x2..offset + 0x6], dir.sector_pos);
+		offset += 0x8 + name_len + name_len % 2;
+	}
+}
+
+/// Converts a little endian path table into a big endian one
+fn path_table_to_msb(bytes: &mut [u8]) {
+	let mut offset = 0;
+	while offset < bytes.len() {
+		let name_len = usize::from(bytes[offset]);
+		bytes[offset + 0x2..offset + 0x6].reverse();
+		bytes[offset + 0x6..offset + 0x8].reverse();
+		offset += 0x8 + name_len + name_len % 2;
+	}
+}
+
+/// Returns all records of a directory, including the current and parent directory records
+fn dir_records<R>(dir: &Dir<R>, dirs: &[Dir<R>]) -> Vec<DirEntry> {
+	let parent = &dirs[dir.parent_idx];
+	let special_records = vec![(0, dir), (1, parent)].into_iter().map(|(name, dir)| DirEntry {
+		name:       FileString::from_bytes(&[name]).expect("Current and parent directory names are valid"),
+		sector_pos: dir.sector_pos,
+		size:       dir.sectors_len * 0x800,
+		flags:      Flags::DIR,
+		date:       dir.date,
+		system_use: dir.system_use.clone(),
+	});
+
+	let records = dir.entries.iter().map(|entry| {
+		let (sector_pos, size, flags) = match entry.kind {
+			EntryKind::File { size, sector_pos, .. } => (sector_pos, size, Flags::empty()),
+			EntryKind::Dir { idx } => (dirs[idx].sector_pos, dirs[idx].sectors_len * 0x800, Flags::DIR),
+		};
+
+		DirEntry {
+			name: entry.name.clone(),
+			sector_pos,
+			size,
+			flags,
+			date: entry.date,
+			system_use: entry.system_use.clone(),
+		}
+	});
+
+	special_records.chain(records).collect()
+}
+
+/// Returns the number of sectors a directory occupies
+///
+/// Note: Records may not cross sector boundaries.
+fn dir_sectors_len<R, E: std::error::Error + 'static>(dir: &Dir<R>, parent: &Dir<R>) -> Result<u32, WriteError<E>> {
+	let special_records = vec![dir, parent]
+		.into_iter()
+		.map(|dir| DirEntry::record_size_of(1, dir.system_use.len()));
+	let records = dir
+		.entries
+		.iter()
+		.map(|entry| DirEntry::record_size_of(entry.name.len(), entry.system_use.len()));
+
+	let mut sectors_len = 1;
+	let mut cur_offset = 0;
+	for record_size in special_records.chain(records) {
+		if record_size > 0xff {
+			return Err(WriteError::TooLarge);
+		}
+
+		if cur_offset + record_size > 0x800 {
+			sectors_len += 1;
+			cur_offset = 0;
+		}
+		cur_offset += record_size;
+	}
+
+	Ok(sectors_len)
+}
+
+/// Returns the bytes of a directory
+fn dir_bytes<R, E: std::error::Error + 'static>(dir: &Dir<R>, dirs: &[Dir<R>]) -> Result<Vec<u8>, WriteError<E>> {
+	let size = usize::try_from(dir.sectors_len * 0x800).expect("Directory size didn't fit into a `usize`");
+	let mut bytes = io::Cursor::new(vec![0; size]);
+
+	for record in self::dir_records(dir, dirs) {
+		// If the record doesn't fit in the current sector, go to the next one
+		let cur_offset = usize::try_from(bytes.position()).expect("Position didn't fit into a `usize`");
+		if cur_offset % 0x800 + record.record_size() > 0x800 {
+			bytes.set_position(u64::try_from(cur_offset + 0x800 - cur_offset % 0x800).expect("Position didn't fit"));
+		}
+
+		record.to_writer(&mut bytes).map_err(WriteError::WriteDirEntry)?;
+	}
+
+	Ok(bytes.into_inner())
+}
+
+/// Writes `bytes` as `sectors_len` sectors, padding it with zeroes
+fn write_bytes<W: io::Write>(
+	cdrom: &mut CdRomWriter<W>, bytes: &[u8], sectors_len: u32,
+) -> Result<(), dcb_cdrom_xa::writer::WriteSectorError> {
+	let mut chunks = bytes.chunks(0x800);
+	for sector_idx in 0..sectors_len {
+		let mut data = [0; 0x800];
+		if let Some(chunk) = chunks.next() {
+			data[..chunk.len()].copy_from_slice(chunk);
+		}
+
+		cdrom.write_sector(data, self::data_subheader(sector_idx == sectors_len - 1))?;
+	}
+
+	Ok(())
+}
+
+/// Writes a file of size `size`
+///
+/// If `subheaders` isn't given, all sectors are written as form 1 data sectors.
+fn write_file<W: io::Write, R: io::Read>(
+	cdrom: &mut CdRomWriter<W>, reader: &mut R, size: u32, subheaders: Option<&[SubHeader]>,
+) -> Result<(), WriteFileError> {
+	let sectors_len = self::sector_count(size);
+	if let Some(subheaders) = subheaders {
+		if subheaders.len() != usize::try_from(sectors_len).expect("Sector count didn't fit into a `usize`") {
+			return Err(WriteFileError::SubHeadersLen {
+				sectors_len,
+				subheaders_len: subheaders.len(),
+			});
+		}
+	}
+
+	for sector_idx in 0..sectors_len {
+		let subheader = match subheaders {
+			Some(subheaders) => {
+				subheaders[usize::try_from(sector_idx).expect("Sector index didn't fit into a `usize`")]
+			},
+			None => self::data_subheader(sector_idx == sectors_len - 1),
+		};
+
+		// Note: We read the same length per sector as the file reader.
+		let form = Form::from_submode(subheader.submode);
+		let data_len = file::sector_len(u64::from(size), u64::from(sector_idx), form);
+		let data_len = usize::try_from(data_len).expect("Sector size didn't fit into a `usize`");
+		let data = match form {
+			Form::Form1 => {
+				let mut data = [0; 0x800];
+				reader.read_exact(&mut data[..data_len]).map_err(WriteFileError::Read)?;
+				Data::Form1(data)
+			},
+			Form::Form2 => {
+				let mut data = [0; 0x914];
+				reader.read_exact(&mut data[..data_len]).map_err(WriteFileError::Read)?;
+				Data::Form2(data)
+			},
+		};
+
+		cdrom
+			.write_sector(data, subheader)
+			.map_err(WriteFileError::WriteSector)?;
+	}
+
+	Ok(())
+}
+
+/// Writes `sectors_len` empty sectors
+fn write_empty<W: io::Write>(
+	cdrom: &mut CdRomWriter<W>, sectors_len: u32,
+) -> Result<(), dcb_cdrom_xa::writer::WriteSectorError> {
+	for _ in 0..sectors_len {
+		cdrom.write_sector([0; 0x800], SubHeader::new())?;
+	}
+
+	Ok(())
+}
+
+/// Returns the subheader for a data sector
+///
+/// This is the sub-header written for every file sector, unless others are given.
+#[must_use]
+pub fn data_subheader(is_last: bool) -> SubHeader {
+	let submode = match is_last {
+		true => SubMode::DATA | SubMode::END_OF_RECORD | SubMode::END_OF_FILE,
+		false => SubMode::DATA,
+	};
+
+	SubHeader {
+		file:        0,
+		channel:     0,
+		submode,
+		coding_info: 0,
+	}
+}
+
+/// Returns the number of sectors needed for `size` bytes
+fn sector_count(size: u32) -> u32 {
+	size / 0x800 + u32::from(size % 0x800 != 0)
+}
@@ -0,0 +1,71 @@
+//! Errors
+
+// Imports
+use crate::{entry, string::FileString};
+use dcb_cdrom_xa::writer::WriteSectorError;
+use std::io;
+
+/// Error type for [`FilesystemWriter::write`](super::FilesystemWriter::write)
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError<E: std::error::Error + 'static> {
+	/// Unable to get entry
+	#[error("Unable to get entry")]
+	GetEntry(#[source] E),
+
+	/// Filesystem was too large
+	#[error("Filesystem was too large")]
+	TooLarge,
+
+	/// Unable to write volume descriptor
+	#[error("Unable to write volume descriptor")]
+	WriteVolumeDescriptor(#[source] WriteSectorError),
+
+	/// Unable to write path table
+	#[error("Unable to write path table")]
+	WritePathTable(#[source] WriteSectorError),
+
+	/// Unable to write directory entry
+	#[error("Unable to write directory entry")]
+	WriteDirEntry(#[source] entry::ToWriterError),
+
+	/// Unable to write directory
+	#[error("Unable to write directory")]
+	WriteDir(#[source] WriteSectorError),
+
+	/// Unable to write empty sectors
+	#[error("Unable to write empty sectors")]
+	WriteGap(#[source] WriteSectorError),
+
+	/// Unable to write file
+	#[error("Unable to write file {name}")]
+	WriteFile {
+		/// File name
+		name: FileString,
+
+		/// Underlying error
+		#[source]
+		err: WriteFileError,
+	},
+}
+
+/// Error type for writing a file
+#[derive(Debug, thiserror::Error)]
+pub enum WriteFileError {
+	/// Unable to read file
+	#[error("Unable to read file")]
+	Read(#[source] io::Error),
+
+	/// Unable to write sector
+	#[error("Unable to write sector")]
+	WriteSector(#[source] WriteSectorError),
+
+	/// Wrong number of sub-headers
+	#[error("File has {sectors_len} sectors, but {subheaders_len} sub-headers were given")]
+	SubHeadersLen {
+		/// Number of sectors
+		sectors_len: u32,
+
+		/// Number of sub-headers
+		subheaders_len: usize,
+	},
+}
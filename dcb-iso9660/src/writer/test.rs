@@ -0,0 +1,223 @@
+//! Tests
+
+// Imports
+use super::*;
+use crate::FilesystemReader;
+use dcb_cdrom_xa::CdRomReader;
+use std::{convert::Infallible, io::Read};
+
+/// Directory lister over in-memory entries
+struct Lister(Vec<DirEntryWriter<Lister>>);
+
+impl DirWriterLister for Lister {
+	type Error = Infallible;
+	type FileReader = io::Cursor<Vec<u8>>;
+}
+
+impl IntoIterator for Lister {
+	type IntoIter = std::iter::Map<std::vec::IntoIter<DirEntryWriter<Self>>, fn(DirEntryWriter<Self>) -> Self::Item>;
+	type Item = Result<DirEntryWriter<Self>, Infallible>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.0.into_iter().map(Ok as fn(_) -> _)
+	}
+}
+
+/// Date used for all entries
+const DATE: DirDateTime = DirDateTime {
+	year:      100,
+	month:     1,
+	day:       1,
+	hour:      0,
+	minutes:   0,
+	seconds:   0,
+	time_zone: 0,
+};
+
+/// Creates a file entry
+fn file(name: &str, contents: Vec<u8>, size: u32, subheaders: Option<Vec<SubHeader>>) -> DirEntryWriter<Lister> {
+	DirEntryWriter {
+		name:       FileString::from_bytes(name.as_bytes()).expect("Invalid name"),
+		date:       self::DATE,
+		system_use: vec![],
+		sector_pos: None,
+		kind:       DirEntryWriterKind::File {
+			reader: io::Cursor::new(contents),
+			size,
+			subheaders,
+		},
+	}
+}
+
+/// Creates a directory entry
+fn dir(name: &str, entries: Vec<DirEntryWriter<Lister>>) -> DirEntryWriter<Lister> {
+	DirEntryWriter {
+		name:       FileString::from_bytes(name.as_bytes()).expect("Invalid name"),
+		date:       self::DATE,
+		system_use: vec![],
+		sector_pos: None,
+		kind:       DirEntryWriterKind::Dir(Lister(entries)),
+	}
+}
+
+/// Writes a filesystem with root directory `root` and path tables at `path_table_locations`, returning the image
+fn write_fs(root: Lister, path_table_locations: Option<[u32; 4]>) -> Vec<u8> {
+	let date_time = DecDateTime::deserialize_bytes(b"0000000000000000\0").expect("Unable to create date time");
+	let info = VolumeInfo {
+		system_id:                     StrArrA::from_bytes(&[b' '; 0x20]).expect("Invalid system id"),
+		volume_id:                     StrArrD::from_bytes(&[b' '; 0x20]).expect("Invalid volume id"),
+		volume_set_id:                 StrArrD::from_bytes(&[b' '; 0x80]).expect("Invalid volume set id"),
+		publisher_id:                  StrArrA::from_bytes(&[b' '; 0x80]).expect("Invalid publisher id"),
+		data_preparer_id:              StrArrA::from_bytes(&[b' '; 0x80]).expect("Invalid data preparer id"),
+		application_id:                StrArrA::from_bytes(&[b' '; 0x80]).expect("Invalid application id"),
+		copyright_file_id:             StrArrD::from_bytes(&[b' '; 0x26]).expect("Invalid copyright file id"),
+		abstract_file_id:              StrArrD::from_bytes(&[b' '; 0x24]).expect("Invalid abstract file id"),
+		bibliographic_file_id:         StrArrD::from_bytes(&[b' '; 0x25]).expect("Invalid bibliographic file id"),
+		volume_creation_date_time:     date_time,
+		volume_modification_date_time: date_time,
+		volume_expiration_date_time:   date_time,
+		volume_effective_date_time:    date_time,
+		application_use:               [0; 0x200],
+		root_date:                     self::DATE,
+		root_system_use:               vec![],
+		root_sector_pos:               None,
+		path_table_locations,
+		volume_space_size:             None,
+	};
+
+	// Note: The system area is left empty
+	let mut image = vec![];
+	let mut writer = CdRomWriter::new(&mut image, 0);
+	for _ in 0..FilesystemWriter::<Lister>::PRIMARY_VOLUME_DESCRIPTOR_SECTOR {
+		writer
+			.write_sector([0; 0x800], SubHeader::new())
+			.expect("Unable to write system area");
+	}
+	FilesystemWriter::new(info, root)
+		.write(&mut writer)
+		.expect("Unable to write filesystem");
+
+	image
+}
+
+#[test]
+fn write_mixed_forms() {
+	// Create a file with a form 2 sector between form 1 sectors, with the last one incomplete
+	let contents = vec![[0x12; 0x800].as_slice(), &[0x34; 0x914], &[0x56; 0x700]].concat();
+	let subheaders = vec![
+		SubHeader {
+			file:        1,
+			channel:     0,
+			submode:     SubMode::DATA,
+			coding_info: 0,
+		},
+		SubHeader {
+			file:        1,
+			channel:     1,
+			submode:     SubMode::VIDEO | SubMode::REAL_TIME | SubMode::FORM,
+			coding_info: 0,
+		},
+		SubHeader {
+			file:        1,
+			channel:     0,
+			submode:     SubMode::DATA | SubMode::END_OF_RECORD | SubMode::END_OF_FILE,
+			coding_info: 0,
+		},
+	];
+	let size = 3 * 0x800 - 0x100;
+	let image = self::write_fs(
+		Lister(vec![
+			self::file("A.STR;1", contents.clone(), size, Some(subheaders.clone())),
+			self::file("B.BIN;1", vec![0x78; 0x801], 0x801, None),
+		]),
+		None,
+	);
+
+	// Then read it back and check the data and sub-headers of every sector
+	let mut cdrom = CdRomReader::new(io::Cursor::new(image));
+	let fs = FilesystemReader::new(&mut cdrom).expect("Unable to read filesystem");
+	let default_subheaders = vec![self::data_subheader(false), self::data_subheader(true)];
+	let files = vec![
+		("A.STR", contents, size, subheaders),
+		("B.BIN", vec![0x78; 0x801], 0x801, default_subheaders),
+	];
+	for (path, contents, size, subheaders) in files {
+		let entry = fs.find(&mut cdrom, path).expect("Unable to find file");
+		assert_eq!(entry.size, size);
+
+		let mut read = vec![];
+		entry
+			.read_file(&mut cdrom)
+			.expect("Unable to open file")
+			.read_to_end(&mut read)
+			.expect("Unable to read file");
+		assert_eq!(read, contents);
+
+		for (sector_idx, subheader) in (0..).zip(subheaders) {
+			let sector = cdrom
+				.read_nth_sector(u64::from(entry.sector_pos) + sector_idx)
+				.expect("Unable to read sector");
+			assert_eq!(sector.header.subheader, subheader);
+		}
+	}
+}
+
+#[test]
+fn write_read_round_trip() {
+	// Create a directory with enough files to span 2 sectors, along with a file in the root
+	let file_contents = |idx: u8| vec![idx; 0x100 * usize::from(idx)];
+	let files = (0..60)
+		.map(|idx| {
+			let contents = file_contents(idx);
+			let size = u32::try_from(contents.len()).expect("File size didn't fit into a `u32`");
+			self::file(&format!("FILE{idx:02}.BIN;1"), contents, size, None)
+		})
+		.collect();
+	let image = self::write_fs(
+		Lister(vec![
+			self::dir("DIR", files),
+			self::file("ROOT.BIN;1", vec![0xff; 0x1234], 0x1234, None),
+		]),
+		None,
+	);
+
+	// Then read it back
+	let mut cdrom = CdRomReader::new(io::Cursor::new(image));
+	let fs = FilesystemReader::new(&mut cdrom).expect("Unable to read filesystem");
+
+	let dir = fs.find(&mut cdrom, "DIR").expect("Unable to find directory");
+	assert!(dir.is_dir());
+	assert_eq!(dir.size, 2 * 0x800, "Directory didn't span 2 sectors");
+	let dir = dir.read_dir(&mut cdrom).expect("Unable to read directory");
+	assert_eq!(dir.entries().len(), 60);
+
+	let mut read_file = |path: &str| {
+		let mut contents = vec![];
+		fs.open(&mut cdrom, path)
+			.expect("Unable to open file")
+			.read_to_end(&mut contents)
+			.expect("Unable to read file");
+		contents
+	};
+	for idx in 0..60 {
+		assert_eq!(read_file(&format!("DIR/FILE{idx:02}.BIN")), file_contents(idx));
+	}
+	assert_eq!(read_file("ROOT.BIN;1"), vec![0xff; 0x1234]);
+}
+
+#[test]
+fn write_no_optional_path_tables() {
+	let image = self::write_fs(
+		Lister(vec![self::file("A.BIN;1", vec![0x12; 0x10], 0x10, None)]),
+		Some([0x12, 0, 0x13, 0]),
+	);
+
+	// Make sure the optional path tables are still missing
+	let mut cdrom = CdRomReader::new(io::Cursor::new(image));
+	let fs = FilesystemReader::new(&mut cdrom).expect("Unable to read filesystem");
+	let primary_volume_descriptor = fs.primary_volume_descriptor();
+	assert_eq!(primary_volume_descriptor.path_table_location, 0x12);
+	assert_eq!(primary_volume_descriptor.path_table_opt_location, 0);
+	assert_eq!(primary_volume_descriptor.path_table_msb_location, 0x13);
+	assert_eq!(primary_volume_descriptor.path_table_msb_opt_location, 0);
+}
@@ -12,6 +12,12 @@ pub struct Args {
 
 	/// The bios path
 	pub bios_path: PathBuf,
+
+	/// Game functions path
+	pub game_funcs_path: Option<PathBuf>,
+
+	/// Address to serve gdb on
+	pub gdb_addr: Option<String>,
 }
 
 impl Args {
@@ -19,6 +25,8 @@ impl Args {
 	pub fn new() -> Self {
 		const GAME_FILE_STR: &str = "game-file";
 		const BIOS_STR: &str = "bios";
+		const GAME_FUNCS_PATH_STR: &str = "game-funcs-path";
+		const GDB_STR: &str = "gdb";
 
 		// Get all matches from cli
 		let matches = ClapApp::new("Dcb Debugger")
@@ -39,6 +47,23 @@ impl Args {
 					.required(true)
 					.long("bios"),
 			)
+			.arg(
+				ClapArg::with_name(GAME_FUNCS_PATH_STR)
+					.long(GAME_FUNCS_PATH_STR)
+					.help("Sets the path of the game funcs")
+					.long_help("Sets the path of the game funcs, used to resolve names of functions and labels")
+					.takes_value(true),
+			)
+			.arg(
+				ClapArg::with_name(GDB_STR)
+					.help("Serves gdb on an address")
+					.long_help(
+						"Serves gdb on an address, such as `127.0.0.1:9123`, instead of running the repl. Use `set \
+						 architecture mips:3000` and `target remote <addr>` from `gdb-multiarch` to connect",
+					)
+					.takes_value(true)
+					.long(GDB_STR),
+			)
 			.get_matches();
 
 		let game_path = matches
@@ -50,7 +75,15 @@ impl Args {
 			.map(PathBuf::from)
 			.expect("Unable to get required argument");
 
+		let game_funcs_path = matches.value_of(GAME_FUNCS_PATH_STR).map(PathBuf::from);
+		let gdb_addr = matches.value_of(GDB_STR).map(str::to_owned);
+
 		// Return the cli data
-		Self { game_path, bios_path }
+		Self {
+			game_path,
+			bios_path,
+			game_funcs_path,
+			gdb_addr,
+		}
 	}
 }
@@ -0,0 +1,156 @@
+//! Backtraces
+//!
+//! Reconstructs the call stack by scanning the prologue of each function
+//! for the size of it's stack frame and where it saved `$ra`.
+
+// Modules
+#[cfg(test)]
+mod test;
+
+// Imports
+use crate::{symbols::Symbols, ExecState};
+use dcb_exe::{
+	inst::{
+		basic::{self, alu, jmp, store},
+		Register,
+	},
+	Func, Pos,
+};
+
+/// Maximum number of frames in a backtrace
+pub const MAX_FRAMES: usize = 64;
+
+/// Stack frame
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Frame {
+	/// Position within the frame's function
+	pub pc: Pos,
+
+	/// Stack pointer of the frame
+	pub sp: u32,
+}
+
+/// Returns the backtrace of `state`, starting from the current frame.
+///
+/// Stops at the first frame outside of a known function, or whose return
+/// address can't be found.
+pub fn backtrace(state: &ExecState, symbols: &Symbols) -> Vec<Frame> {
+	let mut frames = vec![];
+	let mut frame = Frame {
+		pc: state.pc,
+		sp: state[Register::Sp],
+	};
+	while frames.len() < MAX_FRAMES {
+		frames.push(frame);
+
+		let func = match symbols.func_table().get_containing(frame.pc) {
+			Some(func) => func,
+			None => break,
+		};
+		let prologue = Prologue::scan(|pos| state.inst_at(pos), func, frame.pc);
+
+		// Note: If `$ra` wasn't saved yet, only the current frame may still have it in the register.
+		let ret = match prologue.ra_offset {
+			Some(offset) => match state.peek_word(Pos(frame.sp) + offset) {
+				Some(ret) => ret,
+				None => break,
+			},
+			None if frames.len() == 1 => state[Register::Ra],
+			None => break,
+		};
+		if ret == 0 {
+			break;
+		}
+
+		// Note: The return address points past the delay slot of the call
+		frame = Frame {
+			pc: Pos(ret) - 8u32,
+			sp: frame.sp.wrapping_add(prologue.frame_size),
+		};
+	}
+
+	frames
+}
+
+/// Function prologue
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+struct Prologue {
+	/// Stack frame size
+	frame_size: u32,
+
+	/// Offset of the saved `$ra` from the stack pointer
+	ra_offset: Option<i32>,
+}
+
+impl Prologue {
+	/// Scans the prologue of `func`, considering only instructions before `pc`, as the
+	/// rest weren't executed yet.
+	///
+	/// Any epilogue before `pc` is also considered, so that positions after the
+	/// frame is popped, such as the delay slot of `jr $ra`, use the caller's frame.
+	fn scan(inst_at: impl Fn(Pos) -> Option<basic::Inst>, func: &Func, pc: Pos) -> Self {
+		let mut prologue = Self {
+			frame_size: 0,
+			ra_offset:  None,
+		};
+
+		// Note: Functions may have several epilogues, so once one returns, the code after
+		//       it's delay slot belongs to a different path, which still has the whole frame.
+		let mut before_epilogue = None;
+		let mut ret_pos = None;
+
+		let mut pos = func.start_pos;
+		while pos < pc && pos < func.end_pos {
+			match inst_at(pos) {
+				// `addiu $sp, $sp, -size`
+				Some(basic::Inst::Alu(alu::Inst::Imm(alu::imm::Inst {
+					dst: Register::Sp,
+					lhs: Register::Sp,
+					kind: alu::imm::Kind::Add(offset) | alu::imm::Kind::AddUnsigned(offset),
+				}))) if offset < 0 => prologue.frame_size = u32::from(offset.unsigned_abs()),
+
+				// `addiu $sp, $sp, size`
+				Some(basic::Inst::Alu(alu::Inst::Imm(alu::imm::Inst {
+					dst: Register::Sp,
+					lhs: Register::Sp,
+					kind: alu::imm::Kind::Add(offset) | alu::imm::Kind::AddUnsigned(offset),
+				}))) => {
+					before_epilogue.get_or_insert(prologue);
+					prologue.frame_size = prologue.frame_size.saturating_sub(u32::from(offset.unsigned_abs()));
+
+					// Note: Once the frame is popped, `$ra` was already restored
+					if prologue.frame_size == 0 {
+						prologue.ra_offset = None;
+					}
+				},
+
+				// `sw $ra, offset($sp)`
+				Some(basic::Inst::Store(store::Inst {
+					value: Register::Ra,
+					addr: Register::Sp,
+					offset,
+					kind: store::Kind::Word,
+				})) => prologue.ra_offset = Some(i32::from(offset)),
+
+				// `jr $ra`
+				Some(basic::Inst::Jmp(jmp::Inst::Reg(jmp::reg::Inst {
+					target: Register::Ra,
+					kind: jmp::reg::Kind::Jump,
+				}))) => ret_pos = Some(pos),
+
+				_ => (),
+			}
+
+			pos += 4u32;
+
+			if ret_pos == Some(pos - 8u32) {
+				if let Some(before_epilogue) = before_epilogue.take() {
+					prologue = before_epilogue;
+				}
+				ret_pos = None;
+			}
+		}
+
+		prologue
+	}
+}
@@ -0,0 +1,164 @@
+//! Tests
+
+// Imports
+use super::*;
+use dcb_exe::{func::FuncKind, inst::basic::load};
+use std::{collections::BTreeMap, convert::TryFrom};
+
+/// Start of the test function
+const START_POS: Pos = Pos(0x8001_0000);
+
+/// Creates a function over `insts`
+fn func(insts: &[basic::Inst]) -> Func {
+	Func {
+		name:               "func".to_owned(),
+		signature:          String::new(),
+		desc:               String::new(),
+		inline_comments:    BTreeMap::new(),
+		block_comments:     BTreeMap::new(),
+		labels:             BTreeMap::new(),
+		inst_arg_overrides: BTreeMap::new(),
+		start_pos:          START_POS,
+		end_pos:            START_POS + 4 * insts.len(),
+		kind:               FuncKind::Known,
+	}
+}
+
+/// Scans the prologue of a function with `insts`, with `pc` as the index of the current instruction
+fn scan(insts: &[basic::Inst], pc: usize) -> Prologue {
+	let inst_at = |pos: Pos| {
+		let idx = usize::try_from((pos - START_POS) / 4).expect("Position was before the function");
+		insts.get(idx).copied()
+	};
+
+	Prologue::scan(inst_at, &self::func(insts), START_POS + 4 * pc)
+}
+
+/// `addiu $sp, $sp, offset`
+fn addiu_sp(offset: i16) -> basic::Inst {
+	basic::Inst::Alu(alu::Inst::Imm(alu::imm::Inst {
+		dst:  Register::Sp,
+		lhs:  Register::Sp,
+		kind: alu::imm::Kind::AddUnsigned(offset),
+	}))
+}
+
+/// `sw $ra, offset($sp)`
+fn sw_ra(offset: i16) -> basic::Inst {
+	basic::Inst::Store(store::Inst {
+		value: Register::Ra,
+		addr: Register::Sp,
+		offset,
+		kind: store::Kind::Word,
+	})
+}
+
+/// `lw $ra, offset($sp)`
+fn lw_ra(offset: i16) -> basic::Inst {
+	basic::Inst::Load(load::Inst {
+		value: Register::Ra,
+		addr: Register::Sp,
+		offset,
+		kind: load::Kind::Word,
+	})
+}
+
+/// `jr $ra`
+fn jr_ra() -> basic::Inst {
+	basic::Inst::Jmp(jmp::Inst::Reg(jmp::reg::Inst {
+		target: Register::Ra,
+		kind:   jmp::reg::Kind::Jump,
+	}))
+}
+
+/// `addiu $v0, $zr, 1`
+fn filler() -> basic::Inst {
+	basic::Inst::Alu(alu::Inst::Imm(alu::imm::Inst {
+		dst:  Register::V0,
+		lhs:  Register::Zr,
+		kind: alu::imm::Kind::AddUnsigned(1),
+	}))
+}
+
+/// Frame of the test functions
+const FRAME: Prologue = Prologue {
+	frame_size: 0x18,
+	ra_offset:  Some(0x10),
+};
+
+/// Popped frame of the test functions
+const POPPED: Prologue = Prologue {
+	frame_size: 0,
+	ra_offset:  None,
+};
+
+#[test]
+fn prologue() {
+	let insts = [self::addiu_sp(-0x18), self::sw_ra(0x10), self::filler()];
+
+	assert_eq!(self::scan(&insts, 0), POPPED);
+	assert_eq!(self::scan(&insts, 1), Prologue {
+		frame_size: 0x18,
+		ra_offset:  None,
+	});
+	assert_eq!(self::scan(&insts, 2), FRAME);
+	assert_eq!(self::scan(&insts, 3), FRAME);
+}
+
+#[test]
+fn epilogue_delay_slot() {
+	let insts = [
+		self::addiu_sp(-0x18),
+		self::sw_ra(0x10),
+		self::filler(),
+		self::lw_ra(0x10),
+		self::jr_ra(),
+		self::addiu_sp(0x18),
+	];
+
+	// Note: The delay slot hasn't executed yet while we're on it
+	assert_eq!(self::scan(&insts, 4), FRAME);
+	assert_eq!(self::scan(&insts, 5), FRAME);
+}
+
+#[test]
+fn epilogue_before_ret() {
+	let insts = [
+		self::addiu_sp(-0x18),
+		self::sw_ra(0x10),
+		self::filler(),
+		self::lw_ra(0x10),
+		self::addiu_sp(0x18),
+		self::jr_ra(),
+		self::filler(),
+	];
+
+	assert_eq!(self::scan(&insts, 4), FRAME);
+	assert_eq!(self::scan(&insts, 5), POPPED);
+	assert_eq!(self::scan(&insts, 6), POPPED);
+}
+
+#[test]
+fn multiple_epilogues() {
+	let insts = [
+		self::addiu_sp(-0x18),
+		self::sw_ra(0x10),
+		self::filler(),
+		self::lw_ra(0x10),
+		self::jr_ra(),
+		self::addiu_sp(0x18),
+		self::filler(),
+		self::lw_ra(0x10),
+		self::addiu_sp(0x18),
+		self::jr_ra(),
+		self::filler(),
+	];
+
+	// After the first return, the frame is back
+	assert_eq!(self::scan(&insts, 6), FRAME);
+	assert_eq!(self::scan(&insts, 8), FRAME);
+
+	// Until the second epilogue pops it
+	assert_eq!(self::scan(&insts, 9), POPPED);
+	assert_eq!(self::scan(&insts, 10), POPPED);
+}
@@ -0,0 +1,118 @@
+//! Breakpoints and watchpoints
+
+// Modules
+#[cfg(test)]
+mod test;
+
+// Imports
+use dcb_exe::Pos;
+use std::{collections::BTreeMap, ops::Range};
+
+/// Breakpoints and watchpoints
+#[derive(Clone, Default, Debug)]
+pub struct Breakpoints {
+	/// All breakpoints, with their names
+	breakpoints: BTreeMap<Pos, String>,
+
+	/// All watchpoints
+	watchpoints: Vec<Watchpoint>,
+}
+
+impl Breakpoints {
+	/// Adds a breakpoint named `name` at `pos`, returning the name of any breakpoint it replaced
+	pub fn add_breakpoint(&mut self, pos: Pos, name: String) -> Option<String> {
+		self.breakpoints.insert(pos, name)
+	}
+
+	/// Adds a watchpoint
+	pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) {
+		self.watchpoints.push(watchpoint);
+	}
+
+	/// Removes all breakpoints and watchpoints named `name`, returning if any were removed
+	pub fn remove_named(&mut self, name: &str) -> bool {
+		let (breakpoints_len, watchpoints_len) = (self.breakpoints.len(), self.watchpoints.len());
+		self.breakpoints.retain(|_, breakpoint_name| breakpoint_name != name);
+		self.watchpoints.retain(|watchpoint| watchpoint.name != name);
+
+		self.breakpoints.len() != breakpoints_len || self.watchpoints.len() != watchpoints_len
+	}
+
+	/// Removes the breakpoint at `pos`, returning it's name
+	pub fn remove_breakpoint(&mut self, pos: Pos) -> Option<String> {
+		self.breakpoints.remove(&pos)
+	}
+
+	/// Returns the name of the breakpoint at `pos`, if any
+	pub fn breakpoint_at(&self, pos: Pos) -> Option<&str> {
+		self.breakpoints.get(&pos).map(String::as_str)
+	}
+
+	/// Returns the first watchpoint triggered by an access of `len` bytes to `pos`
+	pub fn watchpoint_hit(&self, pos: Pos, len: u32, is_write: bool) -> Option<&Watchpoint> {
+		self.watchpoints.iter().find(|watchpoint| {
+			watchpoint.kind.matches(is_write) && pos < watchpoint.range.end && watchpoint.range.start < pos + len
+		})
+	}
+
+	/// Returns all breakpoints
+	pub fn breakpoints(&self) -> impl Iterator<Item = (Pos, &str)> {
+		self.breakpoints.iter().map(|(&pos, name)| (pos, name.as_str()))
+	}
+
+	/// Returns all watchpoints
+	pub fn watchpoints(&self) -> &[Watchpoint] {
+		&self.watchpoints
+	}
+}
+
+/// Watchpoint
+#[derive(Clone, Debug)]
+pub struct Watchpoint {
+	/// Name
+	pub name: String,
+
+	/// Positions watched
+	pub range: Range<Pos>,
+
+	/// Kind
+	pub kind: WatchKind,
+}
+
+/// Watchpoint kind
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(derive_more::Display)]
+pub enum WatchKind {
+	/// Reads
+	#[display(fmt = "read")]
+	Read,
+
+	/// Writes
+	#[display(fmt = "write")]
+	Write,
+
+	/// Both reads and writes
+	#[display(fmt = "access")]
+	Access,
+}
+
+impl WatchKind {
+	/// Parses a watchpoint kind from `r`, `w` or `rw`
+	pub fn parse(s: &str) -> Option<Self> {
+		match s {
+			"r" => Some(Self::Read),
+			"w" => Some(Self::Write),
+			"rw" => Some(Self::Access),
+			_ => None,
+		}
+	}
+
+	/// Returns if this kind watches reads or writes, depending on `is_write`
+	pub const fn matches(self, is_write: bool) -> bool {
+		match self {
+			Self::Read => !is_write,
+			Self::Write => is_write,
+			Self::Access => true,
+		}
+	}
+}
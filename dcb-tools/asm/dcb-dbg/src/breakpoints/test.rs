@@ -0,0 +1,59 @@
+//! Tests
+
+// Imports
+use super::*;
+
+/// Creates breakpoints with a single watchpoint over `start..end`
+fn watching(start: u32, end: u32, kind: WatchKind) -> Breakpoints {
+	let mut breakpoints = Breakpoints::default();
+	breakpoints.add_watchpoint(Watchpoint {
+		name: "watch".to_owned(),
+		range: Pos(start)..Pos(end),
+		kind,
+	});
+	breakpoints
+}
+
+#[test]
+fn watchpoint_overlap() {
+	let breakpoints = self::watching(0x8001_0004, 0x8001_0008, WatchKind::Access);
+	let hit = |pos: u32, len: u32| breakpoints.watchpoint_hit(Pos(pos), len, false).is_some();
+
+	// Accesses within the range
+	assert!(hit(0x8001_0004, 4));
+	assert!(hit(0x8001_0007, 1));
+
+	// Accesses partially overlapping the range
+	assert!(hit(0x8001_0002, 4));
+	assert!(hit(0x8001_0006, 4));
+
+	// Accesses covering the whole range
+	assert!(hit(0x8001_0000, 0x10));
+
+	// Accesses right before and after the range
+	assert!(!hit(0x8001_0000, 4));
+	assert!(!hit(0x8001_0003, 1));
+	assert!(!hit(0x8001_0008, 4));
+}
+
+#[test]
+fn watchpoint_kind() {
+	let read = self::watching(0x8001_0000, 0x8001_0004, WatchKind::Read);
+	assert!(read.watchpoint_hit(Pos(0x8001_0000), 4, false).is_some());
+	assert!(read.watchpoint_hit(Pos(0x8001_0000), 4, true).is_none());
+
+	let write = self::watching(0x8001_0000, 0x8001_0004, WatchKind::Write);
+	assert!(write.watchpoint_hit(Pos(0x8001_0000), 4, false).is_none());
+	assert!(write.watchpoint_hit(Pos(0x8001_0000), 4, true).is_some());
+}
+
+#[test]
+fn remove_named() {
+	let mut breakpoints = self::watching(0x8001_0000, 0x8001_0004, WatchKind::Access);
+	assert_eq!(breakpoints.add_breakpoint(Pos(0x8001_0000), "watch".to_owned()), None);
+
+	assert!(breakpoints.remove_named("watch"));
+	assert!(breakpoints.breakpoints().next().is_none());
+	assert!(breakpoints.watchpoints().is_empty());
+	assert!(!breakpoints.remove_named("watch"));
+}
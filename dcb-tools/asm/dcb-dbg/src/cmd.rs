@@ -0,0 +1,212 @@
+//! Commands
+
+// Modules
+#[cfg(test)]
+mod test;
+
+// Imports
+use crate::breakpoints::WatchKind;
+use anyhow::Context;
+use dcb_exe::inst::{basic::mult::MultReg, exec::bus::AccessSize, Register};
+use int_conv::Signed;
+use std::{convert::TryFrom, str::FromStr};
+
+/// Help for all commands
+pub const HELP: &str = "\
+s, step [count]               Steps `count` instructions, 1 by default
+n, next                       Steps over calls
+f, finish                     Runs until the current function returns
+c, r, continue [location]     Runs until a breakpoint, watchpoint or `location`
+b, break <location>           Adds a breakpoint at `location`
+w, watch <r|w|rw> <location> [len]
+                              Adds a watchpoint of `len` bytes at `location`, 4 by default
+d, delete <location|name>     Deletes breakpoints and watchpoints
+l, list                       Lists all breakpoints and watchpoints
+regs [register]               Prints all registers, or just `register`
+set <register> <value>        Sets `register` to `value`
+x <location> [count]          Prints `count` words at `location`, 1 by default
+poke <location> <value> [b|h|w]
+                              Writes `value` to `location`, as a word by default
+bt, backtrace                 Prints the backtrace
+h, help                       Prints this help
+q, quit                       Quits
+
+Locations may be a number, a function `func`, a label `func.label`, a label in the current
+function `.label`, optionally followed by an offset, such as `func+0x10`.
+An empty line repeats the last command.";
+
+/// Command
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Command {
+	/// Steps `count` instructions
+	Step { count: usize },
+
+	/// Steps over calls
+	Next,
+
+	/// Runs until the current function returns
+	Finish,
+
+	/// Runs until a breakpoint, watchpoint, or `until`
+	Continue { until: Option<String> },
+
+	/// Adds a breakpoint
+	Break { location: String },
+
+	/// Adds a watchpoint
+	Watch {
+		location: String,
+		len:      u32,
+		kind:     WatchKind,
+	},
+
+	/// Deletes breakpoints and watchpoints
+	Delete { name: String },
+
+	/// Lists all breakpoints and watchpoints
+	List,
+
+	/// Prints registers
+	Regs { reg: Option<RegTarget> },
+
+	/// Sets a register
+	Set { reg: RegTarget, value: u32 },
+
+	/// Prints memory
+	Examine { location: String, count: u32 },
+
+	/// Writes memory
+	Poke {
+		location: String,
+		value:    u32,
+		size:     AccessSize,
+	},
+
+	/// Prints the backtrace
+	Backtrace,
+
+	/// Prints the help
+	Help,
+
+	/// Quits
+	Quit,
+}
+
+impl Command {
+	/// Parses a command from it's arguments.
+	///
+	/// Returns `None` if there were no arguments.
+	pub fn parse<'a>(mut args: impl Iterator<Item = &'a str>) -> Result<Option<Self>, anyhow::Error> {
+		let cmd = match args.next() {
+			Some(cmd) => cmd,
+			None => return Ok(None),
+		};
+
+		let mut next_arg = |name: &str| args.next().with_context(|| format!("Missing argument `{name}`"));
+		let cmd = match cmd {
+			"s" | "step" => Self::Step {
+				count: match next_arg("count") {
+					Ok(count) => count.parse().context("Unable to parse count")?,
+					Err(_) => 1,
+				},
+			},
+			"n" | "next" => Self::Next,
+			"f" | "finish" => Self::Finish,
+			"c" | "continue" | "r" | "run" => Self::Continue {
+				until: next_arg("location").ok().map(str::to_owned),
+			},
+			"b" | "break" => Self::Break {
+				location: next_arg("location")?.to_owned(),
+			},
+			"w" | "watch" => {
+				let kind = next_arg("kind")?;
+				let kind = WatchKind::parse(kind).with_context(|| format!("Unknown watchpoint kind {kind:?}"))?;
+				let location = next_arg("location")?.to_owned();
+				let len = match next_arg("len") {
+					Ok(len) => self::parse_value(len).context("Unable to parse length")?,
+					Err(_) => 4,
+				};
+
+				Self::Watch { location, len, kind }
+			},
+			"d" | "delete" => Self::Delete {
+				name: next_arg("location")?.to_owned(),
+			},
+			"l" | "list" => Self::List,
+			"regs" => Self::Regs {
+				reg: next_arg("register").ok().map(str::parse).transpose()?,
+			},
+			"set" => Self::Set {
+				reg:   next_arg("register")?.parse()?,
+				value: self::parse_value(next_arg("value")?).context("Unable to parse value")?,
+			},
+			"x" => Self::Examine {
+				location: next_arg("location")?.to_owned(),
+				count:    match next_arg("count") {
+					Ok(count) => self::parse_value(count).context("Unable to parse count")?,
+					Err(_) => 1,
+				},
+			},
+			"poke" => Self::Poke {
+				location: next_arg("location")?.to_owned(),
+				value:    self::parse_value(next_arg("value")?).context("Unable to parse value")?,
+				size:     match next_arg("size") {
+					Ok("b") => AccessSize::Byte,
+					Ok("h") => AccessSize::HalfWord,
+					Ok("w") | Err(_) => AccessSize::Word,
+					Ok(size) => anyhow::bail!("Unknown size {size:?}"),
+				},
+			},
+			"bt" | "backtrace" => Self::Backtrace,
+			"h" | "help" => Self::Help,
+			"q" | "quit" => Self::Quit,
+			_ => anyhow::bail!("Unknown command {cmd:?}, see `help`"),
+		};
+
+		if let Some(arg) = args.next() {
+			anyhow::bail!("Unexpected argument {arg:?}");
+		}
+
+		Ok(Some(cmd))
+	}
+}
+
+/// Register target of a command
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(derive_more::Display)]
+pub enum RegTarget {
+	/// Program counter
+	#[display(fmt = "pc")]
+	Pc,
+
+	/// Register
+	#[display(fmt = "{}", _0)]
+	Reg(Register),
+
+	/// Mult register
+	#[display(fmt = "{}", _0)]
+	Mult(MultReg),
+}
+
+impl FromStr for RegTarget {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"pc" | "$pc" => Ok(Self::Pc),
+			"$lo" => Ok(Self::Mult(MultReg::Lo)),
+			"$hi" => Ok(Self::Mult(MultReg::Hi)),
+			_ => Register::from_str(s)
+				.map(Self::Reg)
+				.map_err(|()| anyhow::anyhow!("Unknown register {s:?}")),
+		}
+	}
+}
+
+/// Parses a value that fits into a `u32`, either signed or unsigned
+pub fn parse_value(s: &str) -> Result<u32, anyhow::Error> {
+	let value = crate::parse_number(s)?;
+	u32::try_from(value)
+		.or_else(|_| i32::try_from(value).map(i32::as_unsigned))
+		.context("Value didn't fit into a `u32`")
+}
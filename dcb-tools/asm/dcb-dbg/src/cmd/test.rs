@@ -0,0 +1,98 @@
+//! Tests
+
+// Imports
+use super::*;
+
+/// Parses `cmd`
+fn parse(cmd: &str) -> Result<Option<Command>, anyhow::Error> {
+	Command::parse(cmd.split_whitespace())
+}
+
+#[test]
+fn parse_empty() {
+	assert_eq!(self::parse("").expect("Unable to parse command"), None);
+}
+
+#[test]
+fn parse_defaults() {
+	assert_eq!(
+		self::parse("s").expect("Unable to parse command"),
+		Some(Command::Step { count: 1 })
+	);
+	assert_eq!(
+		self::parse("c").expect("Unable to parse command"),
+		Some(Command::Continue { until: None })
+	);
+	assert_eq!(
+		self::parse("watch w func").expect("Unable to parse command"),
+		Some(Command::Watch {
+			location: "func".to_owned(),
+			len:      4,
+			kind:     WatchKind::Write,
+		})
+	);
+	assert_eq!(
+		self::parse("x func.label+4").expect("Unable to parse command"),
+		Some(Command::Examine {
+			location: "func.label+4".to_owned(),
+			count:    1,
+		})
+	);
+	assert_eq!(
+		self::parse("poke 0x80010000 1").expect("Unable to parse command"),
+		Some(Command::Poke {
+			location: "0x80010000".to_owned(),
+			value:    1,
+			size:     AccessSize::Word,
+		})
+	);
+}
+
+#[test]
+fn parse_args() {
+	assert_eq!(
+		self::parse("step 10").expect("Unable to parse command"),
+		Some(Command::Step { count: 10 })
+	);
+	assert_eq!(
+		self::parse("w rw .label 0x10").expect("Unable to parse command"),
+		Some(Command::Watch {
+			location: ".label".to_owned(),
+			len:      0x10,
+			kind:     WatchKind::Access,
+		})
+	);
+	assert_eq!(
+		self::parse("set $t0 -1").expect("Unable to parse command"),
+		Some(Command::Set {
+			reg:   RegTarget::Reg(Register::T0),
+			value: 0xffff_ffff,
+		})
+	);
+	assert_eq!(
+		self::parse("regs $hi").expect("Unable to parse command"),
+		Some(Command::Regs {
+			reg: Some(RegTarget::Mult(MultReg::Hi)),
+		})
+	);
+	assert_eq!(
+		self::parse("poke func 0xff b").expect("Unable to parse command"),
+		Some(Command::Poke {
+			location: "func".to_owned(),
+			value:    0xff,
+			size:     AccessSize::Byte,
+		})
+	);
+}
+
+#[test]
+fn parse_errors() {
+	assert!(self::parse("unknown").is_err());
+	assert!(self::parse("b").is_err());
+	assert!(self::parse("w x func").is_err());
+	assert!(self::parse("set $t0").is_err());
+	assert!(self::parse("set $xx 0").is_err());
+	assert!(self::parse("poke func 0 q").is_err());
+	assert!(self::parse("n 1").is_err());
+	assert!(self::parse("set $t0 0x100000000").is_err());
+}
@@ -0,0 +1,423 @@
+//! Debugger
+
+// Imports
+use crate::{
+	backtrace,
+	breakpoints::{Breakpoints, Watchpoint},
+	cmd::{self, Command, RegTarget},
+	symbols::Symbols,
+	ExecResult, ExecState, JumpTarget,
+};
+use anyhow::Context;
+use dcb_exe::{
+	inst::{
+		basic::{self, jmp, mult::MultReg},
+		Register,
+	},
+	Pos,
+};
+use itertools::Itertools;
+use std::fmt;
+
+/// Debugger
+pub struct Debugger {
+	/// Execution state
+	pub exec_state: ExecState,
+
+	/// Symbols
+	pub symbols: Symbols,
+
+	/// Breakpoints and watchpoints
+	pub breakpoints: Breakpoints,
+}
+
+impl Debugger {
+	/// Creates a new debugger
+	pub fn new(exec_state: ExecState, symbols: Symbols) -> Self {
+		Self {
+			exec_state,
+			symbols,
+			breakpoints: Breakpoints::default(),
+		}
+	}
+
+	/// Runs a command.
+	///
+	/// Returns `false` if the debugger should quit.
+	pub fn run_cmd(&mut self, cmd: &Command) -> Result<bool, anyhow::Error> {
+		match *cmd {
+			Command::Step { count } => {
+				for _ in 0..count {
+					let (_, reason) = self.step(true)?;
+					if let Some(reason) = reason {
+						println!("{reason}");
+						break;
+					}
+				}
+			},
+
+			Command::Next => {
+				let pc = self.exec_state.pc;
+				match self.exec_state.inst_at(pc) {
+					// Note: We check the stack pointer, in case the function recursively calls itself
+					Some(inst) if self::is_call(&inst) => {
+						let (ret, sp) = (pc + 8u32, self.exec_state[Register::Sp]);
+						self.run_until(|state, _| state.pc == ret && state[Register::Sp] >= sp)?;
+					},
+					_ => {
+						let (_, reason) = self.step(true)?;
+						if let Some(reason) = reason {
+							println!("{reason}");
+						}
+					},
+				}
+			},
+
+			Command::Finish => {
+				let func = self
+					.symbols
+					.func_table()
+					.get_containing(self.exec_state.pc)
+					.map(|func| (func.name.clone(), func.start_pos..func.end_pos));
+				if let Some((name, _)) = &func {
+					println!("Running until {name} returns");
+				}
+
+				// Note: We stop once we return, or jump outside the function without having called anything.
+				let (mut depth, mut returned) = (0_usize, false);
+				self.run_until(|state, inst| {
+					match inst {
+						Some(inst) if self::is_call(&inst) => depth += 1,
+						Some(inst) if self::is_return(&inst) => match depth.checked_sub(1) {
+							Some(new_depth) => depth = new_depth,
+							None => returned = true,
+						},
+						_ => (),
+					}
+
+					let left_func = depth == 0 && func.as_ref().map_or(false, |(_, range)| !range.contains(&state.pc));
+					state.jump_target == JumpTarget::None && (returned || left_func)
+				})?;
+			},
+
+			Command::Continue { ref until } => {
+				let until = until.as_deref().map(|location| self.resolve(location)).transpose()?;
+				self.run_until(|state, _| Some(state.pc) == until)?;
+			},
+
+			Command::Break { ref location } => {
+				let pos = self.resolve(location)?;
+				if let Some(prev) = self.breakpoints.add_breakpoint(pos, location.clone()) {
+					println!("Replaced breakpoint {prev}");
+				}
+				println!("Breakpoint {location} at {pos}");
+			},
+
+			Command::Watch {
+				ref location,
+				len,
+				kind,
+			} => {
+				let start = self.resolve(location)?;
+				self.breakpoints.add_watchpoint(Watchpoint {
+					name: location.clone(),
+					range: start..(start + len),
+					kind,
+				});
+				println!("Watchpoint {location} on {kind} of {len:#x} bytes at {start}");
+			},
+
+			Command::Delete { ref name } => match self.breakpoints.remove_named(name) {
+				true => println!("Deleted {name}"),
+				false => {
+					let pos = self.resolve(name)?;
+					let name = self
+						.breakpoints
+						.remove_breakpoint(pos)
+						.with_context(|| format!("No breakpoint or watchpoint named {name:?} or at {pos}"))?;
+					println!("Deleted {name}");
+				},
+			},
+
+			Command::List => {
+				for (pos, name) in self.breakpoints.breakpoints() {
+					println!("Breakpoint {name} at {pos}");
+				}
+				for watchpoint in self.breakpoints.watchpoints() {
+					println!(
+						"Watchpoint {} on {} at {}..{}",
+						watchpoint.name, watchpoint.kind, watchpoint.range.start, watchpoint.range.end
+					);
+				}
+			},
+
+			Command::Regs { reg: Some(reg) } => println!("{reg}: {:#010x}", self.load_reg(reg)),
+			Command::Regs { reg: None } => self.print_regs(),
+
+			Command::Set { reg, value } => match reg {
+				RegTarget::Pc => {
+					self.exec_state.pc = Pos(value);
+					self.exec_state.jump_target = JumpTarget::None;
+				},
+				RegTarget::Reg(Register::Zr) => anyhow::bail!("Cannot set `$zr`"),
+				RegTarget::Reg(reg) => self.exec_state[reg] = value,
+				RegTarget::Mult(reg) => self.exec_state[reg] = value,
+			},
+
+			Command::Examine { ref location, count } => {
+				let start = self.resolve(location)?;
+				for idx in 0..count {
+					let pos = start + idx.wrapping_mul(4);
+					match self.exec_state.peek_word(pos) {
+						Some(value) => println!("[{pos:010}] {value:#010x}"),
+						None => println!("[{pos:010}] ?"),
+					}
+				}
+			},
+
+			Command::Poke {
+				ref location,
+				value,
+				size,
+			} => {
+				let pos = self.resolve(location)?;
+				self.exec_state
+					.bus
+					.write(pos, size, value)
+					.with_context(|| format!("Unable to write {size} to {pos}"))?;
+			},
+
+			Command::Backtrace => {
+				for (idx, frame) in backtrace::backtrace(&self.exec_state, &self.symbols).iter().enumerate() {
+					let name = self.symbols.describe(frame.pc).unwrap_or_else(|| "??".to_owned());
+					println!("#{idx} {} in {name} (sp: {:#010x})", frame.pc, frame.sp);
+				}
+			},
+
+			Command::Help => println!("{}", cmd::HELP),
+			Command::Quit => return Ok(false),
+		}
+
+		Ok(true)
+	}
+
+	/// Executes a single instruction, printing it and it's results if `verbose`.
+	///
+	/// Returns the executed instruction and the reason to stop, if any.
+	pub fn step(&mut self, verbose: bool) -> Result<(Option<basic::Inst>, Option<StopReason>), anyhow::Error> {
+		let pc = self.exec_state.pc;
+		if verbose {
+			self.print_inst(pc);
+		}
+
+		let step = self
+			.exec_state
+			.exec()
+			.with_context(|| format!("Unable to execute instruction at {pc}"))?;
+		if verbose {
+			self::print_results(&step.results);
+		}
+		if let Some(exception) = step.exception {
+			println!("Exception at {pc}: {exception}");
+		}
+
+		// Check if we hit any watchpoints or the breakpoints at the next instruction
+		let watchpoint = step.results.iter().find_map(|result| {
+			let (pos, len, is_write) = self::mem_access(result)?;
+			let watchpoint = self.breakpoints.watchpoint_hit(pos, len, is_write)?;
+			Some(StopReason::Watchpoint {
+				name: watchpoint.name.clone(),
+				pos,
+				is_write,
+			})
+		});
+		let breakpoint = self
+			.breakpoints
+			.breakpoint_at(self.exec_state.pc)
+			.map(|name| StopReason::Breakpoint { name: name.to_owned() });
+
+		Ok((step.inst, watchpoint.or(breakpoint)))
+	}
+
+	/// Runs until `should_stop` returns `true` after an instruction, or until a breakpoint
+	/// or watchpoint is hit.
+	fn run_until(
+		&mut self, mut should_stop: impl FnMut(&ExecState, Option<basic::Inst>) -> bool,
+	) -> Result<(), anyhow::Error> {
+		loop {
+			let (inst, reason) = self.step(false)?;
+			if let Some(reason) = reason {
+				println!("{reason}");
+				break;
+			}
+			if should_stop(&self.exec_state, inst) {
+				break;
+			}
+		}
+
+		self.print_location();
+		Ok(())
+	}
+
+	/// Resolves a location
+	fn resolve(&self, location: &str) -> Result<Pos, anyhow::Error> {
+		self.symbols
+			.resolve(location, self.exec_state.pc)
+			.with_context(|| format!("Unable to resolve location {location:?}"))
+	}
+
+	/// Loads a register
+	fn load_reg(&self, reg: RegTarget) -> u32 {
+		match reg {
+			RegTarget::Pc => self.exec_state.pc.0,
+			RegTarget::Reg(reg) => self.exec_state[reg],
+			RegTarget::Mult(reg) => self.exec_state[reg],
+		}
+	}
+
+	/// Prints all registers
+	fn print_regs(&self) {
+		println!("pc: {}", self.exec_state.pc);
+		for regs in Register::ALL_REGISTERS.chunks(4) {
+			let regs = regs
+				.iter()
+				.map(|&reg| format!("{reg}: {:#010x}", self.exec_state[reg]))
+				.join("  ");
+			println!("{regs}");
+		}
+
+		let cop0 = &self.exec_state.cop0;
+		println!(
+			"$lo: {:#010x}  $hi: {:#010x}",
+			self.exec_state[MultReg::Lo],
+			self.exec_state[MultReg::Hi]
+		);
+		println!(
+			"sr: {:#010x}  cause: {:#010x}  epc: {:#010x}",
+			cop0.sr, cop0.cause, cop0.epc
+		);
+	}
+
+	/// Prints the current position and instruction
+	pub fn print_location(&self) {
+		self.print_inst(self.exec_state.pc);
+	}
+
+	/// Prints the instruction at `pos`
+	fn print_inst(&self, pos: Pos) {
+		let name = self
+			.symbols
+			.describe(pos)
+			.map(|name| format!(" <{name}>"))
+			.unwrap_or_default();
+		match self.exec_state.inst_at(pos) {
+			Some(inst) => println!("{pos:010}{name}: {}", crate::inst_display(&inst, pos, &self.symbols)),
+			None => println!("{pos:010}{name}: ??"),
+		}
+	}
+}
+
+/// Reason for stopping
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum StopReason {
+	/// Hit a breakpoint
+	Breakpoint { name: String },
+
+	/// Hit a watchpoint
+	Watchpoint {
+		name:     String,
+		pos:      Pos,
+		is_write: bool,
+	},
+}
+
+impl fmt::Display for StopReason {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Breakpoint { name } => write!(f, "Hit breakpoint {name}"),
+			Self::Watchpoint {
+				name,
+				pos,
+				is_write: true,
+			} => write!(f, "Hit watchpoint {name}: Wrote to {pos}"),
+			Self::Watchpoint {
+				name,
+				pos,
+				is_write: false,
+			} => write!(f, "Hit watchpoint {name}: Read from {pos}"),
+		}
+	}
+}
+
+/// Returns if `inst` is a call
+fn is_call(inst: &basic::Inst) -> bool {
+	matches!(
+		inst,
+		basic::Inst::Jmp(
+			jmp::Inst::Imm(jmp::imm::Inst {
+				kind: jmp::imm::Kind::JumpLink,
+				..
+			}) | jmp::Inst::Reg(jmp::reg::Inst {
+				kind: jmp::reg::Kind::JumpLink(_),
+				..
+			})
+		)
+	)
+}
+
+/// Returns if `inst` is a return, `jr $ra`
+fn is_return(inst: &basic::Inst) -> bool {
+	matches!(
+		inst,
+		basic::Inst::Jmp(jmp::Inst::Reg(jmp::reg::Inst {
+			target: Register::Ra,
+			kind: jmp::reg::Kind::Jump,
+		}))
+	)
+}
+
+/// Returns the position, length and if it's a write, of a memory access result
+fn mem_access(result: &ExecResult) -> Option<(Pos, u32, bool)> {
+	match *result {
+		ExecResult::ReadWord { pos, .. } => Some((pos, 4, false)),
+		ExecResult::ReadHalfWord { pos, .. } => Some((pos, 2, false)),
+		ExecResult::ReadByte { pos, .. } => Some((pos, 1, false)),
+		ExecResult::WriteWord { pos, .. } => Some((pos, 4, true)),
+		ExecResult::WriteHalfWord { pos, .. } => Some((pos, 2, true)),
+		ExecResult::WriteByte { pos, .. } => Some((pos, 1, true)),
+		_ => None,
+	}
+}
+
+/// Prints all results of an instruction
+fn print_results(results: &[ExecResult]) {
+	for result in results {
+		match *result {
+			//ExecResult::ReadRegister { reg, value } if reg != Register::Zr => println!("[{reg}] {value:#x}"),
+			//ExecResult::ReadMultRegister { reg, value } => println!("[{reg}] {value:#x}"),
+			ExecResult::WroteRegister { reg, prev, value } if reg != Register::Zr && prev != value => {
+				println!("[{reg}] {prev:#x} => {value:#x}");
+			},
+			ExecResult::WroteMultRegister { reg, prev, value } if prev != value => {
+				println!("[{reg}] {prev:#x} => {value:#x}");
+			},
+			//ExecResult::ReadWord { pos, value } => println!("[{pos:010}] {value:#x}"),
+			//ExecResult::ReadHalfWord { pos, value } => println!("[{pos:010}] {value:#x}"),
+			//ExecResult::ReadByte { pos, value } => println!("[{pos:010}] {value:#x}"),
+			ExecResult::WriteWord { pos, prev, value } => self::print_write(pos, prev, value),
+			ExecResult::WriteHalfWord { pos, prev, value } => self::print_write(pos, prev, value),
+			ExecResult::WriteByte { pos, prev, value } => self::print_write(pos, prev, value),
+			ExecResult::QueuedJump { pos } => println!("=> [{pos:010}]"),
+			_ => (),
+		}
+	}
+}
+
+/// Prints a write of `value` to `pos`, if it changed the previous value
+fn print_write<T: PartialEq + fmt::LowerHex>(pos: Pos, prev: Option<T>, value: T) {
+	match prev {
+		Some(prev) if prev != value => println!("[{pos:010}] {prev:#x} => {value:#x}"),
+		Some(_) => (),
+		None => println!("[{pos:010}] => {value:#x}"),
+	}
+}
@@ -0,0 +1,553 @@
+//! Gdb remote serial protocol server
+//!
+//! Serves the execution state over the gdb remote serial protocol, so it may be
+//! debugged with `gdb-multiarch` (`set architecture mips:3000`, `target remote <addr>`)
+//! or any other front-end supporting it.
+//!
+//! Registers use gdb's `mips` numbering: 32 general purpose registers, followed by
+//! `sr`, `lo`, `hi`, `bad`, `cause` and `pc`. We don't have the floating point registers,
+//! so they're reported as unavailable.
+
+// Modules
+#[cfg(test)]
+mod test;
+
+// Imports
+use crate::{
+	breakpoints::{WatchKind, Watchpoint},
+	debugger::{Debugger, StopReason},
+	JumpTarget,
+};
+use anyhow::Context;
+use dcb_exe::{
+	inst::{basic::mult::MultReg, exec::bus::AccessSize, Register},
+	Pos,
+};
+use int_conv::Truncated;
+use std::{
+	convert::TryFrom,
+	io::{self, BufRead, BufReader, Read, Write},
+	net::{TcpListener, TcpStream, ToSocketAddrs},
+	str,
+};
+
+/// Signal for interrupts, `SIGINT`
+const SIGINT: u8 = 2;
+/// Signal for execution errors, `SIGILL`
+const SIGILL: u8 = 4;
+/// Signal for breakpoints and steps, `SIGTRAP`
+const SIGTRAP: u8 = 5;
+
+/// Number of registers we have
+const REGS_LEN: usize = 38;
+/// Index of `pc`
+const PC_IDX: usize = 37;
+/// Number of registers gdb knows for `mips`, including the floating point ones
+const ALL_REGS_LEN: usize = 73;
+
+/// Maximum number of bytes read by a single memory read
+const MAX_READ_LEN: u32 = 0x1000;
+
+/// Number of instructions executed between checks for interrupts while running
+const INTERRUPT_CHECK_STEPS: usize = 0x1000;
+
+/// Waits for gdb to connect on `addr` and serves `debugger` until it detaches
+pub fn serve(debugger: &mut Debugger, addr: impl ToSocketAddrs) -> Result<(), anyhow::Error> {
+	let listener = TcpListener::bind(addr).context("Unable to bind address")?;
+	let local_addr = listener.local_addr().context("Unable to get local address")?;
+	println!("Waiting for gdb on {local_addr}");
+
+	let (stream, peer_addr) = listener.accept().context("Unable to accept connection")?;
+	println!("Gdb connected from {peer_addr}");
+
+	let mut session = Session {
+		debugger,
+		conn: Connection::new(stream)?,
+		last_stop: format!("S{SIGTRAP:02x}"),
+	};
+	session.run()?;
+
+	println!("Gdb disconnected");
+	Ok(())
+}
+
+/// Gdb session
+struct Session<'a> {
+	/// Debugger
+	debugger: &'a mut Debugger,
+
+	/// Connection
+	conn: Connection,
+
+	/// Last stop reply
+	last_stop: String,
+}
+
+impl<'a> Session<'a> {
+	/// Runs this session until gdb detaches or disconnects
+	fn run(&mut self) -> Result<(), anyhow::Error> {
+		while let Some(packet) = self.conn.recv()? {
+			match &*packet {
+				b"D" | b"D;1" => {
+					self.conn.send(b"OK")?;
+					break;
+				},
+				b"k" | b"vKill;1" => break,
+
+				// Note: We can only stop acknowledging after acknowledging the reply
+				b"QStartNoAckMode" => {
+					self.conn.send(b"OK")?;
+					self.conn.no_ack = true;
+					continue;
+				},
+				_ => (),
+			}
+
+			let response = self.handle(&packet).unwrap_or_else(|err| {
+				log::warn!(
+					"Unable to handle packet {:?}: {err:?}",
+					String::from_utf8_lossy(&packet)
+				);
+				"E01".to_owned()
+			});
+			self.conn.send(response.as_bytes())?;
+		}
+
+		Ok(())
+	}
+
+	/// Handles a packet, returning the response
+	fn handle(&mut self, packet: &[u8]) -> Result<String, anyhow::Error> {
+		let (&cmd, args) = packet.split_first().context("Empty packet")?;
+
+		// Note: Binary writes aren't valid utf-8, so we handle them before parsing the arguments
+		if cmd == b'X' {
+			let header_len = args.iter().position(|&b| b == b':').context("Missing data")?;
+			let (pos, _) = self::parse_mem_args(str::from_utf8(&args[..header_len])?)?;
+			self.write_mem(pos, &args[(header_len + 1)..])?;
+			return Ok("OK".to_owned());
+		}
+
+		let args = str::from_utf8(args).context("Packet wasn't valid utf-8")?;
+		let response = match cmd {
+			b'?' => self.last_stop.clone(),
+
+			b'g' => (0..REGS_LEN).map(|idx| self::reg_hex(self.load_reg(idx))).collect(),
+			b'G' => {
+				let values = args.as_bytes().chunks(8);
+				for (idx, value) in values.enumerate().take(REGS_LEN) {
+					if let Some(value) = self::parse_reg_hex(value) {
+						self.store_reg(idx, value);
+					}
+				}
+				"OK".to_owned()
+			},
+			b'p' => match usize::from_str_radix(args, 16).context("Unable to parse register")? {
+				idx if idx < ALL_REGS_LEN => self::reg_hex(self.load_reg(idx)),
+				idx => anyhow::bail!("Unknown register {idx}"),
+			},
+			b'P' => {
+				let (idx, value) = args.split_once('=').context("Missing value")?;
+				let idx = usize::from_str_radix(idx, 16).context("Unable to parse register")?;
+				let value = self::parse_reg_hex(value.as_bytes()).context("Unable to parse value")?;
+				self.store_reg(idx, value);
+				"OK".to_owned()
+			},
+
+			b'm' => {
+				let (pos, len) = self::parse_mem_args(args)?;
+				let bytes = self.read_mem(pos, len.min(MAX_READ_LEN));
+				match bytes.is_empty() {
+					true => "E14".to_owned(),
+					false => self::hex(&bytes),
+				}
+			},
+			b'M' => {
+				let (header, data) = args.split_once(':').context("Missing data")?;
+				let (pos, _) = self::parse_mem_args(header)?;
+				let data = self::parse_hex_bytes(data.as_bytes()).context("Unable to parse data")?;
+				self.write_mem(pos, &data)?;
+				"OK".to_owned()
+			},
+
+			b'c' | b's' => {
+				if !args.is_empty() {
+					self.store_reg(
+						PC_IDX,
+						u32::from_str_radix(args, 16).context("Unable to parse address")?,
+					);
+				}
+				self.resume(cmd == b's')?
+			},
+
+			b'Z' | b'z' => self.update_breakpoint(cmd == b'Z', args)?,
+
+			b'H' => "OK".to_owned(),
+			b'q' => match args {
+				"Attached" => "1".to_owned(),
+				"C" => "QC1".to_owned(),
+				"fThreadInfo" => "m1".to_owned(),
+				"sThreadInfo" => "l".to_owned(),
+				_ if args.starts_with("Supported") => "PacketSize=4000;swbreak+;hwbreak+;QStartNoAckMode+".to_owned(),
+				_ => String::new(),
+			},
+
+			// Note: An empty response tells gdb the packet isn't supported
+			_ => String::new(),
+		};
+
+		Ok(response)
+	}
+
+	/// Executes a single instruction if `step`, or until we hit a breakpoint or
+	/// gdb interrupts us, returning the stop reply
+	fn resume(&mut self, step: bool) -> Result<String, anyhow::Error> {
+		let mut steps = 0_usize;
+		let reply = loop {
+			let reason = match self.debugger.step(false) {
+				Ok((_, reason)) => reason,
+				Err(err) => {
+					println!("{err:?}");
+					break format!("S{SIGILL:02x}");
+				},
+			};
+
+			match reason {
+				Some(StopReason::Breakpoint { .. }) => break format!("T{SIGTRAP:02x}swbreak:;"),
+				Some(StopReason::Watchpoint { pos, is_write, .. }) => {
+					let kind = match is_write {
+						true => "watch",
+						false => "rwatch",
+					};
+					break format!("T{SIGTRAP:02x}{kind}:{:x};", pos.0);
+				},
+				None if step => break format!("S{SIGTRAP:02x}"),
+				None => (),
+			}
+
+			steps += 1;
+			if steps % INTERRUPT_CHECK_STEPS == 0 && self.conn.interrupted()? {
+				break format!("S{SIGINT:02x}");
+			}
+		};
+
+		self.last_stop = reply.clone();
+		Ok(reply)
+	}
+
+	/// Inserts or removes a breakpoint or watchpoint from it's arguments
+	fn update_breakpoint(&mut self, insert: bool, args: &str) -> Result<String, anyhow::Error> {
+		// Note: We ignore any conditions after the kind
+		let args = args.split(';').next().unwrap_or_default();
+		let mut args = args.split(',');
+		let ty = args.next().context("Missing type")?;
+		let pos = args.next().context("Missing address")?;
+		let pos = u32::from_str_radix(pos, 16)
+			.map(Pos)
+			.context("Unable to parse address")?;
+		let len = args.next().context("Missing kind")?;
+		let len = u32::from_str_radix(len, 16).context("Unable to parse kind")?;
+
+		// Note: We don't patch memory for software breakpoints, so they're the same as hardware ones
+		let kind = match ty {
+			"0" | "1" => {
+				match insert {
+					true => self.debugger.breakpoints.add_breakpoint(pos, format!("gdb@{pos}")),
+					false => self.debugger.breakpoints.remove_breakpoint(pos),
+				};
+				return Ok("OK".to_owned());
+			},
+			"2" => WatchKind::Write,
+			"3" => WatchKind::Read,
+			"4" => WatchKind::Access,
+			_ => return Ok(String::new()),
+		};
+
+		let name = format!("gdb-{kind}@{pos}+{len:#x}");
+		match insert {
+			true => self.debugger.breakpoints.add_watchpoint(Watchpoint {
+				name,
+				range: pos..(pos + len),
+				kind,
+			}),
+			false => {
+				self.debugger.breakpoints.remove_named(&name);
+			},
+		}
+
+		Ok("OK".to_owned())
+	}
+
+	/// Loads register `idx`, if we have it
+	fn load_reg(&self, idx: usize) -> Option<u32> {
+		let state = &self.debugger.exec_state;
+		match idx {
+			0..=31 => Register::new(u32::try_from(idx).ok()?).map(|reg| state[reg]),
+			32 => Some(state.cop0.sr),
+			33 => Some(state[MultReg::Lo]),
+			34 => Some(state[MultReg::Hi]),
+			35 => Some(state.cop0.bad_vaddr),
+			36 => Some(state.cop0.cause),
+			PC_IDX => Some(state.pc.0),
+			_ => None,
+		}
+	}
+
+	/// Stores register `idx`, if we have it
+	fn store_reg(&mut self, idx: usize, value: u32) {
+		let state = &mut self.debugger.exec_state;
+		match idx {
+			// Note: `$zr` is hardwired to `0`
+			1..=31 => {
+				let reg = Register::new(u32::try_from(idx).expect("Register index didn't fit into a `u32`"))
+					.expect("Register index was invalid");
+				state[reg] = value;
+			},
+			32 => state.cop0.sr = value,
+			33 => state[MultReg::Lo] = value,
+			34 => state[MultReg::Hi] = value,
+			35 => state.cop0.bad_vaddr = value,
+			36 => state.cop0.cause = value,
+			PC_IDX => {
+				state.pc = Pos(value);
+				state.jump_target = JumpTarget::None;
+			},
+			_ => (),
+		}
+	}
+
+	/// Reads up to `len` bytes from `pos`, stopping at the first byte that can't be read
+	/// without side effects
+	fn read_mem(&self, pos: Pos, len: u32) -> Vec<u8> {
+		let bus = &self.debugger.exec_state.bus;
+		let mut bytes = vec![];
+		for offset in 0..len {
+			match bus.peek(pos + offset, AccessSize::Byte) {
+				Ok(Some(value)) => bytes.push(value.truncated()),
+				_ => break,
+			}
+		}
+
+		bytes
+	}
+
+	/// Writes `bytes` to `pos`
+	fn write_mem(&mut self, pos: Pos, bytes: &[u8]) -> Result<(), anyhow::Error> {
+		let bus = &mut self.debugger.exec_state.bus;
+		for (offset, &byte) in (0u32..).zip(bytes) {
+			bus.write(pos + offset, AccessSize::Byte, byte.into())
+				.with_context(|| format!("Unable to write to {}", pos + offset))?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Stream to gdb
+trait Stream: Read + Write + Sized {
+	/// Clones this stream, so it may be read from and written to separately
+	fn try_clone(&self) -> Result<Self, io::Error>;
+
+	/// Sets if reads should be non-blocking
+	fn set_nonblocking(&self, nonblocking: bool) -> Result<(), io::Error>;
+}
+
+impl Stream for TcpStream {
+	fn try_clone(&self) -> Result<Self, io::Error> {
+		Self::try_clone(self)
+	}
+
+	fn set_nonblocking(&self, nonblocking: bool) -> Result<(), io::Error> {
+		Self::set_nonblocking(self, nonblocking)
+	}
+}
+
+/// Connection to gdb
+struct Connection<S = TcpStream> {
+	/// Reader
+	reader: BufReader<S>,
+
+	/// Writer
+	writer: S,
+
+	/// If acknowledgments are disabled
+	no_ack: bool,
+}
+
+impl<S: Stream> Connection<S> {
+	/// Creates a new connection
+	fn new(stream: S) -> Result<Self, anyhow::Error> {
+		let writer = stream.try_clone().context("Unable to clone stream")?;
+		Ok(Self {
+			reader: BufReader::new(stream),
+			writer,
+			no_ack: false,
+		})
+	}
+
+	/// Receives the next packet, returning `None` if the connection was closed
+	fn recv(&mut self) -> Result<Option<Vec<u8>>, anyhow::Error> {
+		loop {
+			// Skip until the start of the packet
+			// Note: Any acknowledgments or interrupts while stopped can be ignored
+			loop {
+				match self.read_byte()? {
+					Some(b'$') => break,
+					Some(_) => continue,
+					None => return Ok(None),
+				}
+			}
+
+			// Then read the data until the checksum, un-escaping it
+			let mut data = vec![];
+			let mut checksum = 0_u8;
+			loop {
+				let byte = self.read_byte()?.context("Connection closed during packet")?;
+				match byte {
+					b'#' => break,
+					b'}' => {
+						let escaped = self.read_byte()?.context("Connection closed during packet")?;
+						checksum = checksum.wrapping_add(byte).wrapping_add(escaped);
+						data.push(escaped ^ 0x20);
+					},
+					_ => {
+						checksum = checksum.wrapping_add(byte);
+						data.push(byte);
+					},
+				}
+			}
+
+			let mut expected_checksum = [0; 2];
+			self.reader
+				.read_exact(&mut expected_checksum)
+				.context("Unable to read checksum")?;
+			if self.no_ack {
+				return Ok(Some(data));
+			}
+
+			// If the checksum doesn't match, ask for the packet again
+			match self::parse_hex_bytes(&expected_checksum).as_deref() == Some(&[checksum]) {
+				true => {
+					self.writer.write_all(b"+").context("Unable to write acknowledgment")?;
+					return Ok(Some(data));
+				},
+				false => self.writer.write_all(b"-").context("Unable to write acknowledgment")?,
+			}
+		}
+	}
+
+	/// Sends a packet with `data`
+	fn send(&mut self, data: &[u8]) -> Result<(), anyhow::Error> {
+		let checksum = data.iter().fold(0_u8, |checksum, &byte| checksum.wrapping_add(byte));
+		let mut packet = Vec::with_capacity(data.len() + 4);
+		packet.push(b'$');
+		packet.extend_from_slice(data);
+		write!(packet, "#{checksum:02x}").expect("Unable to write to vector");
+
+		// Then send it until gdb acknowledges it
+		loop {
+			self.writer.write_all(&packet).context("Unable to write packet")?;
+			if self.no_ack {
+				return Ok(());
+			}
+
+			match self.read_byte()? {
+				Some(b'+') => return Ok(()),
+				Some(b'-') => continue,
+				Some(byte) => {
+					log::warn!("Expected acknowledgment, found {byte:#x}");
+					return Ok(());
+				},
+				None => anyhow::bail!("Connection closed before acknowledgment"),
+			}
+		}
+	}
+
+	/// Checks, without blocking, if gdb sent an interrupt.
+	///
+	/// Also returns `true` if the connection was closed, so we stop running.
+	fn interrupted(&mut self) -> Result<bool, anyhow::Error> {
+		loop {
+			if self.reader.buffer().is_empty() {
+				self.reader
+					.get_ref()
+					.set_nonblocking(true)
+					.context("Unable to set non-blocking")?;
+				let res = self.reader.fill_buf().map(<[u8]>::len);
+				self.reader
+					.get_ref()
+					.set_nonblocking(false)
+					.context("Unable to set blocking")?;
+
+				match res {
+					Ok(0) => return Ok(true),
+					Ok(_) => (),
+					Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+					Err(err) => return Err(err).context("Unable to read from connection"),
+				}
+			}
+
+			// Note: Anything else gdb sends while we're running, such as acknowledgments, is discarded
+			let buffer = self.reader.buffer();
+			match buffer.iter().position(|&byte| byte == 0x03) {
+				Some(idx) => {
+					self.reader.consume(idx + 1);
+					return Ok(true);
+				},
+				None => {
+					let len = buffer.len();
+					self.reader.consume(len);
+				},
+			}
+		}
+	}
+
+	/// Reads a byte, returning `None` if the connection was closed
+	fn read_byte(&mut self) -> Result<Option<u8>, anyhow::Error> {
+		let mut byte = [0];
+		match self.reader.read(&mut byte).context("Unable to read from connection")? {
+			0 => Ok(None),
+			_ => Ok(Some(byte[0])),
+		}
+	}
+}
+
+/// Parses the `addr,len` arguments of memory reads and writes
+fn parse_mem_args(args: &str) -> Result<(Pos, u32), anyhow::Error> {
+	let (pos, len) = args.split_once(',').context("Missing length")?;
+	let pos = u32::from_str_radix(pos, 16)
+		.map(Pos)
+		.context("Unable to parse address")?;
+	let len = u32::from_str_radix(len, 16).context("Unable to parse length")?;
+
+	Ok((pos, len))
+}
+
+/// Returns the hex of a register, in target byte order, or `x`s, if unavailable
+fn reg_hex(value: Option<u32>) -> String {
+	match value {
+		Some(value) => self::hex(&value.to_le_bytes()),
+		None => "xxxxxxxx".to_owned(),
+	}
+}
+
+/// Parses the hex of a register, in target byte order
+fn parse_reg_hex(s: &[u8]) -> Option<u32> {
+	let bytes = self::parse_hex_bytes(s)?;
+	<[u8; 4]>::try_from(&*bytes).ok().map(u32::from_le_bytes)
+}
+
+/// Returns the hex of `bytes`
+fn hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Parses the hex of some bytes
+fn parse_hex_bytes(s: &[u8]) -> Option<Vec<u8>> {
+	s.chunks(2)
+		.map(|byte| {
+			let byte = str::from_utf8(byte).ok()?;
+			u8::from_str_radix(byte, 16).ok()
+		})
+		.collect()
+}
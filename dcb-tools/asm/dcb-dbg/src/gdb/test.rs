@@ -0,0 +1,229 @@
+//! Tests
+
+// Imports
+use super::*;
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+/// In-memory stream
+#[derive(Clone, Default, Debug)]
+struct MemStream(Rc<RefCell<MemStreamInner>>);
+
+/// In-memory stream inner state
+#[derive(Default, Debug)]
+struct MemStreamInner {
+	/// Bytes sent by gdb
+	input: VecDeque<u8>,
+
+	/// Bytes sent to gdb
+	output: Vec<u8>,
+
+	/// If gdb closed the connection
+	///
+	/// Note: Blocking reads once the input is empty also act as if the connection was closed.
+	closed: bool,
+
+	/// If reads are non-blocking
+	nonblocking: bool,
+}
+
+impl MemStream {
+	/// Adds `bytes` to the input
+	fn push_input(&self, bytes: &[u8]) {
+		self.0.borrow_mut().input.extend(bytes);
+	}
+
+	/// Takes all output
+	fn take_output(&self) -> Vec<u8> {
+		std::mem::take(&mut self.0.borrow_mut().output)
+	}
+}
+
+impl Read for MemStream {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let mut inner = self.0.borrow_mut();
+		if inner.input.is_empty() && inner.nonblocking && !inner.closed {
+			return Err(io::ErrorKind::WouldBlock.into());
+		}
+
+		let len = buf.len().min(inner.input.len());
+		for (dst, src) in buf.iter_mut().zip(inner.input.drain(..len)) {
+			*dst = src;
+		}
+		Ok(len)
+	}
+}
+
+impl Write for MemStream {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.borrow_mut().output.extend_from_slice(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+impl Stream for MemStream {
+	fn try_clone(&self) -> Result<Self, io::Error> {
+		Ok(self.clone())
+	}
+
+	fn set_nonblocking(&self, nonblocking: bool) -> Result<(), io::Error> {
+		self.0.borrow_mut().nonblocking = nonblocking;
+		Ok(())
+	}
+}
+
+/// Creates a connection where gdb sent `input`
+fn connection(input: &[u8]) -> (Connection<MemStream>, MemStream) {
+	let stream = MemStream::default();
+	stream.push_input(input);
+	let conn = Connection::new(stream.clone()).expect("Unable to create connection");
+
+	(conn, stream)
+}
+
+#[test]
+fn recv() {
+	let (mut conn, stream) = self::connection(b"+$g#67$m80010000,4#56");
+
+	assert_eq!(conn.recv().expect("Unable to receive").as_deref(), Some(&b"g"[..]));
+	assert_eq!(
+		conn.recv().expect("Unable to receive").as_deref(),
+		Some(&b"m80010000,4"[..])
+	);
+	assert_eq!(conn.recv().expect("Unable to receive"), None);
+	assert_eq!(stream.take_output(), b"++");
+}
+
+#[test]
+fn recv_escaped() {
+	// Note: `}` escapes the next byte, xor'd with `0x20`, and both count for the checksum
+	let (mut conn, stream) = self::connection(b"$X0,1:}\x03}]#79");
+
+	assert_eq!(
+		conn.recv().expect("Unable to receive").as_deref(),
+		Some(&b"X0,1:#}"[..])
+	);
+	assert_eq!(stream.take_output(), b"+");
+}
+
+#[test]
+fn recv_bad_checksum() {
+	let (mut conn, stream) = self::connection(b"$g#00$g#67");
+
+	assert_eq!(conn.recv().expect("Unable to receive").as_deref(), Some(&b"g"[..]));
+	assert_eq!(stream.take_output(), b"-+");
+}
+
+#[test]
+fn recv_no_ack() {
+	let (mut conn, stream) = self::connection(b"$g#00");
+	conn.no_ack = true;
+
+	assert_eq!(conn.recv().expect("Unable to receive").as_deref(), Some(&b"g"[..]));
+	assert!(stream.take_output().is_empty());
+}
+
+#[test]
+fn recv_closed() {
+	let (mut conn, _) = self::connection(b"$g#6");
+	assert!(conn.recv().is_err());
+}
+
+#[test]
+fn send() {
+	let (mut conn, stream) = self::connection(b"+");
+
+	conn.send(b"OK").expect("Unable to send");
+	assert_eq!(stream.take_output(), b"$OK#9a");
+}
+
+#[test]
+fn send_nak() {
+	let (mut conn, stream) = self::connection(b"-+");
+
+	conn.send(b"OK").expect("Unable to send");
+	assert_eq!(stream.take_output(), b"$OK#9a$OK#9a");
+}
+
+#[test]
+fn send_no_ack() {
+	let (mut conn, stream) = self::connection(b"");
+	conn.no_ack = true;
+
+	conn.send(b"OK").expect("Unable to send");
+	assert_eq!(stream.take_output(), b"$OK#9a");
+}
+
+#[test]
+fn send_closed() {
+	let (mut conn, _) = self::connection(b"");
+	assert!(conn.send(b"OK").is_err());
+}
+
+#[test]
+fn interrupted() {
+	let (mut conn, stream) = self::connection(b"");
+	assert!(!conn.interrupted().expect("Unable to check for interrupt"));
+
+	// Anything before the interrupt is discarded
+	stream.push_input(b"++\x03");
+	assert!(conn.interrupted().expect("Unable to check for interrupt"));
+	assert!(!conn.interrupted().expect("Unable to check for interrupt"));
+
+	// Along with anything that isn't an interrupt
+	stream.push_input(b"+-+");
+	assert!(!conn.interrupted().expect("Unable to check for interrupt"));
+	stream.push_input(b"\x03");
+	assert!(conn.interrupted().expect("Unable to check for interrupt"));
+}
+
+#[test]
+fn interrupted_keeps_packets() {
+	let (mut conn, stream) = self::connection(b"+\x03$?#3f");
+
+	assert!(conn.interrupted().expect("Unable to check for interrupt"));
+	assert_eq!(conn.recv().expect("Unable to receive").as_deref(), Some(&b"?"[..]));
+	assert_eq!(stream.take_output(), b"+");
+}
+
+#[test]
+fn interrupted_closed() {
+	let (mut conn, stream) = self::connection(b"+");
+	stream.0.borrow_mut().closed = true;
+
+	assert!(conn.interrupted().expect("Unable to check for interrupt"));
+}
+
+#[test]
+fn hex_helpers() {
+	assert_eq!(self::hex(&[]), "");
+	assert_eq!(self::hex(&[0x01, 0xab, 0xff]), "01abff");
+
+	assert_eq!(self::parse_hex_bytes(b"01abFF"), Some(vec![0x01, 0xab, 0xff]));
+	assert_eq!(self::parse_hex_bytes(b""), Some(vec![]));
+	assert_eq!(self::parse_hex_bytes(b"0g"), None);
+}
+
+#[test]
+fn reg_hex_helpers() {
+	assert_eq!(self::reg_hex(Some(0x1234_5678)), "78563412");
+	assert_eq!(self::reg_hex(None), "xxxxxxxx");
+
+	assert_eq!(self::parse_reg_hex(b"78563412"), Some(0x1234_5678));
+	assert_eq!(self::parse_reg_hex(b"7856"), None);
+	assert_eq!(self::parse_reg_hex(b"7856341200"), None);
+	assert_eq!(self::parse_reg_hex(b"xxxxxxxx"), None);
+}
+
+#[test]
+fn mem_args() {
+	assert_eq!(
+		self::parse_mem_args("80010000,10").expect("Unable to parse arguments"),
+		(Pos(0x8001_0000), 0x10)
+	);
+	assert!(self::parse_mem_args("80010000").is_err());
+	assert!(self::parse_mem_args("8001000g,10").is_err());
+}
@@ -14,20 +14,28 @@
 
 // Modules
 mod args;
+mod backtrace;
+mod breakpoints;
+mod cmd;
+mod debugger;
+mod gdb;
+mod symbols;
 
 // Imports
-use crate::args::Args;
+use crate::{args::Args, cmd::Command, debugger::Debugger, symbols::Symbols};
 use anyhow::Context;
-use byteorder::{ByteOrder, LittleEndian};
+use dcb_cdrom_xa::CdRomReader;
 use dcb_exe::{
 	inst::{
 		self,
 		basic::{self, mult::MultReg, Decode},
-		exec::{ExecCtx, ExecError, Executable},
+		exec::{bus::AccessSize, Bus, Cop0, Exception, ExecCtx, ExecError, Executable, Gte, LoadDelay, LoadTarget},
 		InstDisplay, Register,
 	},
-	Pos,
+	FuncTable, Pos,
 };
+use dcb_iso9660::FilesystemReader;
+use int_conv::Truncated;
 use itertools::{Itertools, Position};
 use std::{
 	cell::RefCell,
@@ -37,9 +45,7 @@ use std::{
 	mem,
 	ops::{Index, IndexMut},
 	path::Path,
-	slice::SliceIndex,
 };
-use zutil::TryIntoAs;
 
 fn main() -> Result<(), anyhow::Error> {
 	// Initialize the logger
@@ -53,15 +59,15 @@ fn main() -> Result<(), anyhow::Error> {
 	// Get all arguments
 	let args = Args::new();
 
-	// Setup memory
-	let mut memory = Memory::new();
+	// Setup the bus
+	let mut bus = Bus::new();
 
 	// Load the bios into memory
-	self::load_bios(&args.bios_path, &mut memory)
+	self::load_bios(&args.bios_path, &mut bus)
 		.with_context(|| format!("Unable to load bios from {}", args.bios_path.display()))?;
 
-	// Then open the game and retrieve the root directory.
-	let (mut game_file, game_root_dir) = self::load_game(&args.game_path)
+	// Then open the game and it's filesystem.
+	let (mut game_file, game_fs) = self::load_game(&args.game_path)
 		.with_context(|| format!("Unable to load game from {}", args.game_path.display()))?;
 
 	// Note: In other executables, we should read the `SYSTEM.CNF` to
@@ -69,56 +75,72 @@ fn main() -> Result<(), anyhow::Error> {
 	//       one it is and all it's data.
 
 	// Load the game executable into memory
-	self::load_game_exec(&mut game_file, &game_root_dir, &mut memory)?;
+	self::load_game_exec(&mut game_file, &game_fs, &mut bus)?;
+
+	// Load the known functions
+	let func_table = match &args.game_funcs_path {
+		Some(path) => match zutil::parse_from_file(&path, serde_yaml::from_reader) {
+			Ok(func_table) => func_table,
+			Err(err) => {
+				log::warn!("Unable to load game functions from {}: {err}", path.display());
+				FuncTable::new()
+			},
+		},
+		None => FuncTable::new(),
+	};
 
 	// Create the executor
-	let mut exec_state = ExecState {
+	let exec_state = ExecState {
 		pc: Pos(0x80056270),
 		regs: [0; 32],
 		lo_hi_reg: [0; 2],
-		memory,
+		cop0: Cop0::default(),
+		gte: Gte::default(),
+		bus,
 		jump_target: JumpTarget::None,
-		should_stop: false,
+		loads: LoadDelay::default(),
 		results: RefCell::new(vec![]),
 	};
 
-	// Run the repl
-	self::run_repl(&mut exec_state)?;
+	// Run the repl, or serve gdb
+	let mut debugger = Debugger::new(exec_state, Symbols::new(func_table));
+	match &args.gdb_addr {
+		Some(addr) => gdb::serve(&mut debugger, addr.as_str()).context("Unable to serve gdb")?,
+		None => self::run_repl(&mut debugger)?,
+	}
 
 	Ok(())
 }
 
 /// Runs the `repl` loop
-fn run_repl(exec_state: &mut ExecState) -> Result<(), anyhow::Error> {
+fn run_repl(debugger: &mut Debugger) -> Result<(), anyhow::Error> {
 	// Get stdin and
 	let stdin = io::stdin();
 	let mut stdout = io::stdout();
 
 	let mut input_str = String::new();
-	let mut input_state = InputState::BreakEvery;
-	while !exec_state.should_stop {
-		let run_once = |exec_state: &mut ExecState, input_state| {
-			exec_state
-				.exec(input_state)
-				.with_context(|| format!("Failed to execute at {}", exec_state.pc()))
-		};
-
-		match input_state {
-			InputState::BreakEvery => run_once(exec_state, &input_state)?,
-			InputState::RunUntil { pos } => {
-				while !exec_state.should_stop && Some(exec_state.pc) != pos {
-					run_once(exec_state, &input_state)?;
-				}
-			},
-		};
-
+	let mut cmd = Command::Step { count: 1 };
+	debugger.print_location();
+	loop {
 		let args = match self::get_user_input(&stdin, &mut stdout, &mut input_str)? {
 			Some(args) => args,
 			None => break,
 		};
-		match input_state.update(args) {
-			Ok(()) => (),
-			Err(err) => println!("Unable to update input state: {err}"),
+
+		// Note: An empty line repeats the last command
+		match Command::parse(args) {
+			Ok(Some(new_cmd)) => cmd = new_cmd,
+			Ok(None) => (),
+			Err(err) => {
+				println!("Unable to parse command: {err:?}");
+				continue;
+			},
+		}
+
+		match debugger.run_cmd(&cmd) {
+			Ok(true) => (),
+			Ok(false) => break,
+			Err(err) => println!("{err:?}"),
 		}
 	}
 
@@ -146,179 +168,62 @@ fn get_user_input<'a>(
 
 /// Loads the game executable into memory
 fn load_game_exec(
-	game_file: &mut dcb_cdrom_xa::CdRomReader<impl Read + Seek>, game_root_dir: &dcb_iso9660::Dir, memory: &mut Memory,
+	game_file: &mut CdRomReader<impl Read + Seek>, game_fs: &FilesystemReader, bus: &mut Bus,
 ) -> Result<(), anyhow::Error> {
 	/// Executable position
 	const EXEC_POS: usize = 0x10000;
 
-	// Search for the executable file
-	let exec_file = game_root_dir
-		.entries()
-		.iter()
-		.find(|entry| entry.is_file() && entry.name.as_bytes() == b"SLUS_013.28;1")
-		.context("Unable to find game executable file")?;
-
-	// Then open it and skip past the header
+	// Open the executable and skip past the header
 	// TODO: Do something with the header, like validate?
-	let mut exec_file = exec_file
-		.read_file(game_file)
-		.context("Unable to read game executable")?;
+	let mut exec_file = game_fs
+		.open(game_file, "SLUS_013.28;1")
+		.context("Unable to open game executable")?;
 	exec_file
 		.seek(io::SeekFrom::Start(0x800))
 		.context("Unable to seek past game executable header")?;
 
 	// Finally read until eof into memory
-	zutil::read_slice_until_eof(&mut exec_file, &mut memory[EXEC_POS..])
+	zutil::read_slice_until_eof(&mut exec_file, &mut bus.ram_mut()[EXEC_POS..])
 		.context("Unable to read game executable into memory")?;
 
 	Ok(())
 }
 
-/// Loads the game file and it's root directory
-fn load_game(
-	path: impl AsRef<Path>,
-) -> Result<(dcb_cdrom_xa::CdRomReader<BufReader<fs::File>>, dcb_iso9660::Dir), anyhow::Error> {
+/// Loads the game file and it's filesystem
+fn load_game(path: impl AsRef<Path>) -> Result<(CdRomReader<BufReader<fs::File>>, FilesystemReader), anyhow::Error> {
 	// Open the file, wrap it in a buf reader and then in a cdrom reader.
 	let game_file = fs::File::open(path).context("Unable to open file")?;
 	let game_file = BufReader::new(game_file);
-	let mut game_file = dcb_cdrom_xa::CdRomReader::new(game_file);
+	let mut game_file = CdRomReader::new(game_file);
 
-	// Then read the iso9660 filesystem and the root directory
-	let game_fs = dcb_iso9660::FilesystemReader::new(&mut game_file).context("Unable to read iso9660 filesystem")?;
-	let game_root_dir = game_fs
-		.root_dir()
-		.read_dir(&mut game_file)
-		.context("Unable to read root directory")?;
+	// Then read the iso9660 filesystem
+	let game_fs = FilesystemReader::new(&mut game_file).context("Unable to read iso9660 filesystem")?;
 
-	Ok((game_file, game_root_dir))
+	Ok((game_file, game_fs))
 }
 
 /// Loads the bis from it's path
-fn load_bios(path: impl AsRef<Path>, memory: &mut Memory) -> Result<(), anyhow::Error> {
-	/// Bios position
-	const BIOS_POS: usize = 0x1fc00000;
-
-	/// Bios size
-	const BIOS_SIZE: u64 = 0x80000;
-
+fn load_bios(path: impl AsRef<Path>, bus: &mut Bus) -> Result<(), anyhow::Error> {
 	// Open the file and check that it's the correct length
 	let mut file = fs::File::open(&path).context("Unable to open file")?;
 	let file_len = file.stream_len().context("Unable to get file length")?;
 	anyhow::ensure!(
-		file_len == BIOS_SIZE,
-		"Unexpected size {:#x}. Expected {BIOS_SIZE:#x}",
-		file_len
+		file_len == Bus::BIOS_SIZE as u64,
+		"Unexpected size {:#x}. Expected {:#x}",
+		file_len,
+		Bus::BIOS_SIZE
 	);
 
 	// Then read it into memory
-	file.read_exact(&mut memory[BIOS_POS..(BIOS_POS + BIOS_SIZE as usize)])
-		.context("Unable to read from file")?;
+	file.read_exact(bus.bios_mut()).context("Unable to read from file")?;
 
 	// And copy the first 64k to `0x0`
-	memory.bytes.copy_within(BIOS_POS..(BIOS_POS + 0x10000), 0x0);
+	let bios = bus.bios()[..0x10000].to_vec();
+	bus.ram_mut()[..0x10000].copy_from_slice(&bios);
 
 	Ok(())
 }
 
-/// Input state
-enum InputState {
-	/// Break every instruction
-	BreakEvery,
-
-	/// Run until
-	RunUntil { pos: Option<Pos> },
-}
-
-impl InputState {
-	/// Updates the input state from arguments
-	pub fn update<S: AsRef<str>>(&mut self, mut args: impl Iterator<Item = S>) -> Result<(), anyhow::Error> {
-		match args.next().as_ref().map(S::as_ref) {
-			Some("n") => *self = InputState::BreakEvery,
-			Some("r") => {
-				let pos = args
-					.next()
-					.map(|pos| {
-						self::parse_number(pos.as_ref())
-							.context("Unable to parse position")?
-							.try_into_as::<u32>()
-							.map(Pos)
-							.context("Position didn't fit into a `u32`")
-					})
-					.transpose()?;
-				*self = InputState::RunUntil { pos };
-			},
-			Some(arg) => anyhow::bail!("Unknown argument {:?}", arg),
-			None => (),
-		}
-
-		Ok(())
-	}
-}
-
-/// Memory
-// TODO: Have this work with `u32`s externally.
-// TODO: Make this paginated to not allocate all memory right away.
-pub struct Memory {
-	/// All bytes
-	bytes: Box<[u8; Self::SIZE]>,
-}
-
-impl Memory {
-	/// Size
-	//const SIZE: usize = 0x200000;
-	const SIZE: usize = 0x20000000;
-
-	/// Creates a new memory chunk
-	#[allow(clippy::new_without_default)] // We want an explicit constructor
-	#[must_use]
-	pub fn new() -> Self {
-		Self {
-			bytes: Box::new([0; Self::SIZE]),
-		}
-	}
-
-	/// Dumps all memory to file
-	pub fn dump_to_file(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
-		fs::write(path, &*self.bytes).context("Unable to write to file")
-	}
-
-	/// Returns the bytes at `index`
-	pub fn get<I>(&self, index: I) -> Option<&I::Output>
-	where
-		I: SliceIndex<[u8]>,
-	{
-		index.get(&*self.bytes)
-	}
-
-	/// Returns the bytes at `index` mutably
-	pub fn get_mut<I>(&mut self, index: I) -> Option<&mut I::Output>
-	where
-		I: SliceIndex<[u8]>,
-	{
-		index.get_mut(&mut *self.bytes)
-	}
-}
-
-impl<I> Index<I> for Memory
-where
-	[u8]: Index<I>,
-{
-	type Output = <[u8] as Index<I>>::Output;
-
-	fn index(&self, index: I) -> &Self::Output {
-		&self.bytes[index]
-	}
-}
-
-impl<I> IndexMut<I> for Memory
-where
-	[u8]: IndexMut<I>,
-{
-	fn index_mut(&mut self, index: I) -> &mut Self::Output {
-		&mut self.bytes[index]
-	}
-}
-
 /// Execution state
 pub struct ExecState {
 	/// Program counter
@@ -330,74 +235,79 @@ pub struct ExecState {
 	/// Lo / Hi
 	lo_hi_reg: [u32; 2],
 
-	/// Memory
-	memory: Memory,
+	/// System control co-processor
+	cop0: Cop0,
+
+	/// Geometry transformation engine
+	gte: Gte,
+
+	/// Memory bus
+	bus: Bus,
 
 	/// Jump target
 	jump_target: JumpTarget,
 
-	/// If the processor should stop
-	should_stop: bool,
+	/// Load delay pipeline
+	loads: LoadDelay,
 
 	/// Results
 	results: RefCell<Vec<ExecResult>>,
 }
 
 impl ExecState {
+	/// Writes `value` to `pos`, returning the previous value, if it's known.
+	fn write(&mut self, pos: Pos, size: AccessSize, value: u32) -> Result<Option<u32>, ExecError> {
+		// Note: While the cache is isolated, writes only reach the cache, which we don't emulate
+		if self.cop0.is_cache_isolated() {
+			return Ok(None);
+		}
+
+		// Note: Any errors are raised by the write itself, so they're raised as a store
+		let prev = self.bus.peek(pos, size).ok().flatten();
+		self.bus.write(pos, size, value)?;
+		Ok(prev)
+	}
+
 	/// Executes the next instruction
-	fn exec(&mut self, _input_state: &InputState) -> Result<(), ExecError> {
-		// Read the next instruction
-		let inst = self.read_word(self.pc)?;
-
-		// Parse the instruction
-		let inst = basic::Inst::decode(inst).ok_or(ExecError::DecodeInst)?;
-
-		// Display it
-		// TODO: Check what registers changed in the op and print them, maybe also memory locations
-		//       with a countdown until the change is actually realized or something.
-		let print_inst = || println!("{:010}: {}", self.pc, self::inst_display(&inst, self.pc));
-		print_inst();
-		/*
-		match *input_state {
-			InputState::BreakEvery => print_inst(),
-			InputState::RunUntil { pos }
-				if Some(self.pc + 4) == pos && matches!(self.jump_target, JumpTarget::None) =>
-			{
-				print_inst()
+	pub fn exec(&mut self) -> Result<Step, ExecError> {
+		// Note: Loads have a delay slot, so the load queued by the previous instruction
+		//       is only applied after this instruction executes.
+		self.loads.start();
+
+		let mut inst = None;
+		let res = match self.fetch_inst() {
+			Ok(fetched) => {
+				inst = Some(fetched);
+				fetched.exec(self)
 			},
-			_ => (),
-		}
-		*/
-
-		// Then execute the instruction
-		inst.exec(self)?;
-
-		// And display the results
-		for result in self.results.borrow_mut().drain(..) {
-			match result {
-				//ExecResult::ReadRegister { reg, value } if reg != Register::Zr => println!("[{reg}] {value:#x}"),
-				//ExecResult::ReadMultRegister { reg, value } => println!("[{reg}] {value:#x}"),
-				ExecResult::WroteRegister { reg, prev, value } if reg != Register::Zr && prev != value => {
-					println!("[{reg}] {prev:#x} => {value:#x}");
-				},
-				ExecResult::WroteMultRegister { reg, prev, value } if prev != value => {
-					println!("[{reg}] {prev:#x} => {value:#x}");
-				},
-				//ExecResult::ReadWord { pos, value } => println!("[{pos:010}] {value:#x}"),
-				//ExecResult::ReadHalfWord { pos, value } => println!("[{pos:010}] {value:#x}"),
-				//ExecResult::ReadByte { pos, value } => println!("[{pos:010}] {value:#x}"),
-				ExecResult::WriteWord { pos, prev, value } if prev != value => {
-					println!("[{pos:010}] {prev:#x} => {value:#x}");
-				},
-				ExecResult::WriteHalfWord { pos, prev, value } if prev != value => {
-					println!("[{pos:010}] {prev:#x} => {value:#x}");
-				},
-				ExecResult::WriteByte { pos, prev, value } if prev != value => {
-					println!("[{pos:010}] {prev:#x} => {value:#x}");
-				},
-				ExecResult::QueuedJump { pos } => println!("=> [{pos:010}]"),
-				_ => (),
-			}
+			Err(err) => Err(err),
+		};
+		let res = match self.loads.finish() {
+			Some((LoadTarget::Register(reg), value)) => {
+				self.store_reg(reg, value);
+				res
+			},
+			Some((LoadTarget::GteData(reg), value)) => res.and(self.gte.store_data(reg, value)),
+			None => res,
+		};
+		let results = mem::take(self.results.get_mut());
+
+		// If we raised an exception, jump to it's vector
+		// Note: The jump we might have been doing is cancelled, as `EPC` points to the branch in that case.
+		let exception = match res {
+			Ok(()) => None,
+			Err(ExecError::Exception(exception)) => Some(exception),
+			Err(err) => return Err(err),
+		};
+		if let Some(exception) = exception {
+			let in_delay_slot = matches!(self.jump_target, JumpTarget::JumpNow(_));
+			self.pc = self.cop0.enter_exception(exception, self.pc, in_delay_slot);
+			self.jump_target = JumpTarget::None;
+			return Ok(Step {
+				inst,
+				results,
+				exception,
+			});
 		}
 
 		// Then update our pc depending on whether we have a jump
@@ -413,10 +323,48 @@ impl ExecState {
 			},
 		};
 
-		Ok(())
+		Ok(Step {
+			inst,
+			results,
+			exception,
+		})
+	}
+
+	/// Fetches and decodes the instruction at `pc`
+	fn fetch_inst(&mut self) -> Result<basic::Inst, ExecError> {
+		// Note: We don't go through `read_word`, so fetches don't show up in the results.
+		//       Bus errors while fetching instructions also have their own exception.
+		let inst = self.bus.read(self.pc, AccessSize::Word).map_err(|err| match err {
+			ExecError::Exception(Exception::BusErrorData { pos }) => Exception::BusErrorInst { pos }.into(),
+			err => err,
+		})?;
+
+		basic::Inst::decode(inst).ok_or_else(|| Exception::ReservedInst.into())
+	}
+
+	/// Returns the instruction at `pos`, without any side effects
+	pub fn inst_at(&self, pos: Pos) -> Option<basic::Inst> {
+		self.peek_word(pos).and_then(basic::Inst::decode)
+	}
+
+	/// Reads the word at `pos`, without any side effects
+	pub fn peek_word(&self, pos: Pos) -> Option<u32> {
+		self.bus.peek(pos, AccessSize::Word).ok().flatten()
 	}
 }
 
+/// Result of executing an instruction
+pub struct Step {
+	/// Instruction executed, if it could be fetched
+	pub inst: Option<basic::Inst>,
+
+	/// Results
+	pub results: Vec<ExecResult>,
+
+	/// Exception raised, if any
+	pub exception: Option<Exception>,
+}
+
 /// Execution result
 pub enum ExecResult {
 	/// Read from register `dst`
@@ -441,13 +389,13 @@ pub enum ExecResult {
 	ReadByte { pos: Pos, value: u8 },
 
 	/// Wrote a word to `pos`
-	WriteWord { pos: Pos, prev: u32, value: u32 },
+	WriteWord { pos: Pos, prev: Option<u32>, value: u32 },
 
 	/// Wrote a half-word to `pos`
-	WriteHalfWord { pos: Pos, prev: u16, value: u16 },
+	WriteHalfWord { pos: Pos, prev: Option<u16>, value: u16 },
 
 	/// Wrote a byte to `pos`
-	WriteByte { pos: Pos, prev: u8, value: u8 },
+	WriteByte { pos: Pos, prev: Option<u8>, value: u8 },
 
 	/// Queued a jump to `pos`
 	QueuedJump { pos: Pos },
@@ -465,7 +413,25 @@ impl ExecCtx for ExecState {
 		value
 	}
 
+	fn load_reg_delayed(&self, reg: Register) -> u32 {
+		match self.loads.delayed_value(reg) {
+			Some(value) => {
+				self.results.borrow_mut().push(ExecResult::ReadRegister { reg, value });
+				value
+			},
+			None => self.load_reg(reg),
+		}
+	}
+
 	fn store_reg(&mut self, reg: Register, value: u32) {
+		// Note: Writes to `$zr` are discarded
+		if reg == Register::Zr {
+			return;
+		}
+
+		// Note: If the instruction in a load delay slot writes the register, the load is discarded
+		self.loads.cancel(LoadTarget::Register(reg));
+
 		let idx: usize = reg.idx().try_into().expect("Register index didn't fit into `usize`");
 		let prev = mem::replace(&mut self.regs[idx], value);
 		self.results
@@ -505,150 +471,78 @@ impl ExecCtx for ExecState {
 		}
 	}
 
-	/// Reads a word from a memory position
-	fn read_word(&self, pos: Pos) -> Result<u32, ExecError> {
-		// If the position isn't aligned, return Err
-		if !pos.is_word_aligned() {
-			return Err(ExecError::MemoryUnalignedAccess { pos });
-		}
+	fn queue_load(&mut self, reg: Register, value: u32) {
+		self.loads.queue(LoadTarget::Register(reg), value);
+	}
 
-		// Ignore the top nibble
-		let idx = pos.0 & 0x0FFF_FFFF;
-		let idx = idx.try_into().expect("Memory position didn't fit into `usize`");
-
-		// Then read from memory
-		let value = self
-			.memory
-			.get(idx..(idx + 4))
-			.map(LittleEndian::read_u32)
-			.ok_or(ExecError::MemoryOutOfBounds { pos })?;
-		self.results.borrow_mut().push(ExecResult::ReadWord { pos, value });
-		Ok(value)
+	fn queue_gte_load(&mut self, reg: u8, value: u32) {
+		self.loads.queue(LoadTarget::GteData(reg), value);
 	}
 
-	fn read_half_word(&self, pos: Pos) -> Result<u16, ExecError> {
-		// If the position isn't aligned, return Err
-		if !pos.is_half_word_aligned() {
-			return Err(ExecError::MemoryUnalignedAccess { pos });
-		}
+	/// Reads a word from a memory position
+	fn read_word(&mut self, pos: Pos) -> Result<u32, ExecError> {
+		let value = self.bus.read(pos, AccessSize::Word)?;
+		self.results.get_mut().push(ExecResult::ReadWord { pos, value });
+		Ok(value)
+	}
 
-		// Ignore the top nibble
-		let idx = pos.0 & 0x0FFF_FFFF;
-		let idx = idx.try_into().expect("Memory position didn't fit into `usize`");
-
-		// Then read from memory
-		let value = self
-			.memory
-			.get(idx..(idx + 2))
-			.map(LittleEndian::read_u16)
-			.ok_or(ExecError::MemoryOutOfBounds { pos })?;
-		self.results.borrow_mut().push(ExecResult::ReadHalfWord { pos, value });
+	/// Reads a half-word from a memory position
+	fn read_half_word(&mut self, pos: Pos) -> Result<u16, ExecError> {
+		let value = self.bus.read(pos, AccessSize::HalfWord)?.truncated();
+		self.results.get_mut().push(ExecResult::ReadHalfWord { pos, value });
 		Ok(value)
 	}
 
 	/// Reads a byte from a memory position
-	fn read_byte(&self, pos: Pos) -> Result<u8, ExecError> {
-		// Ignore the top nibble
-		let idx = pos.0 & 0x0FFF_FFFF;
-		let idx: usize = idx.try_into().expect("Memory position didn't fit into `usize`");
-
-		// Then read from memory
-		let value = self
-			.memory
-			.get(idx)
-			.copied()
-			.ok_or(ExecError::MemoryOutOfBounds { pos })?;
-		self.results.borrow_mut().push(ExecResult::ReadByte { pos, value });
+	fn read_byte(&mut self, pos: Pos) -> Result<u8, ExecError> {
+		let value = self.bus.read(pos, AccessSize::Byte)?.truncated();
+		self.results.get_mut().push(ExecResult::ReadByte { pos, value });
 		Ok(value)
 	}
 
 	/// Stores a word to a memory position
 	fn write_word(&mut self, pos: Pos, value: u32) -> Result<(), ExecError> {
-		// If the position isn't aligned, return Err
-		if !pos.is_word_aligned() {
-			return Err(ExecError::MemoryUnalignedAccess { pos });
-		}
-
-		// Ignore the top nibble
-		let idx = pos.0 & 0x0FFF_FFFF;
-		let idx = idx.try_into().expect("Memory position didn't fit into `usize`");
-
-		// Then write to memory
-		let mem = self
-			.memory
-			.get_mut(idx..(idx + 4))
-			.ok_or(ExecError::MemoryOutOfBounds { pos })?;
-		let prev = LittleEndian::read_u32(mem);
-		LittleEndian::write_u32(mem, value);
+		let prev = self.write(pos, AccessSize::Word, value)?;
 		self.results.get_mut().push(ExecResult::WriteWord { pos, prev, value });
 		Ok(())
 	}
 
 	/// Writes a half-word to a memory position
 	fn write_half_word(&mut self, pos: Pos, value: u16) -> Result<(), ExecError> {
-		// If the position isn't aligned, return Err
-		if !pos.is_half_word_aligned() {
-			return Err(ExecError::MemoryUnalignedAccess { pos });
-		}
-
-		// Ignore the top nibble
-		let idx = pos.0 & 0x0FFF_FFFF;
-		let idx = idx.try_into().expect("Memory position didn't fit into `usize`");
-
-		// Then write to memory
-		let mem = self
-			.memory
-			.get_mut(idx..(idx + 2))
-			.ok_or(ExecError::MemoryOutOfBounds { pos })?;
-		let prev = LittleEndian::read_u16(mem);
-		LittleEndian::write_u16(mem, value);
-		self.results
-			.get_mut()
-			.push(ExecResult::WriteHalfWord { pos, prev, value });
+		let prev = self.write(pos, AccessSize::HalfWord, value.into())?;
+		self.results.get_mut().push(ExecResult::WriteHalfWord {
+			pos,
+			prev: prev.map(|prev| prev.truncated()),
+			value,
+		});
 		Ok(())
 	}
 
 	/// Writes a byte to a memory position
 	fn write_byte(&mut self, pos: Pos, value: u8) -> Result<(), ExecError> {
-		// Ignore the top nibble
-		let idx = pos.0 & 0x0FFF_FFFF;
-		let idx: usize = idx.try_into().expect("Memory position didn't fit into `usize`");
+		let prev = self.write(pos, AccessSize::Byte, value.into())?;
+		self.results.get_mut().push(ExecResult::WriteByte {
+			pos,
+			prev: prev.map(|prev| prev.truncated()),
+			value,
+		});
+		Ok(())
+	}
 
-		// Then write to memory
-		let mem = self.memory.get_mut(idx).ok_or(ExecError::MemoryOutOfBounds { pos })?;
-		let prev = *mem;
-		*mem = value;
-		self.results.get_mut().push(ExecResult::WriteByte { pos, prev, value });
+	fn cop0(&self) -> &Cop0 {
+		&self.cop0
+	}
 
-		Ok(())
+	fn cop0_mut(&mut self) -> &mut Cop0 {
+		&mut self.cop0
 	}
 
-	fn sys(&mut self, inst: basic::sys::Inst) -> Result<(), ExecError> {
-		match inst.comment {
-			0x0 => {
-				self.should_stop = true;
-			},
-			0x1 => {
-				// Print whatever string is in `$v0`
-				let ptr = Pos(self[Register::V0]);
-
-				for n in 0u32.. {
-					match self.read_byte(ptr + n)? {
-						0 => break,
-						b => print!("{}", char::from(b)),
-					}
-				}
-			},
-			0x2 => {
-				// Print all registers
-				for &reg in &Register::ALL_REGISTERS {
-					println!("{}: {:#x}", reg, self[reg]);
-				}
-			},
-			comment => return Err(ExecError::UnknownSys { comment }),
-		}
+	fn gte(&self) -> &Gte {
+		&self.gte
+	}
 
-		Ok(())
+	fn gte_mut(&mut self) -> &mut Gte {
+		&mut self.gte
 	}
 }
 
@@ -704,11 +598,11 @@ pub enum JumpTarget {
 
 /// Returns a display-able for an instruction inside a possible function
 #[must_use]
-pub fn inst_display(inst: &basic::Inst, pos: Pos) -> impl fmt::Display + '_ {
+pub fn inst_display<'a>(inst: &'a basic::Inst, pos: Pos, symbols: &'a Symbols) -> impl fmt::Display + 'a {
 	// Overload the target of as many as possible using `inst_target`.
 	zutil::DisplayWrapper::new(move |f| {
 		// Build the context and get the mnemonic + args
-		let ctx = DisplayCtx { pos };
+		let ctx = DisplayCtx { pos, symbols };
 		let mnemonic = inst.mnemonic(&ctx);
 
 		write!(f, "{mnemonic}")?;
@@ -729,20 +623,23 @@ pub fn inst_display(inst: &basic::Inst, pos: Pos) -> impl fmt::Display + '_ {
 }
 
 /// Displaying context for instructions.
-pub struct DisplayCtx {
+pub struct DisplayCtx<'a> {
 	/// Current Position
 	pos: Pos,
+
+	/// Symbols
+	symbols: &'a Symbols,
 }
 
-impl inst::DisplayCtx for DisplayCtx {
-	type Label = !;
+impl<'a> inst::DisplayCtx for DisplayCtx<'a> {
+	type Label = String;
 
 	fn cur_pos(&self) -> Pos {
 		self.pos
 	}
 
-	fn pos_label(&self, _pos: Pos) -> Option<(Self::Label, i64)> {
-		None
+	fn pos_label(&self, pos: Pos) -> Option<(Self::Label, i64)> {
+		self.symbols.label(pos)
 	}
 }
 
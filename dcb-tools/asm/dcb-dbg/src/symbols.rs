@@ -0,0 +1,108 @@
+//! Symbols
+//!
+//! Resolves locations given by the user and names positions, using
+//! the known functions of the game.
+
+// Modules
+#[cfg(test)]
+mod test;
+
+// Imports
+use anyhow::Context;
+use dcb_exe::{Func, FuncTable, Pos};
+use std::convert::TryFrom;
+
+/// Symbols
+#[derive(Clone, Debug)]
+pub struct Symbols {
+	/// Function table
+	func_table: FuncTable,
+}
+
+impl Symbols {
+	/// Creates symbols from a function table
+	pub const fn new(func_table: FuncTable) -> Self {
+		Self { func_table }
+	}
+
+	/// Returns the function table
+	pub const fn func_table(&self) -> &FuncTable {
+		&self.func_table
+	}
+
+	/// Returns the function with name `name`
+	pub fn func_named(&self, name: &str) -> Option<&Func> {
+		self.func_table.range(..).find(|func| func.name == name)
+	}
+
+	/// Resolves a location, with `pc` as the current position.
+	///
+	/// Locations may be a number, a function name, a label within a function, `func.label`,
+	/// or a label within the current function, `.label`, optionally followed by an offset.
+	pub fn resolve(&self, location: &str, pc: Pos) -> Result<Pos, anyhow::Error> {
+		// Split the offset, if any
+		let (location, offset) = match location.rfind(|ch: char| ch == '+' || ch == '-') {
+			Some(idx) if idx != 0 => (
+				&location[..idx],
+				crate::parse_number(&location[idx..]).context("Unable to parse offset")?,
+			),
+			_ => (location, 0),
+		};
+
+		let pos = match location.split_once('.') {
+			Some(("", label)) => {
+				let func = self
+					.func_table
+					.get_containing(pc)
+					.context("Current position isn't within a known function")?;
+				self::func_label(func, label)?
+			},
+			Some((func, label)) => {
+				let func = self
+					.func_named(func)
+					.with_context(|| format!("Unknown function {func:?}"))?;
+				self::func_label(func, label)?
+			},
+			None => match crate::parse_number(location) {
+				Ok(pos) => u32::try_from(pos)
+					.map(Pos)
+					.context("Position didn't fit into a `u32`")?,
+				Err(_) => {
+					self.func_named(location)
+						.with_context(|| format!("Unknown function {location:?}"))?
+						.start_pos
+				},
+			},
+		};
+
+		Ok(pos + offset)
+	}
+
+	/// Returns a label for `pos` and it's offset from it, if it's within a known function.
+	///
+	/// The label is either `func.label`, if `pos` has a label, or `func` otherwise.
+	pub fn label(&self, pos: Pos) -> Option<(String, i64)> {
+		let func = self.func_table.get_containing(pos)?;
+		match func.labels.get(&pos) {
+			Some(label) => Some((format!("{}.{label}", func.name), 0)),
+			None => Some((func.name.clone(), pos - func.start_pos)),
+		}
+	}
+
+	/// Describes `pos` as `func.label` or `func+offset`, if it's within a known function
+	pub fn describe(&self, pos: Pos) -> Option<String> {
+		self.label(pos).map(|(label, offset)| match offset {
+			0 => label,
+			_ => format!("{label}+{offset:#x}"),
+		})
+	}
+}
+
+/// Returns the position of `label` within `func`
+fn func_label(func: &Func, label: &str) -> Result<Pos, anyhow::Error> {
+	func.labels
+		.iter()
+		.find(|(_, func_label)| *func_label == label)
+		.map(|(&pos, _)| pos)
+		.with_context(|| format!("Unknown label {label:?} in function {}", func.name))
+}
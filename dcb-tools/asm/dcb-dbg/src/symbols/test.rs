@@ -0,0 +1,78 @@
+//! Tests
+
+// Imports
+use super::*;
+use dcb_exe::func::FuncKind;
+use std::collections::BTreeMap;
+
+/// Creates a function named `name` over `start_pos..end_pos`, with `labels`
+fn func(name: &str, start_pos: u32, end_pos: u32, labels: &[(u32, &str)]) -> Func {
+	Func {
+		name:               name.to_owned(),
+		signature:          String::new(),
+		desc:               String::new(),
+		inline_comments:    BTreeMap::new(),
+		block_comments:     BTreeMap::new(),
+		labels:             labels
+			.iter()
+			.map(|&(pos, label)| (Pos(pos), label.to_owned()))
+			.collect(),
+		inst_arg_overrides: BTreeMap::new(),
+		start_pos:          Pos(start_pos),
+		end_pos:            Pos(end_pos),
+		kind:               FuncKind::Known,
+	}
+}
+
+/// Creates the symbols used by all tests
+fn symbols() -> Symbols {
+	Symbols::new(
+		vec![
+			self::func("func", 0x8001_0000, 0x8001_0100, &[(0x8001_0010, "label")]),
+			self::func("other", 0x8002_0000, 0x8002_0040, &[(0x8002_0008, "loop")]),
+		]
+		.into_iter()
+		.collect(),
+	)
+}
+
+/// Position outside of any function
+const OUTSIDE: Pos = Pos(0x8003_0000);
+
+#[test]
+fn resolve() {
+	let symbols = self::symbols();
+	let resolve = |location: &str, pc: Pos| symbols.resolve(location, pc).expect("Unable to resolve location");
+
+	assert_eq!(resolve("0x80030000", OUTSIDE), Pos(0x8003_0000));
+	assert_eq!(resolve("func", OUTSIDE), Pos(0x8001_0000));
+	assert_eq!(resolve("func+0x10", OUTSIDE), Pos(0x8001_0010));
+	assert_eq!(resolve("func.label", OUTSIDE), Pos(0x8001_0010));
+	assert_eq!(resolve("func.label+0x4", OUTSIDE), Pos(0x8001_0014));
+	assert_eq!(resolve("func.label+8", OUTSIDE), Pos(0x8001_0018));
+	assert_eq!(resolve("func.label-4", OUTSIDE), Pos(0x8001_000c));
+	assert_eq!(resolve(".loop", Pos(0x8002_0010)), Pos(0x8002_0008));
+	assert_eq!(resolve(".loop+0x8", Pos(0x8002_0010)), Pos(0x8002_0010));
+}
+
+#[test]
+fn resolve_errors() {
+	let symbols = self::symbols();
+
+	assert!(symbols.resolve("unknown", OUTSIDE).is_err());
+	assert!(symbols.resolve("func.unknown", OUTSIDE).is_err());
+	assert!(symbols.resolve("unknown.label", OUTSIDE).is_err());
+	assert!(symbols.resolve(".label", OUTSIDE).is_err());
+	assert!(symbols.resolve(".label", Pos(0x8002_0010)).is_err());
+	assert!(symbols.resolve("func.label+x", OUTSIDE).is_err());
+}
+
+#[test]
+fn describe() {
+	let symbols = self::symbols();
+
+	assert_eq!(symbols.describe(Pos(0x8001_0000)).as_deref(), Some("func"));
+	assert_eq!(symbols.describe(Pos(0x8001_0010)).as_deref(), Some("func.label"));
+	assert_eq!(symbols.describe(Pos(0x8001_0014)).as_deref(), Some("func+0x14"));
+	assert_eq!(symbols.describe(OUTSIDE), None);
+}
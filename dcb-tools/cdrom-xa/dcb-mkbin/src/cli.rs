@@ -12,6 +12,9 @@ pub struct CliData {
 
 	/// The output file
 	pub output_file: PathBuf,
+
+	/// If the cue sheet should be overwritten if it exists
+	pub overwrite_cue: bool,
 }
 
 impl CliData {
@@ -40,6 +43,15 @@ impl CliData {
 					.takes_value(true)
 					.required(false),
 			)
+			.arg(
+				ClapArg::with_name("OVERWRITE_CUE")
+					.help("Overwrites the cue sheet if it already exists")
+					.long_help(
+						"Overwrites the cue sheet if it already exists. The cue sheet is written alongside the output \
+						 file, with the `.cue` extension",
+					)
+					.long("overwrite-cue"),
+			)
 			.get_matches();
 
 		// Get the input filename
@@ -63,10 +75,13 @@ impl CliData {
 			},
 		};
 
+		let overwrite_cue = matches.is_present("OVERWRITE_CUE");
+
 		// Return the data
 		Self {
 			input_file,
 			output_file,
+			overwrite_cue,
 		}
 	}
 }
@@ -7,7 +7,7 @@ mod cli;
 use anyhow::Context;
 use dcb_cdrom_xa::{
 	sector::header::{subheader::SubMode, SubHeader},
-	CdRomWriter,
+	CdRomWriter, CueSheet,
 };
 use std::{
 	fs,
@@ -29,11 +29,29 @@ fn main() -> Result<(), anyhow::Error> {
 	let cli::CliData {
 		input_file,
 		output_file,
+		overwrite_cue,
 	} = cli::CliData::new();
 
+	// Make sure we don't overwrite an existing cue sheet, before doing any work
+	let cue_path = output_file.with_extension("cue");
+	anyhow::ensure!(
+		overwrite_cue || !cue_path.exists(),
+		"Cue sheet {:?} already exists, use `--overwrite-cue` to overwrite it",
+		cue_path
+	);
+
 	// Try to pack it into a `CdRom/XA`
 	self::pack_cdrom_xa(&input_file, &output_file).context("Unable to pack file")?;
 
+	// Then write the cue sheet alongside it
+	let output_file_name = output_file
+		.file_name()
+		.context("Output file had no file name")?
+		.to_str()
+		.context("Output file name must be utf-8")?;
+	let cue_sheet = CueSheet::single_data_track(output_file_name);
+	fs::write(cue_path, cue_sheet.to_string()).context("Unable to write cue sheet")?;
+
 	Ok(())
 }
 
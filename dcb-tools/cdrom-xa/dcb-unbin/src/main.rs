@@ -5,7 +5,7 @@ mod cli;
 
 // Imports
 use anyhow::Context;
-use dcb_cdrom_xa::CdRomReader;
+use dcb_cdrom_xa::{CdRomReader, ImageReader};
 use std::{fs, io::Write, path::Path};
 
 
@@ -34,6 +34,7 @@ fn main() -> Result<(), anyhow::Error> {
 fn extract_cdrom_xa(input_file: &Path, output_file: &Path) -> Result<(), anyhow::Error> {
 	// Open the input file
 	let input_file = fs::File::open(input_file).context("Unable to open input file")?;
+	let input_file = ImageReader::new(input_file).context("Unable to open input image")?;
 	let mut input_file = CdRomReader::new(input_file);
 
 	// Create the output file
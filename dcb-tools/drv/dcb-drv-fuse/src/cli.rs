@@ -1,7 +1,7 @@
 //! Cli manager
 
 // Imports
-use clap::{App as ClapApp, Arg as ClapArg};
+use clap::{App as ClapApp, AppSettings, Arg as ClapArg, ArgMatches, SubCommand};
 use std::path::PathBuf;
 
 /// Data from the command line
@@ -10,8 +10,27 @@ pub struct CliData {
 	/// Input file
 	pub input_file: PathBuf,
 
-	/// Mount point
-	pub mount_point: PathBuf,
+	/// If the input file is the game disc, instead of a drive
+	pub disc: bool,
+
+	/// Command
+	pub cmd: Command,
+}
+
+/// Command
+#[derive(PartialEq, Clone, Debug)]
+pub enum Command {
+	/// Mounts the input file
+	Mount {
+		/// Mount point
+		mount_point: PathBuf,
+	},
+
+	/// Checks the input file for inconsistencies
+	Fsck {
+		/// If all repairable issues should be repaired
+		repair: bool,
+	},
 }
 
 impl CliData {
@@ -21,14 +40,9 @@ impl CliData {
 		let matches = ClapApp::new("Drv Fuse")
 			.version("0.1")
 			.author("Filipe [...] <[...]@gmail.com>")
-			.about("Mounts a `.drv` file as a `fuse` filesystem")
-			.arg(
-				ClapArg::with_name("INPUT_FILE")
-					.help("The input file to use")
-					.required(true)
-					.takes_value(true)
-					.index(1),
-			)
+			.about("Mounts a `.drv` file, or the whole game disc, as a `fuse` filesystem")
+			.setting(AppSettings::SubcommandsNegateReqs)
+			.arg(self::input_file_arg())
 			.arg(
 				ClapArg::with_name("MOUNT_POINT")
 					.help("The mount point")
@@ -37,23 +51,73 @@ impl CliData {
 					.takes_value(true)
 					.index(2),
 			)
+			.arg(
+				ClapArg::with_name("DISC")
+					.help("Mounts the game disc, with all drives as directories")
+					.long_help(
+						"Mounts the game disc `.bin` instead of a single `.drv` file, exposing it's filesystem with \
+						 each drive as a directory",
+					)
+					.long("disc"),
+			)
+			.subcommand(
+				SubCommand::with_name("fsck")
+					.about("Checks a `.drv` file, or all drives of the game disc, for inconsistencies")
+					.arg(self::input_file_arg())
+					.arg(
+						ClapArg::with_name("DISC")
+							.help("Checks all drives of the game disc")
+							.long_help("Checks all drives within the game disc `.bin` instead of a single `.drv` file")
+							.long("disc"),
+					)
+					.arg(
+						ClapArg::with_name("REPAIR")
+							.help("Repairs all issues that can be repaired")
+							.long_help(
+								"Repairs all issues that can be repaired, such as directories with a size or \
+								 extension and files past the end of the drive, which are truncated",
+							)
+							.long("repair"),
+					),
+			)
 			.get_matches();
 
-		// Get the input filename
-		// Note: required
-		let input_file = matches
-			.value_of("INPUT_FILE")
-			.map(PathBuf::from)
-			.expect("Unable to get required argument");
-		let mount_point = matches
-			.value_of("MOUNT_POINT")
-			.map(PathBuf::from)
-			.expect("Unable to get required argument");
-
-		// Return the data
-		Self {
-			input_file,
-			mount_point,
+		match matches.subcommand_matches("fsck") {
+			Some(matches) => Self {
+				input_file: self::input_file(matches),
+				disc:       matches.is_present("DISC"),
+				cmd:        Command::Fsck {
+					repair: matches.is_present("REPAIR"),
+				},
+			},
+			None => Self {
+				input_file: self::input_file(&matches),
+				disc:       matches.is_present("DISC"),
+				cmd:        Command::Mount {
+					mount_point: matches
+						.value_of("MOUNT_POINT")
+						.map(PathBuf::from)
+						.expect("Unable to get required argument"),
+				},
+			},
 		}
 	}
 }
+
+/// Returns the input file argument
+fn input_file_arg() -> ClapArg<'static, 'static> {
+	ClapArg::with_name("INPUT_FILE")
+		.help("The input file to use")
+		.required(true)
+		.takes_value(true)
+		.index(1)
+}
+
+/// Returns the input file
+fn input_file(matches: &ArgMatches) -> PathBuf {
+	// Note: required
+	matches
+		.value_of("INPUT_FILE")
+		.map(PathBuf::from)
+		.expect("Unable to get required argument")
+}
@@ -0,0 +1,475 @@
+//! Disc filesystem
+
+// Modules
+#[cfg(test)]
+mod test;
+
+// Imports
+use crate::fs::DrvFs;
+use anyhow::Context;
+use dcb_cdrom_xa::{CdRomCursor, CdRomReader};
+use dcb_io::game_file::{DriveCursor, Drives};
+use dcb_iso9660::{string::FileStrWithoutVersion, DirEntry, FilesystemReader};
+use fuser::{Filesystem, TimeOrNow};
+use std::{
+	collections::{hash_map, HashMap},
+	convert::TryFrom,
+	ffi::OsStr,
+	fs,
+	io::{self, Read, Seek, SeekFrom, Write},
+	time::{Duration, SystemTime},
+};
+
+/// Cursor of each drive
+type DriveFile<F> = DriveCursor<CdRomCursor<F>>;
+
+/// Disc file.
+///
+/// Each drive uses it's own clone of the disc file.
+pub trait DiscFile: Read + Write + Seek + Sized {
+	/// Clones this file, sharing it's contents with the original
+	fn try_clone(&self) -> Result<Self, io::Error>;
+}
+
+impl DiscFile for fs::File {
+	fn try_clone(&self) -> Result<Self, io::Error> {
+		fs::File::try_clone(self)
+	}
+}
+
+/// Disc filesystem
+///
+/// Exposes the iso9660 filesystem of the game disc as read-only, with
+/// each drive, e.g. `B.DRV`, exposed as a writable directory.
+///
+/// Each drive is a [`DrvFs`] with it's inodes starting at `(idx + 1) << 32`,
+/// so all operations on them may be forwarded to it.
+///
+/// Note: Drives have a fixed size within the disc, so files may only be written
+///       while there are free sectors within the drive.
+#[derive(Debug)]
+pub struct DiscFs<F> {
+	/// Cdrom reader
+	cdrom: CdRomReader<F>,
+
+	/// Filesystem
+	filesystem: FilesystemReader,
+
+	/// Inodes
+	inodes: HashMap<u64, Inode>,
+
+	/// Ino by path
+	ino_by_path: HashMap<String, u64>,
+
+	/// Next ino
+	next_ino: u64,
+
+	/// All drives, alongside their file name
+	drives: Vec<(String, DrvFs<DriveFile<F>>)>,
+}
+
+impl<F: DiscFile> DiscFs<F> {
+	/// Time to live (?)
+	const TTL: Duration = Duration::from_secs(1);
+
+	/// Creates a new disc fs
+	pub fn new(file: F) -> Result<Self, anyhow::Error> {
+		// Read the filesystem
+		let mut cdrom = CdRomReader::new(file.try_clone().context("Unable to clone file")?);
+		let filesystem = FilesystemReader::new(&mut cdrom).context("Unable to read filesystem")?;
+
+		// Then locate all drives
+		let game_file = dcb_io::GameFile::new(CdRomCursor::new(file.try_clone().context("Unable to clone file")?))
+			.context("Unable to open game file")?;
+		let drive_locations = *game_file.drives();
+
+		// And create a filesystem for each of them, with their own file
+		// Note: Each cursor repairs the sectors it writes, so they can't share the file.
+		// Note: Missing drives are skipped, they were already reported when locating them.
+		let drives = Drives::LETTERS
+			.iter()
+			.filter_map(|&drive| drive_locations.get(drive).map(|location| (drive, location)))
+			.zip(1_u64..)
+			.map(|((drive, location), idx)| {
+				let file = file.try_clone().context("Unable to clone file")?;
+				let cursor = DriveCursor::new_with_offset_len(CdRomCursor::new(file), location.offset, location.size)
+					.with_context(|| format!("Unable to open drive {drive}"))?;
+
+				let drv_fs = DrvFs::with_root_ino(cursor, (idx << 32) | fuser::FUSE_ROOT_ID);
+				Ok((format!("{drive}.DRV"), drv_fs))
+			})
+			.collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+		// Creates the inode maps with the root inode
+		let mut inodes = HashMap::new();
+		let mut ino_by_path = HashMap::new();
+		inodes.insert(fuser::FUSE_ROOT_ID, Inode {
+			path:  String::new(),
+			entry: filesystem.root_dir().clone(),
+		});
+		ino_by_path.insert(String::new(), fuser::FUSE_ROOT_ID);
+
+		Ok(Self {
+			cdrom,
+			filesystem,
+			inodes,
+			ino_by_path,
+			next_ino: fuser::FUSE_ROOT_ID + 1,
+			drives,
+		})
+	}
+
+	/// Returns the drive an inode belongs to, if any
+	fn drive(&mut self, ino: u64) -> Option<&mut DrvFs<DriveFile<F>>> {
+		match ino >> 32 {
+			0 => None,
+			idx => {
+				let idx = usize::try_from(idx - 1).ok()?;
+				self.drives.get_mut(idx).map(|(_, drive)| drive)
+			},
+		}
+	}
+
+	/// Returns the drive with the file name `name` if `parent` is the root
+	fn drive_by_name(&mut self, parent: u64, name: &str) -> Option<&mut DrvFs<DriveFile<F>>> {
+		if parent != fuser::FUSE_ROOT_ID {
+			return None;
+		}
+
+		self.drives
+			.iter_mut()
+			.find(|(drive_name, _)| drive_name == name)
+			.map(|(_, drive)| drive)
+	}
+
+	/// Returns an inode
+	fn inode(&self, ino: u64) -> Result<&Inode, anyhow::Error> {
+		self.inodes.get(&ino).context("Unable to get inode")
+	}
+
+	/// Returns the inode of a path, creating it if it doesn't exist
+	fn ino_by_path(&mut self, path: String, entry: DirEntry) -> u64 {
+		match self.ino_by_path.entry(path) {
+			hash_map::Entry::Occupied(ino) => *ino.get(),
+			hash_map::Entry::Vacant(ino_entry) => {
+				let ino = self.next_ino;
+				self.next_ino += 1;
+
+				let inode = Inode {
+					path: ino_entry.key().clone(),
+					entry,
+				};
+				assert!(self.inodes.insert(ino, inode).is_none(), "Inode already existed");
+
+				*ino_entry.insert(ino)
+			},
+		}
+	}
+
+	/// Returns the attributes of an inode
+	fn attr(&self, ino: u64) -> Result<fuser::FileAttr, anyhow::Error> {
+		let entry = &self.inode(ino)?.entry;
+
+		let (kind, size, blocks, perm) = match entry.is_dir() {
+			true => (fuser::FileType::Directory, 0, 1, 0o555),
+			false => {
+				let size = u64::from(entry.size);
+				(fuser::FileType::RegularFile, size, (size + 0x7ff) / 0x800, 0o444)
+			},
+		};
+
+		// Note: Invalid dates, and dates before the unix epoch, are simply ignored
+		let date = chrono::NaiveDate::from_ymd_opt(
+			1900 + i32::from(entry.date.year),
+			u32::from(entry.date.month),
+			u32::from(entry.date.day),
+		)
+		.and_then(|date| {
+			date.and_hms_opt(
+				u32::from(entry.date.hour),
+				u32::from(entry.date.minutes),
+				u32::from(entry.date.seconds),
+			)
+		})
+		.and_then(|date| u64::try_from(date.timestamp()).ok())
+		.map_or(SystemTime::UNIX_EPOCH, |secs| {
+			SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+		});
+
+		Ok(fuser::FileAttr {
+			ino,
+			size,
+			blocks,
+			atime: date,
+			mtime: date,
+			ctime: date,
+			crtime: date,
+			kind,
+			perm,
+			nlink: 1,
+			uid: 1000,
+			gid: 1001,
+			rdev: 0,
+			flags: 0,
+			blksize: 0x800,
+		})
+	}
+}
+
+impl<F: DiscFile> DiscFs<F> {
+	fn lookup(&mut self, parent: u64, name: &OsStr) -> Result<fuser::FileAttr, anyhow::Error> {
+		let name = name.to_str().context("Unable to get name as utf-8")?;
+
+		// If it's a drive, get it's root
+		if let Some(drive) = self.drive_by_name(parent, name) {
+			let root_ino = drive.root_ino();
+			return drive.attr(root_ino);
+		}
+
+		// Else find the entry
+		let mut path = self.inode(parent)?.path.clone();
+		path.push('/');
+		path.push_str(name);
+		let entry = self
+			.filesystem
+			.find(&mut self.cdrom, &path)
+			.context("Unable to find entry")?;
+
+		let ino = self.ino_by_path(path, entry);
+		self.attr(ino)
+	}
+
+	fn read(&mut self, ino: u64, offset: i64, size: u32) -> Result<Vec<u8>, anyhow::Error> {
+		// Get the file
+		let entry = &self.inodes.get(&ino).context("Unable to get inode")?.entry;
+		let mut file = entry.read_file(&mut self.cdrom).context("Unable to read file")?;
+
+		// Then seek and read
+		let offset = u64::try_from(offset).context("Offset was negative")?;
+		let offset = u64::min(offset, u64::from(entry.size));
+		file.seek(SeekFrom::Start(offset)).context("Unable to seek to offset")?;
+
+		let mut data = vec![];
+		file.take(u64::from(size))
+			.read_to_end(&mut data)
+			.context("Unable to read data")?;
+
+		Ok(data)
+	}
+
+	fn read_dir(
+		&mut self, ino: u64, offset: i64, mut add_entry: impl FnMut(u64, i64, fuser::FileType, &str) -> bool,
+	) -> Result<(), anyhow::Error> {
+		// Read the directory
+		let inode = self.inodes.get(&ino).context("Unable to get inode")?;
+		let dir_path = inode.path.clone();
+		let dir = inode
+			.entry
+			.read_dir(&mut self.cdrom)
+			.context("Cannot read-dir non-directories")?;
+
+		let skip = usize::try_from(offset).context("Offset was negative")?;
+		for (entry, idx) in dir.into_entries().into_iter().skip(skip).zip(offset..) {
+			let name = entry.name.without_version().to_owned();
+
+			// If it's a drive, use it's root, else get it's inode
+			let (entry_ino, kind) = match self.drive_by_name(ino, &name) {
+				Some(drive) => (drive.root_ino(), fuser::FileType::Directory),
+				None => {
+					let kind = match entry.is_dir() {
+						true => fuser::FileType::Directory,
+						false => fuser::FileType::RegularFile,
+					};
+					(self.ino_by_path(format!("{dir_path}/{name}"), entry), kind)
+				},
+			};
+
+			if add_entry(entry_ino, idx + 1, kind, &name) {
+				break;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl<F: DiscFile> Filesystem for DiscFs<F> {
+	fn destroy(&mut self, _req: &fuser::Request<'_>) {
+		for (_, drive) in &mut self.drives {
+			drive.write_back_all();
+		}
+	}
+
+	fn lookup(&mut self, req: &fuser::Request<'_>, parent: u64, name: &OsStr, reply: fuser::ReplyEntry) {
+		if let Some(drive) = self.drive(parent) {
+			return Filesystem::lookup(drive, req, parent, name, reply);
+		}
+
+		match self.lookup(parent, name) {
+			Ok(attr) => reply.entry(&Self::TTL, &attr, 0),
+			Err(err) => {
+				log::error!("Unable to lookup {name:?}@{parent}: {err:?}");
+				reply.error(libc::ENOENT);
+			},
+		}
+	}
+
+	fn getattr(&mut self, req: &fuser::Request<'_>, ino: u64, reply: fuser::ReplyAttr) {
+		if let Some(drive) = self.drive(ino) {
+			return Filesystem::getattr(drive, req, ino, reply);
+		}
+
+		match self.attr(ino) {
+			Ok(attr) => reply.attr(&Self::TTL, &attr),
+			Err(err) => {
+				log::error!("Unable to get attributes for {ino}: {err:?}");
+				reply.error(libc::ENOENT);
+			},
+		}
+	}
+
+	fn setattr(
+		&mut self, req: &fuser::Request<'_>, ino: u64, mode: Option<u32>, uid: Option<u32>, gid: Option<u32>,
+		size: Option<u64>, atime: Option<TimeOrNow>, mtime: Option<TimeOrNow>, ctime: Option<SystemTime>,
+		fh: Option<u64>, crtime: Option<SystemTime>, chgtime: Option<SystemTime>, bkuptime: Option<SystemTime>,
+		flags: Option<u32>, reply: fuser::ReplyAttr,
+	) {
+		match self.drive(ino) {
+			Some(drive) => Filesystem::setattr(
+				drive, req, ino, mode, uid, gid, size, atime, mtime, ctime, fh, crtime, chgtime, bkuptime, flags, reply,
+			),
+			None => reply.error(libc::EROFS),
+		}
+	}
+
+	fn read(
+		&mut self, req: &fuser::Request<'_>, ino: u64, fh: u64, offset: i64, size: u32, flags: i32,
+		lock_owner: Option<u64>, reply: fuser::ReplyData,
+	) {
+		if let Some(drive) = self.drive(ino) {
+			return Filesystem::read(drive, req, ino, fh, offset, size, flags, lock_owner, reply);
+		}
+
+		match self.read(ino, offset, size) {
+			Ok(data) => reply.data(&data),
+			Err(err) => {
+				log::error!("Unable to read {offset}/{size}@{ino}: {err:?}");
+				reply.error(libc::ENOENT);
+			},
+		}
+	}
+
+	fn write(
+		&mut self, req: &fuser::Request<'_>, ino: u64, fh: u64, offset: i64, data: &[u8], write_flags: u32, flags: i32,
+		lock_owner: Option<u64>, reply: fuser::ReplyWrite,
+	) {
+		match self.drive(ino) {
+			Some(drive) => Filesystem::write(drive, req, ino, fh, offset, data, write_flags, flags, lock_owner, reply),
+			None => reply.error(libc::EROFS),
+		}
+	}
+
+	fn flush(&mut self, req: &fuser::Request<'_>, ino: u64, fh: u64, lock_owner: u64, reply: fuser::ReplyEmpty) {
+		match self.drive(ino) {
+			Some(drive) => Filesystem::flush(drive, req, ino, fh, lock_owner, reply),
+			None => reply.ok(),
+		}
+	}
+
+	fn release(
+		&mut self, req: &fuser::Request<'_>, ino: u64, fh: u64, flags: i32, lock_owner: Option<u64>, flush: bool,
+		reply: fuser::ReplyEmpty,
+	) {
+		match self.drive(ino) {
+			Some(drive) => Filesystem::release(drive, req, ino, fh, flags, lock_owner, flush, reply),
+			None => reply.ok(),
+		}
+	}
+
+	fn fsync(&mut self, req: &fuser::Request<'_>, ino: u64, fh: u64, datasync: bool, reply: fuser::ReplyEmpty) {
+		match self.drive(ino) {
+			Some(drive) => Filesystem::fsync(drive, req, ino, fh, datasync, reply),
+			None => reply.ok(),
+		}
+	}
+
+	fn create(
+		&mut self, req: &fuser::Request<'_>, parent: u64, name: &OsStr, mode: u32, umask: u32, flags: i32,
+		reply: fuser::ReplyCreate,
+	) {
+		match self.drive(parent) {
+			Some(drive) => Filesystem::create(drive, req, parent, name, mode, umask, flags, reply),
+			None => reply.error(libc::EROFS),
+		}
+	}
+
+	fn mkdir(
+		&mut self, req: &fuser::Request<'_>, parent: u64, name: &OsStr, mode: u32, umask: u32, reply: fuser::ReplyEntry,
+	) {
+		match self.drive(parent) {
+			Some(drive) => Filesystem::mkdir(drive, req, parent, name, mode, umask, reply),
+			None => reply.error(libc::EROFS),
+		}
+	}
+
+	fn unlink(&mut self, req: &fuser::Request<'_>, parent: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
+		match self.drive(parent) {
+			Some(drive) => Filesystem::unlink(drive, req, parent, name, reply),
+			None => reply.error(libc::EROFS),
+		}
+	}
+
+	fn rmdir(&mut self, req: &fuser::Request<'_>, parent: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
+		match self.drive(parent) {
+			Some(drive) => Filesystem::rmdir(drive, req, parent, name, reply),
+			None => reply.error(libc::EROFS),
+		}
+	}
+
+	fn rename(
+		&mut self, req: &fuser::Request<'_>, parent: u64, name: &OsStr, new_parent: u64, new_name: &OsStr, flags: u32,
+		reply: fuser::ReplyEmpty,
+	) {
+		// Note: The drive filesystem already rejects renames across directories
+		match self.drive(parent) {
+			Some(drive) => Filesystem::rename(drive, req, parent, name, new_parent, new_name, flags, reply),
+			None => reply.error(libc::EROFS),
+		}
+	}
+
+	fn readdir(&mut self, req: &fuser::Request, ino: u64, fh: u64, offset: i64, mut reply: fuser::ReplyDirectory) {
+		if let Some(drive) = self.drive(ino) {
+			return Filesystem::readdir(drive, req, ino, fh, offset, reply);
+		}
+
+		let new_entry = |ino, offset, kind, name: &str| reply.add(ino, offset, kind, name);
+		match self.read_dir(ino, offset, new_entry) {
+			Ok(()) => reply.ok(),
+			Err(err) => {
+				log::error!("Unable to read directory {ino}/{offset}: {err:?}");
+				reply.error(libc::ENOENT);
+			},
+		}
+	}
+
+	fn statfs(&mut self, req: &fuser::Request<'_>, ino: u64, reply: fuser::ReplyStatfs) {
+		match self.drive(ino) {
+			Some(drive) => Filesystem::statfs(drive, req, ino, reply),
+			None => {
+				let blocks = u64::from(self.filesystem.primary_volume_descriptor().volume_space_size);
+				let files = u64::try_from(self.inodes.len()).expect("Number of inodes didn't fit into a `u64`");
+				reply.statfs(blocks, 0, 0, files, 0, 0x800, 0xff, 0x800);
+			},
+		}
+	}
+}
+
+/// Inode
+#[derive(Debug)]
+pub struct Inode {
+	/// Path, separated by `/`, empty for the root
+	path: String,
+
+	/// Entry
+	entry: DirEntry,
+}
@@ -0,0 +1,218 @@
+//! Tests
+
+// Imports
+use super::*;
+use dcb_bytes::Bytes;
+use dcb_cdrom_xa::{sector::header::SubHeader, CdRomWriter};
+use dcb_drv::DirPtr;
+use dcb_iso9660::{
+	date_time::{DecDateTime, DirDateTime},
+	string::FileString,
+	writer::{DirEntryWriter, DirEntryWriterKind, DirWriterLister, VolumeInfo},
+	FilesystemWriter, StrArrA, StrArrD,
+};
+use std::{cell::RefCell, convert::Infallible, rc::Rc};
+
+/// In-memory disc file, shared by all it's clones
+#[derive(Clone, Debug)]
+struct MemFile(Rc<RefCell<io::Cursor<Vec<u8>>>>);
+
+impl Read for MemFile {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+		self.0.borrow_mut().read(buf)
+	}
+}
+
+impl Write for MemFile {
+	fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+		self.0.borrow_mut().write(buf)
+	}
+
+	fn flush(&mut self) -> Result<(), io::Error> {
+		self.0.borrow_mut().flush()
+	}
+}
+
+impl Seek for MemFile {
+	fn seek(&mut self, pos: SeekFrom) -> Result<u64, io::Error> {
+		self.0.borrow_mut().seek(pos)
+	}
+}
+
+impl DiscFile for MemFile {
+	fn try_clone(&self) -> Result<Self, io::Error> {
+		Ok(self.clone())
+	}
+}
+
+/// Directory lister over in-memory files
+struct Lister(Vec<DirEntryWriter<Lister>>);
+
+impl DirWriterLister for Lister {
+	type Error = Infallible;
+	type FileReader = io::Cursor<Vec<u8>>;
+}
+
+impl IntoIterator for Lister {
+	type IntoIter = std::iter::Map<std::vec::IntoIter<DirEntryWriter<Self>>, fn(DirEntryWriter<Self>) -> Self::Item>;
+	type Item = Result<DirEntryWriter<Self>, Infallible>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.0.into_iter().map(Ok)
+	}
+}
+
+/// Date used for all entries
+const DATE: DirDateTime = DirDateTime {
+	year:      100,
+	month:     1,
+	day:       1,
+	hour:      0,
+	minutes:   0,
+	seconds:   0,
+	time_zone: 0,
+};
+
+/// Creates a file entry writer named `name` with `contents`
+fn file_writer(name: &str, contents: Vec<u8>) -> DirEntryWriter<Lister> {
+	DirEntryWriter {
+		name:       FileString::from_bytes(name.as_bytes()).expect("Invalid name"),
+		date:       self::DATE,
+		system_use: vec![],
+		sector_pos: None,
+		kind:       DirEntryWriterKind::File {
+			size:       u32::try_from(contents.len()).expect("File size didn't fit into a `u32`"),
+			reader:     io::Cursor::new(contents),
+			subheaders: None,
+		},
+	}
+}
+
+/// Creates the drive `B.DRV`, with the file `A.BIN`
+fn create_drive() -> Vec<u8> {
+	let mut drive = io::Cursor::new(vec![0; 2 * 0x800]);
+	let name = ascii::AsciiStr::from_ascii("A.BIN").expect("Invalid name");
+	DirPtr::root()
+		.create_file(
+			&mut drive,
+			name,
+			chrono::NaiveDateTime::from_timestamp(0, 0),
+			&mut [0xaa; 0x10].as_slice(),
+		)
+		.expect("Unable to create file");
+
+	drive.into_inner()
+}
+
+/// Creates a disc with the file `README.TXT` and the drive `B.DRV` in it's root directory
+fn create_disc() -> MemFile {
+	let entries = vec![
+		self::file_writer("README.TXT;1", b"Hello".to_vec()),
+		self::file_writer("B.DRV;1", self::create_drive()),
+	];
+
+	let date_time = DecDateTime::deserialize_bytes(b"0000000000000000\0").expect("Unable to create date time");
+	let info = VolumeInfo {
+		system_id:                     StrArrA::from_bytes(&[b' '; 0x20]).expect("Invalid system id"),
+		volume_id:                     StrArrD::from_bytes(&[b' '; 0x20]).expect("Invalid volume id"),
+		volume_set_id:                 StrArrD::from_bytes(&[b' '; 0x80]).expect("Invalid volume set id"),
+		publisher_id:                  StrArrA::from_bytes(&[b' '; 0x80]).expect("Invalid publisher id"),
+		data_preparer_id:              StrArrA::from_bytes(&[b' '; 0x80]).expect("Invalid data preparer id"),
+		application_id:                StrArrA::from_bytes(&[b' '; 0x80]).expect("Invalid application id"),
+		copyright_file_id:             StrArrD::from_bytes(&[b' '; 0x26]).expect("Invalid copyright file id"),
+		abstract_file_id:              StrArrD::from_bytes(&[b' '; 0x24]).expect("Invalid abstract file id"),
+		bibliographic_file_id:         StrArrD::from_bytes(&[b' '; 0x25]).expect("Invalid bibliographic file id"),
+		volume_creation_date_time:     date_time,
+		volume_modification_date_time: date_time,
+		volume_expiration_date_time:   date_time,
+		volume_effective_date_time:    date_time,
+		application_use:               [0; 0x200],
+		root_date:                     self::DATE,
+		root_system_use:               vec![],
+		root_sector_pos:               None,
+		path_table_locations:          None,
+		volume_space_size:             None,
+	};
+
+	// Note: The system area is left empty
+	let mut image = vec![];
+	let mut writer = CdRomWriter::new(&mut image, 0);
+	for _ in 0..FilesystemWriter::<Lister>::PRIMARY_VOLUME_DESCRIPTOR_SECTOR {
+		writer
+			.write_sector([0; 0x800], SubHeader::new())
+			.expect("Unable to write system area");
+	}
+	FilesystemWriter::new(info, Lister(entries))
+		.write(&mut writer)
+		.expect("Unable to write filesystem");
+
+	MemFile(Rc::new(RefCell::new(io::Cursor::new(image))))
+}
+
+/// Looks up `name` in `parent`, returning it's inode
+fn lookup(fs: &mut DiscFs<MemFile>, parent: u64, name: &str) -> u64 {
+	fs.lookup(parent, OsStr::new(name)).expect("Unable to lookup entry").ino
+}
+
+#[test]
+fn lookup_entries() {
+	let mut fs = DiscFs::new(self::create_disc()).expect("Unable to open disc");
+
+	let attr = fs
+		.lookup(fuser::FUSE_ROOT_ID, OsStr::new("README.TXT"))
+		.expect("Unable to lookup file");
+	assert_eq!(attr.kind, fuser::FileType::RegularFile);
+	assert_eq!(attr.size, 5);
+	assert_eq!(self::lookup(&mut fs, fuser::FUSE_ROOT_ID, "README.TXT"), attr.ino);
+
+	// Drives should be looked up as their root directory
+	let attr = fs
+		.lookup(fuser::FUSE_ROOT_ID, OsStr::new("B.DRV"))
+		.expect("Unable to lookup drive");
+	assert_eq!(attr.kind, fuser::FileType::Directory);
+	assert_eq!(attr.ino, (1 << 32) | fuser::FUSE_ROOT_ID);
+
+	assert!(fs.lookup(fuser::FUSE_ROOT_ID, OsStr::new("MISSING.TXT")).is_err());
+}
+
+#[test]
+fn read_file() {
+	let mut fs = DiscFs::new(self::create_disc()).expect("Unable to open disc");
+	let ino = self::lookup(&mut fs, fuser::FUSE_ROOT_ID, "README.TXT");
+
+	assert_eq!(fs.read(ino, 0, 0x100).expect("Unable to read file"), b"Hello");
+	assert_eq!(fs.read(ino, 2, 2).expect("Unable to read file"), b"ll");
+	assert!(fs.read(ino, 6, 1).expect("Unable to read file").is_empty());
+	assert!(fs.read(ino, -1, 1).is_err());
+}
+
+#[test]
+fn write_drive_file() {
+	let disc = self::create_disc();
+
+	// Write to a file within the drive
+	{
+		let mut fs = DiscFs::new(disc.clone()).expect("Unable to open disc");
+		let drive_ino = self::lookup(&mut fs, fuser::FUSE_ROOT_ID, "B.DRV");
+		let drive = fs.drive(drive_ino).expect("Drive wasn't found");
+		let ino = drive
+			.lookup(drive_ino, OsStr::new("A.BIN"))
+			.expect("Unable to lookup file")
+			.ino;
+		assert_eq!(drive.write(ino, 0, &[0xbb; 0x20]).expect("Unable to write file"), 0x20);
+		drive.write_back_all();
+	}
+
+	// Then make sure it's there after re-opening the disc, and the rest of the disc is intact
+	let mut fs = DiscFs::new(disc).expect("Unable to open disc");
+	let drive_ino = self::lookup(&mut fs, fuser::FUSE_ROOT_ID, "B.DRV");
+	let drive = fs.drive(drive_ino).expect("Drive wasn't found");
+	let ino = drive
+		.lookup(drive_ino, OsStr::new("A.BIN"))
+		.expect("Unable to lookup file")
+		.ino;
+	assert_eq!(drive.read(ino, 0, 0x100).expect("Unable to read file"), [0xbb; 0x20]);
+
+	let ino = self::lookup(&mut fs, fuser::FUSE_ROOT_ID, "README.TXT");
+	assert_eq!(fs.read(ino, 0, 0x100).expect("Unable to read file"), b"Hello");
+}
@@ -4,18 +4,25 @@
 // TODO: Check all usages and remove
 #![allow(clippy::as_conversions)]
 
+// Modules
+#[cfg(test)]
+mod test;
+
 // Imports
 use anyhow::Context;
-use ascii::{AsciiChar, AsciiStr};
-use dcb_drv::{DirEntry, DirEntryKind, DirPtr, FilePtr};
-use fuser::Filesystem;
+use ascii::{AsciiChar, AsciiStr, AsciiString};
+use chrono::NaiveDateTime;
+use dcb_drv::{
+	ptr::dir::{CreateDirError, CreateFileError, RemoveEntryError},
+	DirEntry, DirEntryKind, DirPtr, Path, SectorAllocator,
+};
+use fuser::{Filesystem, TimeOrNow};
 use itertools::Itertools;
 use std::{
 	collections::{hash_map, HashMap},
 	convert::TryInto,
-	ffi::{OsStr, OsString},
-	fs,
-	io::{Read, Seek, SeekFrom},
+	ffi::OsStr,
+	io::{Read, Seek, SeekFrom, Write},
 	os::unix::prelude::OsStrExt,
 	time::{Duration, SystemTime},
 };
@@ -24,91 +31,273 @@ use zutil::AsciiStrArr;
 
 /// Drv filesystem
 #[derive(Debug)]
-pub struct DrvFs {
+pub struct DrvFs<T> {
 	/// Open file
-	file: fs::File,
+	file: T,
+
+	/// Root ino
+	root_ino: u64,
 
 	/// Inodes
 	inodes: HashMap<u64, Inode>,
 
-	/// Ino by sector pos
-	ino_by_sector_pos: HashMap<u32, u64>,
+	/// Ino by path
+	ino_by_path: HashMap<AsciiString, u64>,
+
+	/// Next ino
+	next_ino: u64,
 }
 
-impl DrvFs {
+impl<T: Read + Write + Seek> DrvFs<T> {
 	/// Time to live (?)
 	const TTL: Duration = Duration::from_secs(1);
 
 	/// Creates a new drv fs
-	pub fn new(path: impl AsRef<std::path::Path>) -> Result<Self, anyhow::Error> {
-		// Try to open the file
-		let file = fs::File::open(path).context("Unable to open file")?;
-
-		// Creates the inode maps
-		let mut inodes_by_ino = HashMap::new();
-		let mut ino_by_sector_pos = HashMap::new();
+	pub fn new(file: T) -> Self {
+		Self::with_root_ino(file, fuser::FUSE_ROOT_ID)
+	}
 
-		// Insert the root inode
-		inodes_by_ino.insert(1, Inode::root());
-		ino_by_sector_pos.insert(0, 1);
+	/// Creates a new drv fs with all inodes starting at `root_ino`.
+	///
+	/// Allows the filesystem to be mounted within another one.
+	pub fn with_root_ino(file: T, root_ino: u64) -> Self {
+		// Creates the inode maps with the root inode
+		let mut inodes = HashMap::new();
+		let mut ino_by_path = HashMap::new();
+		inodes.insert(root_ino, Inode::new(AsciiString::new()));
+		ino_by_path.insert(AsciiString::new(), root_ino);
 
-		Ok(Self {
+		Self {
 			file,
-			inodes: inodes_by_ino,
-			ino_by_sector_pos,
-		})
+			root_ino,
+			inodes,
+			ino_by_path,
+			next_ino: root_ino + 1,
+		}
 	}
-}
 
-impl DrvFs {
-	fn lookup(
-		&mut self, _req: &fuser::Request<'_>, parent: u64, name: &std::ffi::OsStr,
-	) -> Result<fuser::FileAttr, anyhow::Error> {
-		// Get the parent inode
-		let parent_inode = self.inodes.get(&parent).context("Unable to get inode")?;
+	/// Returns the root ino
+	pub const fn root_ino(&self) -> u64 {
+		self.root_ino
+	}
 
-		// It it's not a directory, return Err
-		let dir = match parent_inode.kind {
-			InodeKind::Dir { ptr } => ptr,
-			_ => anyhow::bail!("Cannot lookup non-directories"),
-		};
+	/// Writes all modified files to the drive and flushes it
+	pub fn write_back_all(&mut self) {
+		let inos = self.inodes.keys().copied().collect::<Vec<_>>();
+		for ino in inos {
+			if let Err(err) = self.write_back(ino) {
+				log::error!("Unable to write {ino}: {err:?}");
+			}
+		}
 
-		// Get the name
-		let name = AsciiStr::from_ascii(name.as_bytes()).context("Unable to get name as ascii")?;
+		if let Err(err) = self.file.flush() {
+			log::error!("Unable to flush drive: {err:?}");
+		}
+	}
 
-		// Then find the entry
-		let (_, entry) = dir.find_entry(&mut self.file, name).context("Unable to find entry")?;
+	/// Returns an inode
+	fn inode(&self, ino: u64) -> Result<&Inode, anyhow::Error> {
+		self.inodes.get(&ino).context("Unable to get inode")
+	}
 
-		let ino = self
-			.ino_by_sector_pos
-			.get(&entry.kind.sector_pos())
-			.context("Unable to get ino by sector pos")?;
-		let inode = self.inodes.get(ino).context("Unable to get inode by ino")?;
-		Ok(inode.attr())
+	/// Returns the inode of a path, creating it if it doesn't exist
+	fn ino_by_path(&mut self, path: AsciiString) -> u64 {
+		match self.ino_by_path.entry(path) {
+			hash_map::Entry::Occupied(entry) => *entry.get(),
+			hash_map::Entry::Vacant(entry) => {
+				let ino = self.next_ino;
+				self.next_ino += 1;
+
+				let inode = Inode::new(entry.key().clone());
+				assert!(self.inodes.insert(ino, inode).is_none(), "Inode already existed");
+
+				*entry.insert(ino)
+			},
+		}
 	}
 
-	fn get_attr(&mut self, _req: &fuser::Request<'_>, ino: u64) -> Result<fuser::FileAttr, anyhow::Error> {
-		// Get the inode
+	/// Returns the entry of an inode, or `None` for the root
+	fn entry(&mut self, ino: u64) -> Result<Option<DirEntry>, anyhow::Error> {
 		let inode = self.inodes.get(&ino).context("Unable to get inode")?;
+		if inode.is_root() {
+			return Ok(None);
+		}
 
-		Ok(inode.attr())
+		let (_, entry) = DirPtr::root()
+			.find(&mut self.file, Path::new(&inode.path))
+			.context("Unable to find entry")?;
+		Ok(Some(entry))
 	}
 
-	// TODO: Not return a `Vec<u8>` of data
-	#[allow(clippy::too_many_arguments)] // It's what we receive from `fuse`.
-	fn read(
-		&mut self, _req: &fuser::Request<'_>, ino: u64, _fh: u64, offset: i64, size: u32, _flags: i32,
-		_lock_owner: Option<u64>,
-	) -> Result<Vec<u8>, anyhow::Error> {
-		// Get the inode
-		let inode = self.inodes.get(&ino).context("Unable to get inode")?;
+	/// Returns the directory pointer of an inode
+	fn dir_ptr(&mut self, ino: u64) -> Result<DirPtr, anyhow::Error> {
+		match self.entry(ino)? {
+			None => Ok(DirPtr::root()),
+			Some(entry) => entry.kind.as_dir_ptr().context("Inode wasn't a directory"),
+		}
+	}
 
-		// It it's not a file, return Err
-		let file = match inode.kind {
-			InodeKind::File { ptr } => ptr,
-			_ => anyhow::bail!("Cannot read non-files"),
+	/// Returns the attributes of an inode
+	pub fn attr(&mut self, ino: u64) -> Result<fuser::FileAttr, anyhow::Error> {
+		let entry = self.entry(ino)?;
+		let inode = self.inode(ino)?;
+
+		let (kind, size, date) = match entry {
+			None => (fuser::FileType::Directory, 0, SystemTime::now()),
+			Some(entry) => {
+				let date = SystemTime::UNIX_EPOCH + Duration::from_secs(entry.date.timestamp() as u64);
+				match entry.kind {
+					DirEntryKind::File { ptr, .. } => {
+						let size = inode
+							.contents
+							.as_ref()
+							.map_or(u64::from(ptr.size), |contents| contents.len() as u64);
+						(fuser::FileType::RegularFile, size, date)
+					},
+					DirEntryKind::Dir { .. } => (fuser::FileType::Directory, 0, date),
+				}
+			},
+		};
+
+		let (blocks, perm) = match kind {
+			fuser::FileType::Directory => (1, 0o755),
+			_ => ((size + 0x7ff) / 0x800, 0o644),
 		};
 
+		Ok(fuser::FileAttr {
+			ino,
+			size,
+			blocks,
+			atime: date,
+			mtime: date,
+			ctime: date,
+			crtime: date,
+			kind,
+			perm,
+			nlink: 1,
+			uid: 1000,
+			gid: 1001,
+			rdev: 0,
+			flags: 0,
+			blksize: 0x800,
+		})
+	}
+
+	/// Returns the contents of a file, reading them from the drive if they haven't been yet.
+	fn contents(&mut self, ino: u64) -> Result<&mut Vec<u8>, anyhow::Error> {
+		if self.inode(ino)?.contents.is_none() {
+			let ptr = self
+				.entry(ino)?
+				.and_then(|entry| entry.kind.as_file_ptr())
+				.context("Inode wasn't a file")?;
+
+			let mut contents = Vec::with_capacity(ptr.size as usize);
+			ptr.cursor(&mut self.file)
+				.context("Unable to create cursor")?
+				.read_to_end(&mut contents)
+				.context("Unable to read file")?;
+			self.inodes.get_mut(&ino).context("Unable to get inode")?.contents = Some(contents);
+		}
+
+		Ok(self
+			.inodes
+			.get_mut(&ino)
+			.and_then(|inode| inode.contents.as_mut())
+			.expect("Contents were just read"))
+	}
+
+	/// Writes the modified contents of a file to the drive, if any
+	fn write_back(&mut self, ino: u64) -> Result<(), anyhow::Error> {
+		let inode = self.inodes.get_mut(&ino).context("Unable to get inode")?;
+		if let Some(contents) = &inode.contents {
+			dcb_drv::replace_file(&mut self.file, Path::new(&inode.path), &mut contents.as_slice())
+				.context("Unable to write file")?;
+			inode.contents = None;
+		}
+
+		Ok(())
+	}
+
+	/// Removes an inode and all inodes within it
+	fn remove_inodes(&mut self, path: &AsciiStr) {
+		let inodes = &mut self.inodes;
+		self.ino_by_path
+			.retain(|inode_path, ino| match self::is_within(inode_path, path) {
+				true => {
+					inodes.remove(ino);
+					false
+				},
+				false => true,
+			});
+	}
+}
+
+impl<T: Read + Write + Seek> DrvFs<T> {
+	/// Looks up the entry `name` in the directory `parent`, returning it's attributes
+	pub fn lookup(&mut self, parent: u64, name: &OsStr) -> Result<fuser::FileAttr, anyhow::Error> {
+		// Get the path of the entry and make sure it exists
+		let path = self.inode(parent)?.child_path(self::ascii_name(name)?);
+		DirPtr::root()
+			.find(&mut self.file, Path::new(&path))
+			.context("Unable to find entry")?;
+
+		let ino = self.ino_by_path(path);
+		self.attr(ino)
+	}
+
+	fn get_attr(&mut self, ino: u64) -> Result<fuser::FileAttr, anyhow::Error> {
+		self.attr(ino)
+	}
+
+	fn set_attr(
+		&mut self, ino: u64, size: Option<u64>, mtime: Option<TimeOrNow>,
+	) -> Result<fuser::FileAttr, anyhow::Error> {
+		// If we got a size, truncate or extend the file
+		if let Some(size) = size {
+			let size = size.try_into().context("File size was too large")?;
+			self.contents(ino)?.resize(size, 0);
+		}
+
+		// If we got a modification time, update the entry's date
+		// Note: The root directory has no entry, so it has no date.
+		if let (Some(mtime), false) = (mtime, self.inode(ino)?.is_root()) {
+			let mtime = match mtime {
+				TimeOrNow::SpecificTime(time) => time,
+				TimeOrNow::Now => SystemTime::now(),
+			};
+			let secs = mtime
+				.duration_since(SystemTime::UNIX_EPOCH)
+				.map_or(0, |duration| duration.as_secs());
+
+			let path = self.inode(ino)?.path.clone();
+			let (entry_ptr, mut entry) = DirPtr::root()
+				.find(&mut self.file, Path::new(&path))
+				.context("Unable to find entry")?;
+			entry.date = NaiveDateTime::from_timestamp(secs as i64, 0);
+			entry_ptr
+				.write(&mut self.file, &entry)
+				.context("Unable to write entry")?;
+		}
+
+		self.attr(ino)
+	}
+
+	/// Reads up to `size` bytes of a file at `offset`
+	// TODO: Not return a `Vec<u8>` of data
+	pub fn read(&mut self, ino: u64, offset: i64, size: u32) -> Result<Vec<u8>, anyhow::Error> {
+		// If we have modified contents, read from them
+		if let Some(contents) = &self.inode(ino)?.contents {
+			let start = usize::min(offset as usize, contents.len());
+			let end = usize::min(start + size as usize, contents.len());
+			return Ok(contents[start..end].to_vec());
+		}
+
+		// Else get the file
+		let file = self
+			.entry(ino)?
+			.and_then(|entry| entry.kind.as_file_ptr())
+			.context("Cannot read non-files")?;
+
 		// Then create the cursor
 		let mut cursor = file.cursor(&mut self.file).context("Unable to create cursor")?;
 
@@ -128,26 +317,102 @@ impl DrvFs {
 		Ok(data)
 	}
 
+	/// Writes `data` to a file at `offset`.
+	///
+	/// The file is only written to the drive once it's written back.
+	pub fn write(&mut self, ino: u64, offset: i64, data: &[u8]) -> Result<u32, anyhow::Error> {
+		// Note: Writes are only done on the drive once the file is flushed
+		let contents = self.contents(ino)?;
+		let offset = offset as usize;
+		if contents.len() < offset + data.len() {
+			contents.resize(offset + data.len(), 0);
+		}
+		contents[offset..(offset + data.len())].copy_from_slice(data);
+
+		data.len().try_into().context("Written size was too large")
+	}
+
+	fn create(&mut self, parent: u64, name: &OsStr) -> Result<fuser::FileAttr, anyhow::Error> {
+		let path = self.inode(parent)?.child_path(self::ascii_name(name)?);
+		let dir = self.dir_ptr(parent)?;
+		dir.create_file(
+			&mut self.file,
+			self::ascii_name(name)?,
+			chrono::Utc::now().naive_utc(),
+			&mut std::io::empty(),
+		)
+		.context("Unable to create file")?;
+
+		let ino = self.ino_by_path(path);
+		self.attr(ino)
+	}
+
+	fn mkdir(&mut self, parent: u64, name: &OsStr) -> Result<fuser::FileAttr, anyhow::Error> {
+		let path = self.inode(parent)?.child_path(self::ascii_name(name)?);
+		let dir = self.dir_ptr(parent)?;
+		dir.create_dir(&mut self.file, self::ascii_name(name)?, chrono::Utc::now().naive_utc())
+			.context("Unable to create directory")?;
+
+		let ino = self.ino_by_path(path);
+		self.attr(ino)
+	}
+
+	fn remove(&mut self, parent: u64, name: &OsStr) -> Result<(), anyhow::Error> {
+		let path = self.inode(parent)?.child_path(self::ascii_name(name)?);
+		let dir = self.dir_ptr(parent)?;
+		dir.remove_entry(&mut self.file, self::ascii_name(name)?)
+			.context("Unable to remove entry")?;
+
+		self.remove_inodes(&path);
+		Ok(())
+	}
+
+	fn rename(&mut self, parent: u64, name: &OsStr, new_name: &OsStr) -> Result<(), anyhow::Error> {
+		let path = self.inode(parent)?.child_path(self::ascii_name(name)?);
+		let new_path = self.inode(parent)?.child_path(self::ascii_name(new_name)?);
+		let dir = self.dir_ptr(parent)?;
+
+		// If the new path already exists, remove it first.
+		if path != new_path && dir.find_entry(&mut self.file, self::ascii_name(new_name)?).is_ok() {
+			dir.remove_entry(&mut self.file, self::ascii_name(new_name)?)
+				.context("Unable to remove existing entry")?;
+			self.remove_inodes(&new_path);
+		}
+
+		dir.rename_entry(&mut self.file, self::ascii_name(name)?, self::ascii_name(new_name)?)
+			.context("Unable to rename entry")?;
+
+		// Then move all inodes within the entry to the new path
+		let inodes = self
+			.ino_by_path
+			.iter()
+			.filter(|(inode_path, _)| self::is_within(inode_path, &path))
+			.map(|(inode_path, &ino)| (inode_path.clone(), ino))
+			.collect::<Vec<_>>();
+		for (inode_path, ino) in inodes {
+			let mut inode_new_path = new_path.clone();
+			inode_new_path.push_str(&inode_path[path.len()..]);
+
+			self.ino_by_path.remove(&inode_path);
+			self.ino_by_path.insert(inode_new_path.clone(), ino);
+			self.inodes.get_mut(&ino).context("Unable to get inode")?.path = inode_new_path;
+		}
+
+		Ok(())
+	}
+
 	fn read_dir(
-		&mut self, _req: &fuser::Request, ino: u64, _fh: u64, offset: i64,
+		&mut self, ino: u64, _fh: u64, offset: i64,
 		mut add_entry: impl FnMut(u64, i64, fuser::FileType, AsciiStrArr<0x14>) -> bool,
 	) -> Result<(), anyhow::Error> {
-		// Get the inode
-		let inode = self.inodes.get(&ino).context("Unable to get inode")?;
-
-		// It it's not a directory, return Err
-		let dir = match inode.kind {
-			InodeKind::Dir { ptr } => ptr,
-			_ => anyhow::bail!("Cannot read-dir non-directories"),
-		};
+		// Get the directory
+		let dir = self.dir_ptr(ino).context("Cannot read-dir non-directories")?;
 
 		// Then read all entries
 		// TODO: Skipping here still parses all entries, make `DirPtr::read_entries` return a proper
 		//       iterator in order to implement `skip`.
-		let entries = dir.read_entries(&mut self.file).context("Unable to read entries")?;
-		for (entry, idx) in entries.skip(offset as usize).zip(0..) {
-			let dir_entry: DirEntry = entry.context("Unable to read entry")?;
-
+		let entries = dir.read_all_entries(&mut self.file).context("Unable to read entries")?;
+		for (dir_entry, idx) in entries.into_iter().skip(offset as usize).zip(offset..) {
 			// Get the name
 			let mut name: AsciiStrArr<0x14> = AsciiStrArr::new();
 			for &ch in dir_entry.name.as_ascii() {
@@ -161,28 +426,14 @@ impl DrvFs {
 			}
 
 			// Then get it's inode
-			let sector_pos = dir_entry.kind.sector_pos();
-			let ino = match self.ino_by_sector_pos.entry(sector_pos) {
-				hash_map::Entry::Occupied(entry) => *entry.get(),
-				hash_map::Entry::Vacant(entry) => {
-					// Create the new inode
-					let inode = Inode {
-						ino:   self.inodes.len() as u64 + 1,
-						_name: OsStr::from_bytes(name.as_bytes()).to_os_string(),
-						date:  SystemTime::UNIX_EPOCH + Duration::from_secs(dir_entry.date.timestamp() as u64),
-						kind:  InodeKind::from(dir_entry.kind),
-					};
-
-					// TODO: Maybe use an `HashSet`?
-					let ino = inode.ino;
-					assert!(self.inodes.insert(ino, inode).is_none(), "Inode already existed");
-
-					*entry.insert(ino)
-				},
-			};
+			let path = self.inode(ino)?.child_path(name.as_ascii());
+			let entry_ino = self.ino_by_path(path);
 
-			let file_kind = InodeKind::from(dir_entry.kind).file_type();
-			if add_entry(ino, idx + 1, file_kind, name) {
+			let file_kind = match dir_entry.kind {
+				DirEntryKind::File { .. } => fuser::FileType::RegularFile,
+				DirEntryKind::Dir { .. } => fuser::FileType::Directory,
+			};
+			if add_entry(entry_ino, idx + 1, file_kind, name) {
 				break;
 			}
 		}
@@ -190,12 +441,12 @@ impl DrvFs {
 		Ok(())
 	}
 
-	fn statfs(&mut self, _req: &fuser::Request<'_>, _ino: u64) -> Result<StatFs, anyhow::Error> {
+	fn statfs(&mut self, _ino: u64) -> Result<StatFs, anyhow::Error> {
 		// Get the total file length
 		let len = self.file.stream_len().context("Unable to get file len")?;
 
 		// Then count the number of files
-		fn count_files(dir_ptr: DirPtr, file: &mut fs::File) -> Result<usize, anyhow::Error> {
+		fn count_files<T: Read + Seek>(dir_ptr: DirPtr, file: &mut T) -> Result<usize, anyhow::Error> {
 			let dir_entries = dir_ptr
 				.read_entries(file)
 				.context("Unable to read directory")?
@@ -214,10 +465,13 @@ impl DrvFs {
 		}
 		let files = count_files(DirPtr::root(), &mut self.file).context("Unable to count files")?;
 
+		// And the free sectors
+		let allocator = SectorAllocator::new(&mut self.file).context("Unable to find free sectors")?;
+
 		Ok(StatFs {
 			blocks:           (len + 0x7ff) / 0x800,
-			blocks_free:      0,
-			blocks_available: 0,
+			blocks_free:      u64::from(allocator.free_len()),
+			blocks_available: u64::from(allocator.free_len()),
 			files:            files.try_into().context("Unable to get file count as `u64`")?,
 			files_free:       0,
 			block_size:       0x800,
@@ -227,9 +481,14 @@ impl DrvFs {
 	}
 }
 
-impl Filesystem for DrvFs {
-	fn lookup(&mut self, req: &fuser::Request<'_>, parent: u64, name: &std::ffi::OsStr, reply: fuser::ReplyEntry) {
-		match self.lookup(req, parent, name) {
+impl<T: Read + Write + Seek> Filesystem for DrvFs<T> {
+	fn destroy(&mut self, _req: &fuser::Request<'_>) {
+		// Write all modified files before unmounting
+		self.write_back_all();
+	}
+
+	fn lookup(&mut self, _req: &fuser::Request<'_>, parent: u64, name: &OsStr, reply: fuser::ReplyEntry) {
+		match self.lookup(parent, name) {
 			Ok(attr) => reply.entry(&Self::TTL, &attr, 0),
 			Err(err) => {
 				log::error!("Unable to lookup {name:?}@{parent}: {err:?}");
@@ -238,8 +497,8 @@ impl Filesystem for DrvFs {
 		}
 	}
 
-	fn getattr(&mut self, req: &fuser::Request<'_>, ino: u64, reply: fuser::ReplyAttr) {
-		match self.get_attr(req, ino) {
+	fn getattr(&mut self, _req: &fuser::Request<'_>, ino: u64, reply: fuser::ReplyAttr) {
+		match self.get_attr(ino) {
 			Ok(attr) => reply.attr(&Self::TTL, &attr),
 			Err(err) => {
 				log::error!("Unable to get attributes for {ino}: {err:?}");
@@ -248,11 +507,26 @@ impl Filesystem for DrvFs {
 		}
 	}
 
+	fn setattr(
+		&mut self, _req: &fuser::Request<'_>, ino: u64, _mode: Option<u32>, _uid: Option<u32>, _gid: Option<u32>,
+		size: Option<u64>, _atime: Option<TimeOrNow>, mtime: Option<TimeOrNow>, _ctime: Option<SystemTime>,
+		_fh: Option<u64>, _crtime: Option<SystemTime>, _chgtime: Option<SystemTime>, _bkuptime: Option<SystemTime>,
+		_flags: Option<u32>, reply: fuser::ReplyAttr,
+	) {
+		match self.set_attr(ino, size, mtime) {
+			Ok(attr) => reply.attr(&Self::TTL, &attr),
+			Err(err) => {
+				log::error!("Unable to set attributes for {ino}: {err:?}");
+				reply.error(libc::EIO);
+			},
+		}
+	}
+
 	fn read(
-		&mut self, req: &fuser::Request<'_>, ino: u64, fh: u64, offset: i64, size: u32, flags: i32,
-		lock_owner: Option<u64>, reply: fuser::ReplyData,
+		&mut self, _req: &fuser::Request<'_>, ino: u64, _fh: u64, offset: i64, size: u32, _flags: i32,
+		_lock_owner: Option<u64>, reply: fuser::ReplyData,
 	) {
-		match self.read(req, ino, fh, offset, size, flags, lock_owner) {
+		match self.read(ino, offset, size) {
 			Ok(data) => reply.data(&data),
 			Err(err) => {
 				log::error!("Unable to read {offset}/{size}@{ino}: {err:?}");
@@ -261,10 +535,133 @@ impl Filesystem for DrvFs {
 		}
 	}
 
-	fn readdir(&mut self, req: &fuser::Request, ino: u64, fh: u64, offset: i64, mut reply: fuser::ReplyDirectory) {
+	fn write(
+		&mut self, _req: &fuser::Request<'_>, ino: u64, _fh: u64, offset: i64, data: &[u8], _write_flags: u32,
+		_flags: i32, _lock_owner: Option<u64>, reply: fuser::ReplyWrite,
+	) {
+		match self.write(ino, offset, data) {
+			Ok(size) => reply.written(size),
+			Err(err) => {
+				log::error!("Unable to write {offset}/{}@{ino}: {err:?}", data.len());
+				reply.error(libc::EIO);
+			},
+		}
+	}
+
+	fn flush(&mut self, ino: u64, _fh: u64, _lock_owner: u64, reply: fuser::ReplyEmpty) {
+		match self.write_back(ino) {
+			Ok(()) => reply.ok(),
+			Err(err) => {
+				log::error!("Unable to flush {ino}: {err:?}");
+				reply.error(libc::EIO);
+			},
+		}
+	}
+
+	fn release(
+		&mut self, ino: u64, _fh: u64, _flags: i32, _lock_owner: Option<u64>, _flush: bool, reply: fuser::ReplyEmpty,
+	) {
+		match self.write_back(ino) {
+			Ok(()) => reply.ok(),
+			Err(err) => {
+				log::error!("Unable to release {ino}: {err:?}");
+				reply.error(libc::EIO);
+			},
+		}
+	}
+
+	fn fsync(&mut self, ino: u64, _fh: u64, _datasync: bool, reply: fuser::ReplyEmpty) {
+		match self.write_back(ino) {
+			Ok(()) => reply.ok(),
+			Err(err) => {
+				log::error!("Unable to sync {ino}: {err:?}");
+				reply.error(libc::EIO);
+			},
+		}
+	}
+
+	fn create(
+		&mut self, _req: &fuser::Request<'_>, parent: u64, name: &OsStr, _mode: u32, _umask: u32, _flags: i32,
+		reply: fuser::ReplyCreate,
+	) {
+		match self.create(parent, name) {
+			Ok(attr) => reply.created(&Self::TTL, &attr, 0, 0, 0),
+			Err(err) => {
+				log::error!("Unable to create {name:?}@{parent}: {err:?}");
+				let errno = match err.downcast_ref::<CreateFileError>() {
+					Some(CreateFileError::AlreadyExists) => libc::EEXIST,
+					Some(CreateFileError::InvalidName) => libc::EINVAL,
+					Some(CreateFileError::DriveFull { .. }) => libc::ENOSPC,
+					_ => libc::EIO,
+				};
+				reply.error(errno);
+			},
+		}
+	}
+
+	fn mkdir(
+		&mut self, _req: &fuser::Request<'_>, parent: u64, name: &OsStr, _mode: u32, _umask: u32,
+		reply: fuser::ReplyEntry,
+	) {
+		match self.mkdir(parent, name) {
+			Ok(attr) => reply.entry(&Self::TTL, &attr, 0),
+			Err(err) => {
+				log::error!("Unable to create directory {name:?}@{parent}: {err:?}");
+				let errno = match err.downcast_ref::<CreateDirError>() {
+					Some(CreateDirError::AlreadyExists) => libc::EEXIST,
+					Some(CreateDirError::InvalidName) => libc::EINVAL,
+					Some(CreateDirError::DriveFull { .. }) => libc::ENOSPC,
+					_ => libc::EIO,
+				};
+				reply.error(errno);
+			},
+		}
+	}
+
+	fn unlink(&mut self, _req: &fuser::Request<'_>, parent: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
+		match self.remove(parent, name) {
+			Ok(()) => reply.ok(),
+			Err(err) => {
+				log::error!("Unable to remove {name:?}@{parent}: {err:?}");
+				reply.error(self::remove_errno(&err));
+			},
+		}
+	}
+
+	fn rmdir(&mut self, _req: &fuser::Request<'_>, parent: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
+		match self.remove(parent, name) {
+			Ok(()) => reply.ok(),
+			Err(err) => {
+				log::error!("Unable to remove directory {name:?}@{parent}: {err:?}");
+				reply.error(self::remove_errno(&err));
+			},
+		}
+	}
+
+	fn rename(
+		&mut self, _req: &fuser::Request<'_>, parent: u64, name: &OsStr, new_parent: u64, new_name: &OsStr,
+		_flags: u32, reply: fuser::ReplyEmpty,
+	) {
+		// Note: Entries can only be renamed within the same directory, but returning `EXDEV`
+		//       makes most tools fall back to copying and removing them.
+		if parent != new_parent {
+			reply.error(libc::EXDEV);
+			return;
+		}
+
+		match self.rename(parent, name, new_name) {
+			Ok(()) => reply.ok(),
+			Err(err) => {
+				log::error!("Unable to rename {name:?}@{parent} to {new_name:?}: {err:?}");
+				reply.error(libc::EIO);
+			},
+		}
+	}
+
+	fn readdir(&mut self, _req: &fuser::Request, ino: u64, fh: u64, offset: i64, mut reply: fuser::ReplyDirectory) {
 		let new_entry = |ino, offset, kind, name: AsciiStrArr<0x14>| reply.add(ino, offset, kind, name.as_str());
 
-		match self.read_dir(req, ino, fh, offset, new_entry) {
+		match self.read_dir(ino, fh, offset, new_entry) {
 			Ok(()) => reply.ok(),
 			Err(err) => {
 				log::error!("Unable to read directory {ino}/{offset}: {err:?}");
@@ -273,8 +670,8 @@ impl Filesystem for DrvFs {
 		}
 	}
 
-	fn statfs(&mut self, req: &fuser::Request<'_>, ino: u64, reply: fuser::ReplyStatfs) {
-		match self.statfs(req, ino) {
+	fn statfs(&mut self, _req: &fuser::Request<'_>, ino: u64, reply: fuser::ReplyStatfs) {
+		match self.statfs(ino) {
 			Ok(stats) => reply.statfs(
 				stats.blocks,
 				stats.blocks_free,
@@ -296,85 +693,58 @@ impl Filesystem for DrvFs {
 /// Inode
 #[derive(Debug)]
 pub struct Inode {
-	/// Inode number
-	ino: u64,
-
-	/// Name
-	_name: OsString,
-
-	/// Date
-	date: SystemTime,
+	/// Path, empty for the root
+	path: AsciiString,
 
-	/// Kind
-	kind: InodeKind,
+	/// Modified contents, if a file, that haven't been written yet
+	contents: Option<Vec<u8>>,
 }
 
 impl Inode {
-	/// Returns the root inode
-	pub fn root() -> Self {
-		Self {
-			ino:   1,
-			_name: OsString::from("<root>"),
-			date:  SystemTime::now(),
-			kind:  InodeKind::Dir { ptr: DirPtr::root() },
-		}
+	/// Creates a new inode
+	pub fn new(path: AsciiString) -> Self {
+		Self { path, contents: None }
 	}
 
-	/// Returns the attributes of this inode
-	pub fn attr(&self) -> fuser::FileAttr {
-		fuser::FileAttr {
-			ino:     self.ino,
-			size:    match self.kind {
-				InodeKind::File { ptr } => u64::from(ptr.size),
-				InodeKind::Dir { .. } => 0,
-			},
-			blocks:  match self.kind {
-				InodeKind::File { ptr } => u64::from((ptr.size + 0x7ff) / 0x800),
-				// TODO: Report actual number of entries here, instead of assuming 1
-				InodeKind::Dir { .. } => 1,
-			},
-			atime:   self.date,
-			mtime:   self.date,
-			ctime:   self.date,
-			crtime:  self.date,
-			kind:    self.kind.file_type(),
-			perm:    0o444,
-			nlink:   1,
-			uid:     1000,
-			gid:     1001,
-			rdev:    0,
-			flags:   0,
-			blksize: 0x800,
+	/// Returns if this is the root inode
+	pub fn is_root(&self) -> bool {
+		self.path.is_empty()
+	}
+
+	/// Returns the path of a child of this inode
+	///
+	/// Note: Entries are found case-insensitively, so the path is uppercased
+	///       to make sure each entry only has a single inode.
+	pub fn child_path(&self, name: &AsciiStr) -> AsciiString {
+		let mut path = self.path.clone();
+		if !self.is_root() {
+			path.push(AsciiChar::BackSlash);
 		}
+		path.push_str(name);
+		path.make_ascii_uppercase();
+
+		path
 	}
 }
 
-/// Inode kind
-#[derive(Debug)]
-pub enum InodeKind {
-	/// File
-	File { ptr: FilePtr },
-
-	/// Directory
-	Dir { ptr: DirPtr },
+/// Returns a name as ascii
+fn ascii_name(name: &OsStr) -> Result<&AsciiStr, anyhow::Error> {
+	AsciiStr::from_ascii(name.as_bytes()).context("Unable to get name as ascii")
 }
 
-impl InodeKind {
-	fn file_type(&self) -> fuser::FileType {
-		match self {
-			InodeKind::File { .. } => fuser::FileType::RegularFile,
-			InodeKind::Dir { .. } => fuser::FileType::Directory,
-		}
+/// Returns the errno of an error when removing an entry
+fn remove_errno(err: &anyhow::Error) -> i32 {
+	match err.downcast_ref::<RemoveEntryError>() {
+		Some(RemoveEntryError::NotFound) => libc::ENOENT,
+		Some(RemoveEntryError::DirNotEmpty) => libc::ENOTEMPTY,
+		_ => libc::EIO,
 	}
 }
 
-impl From<DirEntryKind> for InodeKind {
-	fn from(kind: DirEntryKind) -> Self {
-		match kind {
-			DirEntryKind::File { ptr, .. } => Self::File { ptr },
-			DirEntryKind::Dir { ptr } => Self::Dir { ptr },
-		}
-	}
+/// Returns if `path` is `parent` or within it
+fn is_within(path: &AsciiStr, parent: &AsciiStr) -> bool {
+	path.as_bytes().starts_with(parent.as_bytes()) &&
+		matches!(path.as_slice().get(parent.len()), None | Some(AsciiChar::BackSlash))
 }
 
 /// Filesystem stats
@@ -0,0 +1,64 @@
+//! Tests
+
+// Imports
+use super::*;
+use std::io;
+
+/// Creates an ascii string from `s`
+fn ascii(s: &str) -> &AsciiStr {
+	AsciiStr::from_ascii(s).expect("Invalid string")
+}
+
+/// Creates a drive with the file `A.BIN` and the directory `DIR`, with the file `B.BIN`
+fn create_drive() -> io::Cursor<Vec<u8>> {
+	let mut drive = io::Cursor::new(vec![0; 2 * 0x800]);
+	let root = DirPtr::root();
+	let date = NaiveDateTime::from_timestamp(0, 0);
+
+	root.create_file(&mut drive, ascii("A.BIN"), date, &mut io::empty())
+		.expect("Unable to create file");
+	let (_, _, dir_entry) = root
+		.create_dir(&mut drive, ascii("DIR"), date)
+		.expect("Unable to create directory");
+	let dir = dir_entry.kind.as_dir_ptr().expect("Entry wasn't a directory");
+	dir.create_file(&mut drive, ascii("B.BIN"), date, &mut io::empty())
+		.expect("Unable to create file");
+
+	drive
+}
+
+#[test]
+fn ino_case_insensitive() {
+	let mut fs = DrvFs::new(self::create_drive());
+	let root = fs.inode(fs.root_ino()).expect("Unable to get root inode");
+	let lower_path = root.child_path(ascii("dir"));
+	let upper_path = root.child_path(ascii("DIR"));
+
+	let ino = fs.ino_by_path(lower_path);
+	assert_eq!(fs.ino_by_path(upper_path), ino);
+
+	// Children should also get the same inode regardless of case
+	let dir = fs.inode(ino).expect("Unable to get inode");
+	let lower_path = dir.child_path(ascii("b.bin"));
+	let upper_path = dir.child_path(ascii("B.BIN"));
+	let child_ino = fs.ino_by_path(upper_path);
+	assert_eq!(fs.ino_by_path(lower_path), child_ino);
+	assert_eq!(
+		fs.attr(child_ino).expect("Unable to get attributes").kind,
+		fuser::FileType::RegularFile
+	);
+}
+
+#[test]
+fn remove_errors() {
+	let mut drive = self::create_drive();
+	let mut remove = |name| {
+		DirPtr::root()
+			.remove_entry(&mut drive, ascii(name))
+			.context("Unable to remove entry")
+			.expect_err("Entry was removed")
+	};
+
+	assert_eq!(self::remove_errno(&remove("B.BIN")), libc::ENOENT);
+	assert_eq!(self::remove_errno(&remove("DIR")), libc::ENOTEMPTY);
+}
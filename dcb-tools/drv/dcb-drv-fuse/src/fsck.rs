@@ -0,0 +1,62 @@
+//! Consistency checker
+
+// Imports
+use anyhow::Context;
+use dcb_cdrom_xa::CdRomCursor;
+use dcb_drv::fsck::{self, Issue};
+use dcb_io::GameFile;
+use std::{fs, io::Write};
+
+/// Checks all drives of `file`, or `file` itself, if not a `disc`, repairing them if `repair`
+pub fn run(mut file: fs::File, disc: bool, repair: bool) -> Result<(), anyhow::Error> {
+	// Note: Warnings aren't counted as issues
+	let mut issues_len = 0;
+	let mut repaired_len = 0;
+	match disc {
+		true => {
+			let mut game_file = GameFile::new(CdRomCursor::new(file)).context("Unable to open game file")?;
+			let drives = game_file.check_drives().context("Unable to check drives")?;
+			for (drive, issues) in drives {
+				self::print_issues(&format!("{drive}:"), &issues);
+				issues_len += issues.iter().filter(|issue| !issue.is_warning()).count();
+
+				if repair {
+					repaired_len += game_file
+						.repair_drive(drive, &issues)
+						.with_context(|| format!("Unable to repair drive {drive}"))?;
+				}
+			}
+
+			// Note: We flush explicitly, as errors while regenerating the sectors' error
+			//       correction codes are only logged when dropping the cursor.
+			game_file.cdrom().flush().context("Unable to flush game file")?;
+		},
+		false => {
+			let issues = fsck::check(&mut file).context("Unable to check drive")?;
+			self::print_issues("", &issues);
+			issues_len += issues.iter().filter(|issue| !issue.is_warning()).count();
+
+			if repair {
+				repaired_len += fsck::repair(&mut file, &issues).context("Unable to repair drive")?;
+			}
+		},
+	}
+
+	if repair {
+		println!("Repaired {repaired_len}/{issues_len} issues");
+	}
+	anyhow::ensure!(issues_len == repaired_len, "Found {} issues", issues_len - repaired_len);
+
+	Ok(())
+}
+
+/// Prints all issues, with each path prefixed by `prefix`
+fn print_issues(prefix: &str, issues: &[Issue]) {
+	for issue in issues {
+		match (issue.is_repairable(), issue.is_warning()) {
+			(true, _) => println!("{prefix}{issue} (repairable)"),
+			(false, true) => println!("{prefix}{issue} (warning)"),
+			(false, false) => println!("{prefix}{issue}"),
+		}
+	}
+}
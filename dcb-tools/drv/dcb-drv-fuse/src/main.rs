@@ -5,11 +5,15 @@
 
 // Modules
 mod cli;
+mod disc_fs;
 mod fs;
+mod fsck;
 
 // Imports
 use anyhow::Context;
-use fuser::{MountOption, Session};
+use cli::Command;
+use fuser::{Filesystem, MountOption, Session};
+use std::path::Path;
 
 fn main() -> Result<(), anyhow::Error> {
 	// Initialize the logger
@@ -23,12 +27,34 @@ fn main() -> Result<(), anyhow::Error> {
 	// Get CLI
 	let args = cli::CliData::new();
 
-	// Open filesystem
-	let fs = fs::DrvFs::new(&args.input_file).context("Unable to open filesystem")?;
+	// Open the file
+	// Note: When checking, we only need to write if we're repairing
+	let write = match args.cmd {
+		Command::Mount { .. } => true,
+		Command::Fsck { repair } => repair,
+	};
+	let file = std::fs::OpenOptions::new()
+		.read(true)
+		.write(write)
+		.open(&args.input_file)
+		.context("Unable to open input file")?;
 
-	// Then start the session
-	let options = vec![MountOption::RO, MountOption::FSName("drv".to_owned())];
-	let session = Session::new(fs, &args.mount_point, &options).context("Unable to create session")?;
+	// Then mount the filesystem, or check it
+	match (args.cmd, args.disc) {
+		(Command::Mount { mount_point }, true) => {
+			let fs = disc_fs::DiscFs::new(file).context("Unable to open disc filesystem")?;
+			self::mount(fs, "dcb", &mount_point)
+		},
+		(Command::Mount { mount_point }, false) => self::mount(fs::DrvFs::new(file), "drv", &mount_point),
+		(Command::Fsck { repair }, disc) => fsck::run(file, disc, repair),
+	}
+}
+
+/// Mounts a filesystem at `mount_point` until any input is received
+fn mount<FS: Filesystem + Send + 'static>(fs: FS, name: &str, mount_point: &Path) -> Result<(), anyhow::Error> {
+	// Start the session
+	let options = vec![MountOption::FSName(name.to_owned())];
+	let session = Session::new(fs, mount_point, &options).context("Unable to create session")?;
 	let session = session.spawn().context("Unable to spawn session")?;
 
 	// Wait for input
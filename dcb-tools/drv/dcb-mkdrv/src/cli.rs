@@ -12,6 +12,9 @@ pub struct CliData {
 
 	/// The output file
 	pub output_file: PathBuf,
+
+	/// If files with the same contents should be deduplicated
+	pub dedup: bool,
 }
 
 impl CliData {
@@ -40,6 +43,15 @@ impl CliData {
 					.takes_value(true)
 					.required(false),
 			)
+			.arg(
+				ClapArg::with_name("DEDUP")
+					.help("Deduplicates files with the same contents")
+					.long_help(
+						"Deduplicates files with the same contents, writing them only once and pointing all of them \
+						 to the same sectors",
+					)
+					.long("dedup"),
+			)
 			.get_matches();
 
 		// Get the input filename
@@ -60,7 +72,13 @@ impl CliData {
 			},
 		};
 
+		let dedup = matches.is_present("DEDUP");
+
 		// Return the data
-		Self { input_dir, output_file }
+		Self {
+			input_dir,
+			output_file,
+			dedup,
+		}
 	}
 }
@@ -4,19 +4,24 @@
 mod error;
 
 // Exports
-pub use error::{NewError, NextError, ReadEntryError};
+pub use error::{NewError, NextError, OpenFileError, ReadEntryError};
 
 // Imports
-use dcb_drv::{DirEntryWriter, DirEntryWriterKind, DirWriter, DirWriterLister};
+use dcb_drv::{header::EntryHeader, DirEntryWriter, DirEntryWriterKind, DirWriter, DirWriterLister};
 use itertools::{Itertools, Position};
 use std::{
 	cmp::Ordering,
+	collections::HashMap,
 	convert::{TryFrom, TryInto},
-	fs,
-	io::Seek,
+	fs, io,
 	path::{Path, PathBuf},
+	rc::Rc,
 	time::SystemTime,
 };
+use zutil::AsciiStrArr;
+
+/// All entry headers, by their path, alongside their index in the header
+pub type EntryHeaders = HashMap<String, (usize, EntryHeader)>;
 
 /// Directory list
 #[derive(Debug)]
@@ -26,6 +31,9 @@ pub struct DirLister {
 
 	/// Depth
 	depth: usize,
+
+	/// All entry headers
+	headers: Rc<EntryHeaders>,
 }
 
 /// Directory entry
@@ -36,26 +44,49 @@ pub struct DirEntry {
 
 	/// Path
 	path: PathBuf,
+
+	/// Path, relative to the root directory
+	entry_path: String,
 }
 
 impl DirLister {
 	/// Creates a new iterator from a path
-	pub fn new(path: &Path, depth: usize) -> Result<Self, NewError> {
+	pub fn new(
+		path: &Path, entry_path: Option<String>, depth: usize, headers: Rc<EntryHeaders>,
+	) -> Result<Self, NewError> {
 		// Read the directory entries
 		let mut entries = fs::read_dir(path)
 			.map_err(|err| NewError::ReadDir(path.to_path_buf(), err))?
 			.map(|entry| match entry {
-				Ok(entry) => Ok(DirEntry {
-					metadata: entry.metadata().map_err(ReadEntryError::ReadMetadata)?,
-					path:     entry.path(),
-				}),
+				Ok(entry) => {
+					let file_name = entry.file_name();
+					let file_name = file_name.to_str().ok_or(ReadEntryError::NonUtf8Name)?;
+					Ok(DirEntry {
+						metadata:   entry.metadata().map_err(ReadEntryError::ReadMetadata)?,
+						path:       entry.path(),
+						entry_path: match &entry_path {
+							Some(entry_path) => format!("{}/{}", entry_path, file_name),
+							None => file_name.to_owned(),
+						},
+					})
+				},
 				Err(err) => Err(ReadEntryError::Read(err)),
 			})
 			.collect::<Result<Vec<_>, _>>()
 			.map_err(|err| NewError::ReadEntries(path.to_path_buf(), err))?;
 
-		// Then sort them by type and then name
+		// Then sort them by their order in the header, and those not in it by type and then name
 		entries.sort_by(|lhs, rhs| {
+			// Sort entries in the header first, by their index
+			let lhs_idx = headers.get(&lhs.entry_path).map(|&(idx, _)| idx);
+			let rhs_idx = headers.get(&rhs.entry_path).map(|&(idx, _)| idx);
+			match (lhs_idx, rhs_idx) {
+				(Some(lhs_idx), Some(rhs_idx)) => return lhs_idx.cmp(&rhs_idx),
+				(Some(_), None) => return Ordering::Less,
+				(None, Some(_)) => return Ordering::Greater,
+				(None, None) => (),
+			}
+
 			// Get if they're a directory
 			let lhs_is_dir = lhs.metadata.file_type().is_dir();
 			let rhs_is_dir = rhs.metadata.file_type().is_dir();
@@ -71,13 +102,17 @@ impl DirLister {
 			lhs.path.file_name().cmp(&rhs.path.file_name())
 		});
 
-		Ok(Self { entries, depth })
+		Ok(Self {
+			entries,
+			depth,
+			headers,
+		})
 	}
 }
 
 impl DirWriterLister for DirLister {
 	type Error = NextError;
-	type FileReader = fs::File;
+	type FileReader = LazyFile;
 }
 
 impl IntoIterator for DirLister {
@@ -86,8 +121,12 @@ impl IntoIterator for DirLister {
 	type IntoIter = impl Iterator<Item = Self::Item> + ExactSizeIterator;
 
 	fn into_iter(self) -> Self::IntoIter {
-		let depth = self.depth;
-		self.entries.into_iter().with_position().map(move |entry| {
+		let Self {
+			entries,
+			depth,
+			headers,
+		} = self;
+		entries.into_iter().with_position().map(move |entry| {
 			let (entry, is_last) = {
 				match entry {
 					Position::First(entry) | Position::Middle(entry) => (entry, false),
@@ -95,36 +134,44 @@ impl IntoIterator for DirLister {
 				}
 			};
 
-			// Read the entry and it's metadata
-			let name = entry
-				.path
-				.file_stem()
-				.ok_or(NextError::NoEntryName)?
-				.try_into()
-				.map_err(NextError::InvalidEntryName)?;
-			let secs_since_epoch = entry
-				.metadata
-				.modified()
-				.map_err(NextError::EntryDate)?
-				.duration_since(SystemTime::UNIX_EPOCH)
-				.map_err(NextError::EntryDateSinceEpoch)?
-				.as_secs();
-			let date = chrono::NaiveDateTime::from_timestamp(
-				i64::try_from(secs_since_epoch).map_err(|_err| NextError::EntryDateI64Secs)?,
-				0,
-			);
+			// Get the entry's header, if it has any
+			let header = headers.get(&entry.entry_path).map(|(_, header)| header);
+
+			// Then read the entry and it's metadata
+			let name = match header {
+				Some(header) => AsciiStrArr::from_bytes(header.name.as_str()),
+				None => entry.path.file_stem().ok_or(NextError::NoEntryName)?.try_into(),
+			}
+			.map_err(NextError::InvalidEntryName)?;
+			let date = match header {
+				Some(header) => {
+					chrono::NaiveDateTime::from_timestamp_opt(header.date, 0).ok_or(NextError::InvalidHeaderDate)?
+				},
+				None => {
+					let secs_since_epoch = entry
+						.metadata
+						.modified()
+						.map_err(NextError::EntryDate)?
+						.duration_since(SystemTime::UNIX_EPOCH)
+						.map_err(NextError::EntryDateSinceEpoch)?
+						.as_secs();
+					chrono::NaiveDateTime::from_timestamp(
+						i64::try_from(secs_since_epoch).map_err(|_err| NextError::EntryDateI64Secs)?,
+						0,
+					)
+				},
+			};
 
 			// Check if it's a directory or file
 			let kind = match entry.metadata.is_dir() {
 				false => {
-					let mut reader = fs::File::open(&entry.path).map_err(NextError::OpenFile)?;
-					let extension = entry
-						.path
-						.extension()
-						.ok_or(NextError::NoFileExtension)?
-						.try_into()
-						.map_err(NextError::InvalidFileExtension)?;
-					let size = reader.stream_len().ok();
+					let reader = LazyFile::new(entry.path.clone());
+					let extension = match header.and_then(|header| header.extension.as_deref()) {
+						Some(extension) => AsciiStrArr::from_bytes(extension),
+						None => entry.path.extension().ok_or(NextError::NoFileExtension)?.try_into(),
+					}
+					.map_err(NextError::InvalidFileExtension)?;
+					let size = u32::try_from(entry.metadata.len()).map_err(|_err| NextError::FileTooLarge)?;
 
 					let prefix = zutil::DisplayWrapper::new(|f| {
 						match depth {
@@ -143,17 +190,27 @@ impl IntoIterator for DirLister {
 						Ok(())
 					});
 
-					let size = zutil::DisplayWrapper::new(|f| match size {
-						Some(size) => write!(f, "{}B", size_format::SizeFormatterSI::new(size)),
-						None => write!(f, "Unknown Size"),
-					});
-
-					println!("{}{} ({})", prefix, name, size,);
+					println!(
+						"{}{} ({}B)",
+						prefix,
+						name,
+						size_format::SizeFormatterSI::new(u64::from(size))
+					);
 
-					DirEntryWriterKind::File { extension, reader }
+					DirEntryWriterKind::File {
+						extension,
+						reader,
+						size,
+					}
 				},
 				true => {
-					let entries = Self::new(&entry.path, depth + 1).map_err(NextError::OpenDir)?;
+					let entries = Self::new(
+						&entry.path,
+						Some(entry.entry_path.clone()),
+						depth + 1,
+						Rc::clone(&headers),
+					)
+					.map_err(NextError::OpenDir)?;
 
 					println!("{} ({} entries)", entry.path.display(), entries.entries.len());
 
@@ -162,7 +219,67 @@ impl IntoIterator for DirLister {
 				},
 			};
 
-			Ok(DirEntryWriter { name, date, kind })
+			Ok(DirEntryWriter {
+				name,
+				date,
+				sector_pos: header.map(|header| header.sector_pos),
+				kind,
+			})
 		})
 	}
 }
+
+/// A file, only opened once it's first read.
+///
+/// As the whole tree is collected before being written, opening every file while
+/// listing them could exceed the limit of open files.
+#[derive(Debug)]
+pub struct LazyFile {
+	/// Path
+	path: PathBuf,
+
+	/// File, if opened
+	file: Option<fs::File>,
+}
+
+impl LazyFile {
+	/// Creates a new lazy file
+	pub const fn new(path: PathBuf) -> Self {
+		Self { path, file: None }
+	}
+
+	/// Returns the file, opening it if it isn't open yet
+	fn open(&mut self) -> io::Result<&mut fs::File> {
+		if self.file.is_none() {
+			let file = fs::File::open(&self.path).map_err(|err| {
+				let kind = err.kind();
+				io::Error::new(kind, OpenFileError {
+					path: self.path.clone(),
+					err,
+				})
+			})?;
+			self.file = Some(file);
+		}
+
+		Ok(self.file.as_mut().expect("File was just opened"))
+	}
+}
+
+impl io::Read for LazyFile {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		self.open()?.read(buf)
+	}
+}
+
+impl io::Seek for LazyFile {
+	fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+		match pos {
+			// Note: Rewinding closes the file, so files read while deduplicating aren't all kept open
+			io::SeekFrom::Start(0) => {
+				self.file = None;
+				Ok(0)
+			},
+			_ => self.open()?.seek(pos),
+		}
+	}
+}
@@ -25,6 +25,10 @@ pub enum ReadEntryError {
 	/// Unable to read entry metadata
 	#[error("Unable to read entry metadata")]
 	ReadMetadata(#[source] io::Error),
+
+	/// Entry name wasn't utf-8
+	#[error("Entry name wasn't utf-8")]
+	NonUtf8Name,
 }
 
 /// Error for [`Iterator::Item`]
@@ -58,11 +62,27 @@ pub enum NextError {
 	#[error("Unable to get entry date as `i64` seconds since epoch")]
 	EntryDateI64Secs,
 
-	/// Unable to open file
-	#[error("Unable to open file")]
-	OpenFile(#[source] io::Error),
+	/// Invalid date in header
+	#[error("Invalid date in header")]
+	InvalidHeaderDate,
+
+	/// File was too large
+	#[error("File was too large")]
+	FileTooLarge,
 
 	/// Unable to open directory
 	#[error("Unable to open directory")]
 	OpenDir(#[source] self::NewError),
 }
+
+/// Error for opening a [`LazyFile`](super::LazyFile)
+#[derive(Debug, thiserror::Error)]
+#[error("Unable to open file {}", path.display())]
+pub struct OpenFileError {
+	/// Path of the file
+	pub path: PathBuf,
+
+	/// Underlying error
+	#[source]
+	pub err: io::Error,
+}
@@ -1,7 +1,7 @@
 //! `.DRV` packer
 
 // Features
-#![feature(seek_stream_len, type_alias_impl_trait, impl_trait_in_assoc_type)]
+#![feature(type_alias_impl_trait, impl_trait_in_assoc_type)]
 
 // Modules
 mod cli;
@@ -9,8 +9,12 @@ mod dir_lister;
 
 // Imports
 use anyhow::Context;
-use dcb_drv::{DirPtr, DirWriter};
-use std::{fs, path::Path};
+use dcb_drv::{
+	header::{self, Header},
+	DirPtr, DirWriter,
+};
+use dir_lister::{DirLister, EntryHeaders};
+use std::{fs, io, path::Path, rc::Rc};
 
 
 fn main() -> Result<(), anyhow::Error> {
@@ -23,35 +27,65 @@ fn main() -> Result<(), anyhow::Error> {
 	.expect("Unable to initialize logger");
 
 	// Get all data from cli
-	let cli::CliData { input_dir, output_file } = cli::CliData::new();
+	let cli::CliData {
+		input_dir,
+		output_file,
+		dedup,
+	} = cli::CliData::new();
 
 	// Try to pack the filesystem
-	self::write_fs(&input_dir, &output_file).context("Unable to pack `drv` file")?;
+	self::write_fs(&input_dir, &output_file, dedup).context("Unable to pack `drv` file")?;
 
 	Ok(())
 }
 
 /// Writes a `.drv` filesystem to `output_file`.
-pub fn write_fs(input_dir: &Path, output_file: &Path) -> Result<(), anyhow::Error> {
+///
+/// If `input_dir` has a header, the layout and dates of all entries in it are kept.
+/// If `dedup` is true, files with the same contents are only written once.
+pub fn write_fs(input_dir: &Path, output_file: &Path, dedup: bool) -> Result<(), anyhow::Error> {
+	// Read the header file, if it exists
+	let header_file_path = header::path_with_suffix(input_dir, ".header");
+	let header: Option<Header> = match fs::File::open(&header_file_path) {
+		Ok(header_file) => Some(serde_yaml::from_reader(header_file).context("Unable to read header")?),
+		Err(err) if err.kind() == io::ErrorKind::NotFound => {
+			log::info!(
+				"No header found at {}, using default layout",
+				header_file_path.display()
+			);
+			None
+		},
+		Err(err) => return Err(err).context("Unable to open header file"),
+	};
+
 	// Create the output file
 	let mut output_file = fs::File::create(output_file).context("Unable to create output file")?;
 
 	// Create the filesystem writer
-	let root_entries =
-		dir_lister::DirLister::new(input_dir, 0).context("Unable to create new dir lister for root directory")?;
+	let entry_headers: EntryHeaders = header
+		.iter()
+		.flat_map(|header| header.entries.iter().cloned().enumerate())
+		.map(|(idx, entry)| (entry.path.clone(), (idx, entry)))
+		.collect();
+	let root_entries = DirLister::new(input_dir, None, 0, Rc::new(entry_headers))
+		.context("Unable to create new dir lister for root directory")?;
 	DirWriter::new(root_entries)
+		.with_dedup(dedup)
 		.write(DirPtr::root(), &mut output_file)
 		.context("Unable to write filesystem")?;
 
-	// Then pad the file to a sector `2048` if it isn't already
+	// Then pad the file to a sector `2048` if it isn't already, and to the header's length, if we have it
 	let len = output_file
 		.metadata()
 		.context("Unable to get output file metadata")?
 		.len();
-	if len % 2048 != 0 {
-		output_file
-			.set_len(2048 * ((len + 2047) / 2048))
-			.context("Unable to set file length")?;
+	let padded_len = 2048 * ((len + 2047) / 2048);
+	let padded_len = match &header {
+		Some(header) => padded_len.max(2048 * u64::from(header.sectors_len)),
+		None => padded_len,
+	};
+	if len != padded_len {
+		output_file.set_len(padded_len).context("Unable to set file length")?;
 	}
 
 	Ok(())
@@ -16,6 +16,9 @@ pub struct CliData {
 	pub quiet: bool,
 
 	pub warn_on_override: bool,
+
+	/// Pattern of all entries to extract
+	pub filter: Option<String>,
 }
 
 impl CliData {
@@ -25,6 +28,7 @@ impl CliData {
 		const OUTPUT_DIR_STR: &str = "output-dir";
 		const QUIET_STR: &str = "quiet";
 		const WARN_ON_OVERRIDE_STR: &str = "warn-on-override";
+		const FILTER_STR: &str = "filter";
 
 		// Get all matches from cli
 		let matches = ClapApp::new("Drv Extractor")
@@ -67,6 +71,17 @@ impl CliData {
 					)
 					.long("warn-on-override"),
 			)
+			.arg(
+				ClapArg::with_name(FILTER_STR)
+					.help("Only extracts entries matching a pattern")
+					.long_help(
+						"Only extracts entries matching a pattern, such as `CARD\\LC*.TIM`, where `*` matches any \
+						 number of characters and `?` matches a single character. Names are matched ignoring case",
+					)
+					.short("f")
+					.long("filter")
+					.takes_value(true),
+			)
 			.get_matches();
 
 		// Get the input filename
@@ -84,12 +99,15 @@ impl CliData {
 
 		let warn_on_override = matches.is_present(WARN_ON_OVERRIDE_STR);
 
+		let filter = matches.value_of(FILTER_STR).map(str::to_owned);
+
 		// Return the data
 		Self {
 			input_files,
 			output_dir,
 			quiet,
 			warn_on_override,
+			filter,
 		}
 	}
 }
@@ -5,9 +5,15 @@ mod cli;
 
 // Imports
 use anyhow::Context;
+use ascii::AsciiStr;
 use cli::CliData;
-use dcb_drv::{DirEntryKind, DirPtr};
+use dcb_drv::{
+	header::{self, EntryHeader, Header},
+	path::Component,
+	DirEntry, DirEntryKind, DirPtr,
+};
 use std::{
+	convert::TryFrom,
 	fs, io,
 	path::{Path, PathBuf},
 };
@@ -44,8 +50,15 @@ fn main() -> Result<(), anyhow::Error> {
 		zutil::try_create_dir_all(&output_dir)
 			.with_context(|| format!("Unable to create directory {}", output_dir.display()))?;
 
-		// Then extract the tree
-		if let Err(err) = self::extract_tree(&mut input_file, DirPtr::root(), &output_dir, &cli_data) {
+		// Then extract the tree alongside it's header, or only the entries matching the filter
+		let res = match &cli_data.filter {
+			Some(filter) => self::extract_filtered(&mut input_file, filter, &output_dir, &cli_data),
+			None => self::extract_tree(&mut input_file, DirPtr::root(), &output_dir, &cli_data).and_then(|()| {
+				self::write_header(&mut input_file, input_file_metadata.len(), &output_dir)
+					.context("Unable to write header")
+			}),
+		};
+		if let Err(err) = res {
 			log::error!("Unable to extract files from {}: {:?}", input_file_path.display(), err);
 		}
 
@@ -63,6 +76,92 @@ fn main() -> Result<(), anyhow::Error> {
 	Ok(())
 }
 
+/// Writes the header of a `.drv` file, with the layout of all it's entries, next to `output_dir`
+fn write_header<R: io::Read + io::Seek>(reader: &mut R, len: u64, output_dir: &Path) -> Result<(), anyhow::Error> {
+	let mut entries = vec![];
+	self::collect_headers(reader, DirPtr::root(), None, &mut entries)?;
+	let header = Header {
+		sectors_len: u32::try_from(len / 0x800).context("Input file was too large")?,
+		entries,
+	};
+
+	let header_file_path = header::path_with_suffix(output_dir, ".header");
+	let header_file = fs::File::create(header_file_path).context("Unable to create output header file")?;
+	serde_yaml::to_writer(header_file, &header).context("Unable to write header")
+}
+
+/// Collects the headers of all entries of a directory, recursively.
+///
+/// Each directory's header is followed by the headers of it's entries.
+fn collect_headers<R: io::Read + io::Seek>(
+	reader: &mut R, dir_ptr: DirPtr, dir_path: Option<&str>, headers: &mut Vec<EntryHeader>,
+) -> Result<(), anyhow::Error> {
+	let entries = dir_ptr
+		.read_all_entries(reader)
+		.with_context(|| format!("Unable to read directory entries of {}", dir_path.unwrap_or("/")))?;
+
+	for entry in entries {
+		let path = match dir_path {
+			Some(dir_path) => format!("{}/{}", dir_path, entry.full_name()),
+			None => entry.full_name().to_string(),
+		};
+
+		headers.push(EntryHeader {
+			path:       path.clone(),
+			name:       entry.name.to_string(),
+			extension:  match entry.kind {
+				DirEntryKind::File { extension, .. } => Some(extension.to_string()),
+				DirEntryKind::Dir { .. } => None,
+			},
+			sector_pos: entry.kind.sector_pos(),
+			date:       entry.date.timestamp(),
+		});
+
+		if let DirEntryKind::Dir { ptr } = entry.kind {
+			self::collect_headers(reader, ptr, Some(&path), headers)?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Extracts all entries matching `filter` from a reader
+fn extract_filtered<R: io::Read + io::Seek>(
+	reader: &mut R, filter: &str, output_dir: &Path, cli_data: &CliData,
+) -> Result<(), anyhow::Error> {
+	let filter = dcb_drv::Path::new(AsciiStr::from_ascii(filter).context("Filter wasn't valid ascii")?);
+
+	// Get all matching entries
+	// Note: We need to collect to free the reader so it can seek to the files.
+	let entries = DirPtr::root()
+		.glob(&mut *reader, filter)
+		.context("Unable to search entries")?
+		.collect::<Result<Vec<_>, _>>()
+		.context("Unable to search entries")?;
+
+	// Then extract each entry
+	for entry in entries {
+		let path = entry
+			.path
+			.components()
+			.fold(output_dir.to_path_buf(), |path, cmpt| match cmpt {
+				Component::Normal(name) => path.join(name.as_str()),
+				_ => path,
+			});
+
+		// Create the parent directory if it doesn't exist
+		if let Some(parent) = path.parent() {
+			zutil::try_create_dir_all(parent)
+				.with_context(|| format!("Unable to create directory {}", parent.display()))?;
+		}
+
+		self::extract_entry(reader, &entry.entry, &path, cli_data)
+			.with_context(|| format!("Unable to extract {}", entry.path))?;
+	}
+
+	Ok(())
+}
+
 /// Extracts a `.drv` file from a reader and starting directory
 fn extract_tree<R: io::Read + io::Seek>(
 	reader: &mut R, dir_ptr: DirPtr, path: &Path, cli_data: &CliData,
@@ -79,78 +178,84 @@ fn extract_tree<R: io::Read + io::Seek>(
 		// If we can't read it, return Err
 		let entry = entry.with_context(|| format!("Unable to read directory entry of {}", path.display()))?;
 
-		// Create the date
-		// Note: `.DRV` only supports second precision.
-		let time = filetime::FileTime::from_unix_time(entry.date.timestamp(), 0);
-
-		// Then check it's type
-		match entry.kind {
-			// If it's a file, create the file and write all contents
-			DirEntryKind::File { extension, ptr } => {
-				let path = path.join(format!("{}.{}", entry.name, extension));
-
-				// Log the file and it's size
-				if !cli_data.quiet {
-					println!(
-						"{} ({}B)",
-						path.display(),
-						size_format::SizeFormatterSI::new(u64::from(ptr.size))
-					);
-				}
-
-				// If the output file already exists, log a warning
-				if cli_data.warn_on_override && path.exists() {
-					log::warn!("Overriding file {}", path.display());
-				}
-
-				// Get the file's reader.
-				let mut file_reader = ptr
-					.cursor(&mut *reader)
-					.with_context(|| format!("Unable to read file {}", path.display()))?;
-
-				// Then create the output file and copy.
-				let mut output_file =
-					fs::File::create(&path).with_context(|| format!("Unable to create file {}", path.display()))?;
-				std::io::copy(&mut file_reader, &mut output_file)
-					.with_context(|| format!("Unable to write file {}", path.display()))?;
-
-				// And set the file's modification time
-				if let Err(err) = filetime::set_file_handle_times(&output_file, None, Some(time)) {
-					log::warn!(
-						"Unable to write date for file {}: {}",
-						path.display(),
-						zutil::fmt_err_wrapper(&err)
-					);
-				}
-			},
+		let path = path.join(entry.full_name().as_str());
+		self::extract_entry(reader, &entry, &path, cli_data)?;
+	}
 
-			// If it's a directory, create it and recurse for all it's entries
-			DirEntryKind::Dir { ptr } => {
-				let path = path.join(entry.name.as_str());
-
-				// Log the directory
-				if !cli_data.quiet {
-					println!("{}/", path.display());
-				}
-
-				// Create the directory and recurse over it
-				zutil::try_create_dir_all(&path)
-					.with_context(|| format!("Unable to create directory {}", path.display()))?;
-				self::extract_tree(reader, ptr, &path, cli_data)
-					.with_context(|| format!("Unable to extract directory {}", path.display()))?;
-
-				// Then set it's date
-				// Note: We must do this *after* extracting the tree, else the time
-				//       will be updated when we insert files into it.
-				if let Err(err) = filetime::set_file_mtime(&path, time) {
-					log::warn!(
-						"Unable to write date for directory {}: {}",
-						path.display(),
-						zutil::fmt_err_wrapper(&err)
-					);
-				}
-			},
-		}
+	Ok(())
+}
+
+/// Extracts an entry from a reader to `path`
+fn extract_entry<R: io::Read + io::Seek>(
+	reader: &mut R, entry: &DirEntry, path: &Path, cli_data: &CliData,
+) -> Result<(), anyhow::Error> {
+	// Create the date
+	// Note: `.DRV` only supports second precision.
+	let time = filetime::FileTime::from_unix_time(entry.date.timestamp(), 0);
+
+	// Then check it's type
+	match entry.kind {
+		// If it's a file, create the file and write all contents
+		DirEntryKind::File { ptr, .. } => {
+			// Log the file and it's size
+			if !cli_data.quiet {
+				println!(
+					"{} ({}B)",
+					path.display(),
+					size_format::SizeFormatterSI::new(u64::from(ptr.size))
+				);
+			}
+
+			// If the output file already exists, log a warning
+			if cli_data.warn_on_override && path.exists() {
+				log::warn!("Overriding file {}", path.display());
+			}
+
+			// Get the file's reader.
+			let mut file_reader = ptr
+				.cursor(&mut *reader)
+				.with_context(|| format!("Unable to read file {}", path.display()))?;
+
+			// Then create the output file and copy.
+			let mut output_file =
+				fs::File::create(&path).with_context(|| format!("Unable to create file {}", path.display()))?;
+			std::io::copy(&mut file_reader, &mut output_file)
+				.with_context(|| format!("Unable to write file {}", path.display()))?;
+
+			// And set the file's modification time
+			if let Err(err) = filetime::set_file_handle_times(&output_file, None, Some(time)) {
+				log::warn!(
+					"Unable to write date for file {}: {}",
+					path.display(),
+					zutil::fmt_err_wrapper(&err)
+				);
+			}
+		},
+
+		// If it's a directory, create it and recurse for all it's entries
+		DirEntryKind::Dir { ptr } => {
+			// Log the directory
+			if !cli_data.quiet {
+				println!("{}/", path.display());
+			}
+
+			// Create the directory and recurse over it
+			zutil::try_create_dir_all(&path)
+				.with_context(|| format!("Unable to create directory {}", path.display()))?;
+			self::extract_tree(reader, ptr, &path, cli_data)
+				.with_context(|| format!("Unable to extract directory {}", path.display()))?;
+
+			// Then set it's date
+			// Note: We must do this *after* extracting the tree, else the time
+			//       will be updated when we insert files into it.
+			if let Err(err) = filetime::set_file_mtime(&path, time) {
+				log::warn!(
+					"Unable to write date for directory {}: {}",
+					path.display(),
+					zutil::fmt_err_wrapper(&err)
+				);
+			}
+		},
 	}
 
 	Ok(())
@@ -6,13 +6,9 @@ use crate::EditScreen;
 use anyhow::Context;
 use dcb::CardTable;
 use dcb_cdrom_xa::CdRomCursor;
+use dcb_io::{game_file::Drives, GameFile};
 use eframe::{egui, epi::TextureAllocator};
-use std::{
-	convert::TryInto,
-	fs,
-	io::{Seek, Write},
-	path::Path,
-};
+use std::{fs, io::Write, path::Path};
 use zutil::StrContainsCaseInsensitive;
 
 /// Loaded game
@@ -20,6 +16,9 @@ pub struct LoadedGame {
 	/// File
 	file: CdRomCursor<fs::File>,
 
+	/// Drives
+	drives: Drives,
+
 	/// Card table
 	pub card_table: CardTable,
 
@@ -42,7 +41,8 @@ impl LoadedGame {
 		let mut file = dcb_cdrom_xa::CdRomCursor::new(file);
 
 		// Open the card table file and parse it
-		let mut game_file = dcb_io::GameFile::new(&mut file);
+		let mut game_file = GameFile::new(&mut file).context("Unable to open game file")?;
+		let drives = *game_file.drives();
 		let mut table_file = CardTable::open(&mut game_file).context("Unable to open table file")?;
 
 		// Then parse it
@@ -52,6 +52,7 @@ impl LoadedGame {
 		Ok(Self {
 			card_table,
 			file,
+			drives,
 			file_card_table_hash,
 			card_search: String::new(),
 		})
@@ -65,25 +66,17 @@ impl LoadedGame {
 			.serialize(&mut bytes)
 			.context("Unable to serialize table")?;
 
-		// Open the card table file
-		let mut game_file = dcb_io::GameFile::new(&mut self.file);
-		let mut table_file = CardTable::open(&mut game_file).context("Unable to open table file")?;
-
-		// If it's larger than the file, return Err
-		let bytes_len: u64 = bytes.len().try_into().expect("Card table was larger than `u64`");
-		let file_len = table_file.stream_len().context("Unable to get file size")?;
-		anyhow::ensure!(
-			bytes_len <= file_len,
-			"Card table is too big ({} / {})",
-			size_format::SizeFormatterSI::new(bytes_len),
-			size_format::SizeFormatterSI::new(file_len),
-		);
-
-		// Then write it
-		table_file
-			.write_all(&bytes)
+		// Then replace the card table file with it
+		// Note: If the table grew, it's relocated within it's drive.
+		let mut game_file = GameFile::with_drives(&mut self.file, self.drives);
+		let path = dcb_io::game_file::Path::from_ascii(CardTable::PATH).expect("Table path was invalid");
+		game_file
+			.replace_file(path, &mut bytes.as_slice())
 			.context("Unable to write card table to file")?;
 
+		// Then flush the file, repairing all sectors we wrote
+		self.file.flush().context("Unable to flush file")?;
+
 		// And update our hash
 		self.file_card_table_hash = zutil::hash_of(&self.card_table);
 
@@ -122,7 +115,7 @@ impl LoadedGame {
 			.enumerate()
 			.map(|(idx, name)| (idx, format!("{idx}. {name}")))
 			.filter(|(_, name)| name.contains_case_insensitive(card_search));
-		let mut game_file = dcb_io::GameFile::new(&mut self.file);
+		let mut game_file = GameFile::with_drives(&mut self.file, self.drives);
 
 		egui::ScrollArea::auto_sized().show(ui, |ui| {
 			for (idx, name) in names {
@@ -6,7 +6,6 @@
 	format_args_capture,
 	once_cell,
 	never_type,
-	seek_stream_len,
 	try_blocks,
 	unwrap_infallible,
 	lazy_cell
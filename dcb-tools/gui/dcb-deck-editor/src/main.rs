@@ -12,7 +12,7 @@ use ref_cast::RefCast;
 use std::{
 	convert::TryFrom,
 	fs,
-	io::{self, Read, Seek},
+	io::{self, Read, Seek, Write},
 	path::{Path, PathBuf},
 };
 use zutil::{alert, AsciiTextBuffer, StrContainsCaseInsensitive};
@@ -94,7 +94,9 @@ impl DeckEditor {
 	/// Saves the deck table to file
 	pub fn save_deck_table(file_path: &Path, deck_table: &DeckTable) -> Result<(), anyhow::Error> {
 		// Open the file
+		// Note: We need to read the sectors we write to repair them.
 		let file = fs::File::options()
+			.read(true)
 			.write(true)
 			.open(file_path)
 			.context("Unable to open file")?;
@@ -108,6 +110,9 @@ impl DeckEditor {
 		// Then serialize it
 		deck_table.serialize(&mut file).context("Unable to serialize table")?;
 
+		// And flush it, repairing all sectors we wrote
+		file.flush().context("Unable to flush file")?;
+
 		Ok(())
 	}
 }
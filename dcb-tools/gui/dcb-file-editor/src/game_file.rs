@@ -19,6 +19,9 @@ pub struct GameFile {
 	/// Cd rom
 	cdrom: Mutex<CdRomCursor<fs::File>>,
 
+	/// Drive locations
+	drive_locations: dcb_io::game_file::Drives,
+
 	/// Drives
 	drives: Mutex<Drives>,
 }
@@ -27,21 +30,43 @@ impl GameFile {
 	/// Creates a new game file from it's file
 	pub fn new(file: fs::File) -> Result<Self, anyhow::Error> {
 		let mut cdrom = CdRomCursor::new(file);
-		let mut game_file = dcb_io::GameFile::new(&mut cdrom);
+		let mut game_file = dcb_io::GameFile::new(&mut cdrom).context("Unable to open game file")?;
+		let drive_locations = *game_file.drives();
 
-		let mut a_reader = game_file.a_drv().context("Unable to get `a` drive")?;
+		let mut a_reader = game_file
+			.a_drv()
+			.context("Missing `a` drive")?
+			.context("Unable to get `a` drive")?;
 		let a_tree = DrvTree::new(&mut a_reader).context("Unable to load `a` drive")?;
-		let mut b_reader = game_file.b_drv().context("Unable to get `b` drive")?;
+		let mut b_reader = game_file
+			.b_drv()
+			.context("Missing `b` drive")?
+			.context("Unable to get `b` drive")?;
 		let b_tree = DrvTree::new(&mut b_reader).context("Unable to load `b` drive")?;
-		let mut c_reader = game_file.c_drv().context("Unable to get `c` drive")?;
+		let mut c_reader = game_file
+			.c_drv()
+			.context("Missing `c` drive")?
+			.context("Unable to get `c` drive")?;
 		let c_tree = DrvTree::new(&mut c_reader).context("Unable to load `c` drive")?;
-		let mut e_reader = game_file.e_drv().context("Unable to get `e` drive")?;
+		let mut e_reader = game_file
+			.e_drv()
+			.context("Missing `e` drive")?
+			.context("Unable to get `e` drive")?;
 		let e_tree = DrvTree::new(&mut e_reader).context("Unable to load `e` drive")?;
-		let mut f_reader = game_file.f_drv().context("Unable to get `f` drive")?;
+		let mut f_reader = game_file
+			.f_drv()
+			.context("Missing `f` drive")?
+			.context("Unable to get `f` drive")?;
 		let f_tree = DrvTree::new(&mut f_reader).context("Unable to load `f` drive")?;
-		let mut g_reader = game_file.g_drv().context("Unable to get `g` drive")?;
+		let mut g_reader = game_file
+			.g_drv()
+			.context("Missing `g` drive")?
+			.context("Unable to get `g` drive")?;
 		let g_tree = DrvTree::new(&mut g_reader).context("Unable to load `g` drive")?;
-		let mut p_reader = game_file.p_drv().context("Unable to get `p` drive")?;
+		let mut p_reader = game_file
+			.p_drv()
+			.context("Missing `p` drive")?
+			.context("Unable to get `p` drive")?;
 		let p_tree = DrvTree::new(&mut p_reader).context("Unable to load `p` drive")?;
 
 		let drives = Drives {
@@ -55,7 +80,8 @@ impl GameFile {
 		};
 
 		Ok(Self {
-			cdrom:  Mutex::new(cdrom),
+			cdrom: Mutex::new(cdrom),
+			drive_locations,
 			drives: Mutex::new(drives),
 		})
 	}
@@ -64,35 +90,70 @@ impl GameFile {
 	pub fn reload(&self) -> Result<(), anyhow::Error> {
 		// TODO: Avoid deadlock by acquiring these using some special algorithm
 		let mut cdrom = self.cdrom.lock_unwrap();
-		let mut game_file = dcb_io::GameFile::new(&mut *cdrom);
+		let mut game_file = dcb_io::GameFile::with_drives(&mut *cdrom, self.drive_locations);
 		let mut drives = self.drives.lock_unwrap();
 		drives
 			.a_tree
-			.reload(&mut game_file.a_drv().context("Unable to get `A` drive")?)
+			.reload(
+				&mut game_file
+					.a_drv()
+					.context("Missing `A` drive")?
+					.context("Unable to get `A` drive")?,
+			)
 			.context("Unable to reload `A` drive")?;
 		drives
 			.b_tree
-			.reload(&mut game_file.b_drv().context("Unable to get `B` drive")?)
+			.reload(
+				&mut game_file
+					.b_drv()
+					.context("Missing `B` drive")?
+					.context("Unable to get `B` drive")?,
+			)
 			.context("Unable to reload `B` drive")?;
 		drives
 			.c_tree
-			.reload(&mut game_file.c_drv().context("Unable to get `C` drive")?)
+			.reload(
+				&mut game_file
+					.c_drv()
+					.context("Missing `C` drive")?
+					.context("Unable to get `C` drive")?,
+			)
 			.context("Unable to reload `C` drive")?;
 		drives
 			.e_tree
-			.reload(&mut game_file.e_drv().context("Unable to get `E` drive")?)
+			.reload(
+				&mut game_file
+					.e_drv()
+					.context("Missing `E` drive")?
+					.context("Unable to get `E` drive")?,
+			)
 			.context("Unable to reload `E` drive")?;
 		drives
 			.f_tree
-			.reload(&mut game_file.f_drv().context("Unable to get `F` drive")?)
+			.reload(
+				&mut game_file
+					.f_drv()
+					.context("Missing `F` drive")?
+					.context("Unable to get `F` drive")?,
+			)
 			.context("Unable to reload `F` drive")?;
 		drives
 			.g_tree
-			.reload(&mut game_file.g_drv().context("Unable to get `G` drive")?)
+			.reload(
+				&mut game_file
+					.g_drv()
+					.context("Missing `G` drive")?
+					.context("Unable to get `G` drive")?,
+			)
 			.context("Unable to reload `G` drive")?;
 		drives
 			.p_tree
-			.reload(&mut game_file.p_drv().context("Unable to get `P` drive")?)
+			.reload(
+				&mut game_file
+					.p_drv()
+					.context("Missing `P` drive")?
+					.context("Unable to get `P` drive")?,
+			)
 			.context("Unable to reload `P` drive")?;
 
 		Ok(())
@@ -146,7 +207,7 @@ impl GameFile {
 
 		// Get the game file
 		let mut cdrom = self.cdrom.lock_unwrap();
-		let game_file = dcb_io::GameFile::new(&mut *cdrom);
+		let game_file = dcb_io::GameFile::with_drives(&mut *cdrom, self.drive_locations);
 		f(game_file)
 	}
 }
@@ -0,0 +1,200 @@
+//! Directory lister
+
+// Modules
+mod error;
+
+// Exports
+pub use error::{NewError, NextError, ReadEntryError};
+
+// Imports
+use chrono::{Datelike, Timelike};
+use dcb_bytes::Bytes;
+use dcb_iso9660::{
+	date_time::DirDateTime,
+	header::EntryHeader,
+	string::FileString,
+	writer::{DirEntryWriter, DirEntryWriterKind, DirWriterLister},
+};
+use std::{
+	collections::HashMap,
+	convert::TryFrom,
+	fs,
+	path::{Path, PathBuf},
+	rc::Rc,
+	time::SystemTime,
+};
+
+/// All entry headers, by their path
+pub type EntryHeaders = HashMap<String, EntryHeader>;
+
+/// System use area for files not in the header.
+///
+/// Contains the `XA` attributes of a plain form 1 file.
+const FILE_SYSTEM_USE: [u8; 14] = [0, 0, 0, 0, 0x0d, 0x55, b'X', b'A', 0, 0, 0, 0, 0, 0];
+
+/// System use area for directories not in the header.
+///
+/// Contains the `XA` attributes of a directory.
+const DIR_SYSTEM_USE: [u8; 14] = [0, 0, 0, 0, 0x8d, 0x55, b'X', b'A', 0, 0, 0, 0, 0, 0];
+
+/// Directory list
+#[derive(Debug)]
+pub struct DirLister {
+	/// All entries
+	entries: Vec<DirEntry>,
+
+	/// Path of this directory, relative to the root directory
+	path: Option<String>,
+
+	/// All entry headers
+	headers: Rc<EntryHeaders>,
+}
+
+/// Directory entry
+#[derive(Debug)]
+pub struct DirEntry {
+	/// Metadata
+	metadata: fs::Metadata,
+
+	/// Path
+	path: PathBuf,
+}
+
+impl DirLister {
+	/// Creates a new iterator from a path
+	pub fn new(path: &Path, entry_path: Option<String>, headers: Rc<EntryHeaders>) -> Result<Self, NewError> {
+		// Read the directory entries
+		let entries = fs::read_dir(path)
+			.map_err(|err| NewError::ReadDir(path.to_path_buf(), err))?
+			.map(|entry| match entry {
+				Ok(entry) => Ok(DirEntry {
+					metadata: entry.metadata().map_err(ReadEntryError::ReadMetadata)?,
+					path:     entry.path(),
+				}),
+				Err(err) => Err(ReadEntryError::Read(err)),
+			})
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|err| NewError::ReadEntries(path.to_path_buf(), err))?;
+
+		Ok(Self {
+			entries,
+			path: entry_path,
+			headers,
+		})
+	}
+}
+
+impl DirWriterLister for DirLister {
+	type Error = NextError;
+	type FileReader = fs::File;
+}
+
+impl IntoIterator for DirLister {
+	type Item = Result<DirEntryWriter<Self>, <Self as DirWriterLister>::Error>;
+
+	type IntoIter = impl Iterator<Item = Self::Item>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		let Self { entries, path, headers } = self;
+		entries.into_iter().map(move |entry| {
+			// Get the path of the entry and it's header, if it has any.
+			let file_name = entry
+				.path
+				.file_name()
+				.ok_or(NextError::NoEntryName)?
+				.to_str()
+				.ok_or(NextError::NonUtf8EntryName)?;
+			let entry_path = match &path {
+				Some(path) => format!("{path}/{file_name}"),
+				None => file_name.to_owned(),
+			};
+			let header = headers.get(&entry_path);
+			let is_dir = entry.metadata.is_dir();
+
+			// Note: If the entry isn't in the header, we use the first version for files.
+			let name = match header {
+				Some(header) => header.name.clone(),
+				None if is_dir => file_name.to_owned(),
+				None => format!("{file_name};1"),
+			};
+			let name = FileString::from_bytes(name.as_bytes()).map_err(NextError::InvalidEntryName)?;
+
+			let date = match header {
+				Some(header) => DirDateTime::deserialize_bytes(&header.date).into_ok(),
+				None => self::metadata_date(&entry.metadata)?,
+			};
+
+			let system_use = match (header, is_dir) {
+				(Some(header), _) => header.system_use.clone(),
+				(None, false) => FILE_SYSTEM_USE.to_vec(),
+				(None, true) => DIR_SYSTEM_USE.to_vec(),
+			};
+
+			let kind = match is_dir {
+				false => {
+					let reader = fs::File::open(&entry.path).map_err(NextError::OpenFile)?;
+
+					// Note: Files with form 2 sectors are larger than their recorded size, so we
+					//       use the size in the header, if any.
+					let size = match header.and_then(|header| header.size) {
+						Some(size) => size,
+						None => u32::try_from(entry.metadata.len()).map_err(|_err| NextError::FileTooLarge)?,
+					};
+					let subheaders = header
+						.map(EntryHeader::subheaders)
+						.transpose()
+						.map_err(NextError::InvalidSubHeader)?
+						.flatten();
+
+					DirEntryWriterKind::File {
+						reader,
+						size,
+						subheaders,
+					}
+				},
+				true => {
+					let entries =
+						Self::new(&entry.path, Some(entry_path), Rc::clone(&headers)).map_err(NextError::OpenDir)?;
+					DirEntryWriterKind::Dir(entries)
+				},
+			};
+
+			Ok(DirEntryWriter {
+				name,
+				date,
+				system_use,
+				sector_pos: header.map(|header| header.sector_pos),
+				kind,
+			})
+		})
+	}
+}
+
+/// Returns the modification date of an entry from it's metadata
+fn metadata_date(metadata: &fs::Metadata) -> Result<DirDateTime, NextError> {
+	let secs_since_epoch = metadata
+		.modified()
+		.map_err(NextError::EntryDate)?
+		.duration_since(SystemTime::UNIX_EPOCH)
+		.map_err(NextError::EntryDateSinceEpoch)?
+		.as_secs();
+	let date = chrono::NaiveDateTime::from_timestamp(
+		i64::try_from(secs_since_epoch).map_err(|_err| NextError::EntryDateI64Secs)?,
+		0,
+	);
+
+	let as_u8 = |value: u32| u8::try_from(value).expect("Date component didn't fit into a `u8`");
+	Ok(DirDateTime {
+		year:      date
+			.year()
+			.checked_sub(1900)
+			.and_then(|year| u8::try_from(year).ok())
+			.ok_or(NextError::EntryDateYear)?,
+		month:     as_u8(date.month()),
+		day:       as_u8(date.day()),
+		hour:      as_u8(date.hour()),
+		minutes:   as_u8(date.minute()),
+		seconds:   as_u8(date.second()),
+		time_zone: 0,
+	})
+}
@@ -0,0 +1,78 @@
+//! Errors
+
+// Imports
+use dcb_cdrom_xa::sector::header::subheader;
+use dcb_iso9660::string::ValidateFileAlphabetError;
+use std::{io, path::PathBuf};
+
+/// Error for [`DirList::new`](super::DirLister::new)
+#[derive(Debug, thiserror::Error)]
+pub enum NewError {
+	/// Unable to read directory
+	#[error("Unable to read directory {}", _0.display())]
+	ReadDir(PathBuf, #[source] io::Error),
+
+	/// Unable to read entry
+	#[error("Unable to read entry in {}", _0.display())]
+	ReadEntries(PathBuf, #[source] ReadEntryError),
+}
+
+/// Error for [`DirList::new`](super::DirLister::new)'s entry reading
+#[derive(Debug, thiserror::Error)]
+pub enum ReadEntryError {
+	/// Unable to read entry
+	#[error("Unable to read entry")]
+	Read(#[source] io::Error),
+
+	/// Unable to read entry metadata
+	#[error("Unable to read entry metadata")]
+	ReadMetadata(#[source] io::Error),
+}
+
+/// Error for [`Iterator::Item`]
+#[derive(Debug, thiserror::Error)]
+pub enum NextError {
+	/// Entry had no name
+	#[error("Entry had no name")]
+	NoEntryName,
+
+	/// Entry name wasn't utf-8
+	#[error("Entry name wasn't utf-8")]
+	NonUtf8EntryName,
+
+	/// Invalid entry name
+	#[error("Invalid entry name")]
+	InvalidEntryName(#[source] ValidateFileAlphabetError),
+
+	/// Unable to get entry date
+	#[error("Unable to get entry date")]
+	EntryDate(#[source] io::Error),
+
+	/// Unable to get entry date as time since epoch
+	#[error("Unable to get entry date as time since epoch")]
+	EntryDateSinceEpoch(#[source] std::time::SystemTimeError),
+
+	/// Unable to get entry date as `i64` seconds since epoch
+	#[error("Unable to get entry date as `i64` seconds since epoch")]
+	EntryDateI64Secs,
+
+	/// Entry date year must be between 1900 and 2155
+	#[error("Entry date year must be between 1900 and 2155")]
+	EntryDateYear,
+
+	/// File was too large
+	#[error("File was too large")]
+	FileTooLarge,
+
+	/// Unable to open file
+	#[error("Unable to open file")]
+	OpenFile(#[source] io::Error),
+
+	/// Invalid sub-header in header
+	#[error("Invalid sub-header in header")]
+	InvalidSubHeader(#[source] subheader::DeserializeBytesError),
+
+	/// Unable to open directory
+	#[error("Unable to open directory")]
+	OpenDir(#[source] NewError),
+}
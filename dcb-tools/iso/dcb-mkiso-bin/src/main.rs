@@ -1,15 +1,35 @@
-//! Iso extractor from `.bin` files.
+//! Iso packer into `.bin` files.
 
 // Features
-#![feature()]
+#![feature(
+	unwrap_infallible,
+	format_args_capture,
+	type_alias_impl_trait,
+	impl_trait_in_assoc_type
+)]
 
 // Modules
 mod cli;
+mod dir_lister;
 
 // Imports
 use anyhow::Context;
 use cli::CliData;
-use std::{fs, path::PathBuf};
+use dcb_bytes::Bytes;
+use dcb_cdrom_xa::{sector::header::SubHeader, CdRomWriter};
+use dcb_iso9660::{
+	date_time::{DecDateTime, DirDateTime},
+	header::{self, Header},
+	writer::VolumeInfo,
+	FilesystemWriter, StrArrA, StrArrD,
+};
+use dir_lister::{DirLister, EntryHeaders};
+use std::{
+	convert::TryFrom,
+	fs,
+	io::{self, Write},
+	rc::Rc,
+};
 
 fn main() -> Result<(), anyhow::Error> {
 	// Initialize the logger
@@ -26,96 +46,121 @@ fn main() -> Result<(), anyhow::Error> {
 	// If we don't have an output, use the input filename with `.iso`
 	let output_file = match &cli_data.output_file {
 		Some(output) => output.clone(),
-		None => {
-			let mut path = cli_data.input_dir.as_os_str().to_os_string();
-			path.push(".iso");
-			PathBuf::from(path)
-		},
+		None => header::path_with_suffix(&cli_data.input_dir, ".iso"),
 	};
 
 	// Read the header file
-	let header_file_path = {
-		let mut path = cli_data.input_dir.as_os_str().to_os_string();
-		path.push(".header");
-		PathBuf::from(path)
+	let header_file_path = header::path_with_suffix(&cli_data.input_dir, ".header");
+	let header_file = fs::File::open(header_file_path).context("Unable to open header file")?;
+	let header: Header = serde_yaml::from_reader(header_file).context("Unable to read header")?;
+
+	// Create the volume info from it
+	let info = VolumeInfo {
+		system_id:                     StrArrA::from_bytes(&self::padded_bytes(&header.system_id)?)
+			.context("Invalid system id")?,
+		volume_id:                     StrArrD::from_bytes(&self::padded_bytes(&header.volume_id)?)
+			.context("Invalid volume id")?,
+		volume_set_id:                 StrArrD::from_bytes(&self::padded_bytes(&header.volume_set_id)?)
+			.context("Invalid volume set id")?,
+		publisher_id:                  StrArrA::from_bytes(&self::padded_bytes(&header.publisher_id)?)
+			.context("Invalid publisher id")?,
+		data_preparer_id:              StrArrA::from_bytes(&self::padded_bytes(&header.data_preparer_id)?)
+			.context("Invalid data preparer id")?,
+		application_id:                StrArrA::from_bytes(&self::padded_bytes(&header.application_id)?)
+			.context("Invalid application id")?,
+		copyright_file_id:             StrArrD::from_bytes(&self::padded_bytes(&header.copyright_file_id)?)
+			.context("Invalid copyright file id")?,
+		abstract_file_id:              StrArrD::from_bytes(&self::padded_bytes(&header.abstract_file_id)?)
+			.context("Invalid abstract file id")?,
+		bibliographic_file_id:         StrArrD::from_bytes(&self::padded_bytes(&header.bibliographic_file_id)?)
+			.context("Invalid bibliographic file id")?,
+		volume_creation_date_time:     self::parse_date_time(&header.volume_creation_date_time)
+			.context("Invalid volume creation date time")?,
+		volume_modification_date_time: self::parse_date_time(&header.volume_modification_date_time)
+			.context("Invalid volume modification date time")?,
+		volume_expiration_date_time:   self::parse_date_time(&header.volume_expiration_date_time)
+			.context("Invalid volume expiration date time")?,
+		volume_effective_date_time:    self::parse_date_time(&header.volume_effective_date_time)
+			.context("Invalid volume effective date time")?,
+		application_use:               <[u8; 0x200]>::try_from(header.application_use.as_slice())
+			.context("Application use must be 512 bytes")?,
+		root_date:                     DirDateTime::deserialize_bytes(&header.root_date).into_ok(),
+		root_system_use:               header.root_system_use,
+		root_sector_pos:               Some(header.root_sector_pos),
+		path_table_locations:          Some([
+			header.path_table_location,
+			header.path_table_opt_location,
+			header.path_table_msb_location,
+			header.path_table_msb_opt_location,
+		]),
+		volume_space_size:             Some(header.volume_space_size),
 	};
-	let mut header_file = fs::File::create(header_file_path).context("Unable to create output header file")?;
-	let _header: Header = serde_yaml::from_reader(&mut header_file).context("Unable to read header")?;
-
-	// Create the output file
-	let _output_file = fs::File::create(output_file).context("Unable to create output file")?;
 
-	// Go through all files in the directory
-	for entry in std::fs::read_dir(&cli_data.input_dir).context("Unable to read input directory")? {
-		// Get the entry
-		let entry = entry.context("Unable to read entry")?;
-		let path = entry.path();
-		let metadata = entry.metadata().context("Unable to get file metadata")?;
+	// Then create the root directory lister
+	let entry_headers: EntryHeaders = header
+		.entries
+		.into_iter()
+		.map(|entry| (entry.path.clone(), entry))
+		.collect();
+	let root = DirLister::new(&cli_data.input_dir, None, Rc::new(entry_headers))
+		.context("Unable to create new dir lister for root directory")?;
 
-		// If it's a directory, skip
-		if metadata.is_dir() {
-			log::warn!("Skipping directory: {}", path.display());
-			continue;
-		}
-
-		// Open the input file and write it
-		let _input_file = fs::File::open(&path).context("Unable to open input file")?;
+	// Create the output file
+	let mut output_file = fs::File::create(output_file).context("Unable to create output file")?;
+
+	// Write the system area, if we have it, else write empty sectors
+	// Note: The first sector is at 2 seconds
+	let system_area_path = header::path_with_suffix(&cli_data.input_dir, ".system_area");
+	let mut output_file = match fs::read(&system_area_path) {
+		Ok(system_area) => {
+			anyhow::ensure!(
+				system_area.len() == 16 * 0x930,
+				"System area must be 16 raw sectors, found {} bytes",
+				system_area.len()
+			);
+			output_file
+				.write_all(&system_area)
+				.context("Unable to write system area")?;
+
+			CdRomWriter::new(output_file, 75 * 2 + 16)
+		},
+		Err(err) if err.kind() == io::ErrorKind::NotFound => {
+			log::warn!(
+				"No system area found at {}, using empty system area",
+				system_area_path.display()
+			);
+
+			let mut output_file = CdRomWriter::new(output_file, 75 * 2);
+			for _ in 0..16 {
+				output_file
+					.write_sector([0; 0x800], SubHeader::new())
+					.context("Unable to write system area")?;
+			}
+
+			output_file
+		},
+		Err(err) => return Err(err).context("Unable to read system area"),
+	};
 
-		todo!();
-	}
+	// And write the filesystem
+	FilesystemWriter::new(info, root)
+		.write(&mut output_file)
+		.context("Unable to write filesystem")?;
 
 	Ok(())
 }
 
-/// Header
-#[derive(PartialEq, Eq, Clone, Debug)]
-#[derive(serde::Deserialize, serde::Serialize)]
-pub struct Header {
-	/// System Id
-	pub system_id: String,
-
-	/// Volume Id
-	pub volume_id: String,
-
-	/// Volume space size
-	pub volume_space_size: u32,
-
-	/// Volume sequence_number
-	pub volume_sequence_number: u16,
+/// Returns the bytes of `s`, padded with spaces
+fn padded_bytes<const N: usize>(s: &str) -> Result<[u8; N], anyhow::Error> {
+	anyhow::ensure!(s.len() <= N, "String {s:?} was too long, max is {N}");
 
-	/// Logical block size
-	pub logical_block_size: u16,
-
-	/// Volume set identifier
-	pub volume_set_id: String,
-
-	/// Publisher identifier
-	pub publisher_id: String,
-
-	/// Data preparer identifier
-	pub data_preparer_id: String,
-
-	/// Application identifier
-	pub application_id: String,
-
-	/// Copyright file identifier
-	pub copyright_file_id: String,
-
-	/// Abstract file identifier
-	pub abstract_file_id: String,
-
-	/// Bibliographic file identifier
-	pub bibliographic_file_id: String,
-
-	/// Volume creation date time
-	pub volume_creation_date_time: String,
-
-	/// Volume modification date time
-	pub volume_modification_date_time: String,
-
-	/// Volume expiration date time
-	pub volume_expiration_date_time: String,
+	let mut bytes = [b' '; N];
+	bytes[..s.len()].copy_from_slice(s.as_bytes());
+	Ok(bytes)
+}
 
-	/// Volume effective date time
-	pub volume_effective_date_time: String,
+/// Parses a date time
+fn parse_date_time(s: &str) -> Result<DecDateTime, anyhow::Error> {
+	let bytes = <&[u8; 0x11]>::try_from(s.as_bytes()).context("Date time must be 17 bytes")?;
+	DecDateTime::deserialize_bytes(bytes).context("Unable to parse date time")
 }
@@ -1,7 +1,7 @@
 //! Iso extractor from `.bin` files.
 
 // Features
-#![feature(unwrap_infallible)]
+#![feature(unwrap_infallible, format_args_capture)]
 
 // Modules
 mod cli;
@@ -10,9 +10,17 @@ mod cli;
 use anyhow::Context;
 use cli::CliData;
 use dcb_bytes::Bytes;
-use dcb_cdrom_xa::CdRomReader;
-use dcb_iso9660::{date_time::DecDateTime, string::FileStrWithoutVersion, FilesystemReader};
-use std::{fs, io, path::PathBuf};
+use dcb_cdrom_xa::{sector::header::SubHeader, CdRomReader, ImageReader};
+use dcb_iso9660::{
+	date_time::DecDateTime,
+	header::{self, EntryHeader, Header},
+	writer, DirEntry, FilesystemReader,
+};
+use std::{
+	fs,
+	io::{self, Read},
+	path::{Path, PathBuf},
+};
 
 fn main() -> Result<(), anyhow::Error> {
 	// Initialize the logger
@@ -42,15 +50,28 @@ fn main() -> Result<(), anyhow::Error> {
 	// Open the file.
 	let input_file = fs::File::open(&cli_data.input_file).context("Unable to open input file")?;
 	let input_file = io::BufReader::new(input_file);
+	let input_file = ImageReader::new(input_file).context("Unable to open input image")?;
 	let mut input_file = CdRomReader::new(input_file);
 	let fs_reader = FilesystemReader::new(&mut input_file).context("Unable to create filesystem reader")?;
 
+	// Copy the system area
+	// Note: We copy it raw, as it may contain sectors we can't parse.
+	{
+		let mut system_area = vec![0; 16 * 0x930];
+		let file = fs::File::open(&cli_data.input_file).context("Unable to open input file")?;
+		let mut file = ImageReader::new(file).context("Unable to open input image")?;
+		file.read_exact(&mut system_area)
+			.context("Unable to read system area")?;
+		fs::write(header::path_with_suffix(&output_dir, ".system_area"), system_area)
+			.context("Unable to write system area")?;
+	}
+
+	// Extract all files
+	let root_dir_entry = fs_reader.root_dir();
+	let entries = self::extract_all(&mut input_file, &fs_reader, &output_dir)?;
+
 	// Create the header and output it
-	let header_file_path = {
-		let mut path = cli_data.input_file.as_os_str().to_os_string();
-		path.push(".header");
-		PathBuf::from(path)
-	};
+	let header_file_path = header::path_with_suffix(&output_dir, ".header");
 	let mut header_file = fs::File::create(header_file_path).context("Unable to create output header file")?;
 	{
 		let date_time_to_string = |date_time: DecDateTime| {
@@ -60,6 +81,18 @@ fn main() -> Result<(), anyhow::Error> {
 				.to_owned()
 		};
 
+		// Note: The root directory entry in the primary volume descriptor doesn't have the system use
+		//       area, so we get it from the current directory entry, the first one within it.
+		let root_system_use = {
+			let sector = input_file
+				.read_nth_sector(u64::from(root_dir_entry.sector_pos))
+				.context("Unable to read root directory")?;
+			let data = sector.data.as_form1().context("Root directory sector wasn't form 1")?;
+			DirEntry::from_reader(&mut io::Cursor::new(data))
+				.context("Unable to read root directory current directory entry")?
+				.system_use
+		};
+
 		let volume = fs_reader.primary_volume_descriptor();
 		let header = Header {
 			system_id:                     volume.system_id.as_lossy_str().to_string(),
@@ -67,6 +100,10 @@ fn main() -> Result<(), anyhow::Error> {
 			volume_space_size:             volume.volume_space_size,
 			volume_sequence_number:        volume.volume_sequence_number,
 			logical_block_size:            volume.logical_block_size,
+			path_table_location:           volume.path_table_location,
+			path_table_opt_location:       volume.path_table_opt_location,
+			path_table_msb_location:       volume.path_table_msb_location,
+			path_table_msb_opt_location:   volume.path_table_msb_opt_location,
 			volume_set_id:                 volume.volume_set_id.as_lossy_str().to_string(),
 			publisher_id:                  volume.publisher_id.as_lossy_str().to_string(),
 			data_preparer_id:              volume.data_preparer_id.as_lossy_str().to_string(),
@@ -78,86 +115,101 @@ fn main() -> Result<(), anyhow::Error> {
 			volume_modification_date_time: date_time_to_string(volume.volume_modification_date_time),
 			volume_expiration_date_time:   date_time_to_string(volume.volume_expiration_date_time),
 			volume_effective_date_time:    date_time_to_string(volume.volume_effective_date_time),
+			application_use:               volume.application_use.to_vec(),
+			root_sector_pos:               root_dir_entry.sector_pos,
+			root_date:                     root_dir_entry.date.to_bytes().into_ok(),
+			root_system_use,
+			entries,
 		};
 		serde_yaml::to_writer(&mut header_file, &header).context("Unable to write header")?;
 	}
 
-	// Extract all files in the root directory
-	let root_dir = fs_reader
-		.root_dir()
-		.read_dir(&mut input_file)
-		.context("Unable to read root directory entry")?;
+	Ok(())
+}
 
-	for entry in root_dir.entries() {
-		let name = entry.name.without_version();
+/// Extracts all entries of the filesystem into `output_dir`.
+///
+/// Returns all entries extracted.
+fn extract_all<R: io::Read + io::Seek>(
+	input_file: &mut CdRomReader<R>, fs_reader: &FilesystemReader, output_dir: &Path,
+) -> Result<Vec<EntryHeader>, anyhow::Error> {
+	let mut walk = fs_reader.walk(input_file).context("Unable to read root directory")?;
+	let mut entries = vec![];
+	while let Some(walk_entry) = walk.next() {
+		let walk_entry = walk_entry.context("Unable to read directory")?;
+		let entry_path = walk_entry.path();
+		let entry = walk_entry.entry;
+		let output_path = output_dir.join(&entry_path);
+
+		let mut entry_header = EntryHeader {
+			path:       entry_path.clone(),
+			name:       entry.name.to_string(),
+			sector_pos: entry.sector_pos,
+			date:       entry.date.to_bytes().into_ok(),
+			system_use: entry.system_use.clone(),
+			size:       None,
+			subheaders: None,
+		};
 
+		// If it's a directory, create it
+		// Note: It's entries are always extracted after it.
 		if entry.is_dir() {
-			log::warn!("Skipping directory in root dir: {}", &name);
+			zutil::try_create_dir_all(&output_path)
+				.with_context(|| format!("Unable to create directory {}", output_path.display()))?;
+
+			entries.push(entry_header);
 			continue;
 		}
 
 		// Else extract it
-		let mut file = entry.read_file(&mut input_file).context("Unable to read file")?;
-
-		// Open the output file
-		let mut output_file = fs::File::create(output_dir.join(name)).context("Unable to open output file")?;
+		{
+			let mut file = entry
+				.read_file(walk.cdrom())
+				.with_context(|| format!("Unable to read file {entry_path}"))?;
 
-		// And copy the file
-		std::io::copy(&mut file, &mut output_file).context("Unable to write output file")?;
-	}
-
-	Ok(())
-}
+			// Open the output file
+			let mut output_file = fs::File::create(&output_path).context("Unable to open output file")?;
 
-/// Header
-#[derive(PartialEq, Eq, Clone, Debug)]
-#[derive(serde::Deserialize, serde::Serialize)]
-pub struct Header {
-	/// System Id
-	pub system_id: String,
-
-	/// Volume Id
-	pub volume_id: String,
-
-	/// Volume space size
-	pub volume_space_size: u32,
-
-	/// Volume sequence_number
-	pub volume_sequence_number: u16,
-
-	/// Logical block size
-	pub logical_block_size: u16,
-
-	/// Volume set identifier
-	pub volume_set_id: String,
-
-	/// Publisher identifier
-	pub publisher_id: String,
-
-	/// Data preparer identifier
-	pub data_preparer_id: String,
-
-	/// Application identifier
-	pub application_id: String,
-
-	/// Copyright file identifier
-	pub copyright_file_id: String,
-
-	/// Abstract file identifier
-	pub abstract_file_id: String,
-
-	/// Bibliographic file identifier
-	pub bibliographic_file_id: String,
+			// And copy the file
+			std::io::copy(&mut file, &mut output_file).context("Unable to write output file")?;
+		}
 
-	/// Volume creation date time
-	pub volume_creation_date_time: String,
+		// Then save the sub-headers of it's sectors, if they aren't the default ones
+		let subheaders = self::file_subheaders(walk.cdrom(), &entry)
+			.with_context(|| format!("Unable to read sub-headers of file {entry_path}"))?;
+		let sectors_len = subheaders.len();
+		let is_default = subheaders
+			.iter()
+			.enumerate()
+			.all(|(sector_idx, subheader)| *subheader == writer::data_subheader(sector_idx + 1 == sectors_len));
+		if !is_default {
+			entry_header.size = Some(entry.size);
+			entry_header.subheaders = Some(
+				subheaders
+					.iter()
+					.map(|subheader| subheader.to_bytes().context("Unable to serialize sub-header"))
+					.collect::<Result<_, _>>()?,
+			);
+		}
 
-	/// Volume modification date time
-	pub volume_modification_date_time: String,
+		entries.push(entry_header);
+	}
 
-	/// Volume expiration date time
-	pub volume_expiration_date_time: String,
+	Ok(entries)
+}
 
-	/// Volume effective date time
-	pub volume_effective_date_time: String,
+/// Returns the sub-headers of all sectors of a file
+fn file_subheaders<R: io::Read + io::Seek>(
+	input_file: &mut CdRomReader<R>, entry: &DirEntry,
+) -> Result<Vec<SubHeader>, anyhow::Error> {
+	let sectors_len = entry.size / 0x800 + u32::from(entry.size % 0x800 != 0);
+	input_file
+		.seek_sector(u64::from(entry.sector_pos))
+		.context("Unable to seek to file")?;
+	(0..sectors_len)
+		.map(|_| {
+			let sector = input_file.read_sector().context("Unable to read sector")?;
+			Ok(sector.header.subheader)
+		})
+		.collect()
 }
@@ -41,7 +41,7 @@ fn main() -> Result<(), anyhow::Error> {
 	let mut file = dcb_cdrom_xa::CdRomCursor::new(file);
 
 	// Open the card table file and parse it
-	let mut game_file = dcb_io::GameFile::new(&mut file);
+	let mut game_file = dcb_io::GameFile::new(&mut file).context("Unable to open game file")?;
 	let mut table_file = CardTable::open(&mut game_file).context("Unable to open table file")?;
 
 	// Then parse it